@@ -0,0 +1,340 @@
+use std::collections::HashMap;
+use std::env;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use hmac::{Hmac, KeyInit, Mac};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+const IMDS_ENDPOINT: &str = "http://169.254.169.254";
+const CONTAINER_ENDPOINT: &str = "http://169.254.170.2";
+
+#[derive(Clone)]
+pub struct AwsCredentials {
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    pub session_token: Option<String>,
+}
+
+impl AwsCredentials {
+    /// Resolves credentials the same way the AWS CLI does, minus SSO and
+    /// `credential_process`: environment, shared credentials file, ECS
+    /// container endpoint, then EC2 instance metadata.
+    pub async fn from_default_chain() -> anyhow::Result<Self> {
+        if let Some(creds) = Self::from_env() {
+            return Ok(creds);
+        }
+
+        let profile = profile_name();
+        if let Some(creds) = credentials_file()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .and_then(|contents| Self::from_profile_file(&contents, &profile))
+        {
+            return Ok(creds);
+        }
+
+        let client = crate::http::builder()?
+            .timeout(Duration::from_secs(2))
+            .build()?;
+
+        if let Some(creds) = Self::from_container(&client).await {
+            return Ok(creds);
+        }
+        if let Some(creds) = Self::from_imds(&client).await {
+            return Ok(creds);
+        }
+
+        Err(anyhow::anyhow!(
+            "No AWS credentials found. Set AWS_ACCESS_KEY_ID/AWS_SECRET_ACCESS_KEY, \
+            configure the '{}' profile in ~/.aws/credentials, or run on an instance with a role",
+            profile
+        ))
+    }
+
+    fn from_env() -> Option<Self> {
+        Some(Self {
+            access_key_id: env::var("AWS_ACCESS_KEY_ID").ok()?,
+            secret_access_key: env::var("AWS_SECRET_ACCESS_KEY").ok()?,
+            session_token: env::var("AWS_SESSION_TOKEN").ok(),
+        })
+    }
+
+    pub fn from_profile_file(contents: &str, profile: &str) -> Option<Self> {
+        let sections = parse_ini(contents);
+        let section = sections.get(profile)?;
+        Some(Self {
+            access_key_id: section.get("aws_access_key_id")?.clone(),
+            secret_access_key: section.get("aws_secret_access_key")?.clone(),
+            session_token: section.get("aws_session_token").cloned(),
+        })
+    }
+
+    async fn from_container(client: &reqwest::Client) -> Option<Self> {
+        let url = match env::var("AWS_CONTAINER_CREDENTIALS_FULL_URI") {
+            Ok(url) => url,
+            Err(_) => format!(
+                "{}{}",
+                CONTAINER_ENDPOINT,
+                env::var("AWS_CONTAINER_CREDENTIALS_RELATIVE_URI").ok()?
+            ),
+        };
+
+        let mut req = client.get(url);
+        if let Ok(token) = env::var("AWS_CONTAINER_AUTHORIZATION_TOKEN") {
+            req = req.header("Authorization", token);
+        }
+        let v: Value = req.send().await.ok()?.json().await.ok()?;
+        Self::from_metadata_json(&v)
+    }
+
+    async fn from_imds(client: &reqwest::Client) -> Option<Self> {
+        let token = client
+            .put(format!("{}/latest/api/token", IMDS_ENDPOINT))
+            .header("x-aws-ec2-metadata-token-ttl-seconds", "21600")
+            .send()
+            .await
+            .ok()?
+            .text()
+            .await
+            .ok()?;
+
+        let base = format!("{}/latest/meta-data/iam/security-credentials/", IMDS_ENDPOINT);
+        let role = client
+            .get(&base)
+            .header("x-aws-ec2-metadata-token", &token)
+            .send()
+            .await
+            .ok()?
+            .text()
+            .await
+            .ok()?;
+        let role = role.lines().next()?.trim();
+
+        let v: Value = client
+            .get(format!("{}{}", base, role))
+            .header("x-aws-ec2-metadata-token", &token)
+            .send()
+            .await
+            .ok()?
+            .json()
+            .await
+            .ok()?;
+        Self::from_metadata_json(&v)
+    }
+
+    /// Both the container endpoint and IMDS return `AccessKeyId`,
+    /// `SecretAccessKey` and `Token`.
+    fn from_metadata_json(v: &Value) -> Option<Self> {
+        Some(Self {
+            access_key_id: v.get("AccessKeyId")?.as_str()?.to_string(),
+            secret_access_key: v.get("SecretAccessKey")?.as_str()?.to_string(),
+            session_token: v.get("Token").and_then(|t| t.as_str()).map(str::to_string),
+        })
+    }
+}
+
+/// `AWS_REGION`, then `AWS_DEFAULT_REGION`, then the active profile in
+/// `~/.aws/config`, falling back to `us-east-1`.
+pub fn default_region() -> String {
+    if let Ok(region) = env::var("AWS_REGION").or_else(|_| env::var("AWS_DEFAULT_REGION")) {
+        return region;
+    }
+
+    let profile = profile_name();
+    let section = if profile == "default" {
+        profile.clone()
+    } else {
+        format!("profile {}", profile)
+    };
+    config_file()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|contents| parse_ini(&contents).get(&section)?.get("region").cloned())
+        .unwrap_or_else(|| "us-east-1".to_string())
+}
+
+fn profile_name() -> String {
+    env::var("AWS_PROFILE").unwrap_or_else(|_| "default".to_string())
+}
+
+fn credentials_file() -> Option<PathBuf> {
+    env::var("AWS_SHARED_CREDENTIALS_FILE")
+        .map(PathBuf::from)
+        .ok()
+        .or_else(|| dirs::home_dir().map(|home| home.join(".aws").join("credentials")))
+}
+
+fn config_file() -> Option<PathBuf> {
+    env::var("AWS_CONFIG_FILE")
+        .map(PathBuf::from)
+        .ok()
+        .or_else(|| dirs::home_dir().map(|home| home.join(".aws").join("config")))
+}
+
+fn parse_ini(contents: &str) -> HashMap<String, HashMap<String, String>> {
+    let mut sections: HashMap<String, HashMap<String, String>> = HashMap::new();
+    let mut current = None;
+
+    for line in contents.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            let name = name.trim().to_string();
+            sections.entry(name.clone()).or_default();
+            current = Some(name);
+        } else if let (Some(section), Some((key, value))) = (&current, line.split_once('=')) {
+            sections
+                .entry(section.clone())
+                .or_default()
+                .insert(key.trim().to_string(), value.trim().to_string());
+        }
+    }
+
+    sections
+}
+
+/// The parts of an HTTP request that SigV4 covers. `path` must already be
+/// URI-encoded exactly as it will be sent on the wire.
+pub struct SigningRequest<'a> {
+    pub method: &'a str,
+    pub host: &'a str,
+    pub path: &'a str,
+    pub headers: &'a [(&'a str, &'a str)],
+    pub payload: &'a [u8],
+}
+
+/// Computes the `Authorization` header value for `req`. `amz_date` is the
+/// `YYYYMMDD'T'HHMMSS'Z'` timestamp also sent as `x-amz-date`; the caller
+/// must include `x-amz-date` (and `x-amz-security-token` when using
+/// temporary credentials) in `req.headers`.
+pub fn sign(
+    creds: &AwsCredentials,
+    region: &str,
+    service: &str,
+    amz_date: &str,
+    req: &SigningRequest,
+) -> String {
+    let date = &amz_date[..8];
+
+    let mut headers: Vec<(String, String)> = req
+        .headers
+        .iter()
+        .map(|(k, v)| (k.to_ascii_lowercase(), v.trim().to_string()))
+        .chain(std::iter::once(("host".to_string(), req.host.to_string())))
+        .collect();
+    headers.sort();
+
+    let canonical_headers: String = headers.iter().map(|(k, v)| format!("{}:{}\n", k, v)).collect();
+    let signed_headers = headers.iter().map(|(k, _)| k.as_str()).collect::<Vec<_>>().join(";");
+
+    let canonical_request = format!(
+        "{}\n{}\n\n{}\n{}\n{}",
+        req.method,
+        uri_encode(req.path, false),
+        canonical_headers,
+        signed_headers,
+        hex::encode(Sha256::digest(req.payload))
+    );
+
+    let scope = format!("{}/{}/{}/aws4_request", date, region, service);
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+        amz_date,
+        scope,
+        hex::encode(Sha256::digest(canonical_request.as_bytes()))
+    );
+
+    let k_date = hmac(format!("AWS4{}", creds.secret_access_key).as_bytes(), date.as_bytes());
+    let k_region = hmac(&k_date, region.as_bytes());
+    let k_service = hmac(&k_region, service.as_bytes());
+    let k_signing = hmac(&k_service, b"aws4_request");
+    let signature = hex::encode(hmac(&k_signing, string_to_sign.as_bytes()));
+
+    format!(
+        "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+        creds.access_key_id, scope, signed_headers, signature
+    )
+}
+
+/// RFC 3986 percent-encoding as SigV4 defines it. With `encode_slash`
+/// unset, `/` is kept so whole paths can be passed through.
+pub fn uri_encode(input: &str, encode_slash: bool) -> String {
+    let mut out = String::with_capacity(input.len());
+    for byte in input.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                out.push(byte as char)
+            }
+            b'/' if !encode_slash => out.push('/'),
+            _ => out.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    out
+}
+
+fn hmac(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sign_matches_aws_test_suite_get_vanilla() {
+        let creds = AwsCredentials {
+            access_key_id: "AKIDEXAMPLE".to_string(),
+            secret_access_key: "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY".to_string(),
+            session_token: None,
+        };
+        let req = SigningRequest {
+            method: "GET",
+            host: "example.amazonaws.com",
+            path: "/",
+            headers: &[("X-Amz-Date", "20150830T123600Z")],
+            payload: b"",
+        };
+
+        let auth = sign(&creds, "us-east-1", "service", "20150830T123600Z", &req);
+        assert_eq!(
+            auth,
+            "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20150830/us-east-1/service/aws4_request, \
+            SignedHeaders=host;x-amz-date, \
+            Signature=5fa00fa31553b73ebf1942676e86291e8372ff2a2260956d9b8aae1d763fbf31"
+        );
+    }
+
+    #[test]
+    fn test_uri_encode() {
+        assert_eq!(uri_encode("/model/a.b-v1:0/converse", false), "/model/a.b-v1%3A0/converse");
+        assert_eq!(uri_encode("a/b c", true), "a%2Fb%20c");
+    }
+
+    #[test]
+    fn test_from_profile_file() {
+        let contents = "\
+[default]
+aws_access_key_id = AKIADEFAULT
+aws_secret_access_key = secret1
+
+[work]
+aws_access_key_id=AKIAWORK
+aws_secret_access_key=secret2
+aws_session_token=token2
+";
+
+        let default = AwsCredentials::from_profile_file(contents, "default").unwrap();
+        assert_eq!(default.access_key_id, "AKIADEFAULT");
+        assert!(default.session_token.is_none());
+
+        let work = AwsCredentials::from_profile_file(contents, "work").unwrap();
+        assert_eq!(work.secret_access_key, "secret2");
+        assert_eq!(work.session_token.as_deref(), Some("token2"));
+
+        assert!(AwsCredentials::from_profile_file(contents, "missing").is_none());
+    }
+}
@@ -0,0 +1,57 @@
+use std::env;
+use std::process::Command;
+
+/// A GitHub token: `GITHUB_TOKEN` from the keyring, the encrypted
+/// credentials file or the environment, then `GH_TOKEN`, then the GitHub
+/// CLI's stored login, in that order.
+pub fn token() -> anyhow::Result<String> {
+    find(super::keyring::secret, |var| env::var(var).ok(), gh_token)
+}
+
+fn find(
+    secret: impl Fn(&str) -> Option<String>,
+    env: impl Fn(&str) -> Option<String>,
+    gh: impl FnOnce() -> anyhow::Result<String>,
+) -> anyhow::Result<String> {
+    [secret("GITHUB_TOKEN"), env("GH_TOKEN")]
+        .into_iter()
+        .flatten()
+        .map(|token| token.trim().to_string())
+        .find(|token| !token.is_empty())
+        .map_or_else(gh, Ok)
+}
+
+fn gh_token() -> anyhow::Result<String> {
+    let output = Command::new("gh").args(["auth", "token"]).output();
+    match output {
+        Ok(output) if output.status.success() => {
+            let token = String::from_utf8_lossy(&output.stdout).trim().to_string();
+            if token.is_empty() {
+                Err(anyhow::anyhow!("`gh auth token` returned no token; run `gh auth login`"))
+            } else {
+                Ok(token)
+            }
+        }
+        _ => Err(anyhow::anyhow!(
+            "No GitHub token found. Set GITHUB_TOKEN or log in with `gh auth login`"
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_order() {
+        let stored = |var: &str| (var == "GITHUB_TOKEN").then(|| "ghp_stored\n".to_string());
+        let gh_env = |var: &str| (var == "GH_TOKEN").then(|| "ghp_env".to_string());
+        let none = |_: &str| None;
+        let gh = || Ok("gho_cli".to_string());
+
+        assert_eq!(find(stored, gh_env, gh).unwrap(), "ghp_stored");
+        assert_eq!(find(none, gh_env, gh).unwrap(), "ghp_env");
+        assert_eq!(find(|_| Some(" ".to_string()), none, gh).unwrap(), "gho_cli");
+        assert!(find(none, none, || anyhow::bail!("no token")).is_err());
+    }
+}
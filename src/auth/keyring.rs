@@ -0,0 +1,34 @@
+use std::env;
+
+/// Every secret lives under this service, keyed by the environment
+/// variable it stands in for (`GEMINI_API_KEY`, ...).
+const SERVICE: &str = "commitgen";
+
+/// The secret stored in the OS keyring for `var`. A missing entry, or a
+/// machine with no keyring service at all, yields `None`.
+pub fn get(var: &str) -> Option<String> {
+    let entry = ::keyring::Entry::new(SERVICE, var).ok()?;
+    entry.get_password().ok().filter(|secret| !secret.trim().is_empty())
+}
+
+pub fn set(var: &str, secret: &str) -> anyhow::Result<()> {
+    ::keyring::Entry::new(SERVICE, var)?
+        .set_password(secret)
+        .map_err(|e| anyhow::anyhow!("Could not store {} in the keyring: {}; `--encrypted` stores it in a file instead", var, e))
+}
+
+/// Returns whether there was an entry to delete.
+pub fn delete(var: &str) -> anyhow::Result<bool> {
+    match ::keyring::Entry::new(SERVICE, var)?.delete_credential() {
+        Ok(()) => Ok(true),
+        Err(::keyring::Error::NoEntry) => Ok(false),
+        Err(e) => Err(anyhow::anyhow!("Could not remove {} from the keyring: {}", var, e)),
+    }
+}
+
+/// The keyring entry for `var`, falling back to the encrypted credentials
+/// file and then the environment (which includes anything loaded from
+/// `.env`).
+pub fn secret(var: &str) -> Option<String> {
+    get(var).or_else(|| super::vault::get(var)).or_else(|| env::var(var).ok())
+}
@@ -0,0 +1,8 @@
+//! Credential handling: API keys kept in the OS keyring or an encrypted
+//! file, and providers that don't authenticate with a plain API key.
+
+pub mod aws;
+pub mod github;
+pub mod google;
+pub mod keyring;
+pub mod vault;
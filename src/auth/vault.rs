@@ -0,0 +1,234 @@
+use std::collections::BTreeMap;
+use std::num::NonZeroU32;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use ring::{aead, pbkdf2, rand::SecureRandom};
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+
+/// Where the passphrase comes from when there's no terminal to ask at,
+/// such as an agent: `pass show commitgen`, `op read ...`.
+pub const PASSPHRASE_VAR: &str = "COMMITGEN_PASSPHRASE";
+
+/// PBKDF2-HMAC-SHA256 rounds for new files, as OWASP recommends.
+const ITERATIONS: u32 = 600_000;
+
+/// An encrypted credentials file, `credentials.enc` beside the config, for
+/// machines without a keyring. The secrets are one ChaCha20-Poly1305 box
+/// under a key derived from the passphrase; the names of the variables
+/// they're for are left readable, so the passphrase is only asked for
+/// when one of them is needed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct File {
+    version: u32,
+    iterations: u32,
+    salt: String,
+    nonce: String,
+    vars: Vec<String>,
+    ciphertext: String,
+}
+
+static PASSPHRASE_COMMAND: OnceLock<Option<String>> = OnceLock::new();
+/// The secrets once unlocked, so the passphrase is asked for once a run.
+static UNLOCKED: Mutex<Option<BTreeMap<String, String>>> = Mutex::new(None);
+
+/// Takes `passphrase_command` from the config.
+pub fn init(config: &Config) {
+    let _ = PASSPHRASE_COMMAND.set(config.passphrase_command.clone());
+}
+
+/// `credentials.enc` in the config directory.
+pub fn path() -> Option<PathBuf> {
+    Some(Config::default_path()?.with_file_name("credentials.enc"))
+}
+
+/// The secret stored for `var`, unlocking the file if it has one. Failing
+/// to unlock it is logged rather than returned, so the error a missing key
+/// gives is the usual one.
+pub fn get(var: &str) -> Option<String> {
+    let path = path()?;
+    let file = read(&path).ok()??;
+    if !file.vars.iter().any(|stored| stored == var) {
+        return None;
+    }
+    let mut unlocked = UNLOCKED.lock().ok()?;
+    if unlocked.is_none() {
+        match passphrase(&path, false).and_then(|passphrase| open(&file, &passphrase)) {
+            Ok(secrets) => *unlocked = Some(secrets),
+            Err(e) => {
+                tracing::warn!("Could not unlock {}: {}", path.display(), e);
+                return None;
+            }
+        }
+    }
+    unlocked.as_ref()?.get(var).cloned()
+}
+
+/// Whether the file has a secret for `var`, without unlocking it.
+pub fn has(var: &str) -> bool {
+    path().and_then(|path| read(&path).ok().flatten()).is_some_and(|file| file.vars.iter().any(|stored| stored == var))
+}
+
+/// Stores `secret` for `var`, creating the file, and choosing its
+/// passphrase, if need be.
+pub fn set(var: &str, secret: &str) -> anyhow::Result<()> {
+    update(|secrets| {
+        secrets.insert(var.to_string(), secret.to_string());
+    })
+    .map(|_| ())
+}
+
+/// Returns whether there was an entry to delete.
+pub fn delete(var: &str) -> anyhow::Result<bool> {
+    let path = path().ok_or_else(|| anyhow::anyhow!("Cannot find a config directory"))?;
+    if !read(&path)?.is_some_and(|file| file.vars.iter().any(|stored| stored == var)) {
+        return Ok(false);
+    }
+    update(|secrets| {
+        secrets.remove(var);
+    })
+}
+
+fn update(change: impl FnOnce(&mut BTreeMap<String, String>)) -> anyhow::Result<bool> {
+    let path = path().ok_or_else(|| anyhow::anyhow!("Cannot find a config directory"))?;
+    let (mut secrets, passphrase) = match read(&path)? {
+        Some(file) => {
+            let passphrase = passphrase(&path, false)?;
+            (open(&file, &passphrase)?, passphrase)
+        }
+        None => (BTreeMap::new(), passphrase(&path, true)?),
+    };
+    change(&mut secrets);
+    let file = seal(&secrets, &passphrase, ITERATIONS)?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    crate::output::write_private(&path, &serde_json::to_string_pretty(&file)?)?;
+    *UNLOCKED.lock().map_err(|_| anyhow::anyhow!("credentials lock poisoned"))? = Some(secrets);
+    Ok(true)
+}
+
+fn read(path: &Path) -> anyhow::Result<Option<File>> {
+    let contents = match std::fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => anyhow::bail!("Failed to read {}: {}", path.display(), e),
+    };
+    let file: File = serde_json::from_str(&contents).map_err(|e| anyhow::anyhow!("Invalid {}: {}", path.display(), e))?;
+    if file.version != 1 {
+        anyhow::bail!("{} is version {}, which this commitgen can't read", path.display(), file.version);
+    }
+    Ok(Some(file))
+}
+
+/// `COMMITGEN_PASSPHRASE`, then `passphrase_command`'s output, then a
+/// hidden prompt; a new file's passphrase is asked for twice.
+fn passphrase(path: &Path, new: bool) -> anyhow::Result<String> {
+    if let Ok(passphrase) = std::env::var(PASSPHRASE_VAR)
+        && !passphrase.is_empty()
+    {
+        return Ok(passphrase);
+    }
+    if let Some(Some(command)) = PASSPHRASE_COMMAND.get() {
+        return run_command(command);
+    }
+    if !console::user_attended_stderr() {
+        anyhow::bail!("set {} or passphrase_command to unlock {} without a terminal", PASSPHRASE_VAR, path.display());
+    }
+    let mut prompt = dialoguer::Password::new();
+    if new {
+        prompt = prompt
+            .with_prompt(format!("New passphrase for {}", path.display()))
+            .with_confirmation("Repeat it", "The passphrases don't match");
+    } else {
+        prompt = prompt.with_prompt(format!("Passphrase for {}", path.display()));
+    }
+    Ok(prompt.interact()?)
+}
+
+fn run_command(command: &str) -> anyhow::Result<String> {
+    let output = if cfg!(windows) {
+        std::process::Command::new("cmd").args(["/C", command]).output()
+    } else {
+        std::process::Command::new("sh").args(["-c", command]).output()
+    }
+    .map_err(|e| anyhow::anyhow!("Cannot run passphrase_command: {}", e))?;
+    if !output.status.success() {
+        anyhow::bail!("passphrase_command failed: {}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+    let passphrase = String::from_utf8(output.stdout)?.trim_end_matches(['\r', '\n']).to_string();
+    if passphrase.is_empty() {
+        anyhow::bail!("passphrase_command printed nothing");
+    }
+    Ok(passphrase)
+}
+
+fn key(passphrase: &str, salt: &[u8], iterations: u32) -> anyhow::Result<aead::LessSafeKey> {
+    let iterations = NonZeroU32::new(iterations).ok_or_else(|| anyhow::anyhow!("iterations must be above 0"))?;
+    let mut key = [0u8; 32];
+    pbkdf2::derive(pbkdf2::PBKDF2_HMAC_SHA256, iterations, salt, passphrase.as_bytes(), &mut key);
+    let key = aead::UnboundKey::new(&aead::CHACHA20_POLY1305, &key).map_err(|_| anyhow::anyhow!("bad key length"))?;
+    Ok(aead::LessSafeKey::new(key))
+}
+
+/// What's authenticated along with the secrets: everything else in the
+/// file, so the list of names can't be changed without it showing.
+fn associated_data(version: u32, iterations: u32, vars: &[String]) -> aead::Aad<Vec<u8>> {
+    aead::Aad::from(format!("commitgen-credentials:{}:{}:{}", version, iterations, vars.join(",")).into_bytes())
+}
+
+fn seal(secrets: &BTreeMap<String, String>, passphrase: &str, iterations: u32) -> anyhow::Result<File> {
+    let random = ring::rand::SystemRandom::new();
+    let mut salt = [0u8; 16];
+    let mut nonce = [0u8; aead::NONCE_LEN];
+    random.fill(&mut salt).and_then(|_| random.fill(&mut nonce)).map_err(|_| anyhow::anyhow!("no randomness available"))?;
+
+    let vars: Vec<String> = secrets.keys().cloned().collect();
+    let mut data = serde_json::to_vec(secrets)?;
+    key(passphrase, &salt, iterations)?
+        .seal_in_place_append_tag(aead::Nonce::assume_unique_for_key(nonce), associated_data(1, iterations, &vars), &mut data)
+        .map_err(|_| anyhow::anyhow!("encryption failed"))?;
+    Ok(File {
+        version: 1,
+        iterations,
+        salt: STANDARD.encode(salt),
+        nonce: STANDARD.encode(nonce),
+        vars,
+        ciphertext: STANDARD.encode(data),
+    })
+}
+
+fn open(file: &File, passphrase: &str) -> anyhow::Result<BTreeMap<String, String>> {
+    let salt = STANDARD.decode(&file.salt)?;
+    let nonce = aead::Nonce::try_assume_unique_for_key(&STANDARD.decode(&file.nonce)?)
+        .map_err(|_| anyhow::anyhow!("the nonce is the wrong length"))?;
+    let mut data = STANDARD.decode(&file.ciphertext)?;
+    let plaintext = key(passphrase, &salt, file.iterations)?
+        .open_in_place(nonce, associated_data(file.version, file.iterations, &file.vars), &mut data)
+        .map_err(|_| anyhow::anyhow!("wrong passphrase, or the file has been changed"))?;
+    Ok(serde_json::from_slice(plaintext)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seal_and_open() {
+        let secrets: BTreeMap<String, String> = [("GEMINI_API_KEY".to_string(), "AIza-secret".to_string())].into();
+        let file = seal(&secrets, "correct horse", 1000).unwrap();
+        assert_eq!(file.vars, ["GEMINI_API_KEY"]);
+        assert!(!file.ciphertext.contains("AIza"));
+        assert_eq!(open(&file, "correct horse").unwrap(), secrets);
+
+        let error = open(&file, "wrong horse").unwrap_err().to_string();
+        assert_eq!(error, "wrong passphrase, or the file has been changed");
+
+        let renamed = File { vars: vec!["OPENAI_API_KEY".to_string()], ..file.clone() };
+        assert!(open(&renamed, "correct horse").is_err());
+    }
+}
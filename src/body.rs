@@ -0,0 +1,135 @@
+use clap::ValueEnum;
+use serde::Deserialize;
+
+use crate::trailers;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum BodyMode {
+    /// Whatever body the model sees fit to write
+    #[default]
+    Normal,
+    /// A bullet per changed file or area, each summarised from its own hunks
+    Detailed,
+    /// The subject line alone
+    None,
+}
+
+/// What may follow a message's subject.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Shape {
+    pub body: bool,
+    /// Paragraphs and list items the body may have; each bullet counts as one.
+    pub max_items: Option<usize>,
+    /// Whether the model's trailers are kept. Those commitgen adds itself
+    /// (tickets, co-authors, sign-offs) come later and aren't affected.
+    pub footers: bool,
+}
+
+impl Default for Shape {
+    fn default() -> Self {
+        Self { body: true, max_items: None, footers: true }
+    }
+}
+
+impl Shape {
+    /// What the prompt says about the body and footers, empty when
+    /// anything goes.
+    pub fn instructions(&self) -> String {
+        let mut rules = Vec::new();
+        if !self.body {
+            rules.push("Write only the subject line: no body.".to_string());
+        } else if let Some(max) = self.max_items {
+            rules.push(format!(
+                "Keep the body to at most {} {}; each bullet point counts as one.",
+                max,
+                if max == 1 { "paragraph" } else { "paragraphs" }
+            ));
+        }
+        if !self.footers {
+            rules.push("Don't add footers or trailers such as `Refs:` or `BREAKING CHANGE:`.".to_string());
+        }
+        rules.join(" ")
+    }
+
+    /// Cuts `message` down to this shape, for models that don't keep to
+    /// the instructions.
+    pub fn apply(&self, message: &str) -> String {
+        let parts = trailers::split(message);
+        let mut message = parts.subject.to_string();
+        if self.body && !parts.body.is_empty() {
+            let body = match self.max_items {
+                Some(max) => limit_items(parts.body, max),
+                None => parts.body.to_string(),
+            };
+            if !body.is_empty() {
+                message = format!("{}\n\n{}", message, body);
+            }
+        }
+        if self.footers && !parts.footers.is_empty() {
+            message = format!("{}\n\n{}", message, parts.footers.join("\n"));
+        }
+        message
+    }
+}
+
+fn is_list_item(line: &str) -> bool {
+    let line = line.trim_start();
+    let digits = line.chars().take_while(char::is_ascii_digit).count();
+    line.starts_with("- ") || line.starts_with("* ") || (digits > 0 && line[digits..].starts_with(". "))
+}
+
+/// The first `max` items of `body`: paragraphs, or each item of a list,
+/// along with the lines it wraps onto.
+fn limit_items(body: &str, max: usize) -> String {
+    let mut kept: Vec<String> = Vec::new();
+    let mut items = 0;
+    for paragraph in body.split("\n\n").map(str::trim_end).filter(|p| !p.trim().is_empty()) {
+        let mut lines = Vec::new();
+        for line in paragraph.lines() {
+            let starts_item = is_list_item(line) || lines.is_empty();
+            if starts_item {
+                if items == max {
+                    break;
+                }
+                items += 1;
+            }
+            lines.push(line);
+        }
+        if !lines.is_empty() {
+            kept.push(lines.join("\n"));
+        }
+        if items == max {
+            break;
+        }
+    }
+    kept.join("\n\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MESSAGE: &str = "feat: add x\n\nWhy it's needed.\n\n- one\n- two\n  wrapped\n- three\n\nRefs: #1";
+
+    #[test]
+    fn test_apply() {
+        assert_eq!(Shape::default().apply(MESSAGE), MESSAGE);
+        assert_eq!(Shape { body: false, ..Shape::default() }.apply(MESSAGE), "feat: add x\n\nRefs: #1");
+        assert_eq!(
+            Shape { max_items: Some(3), footers: false, ..Shape::default() }.apply(MESSAGE),
+            "feat: add x\n\nWhy it's needed.\n\n- one\n- two\n  wrapped"
+        );
+        assert_eq!(Shape { body: false, footers: false, max_items: None }.apply(MESSAGE), "feat: add x");
+    }
+
+    #[test]
+    fn test_instructions() {
+        assert_eq!(Shape::default().instructions(), "");
+        assert_eq!(
+            Shape { max_items: Some(1), ..Shape::default() }.instructions(),
+            "Keep the body to at most 1 paragraph; each bullet point counts as one."
+        );
+        assert!(Shape { body: false, footers: false, max_items: Some(2) }.instructions().starts_with("Write only the subject line"));
+    }
+}
@@ -0,0 +1,109 @@
+/// Used when neither `--pattern` nor `branch_pattern` in the config is set.
+pub const DEFAULT_PATTERN: &str = "{type}/{scope}-{description}";
+
+/// Long descriptions make unwieldy branch names; words past this are dropped.
+const MAX_DESCRIPTION_WORDS: usize = 6;
+
+/// The parts of a branch name, taken from a conventional-commit style
+/// subject the model is asked for.
+#[derive(Debug, PartialEq)]
+pub struct Parts {
+    pub kind: String,
+    pub scope: String,
+    pub description: String,
+}
+
+/// Reads `type(scope): description` from the first line of the model's
+/// reply. Anything that doesn't fit the form is taken as the description.
+pub fn parse(reply: &str) -> Parts {
+    let line = reply
+        .lines()
+        .map(|line| line.trim().trim_matches('`').trim())
+        .find(|line| !line.is_empty())
+        .unwrap_or("");
+
+    let Some((header, description)) = line.split_once(':').filter(|(header, _)| !header.contains(' ')) else {
+        return Parts { kind: String::new(), scope: String::new(), description: slugify(line) };
+    };
+    let header = header.strip_suffix('!').unwrap_or(header);
+    let (kind, scope) = match header.split_once('(') {
+        Some((kind, scope)) => (kind, scope.trim_end_matches(')')),
+        None => (header, ""),
+    };
+    Parts { kind: slugify(kind), scope: slugify(scope), description: slugify(description) }
+}
+
+/// Fills `{type}`, `{scope}` and `{description}` into `pattern`. Separators
+/// left dangling by an empty part are dropped, so `{type}/{scope}-{description}`
+/// without a scope gives `feat/add-login`.
+pub fn render(pattern: &str, parts: &Parts) -> String {
+    let filled = pattern
+        .replace("{type}", &parts.kind)
+        .replace("{scope}", &parts.scope)
+        .replace("{description}", &parts.description);
+
+    let mut name = String::new();
+    for c in filled.chars() {
+        let previous = name.chars().last();
+        match c {
+            '-' | '_' if matches!(previous, None | Some('-' | '_' | '/')) => {}
+            '/' if matches!(previous, None | Some('/')) => {}
+            '/' if matches!(previous, Some('-' | '_')) => {
+                name.pop();
+                name.push('/');
+            }
+            c => name.push(c),
+        }
+    }
+    name.trim_end_matches(['-', '_', '/']).to_string()
+}
+
+/// Lowercase ASCII words joined by hyphens, at most
+/// [`MAX_DESCRIPTION_WORDS`] of them.
+pub fn slugify(text: &str) -> String {
+    text.to_lowercase()
+        .replace(['\'', '’'], "")
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .take(MAX_DESCRIPTION_WORDS)
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            parse("feat(auth): Add login support\n"),
+            Parts { kind: "feat".into(), scope: "auth".into(), description: "add-login-support".into() }
+        );
+        assert_eq!(
+            parse("```\nfix!: handle empty input\n```"),
+            Parts { kind: "fix".into(), scope: String::new(), description: "handle-empty-input".into() }
+        );
+        assert_eq!(parse("Handle empty input: parser").kind, "");
+    }
+
+    #[test]
+    fn test_render() {
+        let parts = parse("feat(auth): add login support");
+        assert_eq!(render(DEFAULT_PATTERN, &parts), "feat/auth-add-login-support");
+        assert_eq!(render("{description}", &parts), "add-login-support");
+
+        let parts = parse("fix: handle empty input");
+        assert_eq!(render(DEFAULT_PATTERN, &parts), "fix/handle-empty-input");
+        assert_eq!(render("jdoe/{type}-{scope}/{description}", &parts), "jdoe/fix/handle-empty-input");
+
+        let parts = parse("handle empty input");
+        assert_eq!(render(DEFAULT_PATTERN, &parts), "handle-empty-input");
+    }
+
+    #[test]
+    fn test_slugify() {
+        assert_eq!(slugify("Don't panic on `None` values!"), "dont-panic-on-none-values");
+        assert_eq!(slugify("one two three four five six seven"), "one-two-three-four-five-six");
+    }
+}
@@ -0,0 +1,283 @@
+use crate::trailers;
+
+/// A public item the diff takes away or changes.
+#[derive(Debug, PartialEq)]
+pub struct Change {
+    pub name: String,
+    /// The declaration as it was before the change.
+    pub before: String,
+    /// Whether the item is still declared, with a different signature.
+    pub signature_changed: bool,
+}
+
+impl Change {
+    pub fn describe(&self) -> String {
+        if self.signature_changed {
+            format!("the signature of `{}` changed (was `{}`)", self.name, self.before)
+        } else {
+            format!("`{}` was removed or renamed (was `{}`)", self.name, self.before)
+        }
+    }
+}
+
+/// Whether a change breaks compatibility, as detected or as the user said.
+#[derive(Debug, PartialEq)]
+pub enum Verdict {
+    Breaking(Vec<Change>),
+    NotBreaking,
+    /// Nothing was detected; the model decides.
+    Unknown,
+}
+
+impl Verdict {
+    /// `force` comes from `--breaking` (`Some(true)`) or `--no-breaking`
+    /// (`Some(false)`); otherwise `diff` decides, when there is one.
+    pub fn new(force: Option<bool>, diff: Option<&str>) -> Self {
+        let changes = diff.map(detect).unwrap_or_default();
+        match force {
+            Some(true) => Verdict::Breaking(changes),
+            Some(false) => Verdict::NotBreaking,
+            None if changes.is_empty() => Verdict::Unknown,
+            None => Verdict::Breaking(changes),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Language {
+    Rust,
+    JavaScript,
+    Go,
+    Python,
+}
+
+impl Language {
+    fn from_path(path: &str) -> Option<Self> {
+        match path.rsplit_once('.')?.1 {
+            "rs" => Some(Language::Rust),
+            "js" | "jsx" | "mjs" | "cjs" | "ts" | "tsx" | "mts" => Some(Language::JavaScript),
+            "go" => Some(Language::Go),
+            "py" => Some(Language::Python),
+            _ => None,
+        }
+    }
+
+    /// The name of the public item `line` declares, if it declares one.
+    fn public_item(self, line: &str) -> Option<&str> {
+        match self {
+            Language::Rust => declared_name(
+                line.strip_prefix("pub ")?,
+                &["async", "unsafe", "extern", "default", "mut"],
+                &["fn", "struct", "enum", "trait", "type", "const", "static", "mod", "union", "macro"],
+            ),
+            Language::JavaScript => declared_name(
+                line.strip_prefix("export ")?,
+                &["default", "async", "declare", "abstract"],
+                &["function", "function*", "class", "const", "let", "var", "interface", "type", "enum"],
+            ),
+            Language::Go => {
+                let rest = line.strip_prefix("func ")?;
+                // Methods: `func (r *Receiver) Name(`.
+                let rest = match rest.strip_prefix('(') {
+                    Some(receiver) => receiver.split_once(") ")?.1,
+                    None => rest,
+                };
+                Some(ident(rest)).filter(|name| name.starts_with(|c: char| c.is_ascii_uppercase()))
+            }
+            // Only top-level definitions; methods are indented.
+            Language::Python => {
+                let rest = line.strip_prefix("def ").or_else(|| line.strip_prefix("class "))?;
+                Some(ident(rest)).filter(|name| !name.starts_with('_'))
+            }
+        }
+    }
+}
+
+/// Skips `qualifiers` in `rest`, then returns the name after the first of
+/// `keywords`. A `const` in front of `fn` counts as a qualifier.
+fn declared_name<'a>(rest: &'a str, qualifiers: &[&str], keywords: &[&str]) -> Option<&'a str> {
+    let mut words = rest.split_whitespace().peekable();
+    while let Some(word) = words.next() {
+        if qualifiers.contains(&word) || word.starts_with('"') || (word == "const" && words.peek() == Some(&"fn")) {
+            continue;
+        }
+        if !keywords.contains(&word) {
+            return None;
+        }
+        return words.find(|word| !qualifiers.contains(word)).map(ident).filter(|name| !name.is_empty());
+    }
+    None
+}
+
+fn ident(s: &str) -> &str {
+    let end = s.find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '$')).unwrap_or(s.len());
+    &s[..end]
+}
+
+/// A declaration line with its whitespace and opening brace normalised, so
+/// re-indented or reformatted lines compare equal.
+fn signature(line: &str) -> String {
+    let line = line.trim().trim_end_matches('{').trim_end();
+    line.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Public items in Rust, JavaScript/TypeScript, Go and Python files that
+/// `diff` removes, renames or changes the signature of. Declarations that
+/// only move (the same line removed in one place and added in another)
+/// don't count.
+pub fn detect(diff: &str) -> Vec<Change> {
+    let mut removed: Vec<(String, String)> = Vec::new();
+    let mut added: Vec<(String, String)> = Vec::new();
+    let mut language = None;
+
+    for line in diff.lines() {
+        if let Some(header) = line.strip_prefix("diff --git ") {
+            language = header.split_once(" b/").and_then(|(_, path)| Language::from_path(path));
+            continue;
+        }
+        let Some(language) = language else { continue };
+        if line.starts_with("---") || line.starts_with("+++") {
+            continue;
+        }
+        let (list, code) = match (line.strip_prefix('-'), line.strip_prefix('+')) {
+            (Some(code), _) => (&mut removed, code),
+            (_, Some(code)) => (&mut added, code),
+            _ => continue,
+        };
+        if let Some(name) = language.public_item(code) {
+            list.push((name.to_string(), signature(code)));
+        }
+    }
+
+    let mut changes: Vec<Change> = Vec::new();
+    for (name, before) in removed {
+        if added.iter().any(|(_, after)| *after == before) || changes.iter().any(|c| c.name == name) {
+            continue;
+        }
+        let signature_changed = added.iter().any(|(other, _)| *other == name);
+        changes.push(Change { name, before, signature_changed });
+    }
+    changes
+}
+
+fn is_breaking_footer(line: &str) -> bool {
+    line.starts_with("BREAKING CHANGE:") || line.starts_with("BREAKING-CHANGE:")
+}
+
+/// Splits a conventional subject into its `type(scope)` header and the
+/// rest, or `None` when it isn't one.
+fn split_header(subject: &str) -> Option<(&str, &str)> {
+    subject.split_once(':').filter(|(header, _)| !header.is_empty() && !header.contains(' '))
+}
+
+/// Marks a Conventional Commits message as breaking: a `!` after the type
+/// or scope, and a `BREAKING CHANGE:` footer listing `changes` unless the
+/// model already wrote one.
+pub fn mark(message: &str, changes: &[Change]) -> String {
+    let (subject, rest) = message.split_once('\n').unwrap_or((message, ""));
+    let subject = match split_header(subject) {
+        Some((header, description)) if !header.ends_with('!') => format!("{}!:{}", header, description),
+        _ => subject.to_string(),
+    };
+    let message = if rest.is_empty() { subject } else { format!("{}\n{}", subject, rest) };
+
+    if message.lines().any(is_breaking_footer) {
+        return message;
+    }
+    let footer = if changes.is_empty() {
+        "BREAKING CHANGE: this change is not backwards compatible".to_string()
+    } else {
+        let descriptions: Vec<String> = changes.iter().map(Change::describe).collect();
+        format!("BREAKING CHANGE: {}", descriptions.join("; "))
+    };
+    trailers::append(&message, &footer)
+}
+
+/// Takes the breaking change markers back out of a message.
+pub fn unmark(message: &str) -> String {
+    let (subject, rest) = message.split_once('\n').unwrap_or((message, ""));
+    let subject = match split_header(subject) {
+        Some((header, description)) if header.ends_with('!') => {
+            format!("{}:{}", header.trim_end_matches('!'), description)
+        }
+        _ => subject.to_string(),
+    };
+
+    let mut lines = vec![subject];
+    let mut in_footer = false;
+    for line in rest.lines() {
+        if is_breaking_footer(line) {
+            in_footer = true;
+        } else if line.trim().is_empty() || trailers::is_trailer(line) {
+            in_footer = false;
+        }
+        if !in_footer {
+            lines.push(line.to_string());
+        }
+    }
+    lines.join("\n").trim_end().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DIFF: &str = "diff --git a/src/lib.rs b/src/lib.rs\n\
+        --- a/src/lib.rs\n\
+        +++ b/src/lib.rs\n\
+        -pub fn parse(input: &str) -> Ast {\n\
+        +pub fn parse(input: &str, options: Options) -> Ast {\n\
+        -pub struct Token;\n\
+        -pub(crate) fn helper() {}\n\
+        -pub const fn moved() {}\n\
+        diff --git a/src/other.rs b/src/other.rs\n\
+        +pub const fn moved() {}\n\
+        diff --git a/README.md b/README.md\n\
+        -pub fn documented() {}\n";
+
+    #[test]
+    fn test_detect() {
+        assert_eq!(
+            detect(DIFF),
+            vec![
+                Change { name: "parse".into(), before: "pub fn parse(input: &str) -> Ast".into(), signature_changed: true },
+                Change { name: "Token".into(), before: "pub struct Token;".into(), signature_changed: false },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_public_item_other_languages() {
+        assert_eq!(Language::JavaScript.public_item("export default async function load() {"), Some("load"));
+        assert_eq!(Language::Go.public_item("func (c *Client) Send(msg string) error {"), Some("Send"));
+        assert_eq!(Language::Go.public_item("func helper() {"), None);
+        assert_eq!(Language::Python.public_item("def _private():"), None);
+        assert_eq!(Language::Python.public_item("class Parser:"), Some("Parser"));
+    }
+
+    #[test]
+    fn test_verdict() {
+        assert_eq!(Verdict::new(None, Some("+x\n")), Verdict::Unknown);
+        assert_eq!(Verdict::new(Some(false), Some(DIFF)), Verdict::NotBreaking);
+        assert!(matches!(Verdict::new(None, Some(DIFF)), Verdict::Breaking(changes) if changes.len() == 2));
+    }
+
+    #[test]
+    fn test_mark() {
+        let changes = detect(DIFF);
+        assert_eq!(
+            mark("feat(parser): take options", &changes[..1]),
+            "feat(parser)!: take options\n\nBREAKING CHANGE: the signature of `parse` changed (was `pub fn parse(input: &str) -> Ast`)"
+        );
+        let written = "feat!: take options\n\nBREAKING CHANGE: pass `Options::default()`";
+        assert_eq!(mark(written, &changes), written);
+    }
+
+    #[test]
+    fn test_unmark() {
+        assert_eq!(
+            unmark("feat(parser)!: take options\n\nBody.\n\nBREAKING CHANGE: pass\n options\nRefs: #1"),
+            "feat(parser): take options\n\nBody.\n\nRefs: #1"
+        );
+    }
+}
@@ -0,0 +1,196 @@
+use crate::git;
+
+/// Keep a Changelog's sections, in the order they are written.
+pub const SECTIONS: &[&str] = &["Added", "Changed", "Deprecated", "Removed", "Fixed", "Security"];
+
+/// Groups with at least this many commits are summarised by the model
+/// rather than listed one by one.
+pub const NOISY_RUN: usize = 4;
+
+/// Commit types that don't change anything users see.
+const SKIPPED_TYPES: &[&str] = &["docs", "style", "test", "build", "ci", "chore"];
+
+/// The most recent tag reachable from `rev`'s parent, so that a tagged
+/// `rev` gets the changes since the tag before it.
+pub fn previous_tag(rev: &str) -> anyhow::Result<String> {
+    git::run(&["describe", "--tags", "--abbrev=0", &format!("{}^", rev)])
+        .map(|tag| tag.trim().to_string())
+        .map_err(|_| anyhow::anyhow!("No tag before {} to start from; pass --from", rev))
+}
+
+/// The version a section is headed with: `rev`'s tag without a leading
+/// `v`, or "Unreleased" when it isn't tagged.
+pub fn version(rev: &str) -> String {
+    match git::run(&["describe", "--tags", "--exact-match", rev]) {
+        Ok(tag) => {
+            let tag = tag.trim();
+            tag.strip_prefix('v').filter(|v| v.starts_with(|c: char| c.is_ascii_digit())).unwrap_or(tag).to_string()
+        }
+        Err(_) => "Unreleased".to_string(),
+    }
+}
+
+/// `rev`'s commit date as `YYYY-MM-DD`.
+pub fn date(rev: &str) -> anyhow::Result<String> {
+    Ok(git::run(&["log", "-1", "--format=%cs", rev, "--"])?.trim().to_string())
+}
+
+/// Subjects of the non-merge commits in `from..to`, oldest first.
+pub fn subjects(from: &str, to: &str) -> anyhow::Result<Vec<String>> {
+    let range = format!("{}..{}", from, to);
+    let subjects: Vec<String> = git::run(&["log", "--reverse", "--no-merges", "--format=%s", &range, "--"])?
+        .lines()
+        .map(str::to_string)
+        .collect();
+    if subjects.is_empty() {
+        anyhow::bail!("No commits in {}", range);
+    }
+    Ok(subjects)
+}
+
+/// A subject in `type(scope)!: description` form.
+#[derive(Debug, PartialEq)]
+pub struct Entry {
+    pub kind: String,
+    pub scope: Option<String>,
+    pub breaking: bool,
+    pub description: String,
+}
+
+pub fn parse(subject: &str) -> Option<Entry> {
+    let (header, description) = subject.split_once(": ")?;
+    let (header, breaking) = match header.strip_suffix('!') {
+        Some(header) => (header, true),
+        None => (header, false),
+    };
+    let (kind, scope) = match header.split_once('(') {
+        Some((kind, scope)) => (kind, Some(scope.strip_suffix(')')?.to_string())),
+        None => (header, None),
+    };
+    if kind.is_empty() || !kind.chars().all(|c| c.is_ascii_alphabetic()) {
+        return None;
+    }
+    Some(Entry {
+        kind: kind.to_ascii_lowercase(),
+        scope,
+        breaking,
+        description: description.trim().to_string(),
+    })
+}
+
+/// Commits sharing a section and scope.
+#[derive(Debug, PartialEq)]
+pub struct Group {
+    pub section: &'static str,
+    pub scope: Option<String>,
+    /// Bullet texts, oldest first.
+    pub items: Vec<String>,
+}
+
+/// Sorts commit subjects (oldest first) into sections and scopes. Subjects
+/// that aren't conventional go under "Changed"; housekeeping types are left
+/// out.
+pub fn group(subjects: &[String]) -> Vec<Group> {
+    let mut groups: Vec<Group> = Vec::new();
+    for subject in subjects {
+        let (section, scope, item) = match parse(subject) {
+            Some(entry) => {
+                let section = match entry.kind.as_str() {
+                    "feat" => "Added",
+                    "fix" => "Fixed",
+                    "revert" => "Removed",
+                    kind if SKIPPED_TYPES.contains(&kind) && !entry.breaking => continue,
+                    _ => "Changed",
+                };
+                let item = if entry.breaking {
+                    format!("**Breaking:** {}", entry.description)
+                } else {
+                    entry.description
+                };
+                (section, entry.scope, item)
+            }
+            None => ("Changed", None, subject.clone()),
+        };
+
+        match groups.iter_mut().find(|g| g.section == section && g.scope == scope) {
+            Some(group) => group.items.push(item),
+            None => groups.push(Group { section, scope, items: vec![item] }),
+        }
+    }
+    groups.sort_by_key(|g| SECTIONS.iter().position(|s| *s == g.section));
+    groups
+}
+
+/// The bullet for one item, with its scope in bold.
+pub fn bullet(scope: Option<&str>, text: &str) -> String {
+    match scope {
+        Some(scope) => format!("- **{}:** {}", scope, text),
+        None => format!("- {}", text),
+    }
+}
+
+/// A `## [version] - date` section with a `### Heading` per non-empty
+/// section of `bullets`.
+pub fn render(version: &str, date: &str, bullets: &[(&str, Vec<String>)]) -> String {
+    let mut out = format!("## [{}] - {}\n", version, date);
+    for section in SECTIONS {
+        let lines: Vec<&String> = bullets
+            .iter()
+            .filter(|(s, _)| s == section)
+            .flat_map(|(_, lines)| lines)
+            .collect();
+        if lines.is_empty() {
+            continue;
+        }
+        out.push_str(&format!("\n### {}\n\n", section));
+        for line in lines {
+            out.push_str(line);
+            out.push('\n');
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            parse("feat(api)!: drop v1"),
+            Some(Entry { kind: "feat".into(), scope: Some("api".into()), breaking: true, description: "drop v1".into() })
+        );
+        assert_eq!(parse("Fix: typo").unwrap().kind, "fix");
+        assert_eq!(parse("Update README"), None);
+        assert_eq!(parse("Merge branch 'x': y"), None);
+    }
+
+    #[test]
+    fn test_group() {
+        let subjects: Vec<String> = ["fix(cli): a", "feat: b", "chore: c", "fix(cli): d", "tidy up", "ci!: e"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        assert_eq!(
+            group(&subjects),
+            vec![
+                Group { section: "Added", scope: None, items: vec!["b".into()] },
+                Group { section: "Changed", scope: None, items: vec!["tidy up".into(), "**Breaking:** e".into()] },
+                Group { section: "Fixed", scope: Some("cli".into()), items: vec!["a".into(), "d".into()] },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_render() {
+        let bullets = vec![
+            ("Fixed", vec![bullet(Some("cli"), "handle empty input")]),
+            ("Added", vec![bullet(None, "login support")]),
+        ];
+        assert_eq!(
+            render("1.3.0", "2026-10-15", &bullets),
+            "## [1.3.0] - 2026-10-15\n\n### Added\n\n- login support\n\n### Fixed\n\n- **cli:** handle empty input\n"
+        );
+    }
+}
@@ -0,0 +1,319 @@
+use crate::style;
+
+pub const DEFAULT_MAX_SUBJECT_LENGTH: usize = 72;
+
+/// Commit types from the Conventional Commits spec and the Angular
+/// convention it grew out of.
+pub const CONVENTIONAL_TYPES: &[&str] = &[
+    "feat", "fix", "docs", "style", "refactor", "perf", "test", "build", "ci", "chore", "revert",
+];
+
+/// Angular's types, which semantic-release also reads.
+pub const ANGULAR_TYPES: &[&str] = &["build", "ci", "docs", "feat", "fix", "perf", "refactor", "test"];
+
+pub const KARMA_TYPES: &[&str] = &["feat", "fix", "docs", "style", "refactor", "perf", "test", "chore"];
+
+/// Everything below this line is dropped by `git commit --verbose`.
+const SCISSORS: &str = "# ------------------------ >8 ------------------------";
+
+/// The style used when neither `--style` nor the config file names one.
+pub const DEFAULT_STYLE: &str = "conventional";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Style {
+    Conventional,
+    Gitmoji,
+    Angular,
+    Karma,
+    /// A capitalised subject with no prefix.
+    Plain,
+    /// `subsystem: Summary`, signed off.
+    Kernel,
+    /// Any other free-form style; only the generic rules apply.
+    Other,
+}
+
+impl Style {
+    /// A preset's rules (or those of the preset a style file extends), or
+    /// for free-form names the closest built-in ones.
+    pub fn from_name(name: &str) -> Self {
+        if let Some(preset) = style::base(name) {
+            return preset.rules;
+        }
+        if style::is_custom(name) {
+            return Style::Other;
+        }
+        let name = name.to_ascii_lowercase();
+        if name.contains("conventional") {
+            Style::Conventional
+        } else if name.contains("gitmoji") {
+            Style::Gitmoji
+        } else {
+            Style::Other
+        }
+    }
+
+    /// Subjects take the `type(scope): description` form, so breaking
+    /// changes are marked and reverts prefixed the Conventional Commits way.
+    pub fn is_conventional_form(self) -> bool {
+        matches!(self, Style::Conventional | Style::Angular | Style::Karma)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Problem {
+    pub message: String,
+    /// How to fix it.
+    pub hint: String,
+}
+
+fn problem(message: impl Into<String>, hint: impl Into<String>) -> Problem {
+    Problem { message: message.into(), hint: hint.into() }
+}
+
+/// The message as git will record it: comments and anything after the
+/// scissors line removed, surrounding blank lines trimmed.
+pub fn clean(buffer: &str) -> String {
+    let lines: Vec<&str> = buffer
+        .lines()
+        .take_while(|line| *line != SCISSORS)
+        .filter(|line| !line.starts_with('#'))
+        .map(str::trim_end)
+        .collect();
+    lines.join("\n").trim_matches('\n').to_string()
+}
+
+/// How many terminal columns `text` takes up, which is what subject limits
+/// count: CJK characters are two columns wide.
+pub fn width(text: &str) -> usize {
+    console::measure_text_width(text)
+}
+
+pub fn check(buffer: &str, style: Style, max_subject_length: usize) -> Vec<Problem> {
+    let message = clean(buffer);
+    let mut lines = message.lines();
+    let Some(subject) = lines.next().filter(|s| !s.trim().is_empty()) else {
+        return vec![problem("The commit message is empty", "Write a subject line describing the change")];
+    };
+
+    // Messages git writes itself follow their own format.
+    if ["Merge ", "Revert \"", "fixup! ", "squash! ", "amend! "]
+        .iter()
+        .any(|prefix| subject.starts_with(prefix))
+    {
+        return Vec::new();
+    }
+
+    let mut problems = Vec::new();
+    let width = width(subject);
+    if width > max_subject_length {
+        let message = if width == subject.chars().count() {
+            format!("Subject is {} characters long, over the limit of {}", width, max_subject_length)
+        } else {
+            // CJK characters and most emoji take two columns, as in git log.
+            format!("Subject is {} columns wide, over the limit of {}", width, max_subject_length)
+        };
+        problems.push(problem(message, "Shorten the subject and move details into the body"));
+    }
+    if subject != subject.trim_start() {
+        problems.push(problem("Subject starts with whitespace", "Remove the leading spaces"));
+    }
+    if subject.ends_with('.') {
+        problems.push(problem("Subject ends with a period", "Drop the trailing period"));
+    }
+    if let Some(second) = lines.next()
+        && !second.trim().is_empty()
+    {
+        problems.push(problem(
+            "No blank line between the subject and the body",
+            "Insert an empty line after the subject",
+        ));
+    }
+
+    match style {
+        Style::Conventional => problems.extend(check_typed(subject, CONVENTIONAL_TYPES)),
+        Style::Angular => problems.extend(check_typed(subject, ANGULAR_TYPES)),
+        Style::Karma => problems.extend(check_typed(subject, KARMA_TYPES)),
+        Style::Gitmoji => problems.extend(check_gitmoji(subject)),
+        Style::Plain => problems.extend(check_plain(subject)),
+        Style::Kernel => problems.extend(check_kernel(subject, &message)),
+        Style::Other => {}
+    }
+    problems
+}
+
+/// `type(scope)!: description`, with the scope and `!` optional.
+fn check_typed(subject: &str, types: &[&str]) -> Option<Problem> {
+    let expected = "Use the form `type(scope): description`, e.g. `fix(parser): handle empty input`";
+    let Some((header, description)) = subject.split_once(':') else {
+        return Some(problem("Subject is missing the `type: ` prefix", expected));
+    };
+
+    let header = header.strip_suffix('!').unwrap_or(header);
+    let kind = match header.split_once('(') {
+        Some((kind, scope)) => {
+            let Some(scope) = scope.strip_suffix(')') else {
+                return Some(problem(format!("Unclosed scope in `{}`", header), expected));
+            };
+            if scope.trim().is_empty() || scope.contains(['(', ')', ' ']) {
+                return Some(problem(format!("Invalid scope `({})`", scope), "Use a single word such as `(api)`, or omit the scope"));
+            }
+            kind
+        }
+        None => header,
+    };
+
+    if !types.contains(&kind) {
+        return Some(problem(
+            format!("Unknown commit type `{}`", kind),
+            format!("Use one of: {}", types.join(", ")),
+        ));
+    }
+    if !description.starts_with(' ') || description.trim().is_empty() {
+        return Some(problem("Expected a description after `: `", expected));
+    }
+    None
+}
+
+/// An emoji, or a `:shortcode:`, before anything else.
+fn check_gitmoji(subject: &str) -> Option<Problem> {
+    let shortcode = subject
+        .strip_prefix(':')
+        .and_then(|rest| rest.split_once(':'))
+        .is_some_and(|(code, _)| !code.is_empty() && !code.contains(' '));
+    let emoji = subject.chars().next().is_some_and(|c| !c.is_ascii());
+    if shortcode || emoji {
+        None
+    } else {
+        Some(problem(
+            "Subject doesn't start with a gitmoji",
+            "Start with an emoji or its shortcode, e.g. `:bug: fix crash on empty input`",
+        ))
+    }
+}
+
+/// A `word: ` prefix, as in `fix: x` or `net: x`.
+fn prefix(subject: &str) -> Option<&str> {
+    subject.split_once(": ").map(|(prefix, _)| prefix).filter(|prefix| !prefix.is_empty() && !prefix.contains(' '))
+}
+
+fn check_plain(subject: &str) -> Vec<Problem> {
+    let mut problems = Vec::new();
+    if let Some(prefix) = prefix(subject) {
+        problems.push(problem(
+            format!("Subject starts with a `{}: ` prefix", prefix),
+            "Drop the prefix and start with the summary, e.g. `Fix crash on empty input`",
+        ));
+    } else if subject.chars().next().is_some_and(char::is_lowercase) {
+        problems.push(problem("Subject starts with a lowercase letter", "Capitalise the first word"));
+    }
+    problems
+}
+
+fn check_kernel(subject: &str, message: &str) -> Vec<Problem> {
+    let mut problems = Vec::new();
+    if prefix(subject).is_none() {
+        problems.push(problem(
+            "Subject doesn't name a subsystem",
+            "Use the form `subsystem: Summary`, e.g. `net: ipv4: Fix route leak`",
+        ));
+    }
+    if !message.lines().any(|line| line.starts_with("Signed-off-by: ")) {
+        problems.push(problem("No Signed-off-by trailer", "Sign off with `git commit -s` or commitgen's --signoff"));
+    }
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn messages(problems: Vec<Problem>) -> Vec<String> {
+        problems.into_iter().map(|p| p.message).collect()
+    }
+
+    #[test]
+    fn test_clean_strips_comments_and_scissors() {
+        let buffer = "feat: add check\n\n# Please enter the commit message\n# ------------------------ >8 ------------------------\ndiff --git a/x b/x\n";
+        assert_eq!(clean(buffer), "feat: add check");
+    }
+
+    #[test]
+    fn test_valid_conventional() {
+        assert!(check("feat(api)!: drop v1 endpoints\n\nBREAKING CHANGE: gone", Style::Conventional, 72).is_empty());
+        assert!(check("fix: handle empty input", Style::Conventional, 72).is_empty());
+    }
+
+    #[test]
+    fn test_invalid_conventional() {
+        assert_eq!(messages(check("added stuff", Style::Conventional, 72)), ["Subject is missing the `type: ` prefix"]);
+        assert_eq!(messages(check("feature: add x", Style::Conventional, 72)), ["Unknown commit type `feature`"]);
+        assert_eq!(messages(check("fix(: x", Style::Conventional, 72)), ["Unclosed scope in `fix(`"]);
+        assert_eq!(messages(check("fix:x", Style::Conventional, 72)), ["Expected a description after `: `"]);
+    }
+
+    #[test]
+    fn test_generic_rules() {
+        let long = format!("fix: {}", "a".repeat(80));
+        assert_eq!(
+            messages(check(&long, Style::Conventional, 72)),
+            ["Subject is 85 characters long, over the limit of 72"]
+        );
+        // 30 CJK characters take 60 columns.
+        let wide = format!("fix: {}", "修正".repeat(15));
+        assert_eq!(
+            messages(check(&wide, Style::Conventional, 50)),
+            ["Subject is 65 columns wide, over the limit of 50"]
+        );
+        assert!(check(&wide, Style::Conventional, 72).is_empty());
+        assert_eq!(
+            messages(check("fix: x.\nbody", Style::Other, 72)),
+            ["Subject ends with a period", "No blank line between the subject and the body"]
+        );
+        assert_eq!(messages(check("# only comments\n", Style::Other, 72)), ["The commit message is empty"]);
+    }
+
+    #[test]
+    fn test_git_generated_subjects_pass() {
+        assert!(check("Merge branch 'main' into feature", Style::Conventional, 72).is_empty());
+        assert!(check("fixup! feat: add x", Style::Conventional, 72).is_empty());
+    }
+
+    #[test]
+    fn test_angular_and_karma_types() {
+        assert!(check("perf(core): cache lookups", Style::Angular, 100).is_empty());
+        assert_eq!(messages(check("chore: bump", Style::Angular, 100)), ["Unknown commit type `chore`"]);
+        assert!(check("chore: bump", Style::Karma, 70).is_empty());
+    }
+
+    #[test]
+    fn test_plain() {
+        assert!(check("Fix crash on empty input", Style::Plain, 50).is_empty());
+        assert_eq!(messages(check("fix crash", Style::Plain, 50)), ["Subject starts with a lowercase letter"]);
+        assert_eq!(messages(check("Fix: crash", Style::Plain, 50)), ["Subject starts with a `Fix: ` prefix"]);
+    }
+
+    #[test]
+    fn test_kernel() {
+        assert!(check("net: ipv4: Fix leak\n\nSigned-off-by: A <a@b.c>", Style::Kernel, 75).is_empty());
+        assert_eq!(
+            messages(check("Fix leak", Style::Kernel, 75)),
+            ["Subject doesn't name a subsystem", "No Signed-off-by trailer"]
+        );
+    }
+
+    #[test]
+    fn test_style_from_name() {
+        assert_eq!(Style::from_name("semantic-release"), Style::Angular);
+        assert_eq!(Style::from_name("conventional commit"), Style::Conventional);
+        assert_eq!(Style::from_name("my conventional-ish style"), Style::Conventional);
+        assert_eq!(Style::from_name("terse"), Style::Other);
+    }
+
+    #[test]
+    fn test_gitmoji() {
+        assert!(check(":bug: fix crash", Style::Gitmoji, 72).is_empty());
+        assert!(check("🐛 fix crash", Style::Gitmoji, 72).is_empty());
+        assert_eq!(messages(check("fix crash", Style::Gitmoji, 72)), ["Subject doesn't start with a gitmoji"]);
+    }
+}
@@ -0,0 +1,36 @@
+/// The questions in a model's reply to [`crate::prompt::for_questions`]:
+/// one a line, with any numbering or bullets taken off, at most `max` of
+/// them. `NONE`, or a reply without a question in it, means there are none.
+pub fn questions(reply: &str, max: usize) -> Vec<String> {
+    reply
+        .lines()
+        .map(|line| {
+            let line = line.trim().trim_start_matches(['-', '*', '•']).trim_start();
+            let digits = line.chars().take_while(char::is_ascii_digit).count();
+            match line[digits..].strip_prefix(['.', ')']) {
+                Some(rest) if digits > 0 => rest.trim_start(),
+                _ => line,
+            }
+        })
+        .filter(|line| line.ends_with('?'))
+        .take(max)
+        .map(str::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_questions() {
+        let reply = "Here are my questions:\n1. Why was the cache removed?\n2) Is the old flag still accepted?\n- What replaces it?";
+        assert_eq!(
+            questions(reply, 2),
+            ["Why was the cache removed?", "Is the old flag still accepted?"]
+        );
+        assert_eq!(questions(reply, 5).len(), 3);
+        assert!(questions("NONE", 3).is_empty());
+        assert!(questions("The change is clear.", 3).is_empty());
+    }
+}
@@ -0,0 +1,75 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use base64::Engine;
+
+/// Clipboard programs to try, in order, with their arguments. Those that
+/// aren't installed, or have no display to talk to, are skipped.
+const PROGRAMS: &[(&str, &[&str])] = if cfg!(target_os = "macos") {
+    &[("pbcopy", &[])]
+} else if cfg!(windows) {
+    &[("clip.exe", &[])]
+} else {
+    &[
+        ("wl-copy", &[]),
+        ("xclip", &["-selection", "clipboard"]),
+        ("xsel", &["--clipboard", "--input"]),
+    ]
+};
+
+/// Puts `text` on the clipboard and says how. Over SSH the local machine's
+/// clipboard is the one wanted, so the terminal is asked to set it with an
+/// OSC 52 escape sequence; that is also the fallback when no clipboard
+/// program works.
+pub fn copy(text: &str) -> anyhow::Result<&'static str> {
+    let remote = std::env::var_os("SSH_TTY").is_some() || std::env::var_os("SSH_CONNECTION").is_some();
+    if !remote {
+        for (program, args) in PROGRAMS {
+            if pipe_to(program, args, text) {
+                return Ok(program);
+            }
+        }
+    }
+    if !console::user_attended_stderr() {
+        anyhow::bail!("No clipboard program worked, and there's no terminal to copy through");
+    }
+    let sequence = osc52(text, std::env::var_os("TMUX").is_some());
+    std::io::stderr().write_all(sequence.as_bytes())?;
+    Ok("the terminal (OSC 52)")
+}
+
+fn pipe_to(program: &str, args: &[&str], text: &str) -> bool {
+    let Ok(mut child) = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+    else {
+        return false;
+    };
+    let written = child.stdin.take().is_some_and(|mut stdin| stdin.write_all(text.as_bytes()).is_ok());
+    child.wait().is_ok_and(|status| status.success()) && written
+}
+
+/// The escape sequence asking the terminal to set its clipboard to `text`,
+/// wrapped for tmux to pass through when `tmux` is set.
+pub fn osc52(text: &str, tmux: bool) -> String {
+    let sequence = format!("\x1b]52;c;{}\x07", base64::engine::general_purpose::STANDARD.encode(text));
+    if tmux {
+        format!("\x1bPtmux;{}\x1b\\", sequence.replace('\x1b', "\x1b\x1b"))
+    } else {
+        sequence
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_osc52() {
+        assert_eq!(osc52("fix: x", false), "\x1b]52;c;Zml4OiB4\x07");
+        assert_eq!(osc52("fix: x", true), "\x1bPtmux;\x1b\x1b]52;c;Zml4OiB4\x07\x1b\\");
+    }
+}
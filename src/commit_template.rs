@@ -0,0 +1,62 @@
+use std::path::Path;
+
+use crate::git;
+
+/// The commit message template set with git's `commit.template`, or `None`
+/// when there isn't one. Like git, a relative path is taken from the top
+/// of the working tree.
+pub fn load() -> anyhow::Result<Option<String>> {
+    let Some(path) = git::run(&["config", "--path", "--get", "commit.template"])
+        .ok()
+        .map(|path| path.trim().to_string())
+        .filter(|path| !path.is_empty())
+    else {
+        return Ok(None);
+    };
+    let path = match git::run(&["rev-parse", "--show-toplevel"]) {
+        Ok(root) if Path::new(&path).is_relative() => Path::new(root.trim()).join(&path),
+        _ => path.into(),
+    };
+    let template = std::fs::read_to_string(&path)
+        .map_err(|e| anyhow::anyhow!("Failed to read the commit.template {}: {}", path.display(), e))?;
+    Ok((!template.trim().is_empty()).then_some(template))
+}
+
+/// The character git starts comment lines with (`core.commentChar`),
+/// `#` unless configured otherwise.
+pub fn comment_char() -> char {
+    match git::config_value("core.commentChar").ok().flatten() {
+        Some(value) if value != "auto" => value.chars().next().unwrap_or('#'),
+        _ => '#',
+    }
+}
+
+/// `message` without comment lines, as git leaves it after the editor,
+/// for when it's committed without one.
+pub fn strip_comments(message: &str, comment: char) -> String {
+    let mut out: Vec<&str> = Vec::new();
+    for line in message.lines().filter(|line| !line.starts_with(comment)) {
+        let line = line.trim_end();
+        // Blank lines around removed comments would otherwise pile up.
+        if line.is_empty() && out.last().is_none_or(|last| last.is_empty()) {
+            continue;
+        }
+        out.push(line);
+    }
+    while out.last().is_some_and(|line| line.is_empty()) {
+        out.pop();
+    }
+    out.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_comments() {
+        let message = "feat: add login\n# Why:\n\nUsers asked for it.\n\n# Testing:\nManual.\n# end\n";
+        assert_eq!(strip_comments(message, '#'), "feat: add login\n\nUsers asked for it.\n\nManual.");
+        assert_eq!(strip_comments("fix: x\n; note\n# kept", ';'), "fix: x\n# kept");
+    }
+}
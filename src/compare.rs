@@ -0,0 +1,177 @@
+use console::measure_text_width;
+
+use crate::llm::{ClientResult, GenerationOptions};
+
+const COLUMN_GAP: usize = 3;
+/// Below this, side-by-side columns wrap too aggressively to be readable
+/// and candidates are stacked instead.
+const MIN_COLUMN_WIDTH: usize = 30;
+
+pub struct Candidate {
+    pub provider: String,
+    pub result: anyhow::Result<String>,
+}
+
+/// Sends `prompt` to every client at once and collects the results in the
+/// order the clients were given.
+pub async fn generate_all(
+    clients: Vec<(String, ClientResult, GenerationOptions)>,
+    prompt: &str,
+) -> Vec<Candidate> {
+    let futures = clients.into_iter().map(|(provider, client, options)| async move {
+        let result = match client {
+            Ok(client) => client.generate(prompt, &options).await,
+            Err(e) => Err(e),
+        };
+        Candidate { provider, result }
+    });
+    futures::future::join_all(futures).await
+}
+
+/// Lays the bodies out in columns under their headers, or one after the
+/// other when `width` is too narrow for that many columns.
+pub fn render(columns: &[(String, String)], width: usize) -> String {
+    if columns.is_empty() {
+        return String::new();
+    }
+
+    let total_gap = COLUMN_GAP * (columns.len() - 1);
+    let column_width = width.saturating_sub(total_gap) / columns.len();
+    if column_width < MIN_COLUMN_WIDTH {
+        return columns
+            .iter()
+            .map(|(header, body)| format!("{}\n{}\n", header, body))
+            .collect::<Vec<_>>()
+            .join("\n");
+    }
+
+    let wrapped: Vec<Vec<String>> = columns
+        .iter()
+        .map(|(header, body)| {
+            let mut lines = wrap(header, column_width);
+            lines.push(String::new());
+            lines.extend(wrap(body, column_width));
+            lines
+        })
+        .collect();
+    let height = wrapped.iter().map(Vec::len).max().unwrap_or(0);
+
+    let mut out = String::new();
+    for row in 0..height {
+        let mut line = String::new();
+        for (i, column) in wrapped.iter().enumerate() {
+            let cell = column.get(row).map(String::as_str).unwrap_or("");
+            line.push_str(cell);
+            if i + 1 < wrapped.len() {
+                let pad = column_width.saturating_sub(measure_text_width(cell)) + COLUMN_GAP;
+                line.push_str(&" ".repeat(pad));
+            }
+        }
+        out.push_str(line.trim_end());
+        out.push('\n');
+    }
+    out
+}
+
+/// Greedy word wrap that keeps existing line breaks. Words longer than
+/// `width` are split.
+pub fn wrap(text: &str, width: usize) -> Vec<String> {
+    let width = width.max(1);
+    let mut lines = Vec::new();
+
+    for source in text.lines() {
+        let mut current = String::new();
+        for word in source.split_whitespace() {
+            let mut word = word.to_string();
+            while measure_text_width(&word) > width {
+                if !current.is_empty() {
+                    lines.push(std::mem::take(&mut current));
+                }
+                let split = word
+                    .char_indices()
+                    .nth(width)
+                    .map(|(i, _)| i)
+                    .unwrap_or(word.len());
+                lines.push(word[..split].to_string());
+                word = word[split..].to_string();
+            }
+
+            let needed = if current.is_empty() { 0 } else { 1 } + measure_text_width(&word);
+            if !current.is_empty() && measure_text_width(&current) + needed > width {
+                lines.push(std::mem::take(&mut current));
+            }
+            if !current.is_empty() {
+                current.push(' ');
+            }
+            current.push_str(&word);
+        }
+        lines.push(current);
+    }
+
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::LLMClient;
+    use anyhow::Result;
+    use async_trait::async_trait;
+
+    struct Reply(&'static str);
+    #[async_trait]
+    impl LLMClient for Reply {
+        async fn generate(&self, _prompt: &str, _options: &GenerationOptions) -> Result<String> {
+            Ok(self.0.into())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_generate_all_keeps_order_and_errors() {
+        let options = GenerationOptions::default();
+        let clients: Vec<(String, ClientResult, GenerationOptions)> = vec![
+            ("gemini".into(), Ok(Box::new(Reply("feat: a"))), options),
+            ("openai".into(), Err(anyhow::anyhow!("OPENAI_API_KEY must be set in .env")), options),
+            ("ollama".into(), Ok(Box::new(Reply("feat: c"))), options),
+        ];
+
+        let candidates = generate_all(clients, "prompt").await;
+        assert_eq!(candidates.len(), 3);
+        assert_eq!(candidates[0].result.as_ref().unwrap(), "feat: a");
+        assert!(candidates[1].result.is_err());
+        assert_eq!(candidates[2].provider, "ollama");
+    }
+
+    #[test]
+    fn test_wrap() {
+        assert_eq!(wrap("fix: handle the empty input case", 12), ["fix: handle", "the empty", "input case"]);
+        assert_eq!(wrap("a\n\nb", 10), ["a", "", "b"]);
+        assert_eq!(wrap("abcdefghij", 4), ["abcd", "efgh", "ij"]);
+    }
+
+    #[test]
+    fn test_render_side_by_side() {
+        let columns = vec![
+            ("[1] gemini".to_string(), "feat: add login".to_string()),
+            ("[2] openai".to_string(), "feat(auth): add login support".to_string()),
+        ];
+
+        let out = render(&columns, 80);
+        let lines: Vec<&str> = out.lines().collect();
+        assert!(lines[0].starts_with("[1] gemini"));
+        assert!(lines[0].contains("[2] openai"));
+        assert!(lines[2].contains("feat: add login"));
+        assert!(lines[2].contains("feat(auth): add login support"));
+    }
+
+    #[test]
+    fn test_render_stacks_when_narrow() {
+        let columns = vec![
+            ("[1] gemini".to_string(), "feat: a".to_string()),
+            ("[2] openai".to_string(), "feat: b".to_string()),
+        ];
+
+        let out = render(&columns, 40);
+        assert_eq!(out, "[1] gemini\nfeat: a\n\n[2] openai\nfeat: b\n");
+    }
+}
@@ -0,0 +1,133 @@
+use std::ffi::OsStr;
+
+use clap::ValueEnum;
+use clap_complete::engine::{ArgValueCompleter, CompletionCandidate};
+use clap_complete::env::{CompleteEnv, Shells};
+
+use crate::auth;
+use crate::config::Config;
+use crate::llm::registry;
+use crate::selection::Selection;
+use crate::style;
+
+/// Set by the registered scripts when they call commitgen for completions.
+const VAR: &str = "COMPLETE";
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum Shell {
+    Bash,
+    Elvish,
+    Fish,
+    Powershell,
+    Zsh,
+}
+
+impl Shell {
+    fn name(self) -> &'static str {
+        match self {
+            Shell::Bash => "bash",
+            Shell::Elvish => "elvish",
+            Shell::Fish => "fish",
+            Shell::Powershell => "powershell",
+            Shell::Zsh => "zsh",
+        }
+    }
+}
+
+/// Answers the completion request the scripts make and exits, when this run
+/// is one; otherwise returns so the command runs as usual.
+pub fn complete(command: fn() -> clap::Command) {
+    CompleteEnv::with_factory(move || with_values(command())).var(VAR).complete();
+}
+
+/// The script registering completions for `name` with `shell`. It calls
+/// `name` back as you type, so it stays in step with the installed build.
+pub fn script(shell: Shell, name: &str) -> String {
+    const SHELLS: Shells<'static> = Shells::builtins();
+    let completer = SHELLS.completer(shell.name()).expect("clap_complete knows every shell listed");
+    let mut out = Vec::new();
+    completer.write_registration(VAR, name, name, name, &mut out).expect("writing to a Vec can't fail");
+    String::from_utf8(out).expect("the scripts are UTF-8")
+}
+
+/// Has `--provider`, `--fallback`, `--compare` and `--style` complete names
+/// throughout `command`.
+fn with_values(command: clap::Command) -> clap::Command {
+    command
+        .mut_args(|arg| match arg.get_long() {
+            Some("provider" | "fallback" | "compare") => arg.add(ArgValueCompleter::new(providers)),
+            Some("style") => arg.add(ArgValueCompleter::new(styles)),
+            _ => arg,
+        })
+        .mut_subcommands(with_values)
+}
+
+/// Providers worth offering: the ones the config names, and those with a key
+/// stored or in the environment, or needing none.
+fn providers(current: &OsStr) -> Vec<CompletionCandidate> {
+    let config = Config::load(None).ok();
+    let named = config.as_ref().map(|config| Selection::from_config(config).names()).unwrap_or_default();
+    if let Some(config) = &config {
+        auth::vault::init(config);
+    }
+    let configured = registry::PROVIDERS.iter().filter(|provider| {
+        named.iter().any(|name| registry::lookup(name).is_ok_and(|found| found.name == provider.name))
+            || provider.key_var.is_none_or(|var| auth::keyring::secret(var).is_some())
+    });
+    candidates(current, configured.map(|provider| (provider.name.to_string(), Some(provider.description))))
+}
+
+/// The presets, and style files from the repository and config directory.
+fn styles(current: &OsStr) -> Vec<CompletionCandidate> {
+    let _ = style::load_custom();
+    candidates(current, style::names().into_iter().map(|name| (name, None)))
+}
+
+fn candidates(current: &OsStr, names: impl Iterator<Item = (String, Option<&'static str>)>) -> Vec<CompletionCandidate> {
+    let current = current.to_string_lossy();
+    names
+        .filter(|(name, _)| name.starts_with(current.as_ref()))
+        .map(|(name, help)| CompletionCandidate::new(name).help(help.map(Into::into)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::{Arg, Command};
+    use clap_complete::engine::complete;
+
+    fn command() -> Command {
+        with_values(
+            Command::new("tool")
+                .arg(Arg::new("template").long("template").value_parser(clap::value_parser!(std::path::PathBuf)))
+                .subcommand(Command::new("check").arg(Arg::new("style").long("style").short('s'))),
+        )
+    }
+
+    fn values(args: &[&str]) -> Vec<String> {
+        let args: Vec<_> = args.iter().map(Into::into).collect();
+        let index = args.len() - 1;
+        let dir = std::env::current_dir().unwrap();
+        let found = complete(&mut command(), args, index, Some(&dir)).unwrap();
+        found.iter().map(|candidate| candidate.get_value().to_string_lossy().into_owned()).collect()
+    }
+
+    #[test]
+    fn test_styles() {
+        assert!(values(&["tool", "check", "--style", "ang"]).contains(&"angular".to_string()));
+        assert!(values(&["tool", "check", "-s", ""]).contains(&"conventional".to_string()));
+    }
+
+    #[test]
+    fn test_paths() {
+        assert!(values(&["tool", "--template", "Cargo.t"]).contains(&"Cargo.toml".to_string()));
+    }
+
+    #[test]
+    fn test_scripts() {
+        assert!(script(Shell::Bash, "tool").contains("complete -o nospace -o bashdefault -o nosort -F _clap_complete_tool tool"));
+        assert!(script(Shell::Zsh, "tool").starts_with("#compdef tool"));
+        assert!(script(Shell::Fish, "tool").contains("complete --keep-order --exclusive --command tool"));
+    }
+}
@@ -0,0 +1,891 @@
+use std::collections::HashMap;
+use std::env;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::body::{BodyMode, Shape};
+use crate::auth::vault;
+use crate::{check, git, remote};
+use crate::llm::GenerationOptions;
+use crate::wrap::LongSubject;
+
+/// The per-repository config file, at the top of the working tree.
+pub const REPO_FILE_NAME: &str = ".commitgen.toml";
+
+/// Keys only the user's own config can set, which are dropped from the
+/// repository's file.
+const REPO_FORBIDDEN: &[&str] = &[
+    "confirm_send",
+    "redact",
+    "proxy",
+    "ca_bundle",
+    "insecure_skip_verify",
+    "passphrase_command",
+    "endpoints",
+    "prompt_template",
+];
+
+/// Settings read from `~/.config/commitgen/config.toml`, with the
+/// repository's `.commitgen.toml`, the profile and `COMMITGEN_*`
+/// variables over them in turn. Every field is optional; command-line
+/// flags take precedence.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub provider: Option<String>,
+    /// Model for `provider`, as with `--model`; over `[models]`. It isn't
+    /// used when `--provider` picks another.
+    pub model: Option<String>,
+    /// Message style used when `--style` isn't given.
+    pub style: Option<String>,
+    /// File whose template replaces the built-in prompt for commit
+    /// messages, as with `--prompt-template`.
+    pub prompt_template: Option<PathBuf>,
+    /// Language messages are written in when `--lang` isn't given, e.g. `"ja"`.
+    pub lang: Option<String>,
+    /// What happens to subjects over the style's limit: `"truncate"`,
+    /// `"rewrite"` (by the model) or `"keep"`.
+    pub long_subject: Option<LongSubject>,
+    /// Column body lines are wrapped at; 0 turns wrapping off.
+    pub wrap_column: Option<usize>,
+    /// How the body is written when `--body` isn't given: `"normal"`,
+    /// `"detailed"` or `"none"` for subject-only commits.
+    pub body: Option<BodyMode>,
+    /// Paragraphs (or bullet points) the body may have.
+    pub max_body_items: Option<usize>,
+    /// `false` leaves out any trailers the model writes, as with `--no-footers`.
+    pub footers: Option<bool>,
+    /// `false` stops generations being saved for `commitgen history`.
+    pub history: Option<bool>,
+    /// `false` sends diffs to cloud providers without showing what's in them
+    /// and asking first.
+    pub confirm_send: Option<bool>,
+    /// `false` stops secrets with a known shape (AWS keys, tokens, private
+    /// keys, random-looking strings) being replaced with placeholders.
+    pub redact: Option<bool>,
+    /// More secrets to replace, as name and pattern, e.g.
+    /// `[redact_patterns] internal-host = '[a-z0-9-]+\.corp\.example\.com'`.
+    pub redact_patterns: HashMap<String, String>,
+    /// Proxy for every request, e.g. `"http://proxy.corp:3128"`, over
+    /// `HTTPS_PROXY` and the rest.
+    pub proxy: Option<String>,
+    /// PEM file of extra root certificates to trust, such as a corporate
+    /// proxy's.
+    pub ca_bundle: Option<PathBuf>,
+    /// Don't check TLS certificates at all. A last resort.
+    pub insecure_skip_verify: bool,
+    /// Command whose output unlocks the encrypted credentials file, e.g.
+    /// `"pass show commitgen"`.
+    pub passphrase_command: Option<String>,
+    /// Model to use per provider, e.g. `[models] gemini = "gemini-2.5-pro"`.
+    pub models: HashMap<String, String>,
+    /// API root per provider, for a regional endpoint or a gateway, e.g.
+    /// `[endpoints] gemini = "https://gateway.corp/gemini/v1beta"`.
+    pub endpoints: HashMap<String, String>,
+    pub allow_unknown_model: bool,
+    /// Providers to try, in order, when the primary one fails.
+    pub fallback: Vec<String>,
+    /// Per-attempt timeout before moving on to the next fallback.
+    pub fallback_timeout_secs: Option<u64>,
+    /// Seconds a request may wait to connect or for the next part of the
+    /// answer, as with `--timeout`; 0 waits forever.
+    pub timeout_secs: Option<u64>,
+    /// Times a failed request is retried, as with `--max-retries`.
+    pub max_retries: Option<u32>,
+    /// Seconds before the first retry, doubling after that, as with `--backoff`.
+    pub backoff: Option<f64>,
+    /// Providers used by a bare `--compare`.
+    pub compare: Vec<String>,
+    pub temperature: Option<f32>,
+    pub max_tokens: Option<u32>,
+    pub top_p: Option<f32>,
+    /// Per-provider overrides, e.g. `[generation.ollama] temperature = 0.7`.
+    pub generation: HashMap<String, GenerationOptions>,
+    /// Recent commit subjects to show the model as examples; 0 turns them off.
+    pub examples: Option<usize>,
+    /// Where a ticket found in the branch name goes: a trailer such as
+    /// `"Refs: {ticket}"`, or a subject such as `"[{ticket}] {subject}"`.
+    pub ticket_template: Option<String>,
+    /// Record how each commit's message was generated in a git note, as
+    /// with `--note`.
+    pub notes: bool,
+    /// Always add a `Signed-off-by` trailer, as with `--signoff`.
+    pub signoff: bool,
+    /// Aliases for `--co-author`, e.g. `[co_authors] ann = "Ann Lee <ann@example.com>"`.
+    pub co_authors: HashMap<String, String>,
+    /// Globs of generated files, besides the built-in lockfiles, whose
+    /// diffs are summarised in a line instead of sent in full.
+    pub generated_files: Vec<String>,
+    /// Pattern for `commitgen branch`, e.g. `"{type}/{scope}-{description}"`.
+    pub branch_pattern: Option<String>,
+    /// Lines of context around each change, as with `--context-lines`.
+    pub context_lines: Option<u32>,
+    /// Send whole functions around each change, as with `--function-context`.
+    pub function_context: bool,
+    /// With `--all`, include untracked files, as with `--include-untracked`.
+    pub include_untracked: bool,
+    /// `false` leaves out the ticket found in the branch name, as with `--no-ticket`.
+    pub ticket: Option<bool>,
+    /// Put messages on the clipboard, as with `--copy`.
+    pub copy: bool,
+    /// Messages to generate and pick from, as with `-n`.
+    pub candidates: Option<u8>,
+    /// Offer to edit, regenerate or refine each message, as with `--interactive`.
+    pub interactive: bool,
+    /// When committing, open the message in git's editor, as with `--edit`.
+    pub edit: bool,
+    /// When committing, skip the pre-commit and commit-msg hooks, as with `--no-verify`.
+    pub no_verify: bool,
+    /// When committing, `true` signs and `false` doesn't, whatever git's
+    /// `commit.gpgSign` says, as with `-S` and `--no-gpg-sign`.
+    pub gpg_sign: Option<bool>,
+    /// The `[profile.<name>]` table laid over the rest, if one was chosen
+    /// with `--profile` or by the repository's remotes.
+    #[serde(skip)]
+    pub profile: Option<String>,
+}
+
+impl Config {
+    /// `$XDG_CONFIG_HOME/commitgen/config.toml`, falling back to
+    /// `~/.config` (or the platform config dir on Windows).
+    pub fn default_path() -> Option<PathBuf> {
+        let base = match env::var("XDG_CONFIG_HOME") {
+            Ok(dir) if !dir.is_empty() => Some(PathBuf::from(dir)),
+            _ if cfg!(windows) => dirs::config_dir(),
+            _ => dirs::home_dir().map(|home| home.join(".config")),
+        };
+        base.map(|dir| dir.join("commitgen").join("config.toml"))
+    }
+
+    /// `.commitgen.toml` at the top of the current repository, if in one.
+    pub fn repo_path() -> Option<PathBuf> {
+        let root = git::run(&["rev-parse", "--show-toplevel"]).ok()?;
+        Some(Path::new(root.trim()).join(REPO_FILE_NAME))
+    }
+
+    /// Loads the user's config file and then the repository's over it,
+    /// treating missing files as empty, and then the profile: `profile` if
+    /// given, otherwise the first whose `remotes` match one of the
+    /// repository's. Sections for the repository's remotes go over that.
+    pub fn load(profile: Option<&str>) -> anyhow::Result<Self> {
+        Self::from_layers(layers(profile)?)
+    }
+
+    fn from_layers(layers: Vec<Layer>) -> anyhow::Result<Self> {
+        let mut merged = toml::Table::new();
+        let mut profile = None;
+        for layer in layers {
+            if let Origin::Profile(name) = &layer.origin {
+                profile = Some(name.clone());
+            }
+            merge(&mut merged, layer.table);
+        }
+        let mut config: Self = merged.try_into().map_err(|e| match &profile {
+            Some(name) => anyhow::anyhow!("Invalid profile {}: {}", name, e),
+            None => anyhow::Error::from(e),
+        })?;
+        config.profile = profile;
+        Ok(config)
+    }
+
+    pub fn from_toml(contents: &str) -> anyhow::Result<Self> {
+        Ok(toml::from_str(contents)?)
+    }
+
+    /// `--style` when given, then `style` from the config file, then
+    /// Conventional Commits.
+    pub fn style(&self, explicit: Option<String>) -> String {
+        explicit.or_else(|| self.style.clone()).unwrap_or_else(|| check::DEFAULT_STYLE.to_string())
+    }
+
+    /// The body and footers messages may have, as the config file sets them.
+    pub fn shape(&self) -> Shape {
+        Shape {
+            body: self.body != Some(BodyMode::None),
+            max_items: self.max_body_items,
+            footers: self.footers.unwrap_or(true),
+        }
+    }
+
+    /// Generation options for `provider`: its `[generation.<provider>]`
+    /// table, then the top-level keys.
+    pub fn generation_options(&self, provider: &str) -> GenerationOptions {
+        let global = GenerationOptions {
+            temperature: self.temperature,
+            max_tokens: self.max_tokens,
+            top_p: self.top_p,
+        };
+        self.generation.get(provider).copied().unwrap_or_default().or(global)
+    }
+}
+
+/// Where a setting came from.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Origin {
+    User(PathBuf),
+    Repo(PathBuf),
+    Profile(String),
+    /// An `[if.remote."<glob>"]` section matching one of the remotes.
+    Remote(String),
+    Env,
+}
+
+impl std::fmt::Display for Origin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Origin::User(path) | Origin::Repo(path) => write!(f, "{}", path.display()),
+            Origin::Profile(name) => write!(f, "profile {}", name),
+            Origin::Remote(pattern) => write!(f, "[if.remote.\"{}\"]", pattern),
+            Origin::Env => write!(f, "environment ({}*)", ENV_PREFIX),
+        }
+    }
+}
+
+/// One source of settings; later layers win.
+#[derive(Debug, Clone)]
+pub struct Layer {
+    pub origin: Origin,
+    pub table: toml::Table,
+}
+
+/// The config's sources in the order they apply: the user's file, the
+/// repository's, the profile, the `[if.remote."<glob>"]` sections matching
+/// the repository's remotes, then `COMMITGEN_*` variables, each left out
+/// when there's none. `profile` falls back to `COMMITGEN_PROFILE`, and then
+/// to a `profile` named by a matching section.
+pub fn layers(profile: Option<&str>) -> anyhow::Result<Vec<Layer>> {
+    let profile = profile.map(str::to_string).or_else(|| env::var(format!("{}PROFILE", ENV_PREFIX)).ok());
+    let remotes = remote::urls();
+    let mut layers = Vec::new();
+    if let Some(path) = Config::default_path()
+        && let Some(table) = read_table(&path)?
+    {
+        layers.push(Layer { origin: Origin::User(path), table });
+    }
+    if let Some(path) = Config::repo_path()
+        && let Some(mut table) = read_table(&path)?
+    {
+        // A cloned repository doesn't get to switch off the question
+        // before its diffs leave the machine or the redaction of them, to
+        // choose where they go and whom to trust on the way, to run
+        // commands, or to have a local file read into the prompt.
+        for key in REPO_FORBIDDEN {
+            table.remove(*key);
+        }
+        for path in [&["profile"][..], &["if", "remote"]] {
+            let sections = table_at(&mut table, path).into_iter().flat_map(|sections| sections.iter_mut());
+            for section in sections.filter_map(|(_, section)| section.as_table_mut()) {
+                for key in REPO_FORBIDDEN {
+                    section.remove(*key);
+                }
+            }
+        }
+        layers.push(Layer { origin: Origin::Repo(path), table });
+    }
+    let mut conditional = Vec::new();
+    for layer in &layers {
+        conditional.extend(conditional_sections(layer, &remotes)?);
+    }
+    // The most specific pattern wins.
+    conditional.sort_by_key(|(pattern, _)| pattern.len());
+    let forced = conditional.iter().rev().find_map(|(_, table)| table.get("profile").and_then(toml::Value::as_str));
+    let profile = profile.or_else(|| forced.map(str::to_string));
+    let mut profiles = toml::Table::new();
+    for layer in &layers {
+        if let Some(table) = layer.table.get("profile") {
+            let toml::Value::Table(table) = table else {
+                anyhow::bail!("`profile` in {} must be a table of profiles, such as [profile.work]", layer.origin);
+            };
+            merge(&mut profiles, table.clone());
+        }
+    }
+    if let Some((name, table)) = choose_profile(&profiles, profile.as_deref(), &remotes)? {
+        tracing::info!("Using profile {}", name);
+        layers.push(Layer { origin: Origin::Profile(name), table });
+    }
+    for (pattern, mut table) in conditional {
+        table.remove("profile");
+        tracing::info!("Using [if.remote.\"{}\"]", pattern);
+        layers.push(Layer { origin: Origin::Remote(pattern), table });
+    }
+    let table = env_table(env::vars())?;
+    if !table.is_empty() {
+        layers.push(Layer { origin: Origin::Env, table });
+    }
+    Ok(layers)
+}
+
+/// The tables under dotted `path` in `table`, e.g. each
+/// `[if.remote."<glob>"]` under `["if", "remote"]`.
+fn table_at<'a>(table: &'a mut toml::Table, path: &[&str]) -> Option<&'a mut toml::Table> {
+    match path.split_first() {
+        None => Some(table),
+        Some((first, rest)) => table_at(table.get_mut(*first)?.as_table_mut()?, rest),
+    }
+}
+
+/// The `[if.remote."<glob>"]` sections in `layer` whose globs match one of
+/// `remotes`, each checked against [`Config`]. A section's `profile` picks
+/// the profile as `--profile` would.
+fn conditional_sections(layer: &Layer, remotes: &[String]) -> anyhow::Result<Vec<(String, toml::Table)>> {
+    let Some(conditions) = layer.table.get("if") else {
+        return Ok(Vec::new());
+    };
+    let sections = match conditions.as_table().map(|conditions| conditions.get("remote")) {
+        Some(None) => return Ok(Vec::new()),
+        Some(Some(toml::Value::Table(sections))) => sections,
+        _ => anyhow::bail!("`if` in {} must hold tables such as [if.remote.\"github.com/mycorp/*\"]", layer.origin),
+    };
+    let mut matching = Vec::new();
+    for (pattern, section) in sections {
+        let Some(section) = section.as_table() else {
+            anyhow::bail!("[if.remote.\"{}\"] in {} must be a table", pattern, layer.origin);
+        };
+        if let Some(profile) = section.get("profile").filter(|profile| !profile.is_str()) {
+            anyhow::bail!("`profile` in [if.remote.\"{}\"] must be a profile's name, not {}", pattern, profile);
+        }
+        toml::Value::Table(section.clone())
+            .try_into::<Config>()
+            .map_err(|e| anyhow::anyhow!("Invalid [if.remote.\"{}\"] in {}: {}", pattern, layer.origin, e))?;
+        if remotes.iter().any(|url| remote::matches(pattern, url)) {
+            matching.push((pattern.clone(), section.clone()));
+        }
+    }
+    Ok(matching)
+}
+
+/// Environment variables named for config keys start with this, e.g.
+/// `COMMITGEN_PROVIDER` or `COMMITGEN_MODELS__GEMINI` for `models.gemini`.
+pub const ENV_PREFIX: &str = "COMMITGEN_";
+
+/// The config keys among `vars`: `COMMITGEN_` and the key in capitals,
+/// with `__` between table and key. Values are read as on the command line
+/// (see [`parse_value`]), except that one the key won't take as TOML is
+/// taken as a string (`COMMITGEN_STYLE=true`) or, failing that, a
+/// comma-separated list (`COMMITGEN_FALLBACK=openai,ollama`).
+fn env_table(vars: impl Iterator<Item = (String, String)>) -> anyhow::Result<toml::Table> {
+    let mut table = toml::Table::new();
+    let mut ignored = Vec::new();
+    for (var, text) in vars {
+        let Some(name) = var.strip_prefix(ENV_PREFIX) else {
+            continue;
+        };
+        if name == "PROFILE" || var == vault::PASSPHRASE_VAR {
+            continue;
+        }
+        let key = name.to_lowercase().replace("__", ".");
+        if check_key(&key).is_err() || key.starts_with("profile.") {
+            ignored.push(var);
+            continue;
+        }
+        let list = text.split(',').map(|item| toml::Value::String(item.trim().to_string())).collect();
+        let mut error = None;
+        for value in [parse_value(&text), toml::Value::String(text.clone()), toml::Value::Array(list)] {
+            let mut entry = toml::Table::new();
+            insert(&mut entry, &key, value);
+            match toml::Value::Table(entry.clone()).try_into::<Config>() {
+                Ok(_) => {
+                    merge(&mut table, entry);
+                    error = None;
+                    break;
+                }
+                Err(e) => error = error.or(Some(e)),
+            }
+        }
+        if let Some(e) = error {
+            anyhow::bail!("Invalid {}: {}", var, e);
+        }
+    }
+    if !ignored.is_empty() {
+        // The config can be loaded more than once in a run.
+        static WARNED: std::sync::Once = std::sync::Once::new();
+        WARNED.call_once(|| tracing::warn!("Ignoring {}, not named for any config key", ignored.join(", ")));
+    }
+    Ok(table)
+}
+
+/// Puts `value` at dotted `key` in `table`, making tables on the way.
+fn insert(table: &mut toml::Table, key: &str, value: toml::Value) {
+    match key.split_once('.') {
+        Some((first, rest)) => {
+            let inner = table.entry(first).or_insert_with(|| toml::Value::Table(toml::Table::new()));
+            if let toml::Value::Table(inner) = inner {
+                insert(inner, rest, value);
+            }
+        }
+        None => {
+            table.insert(key.to_string(), value);
+        }
+    }
+}
+
+/// The profile named `name`, or else the first whose `remotes` match one
+/// of `remotes`, without its `remotes`.
+fn choose_profile(profiles: &toml::Table, name: Option<&str>, remotes: &[String]) -> anyhow::Result<Option<(String, toml::Table)>> {
+    let chosen = match name {
+        Some(name) => match profiles.get(name) {
+            Some(profile) => Some((name.to_string(), profile)),
+            None if profiles.is_empty() => anyhow::bail!("No profile {}; the config file doesn't define any", name),
+            None => anyhow::bail!(
+                "No profile {}; the config file defines {}",
+                name,
+                profiles.keys().cloned().collect::<Vec<_>>().join(", ")
+            ),
+        },
+        None => profiles
+            .iter()
+            .find(|(_, profile)| profile_remotes(profile).iter().any(|p| remotes.iter().any(|url| remote::matches(p, url))))
+            .map(|(name, profile)| (name.clone(), profile)),
+    };
+    match chosen {
+        Some((name, toml::Value::Table(profile))) => {
+            let mut profile = profile.clone();
+            profile.remove("remotes");
+            Ok(Some((name, profile)))
+        }
+        Some((name, _)) => anyhow::bail!("Profile {} must be a table, such as [profile.{}]", name, name),
+        None => Ok(None),
+    }
+}
+
+/// The config file at `path` as a TOML table, checked against [`Config`];
+/// `None` when there's no such file.
+fn read_table(path: &Path) -> anyhow::Result<Option<toml::Table>> {
+    let contents = match std::fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => anyhow::bail!("Failed to read {}: {}", path.display(), e),
+    };
+    Config::from_toml(&contents).map_err(|e| anyhow::anyhow!("Invalid config file {}: {}", path.display(), e))?;
+    Ok(Some(toml::from_str(&contents)?))
+}
+
+/// The remote URL globs a profile is chosen for, e.g.
+/// `remotes = ["github.com/mycorp/*"]`.
+fn profile_remotes(profile: &toml::Value) -> Vec<&str> {
+    profile
+        .get("remotes")
+        .and_then(toml::Value::as_array)
+        .map(|remotes| remotes.iter().filter_map(toml::Value::as_str).collect())
+        .unwrap_or_default()
+}
+
+/// Lays `over` on top of `base`: tables such as `[models]` are merged key
+/// by key, and anything else, lists included, is replaced.
+pub fn merge(base: &mut toml::Table, over: toml::Table) {
+    for (key, value) in over {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(base)), toml::Value::Table(over)) => merge(base, over),
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+/// The table heading a line opens, e.g. `models` for `[models]`.
+fn header(line: &str) -> Option<&str> {
+    let line = line.trim_start();
+    if line.starts_with("[[") {
+        return None;
+    }
+    let (name, _) = line.strip_prefix('[')?.split_once(']')?;
+    Some(name.trim())
+}
+
+/// The lines under `table` (the top level for `None`), from just after
+/// its header to the next one.
+fn section(lines: &[String], table: Option<&str>) -> Option<(usize, usize)> {
+    let start = match table {
+        None => 0,
+        Some(table) => lines.iter().position(|line| header(line) == Some(table))? + 1,
+    };
+    let end = lines[start..].iter().position(|line| header(line).is_some() || line.trim_start().starts_with("[[")).map_or(lines.len(), |i| start + i);
+    Some((start, end))
+}
+
+fn assignment(lines: &[String], name: &str) -> Option<usize> {
+    lines.iter().position(|l| l.split_once('=').is_some_and(|(key, _)| key.trim().trim_matches('"') == name))
+}
+
+/// Sets `key` to `value` (already TOML, e.g. `"\"ollama\""`) in the config
+/// file's `contents`, keeping everything else, comments included. A dotted
+/// key such as `models.gemini` goes in its table, `[models]`, which is
+/// added at the end if need be. An existing assignment is replaced;
+/// otherwise top-level keys go before the first table, where they have to
+/// be, and others at the end of their table.
+pub fn set_key(contents: &str, key: &str, value: &str) -> String {
+    let (table, name) = match key.rsplit_once('.') {
+        Some((table, name)) => (Some(table), name),
+        None => (None, key),
+    };
+    let line = format!("{} = {}", name, value);
+    let mut lines: Vec<String> = contents.lines().map(str::to_string).collect();
+    match section(&lines, table) {
+        Some((start, end)) => match assignment(&lines[start..end], name) {
+            Some(i) => lines[start + i] = line,
+            None => {
+                let at = lines[start..end].iter().rposition(|l| !l.trim().is_empty()).map_or(start, |i| start + i + 1);
+                lines.insert(at, line);
+                if at == end && end < lines.len() - 1 {
+                    lines.insert(at + 1, String::new());
+                }
+            }
+        },
+        None => {
+            while lines.last().is_some_and(|l| l.trim().is_empty()) {
+                lines.pop();
+            }
+            if !lines.is_empty() {
+                lines.push(String::new());
+            }
+            lines.push(format!("[{}]", table.unwrap_or_default()));
+            lines.push(line);
+        }
+    }
+    lines.join("\n") + "\n"
+}
+
+/// `contents` without the assignment to `key`, or `None` when it isn't set
+/// there.
+pub fn unset_key(contents: &str, key: &str) -> Option<String> {
+    let (table, name) = match key.rsplit_once('.') {
+        Some((table, name)) => (Some(table), name),
+        None => (None, key),
+    };
+    let mut lines: Vec<String> = contents.lines().map(str::to_string).collect();
+    let (start, end) = section(&lines, table)?;
+    let i = assignment(&lines[start..end], name)?;
+    lines.remove(start + i);
+    Some(lines.join("\n") + "\n")
+}
+
+/// The value at dotted `key` in `table`.
+pub fn lookup<'a>(table: &'a toml::Table, key: &str) -> Option<&'a toml::Value> {
+    let (first, rest) = match key.split_once('.') {
+        Some((first, rest)) => (first, Some(rest)),
+        None => (key, None),
+    };
+    match (table.get(first)?, rest) {
+        (value, None) => Some(value),
+        (toml::Value::Table(table), Some(rest)) => lookup(table, rest),
+        _ => None,
+    }
+}
+
+/// Every value in `table` that isn't itself a table, under its dotted key.
+pub fn flatten(table: &toml::Table) -> Vec<(String, toml::Value)> {
+    let mut values = Vec::new();
+    for (key, value) in table {
+        match value {
+            toml::Value::Table(inner) => {
+                values.extend(flatten(inner).into_iter().map(|(inner_key, value)| (format!("{}.{}", key, inner_key), value)));
+            }
+            value => values.push((key.clone(), value.clone())),
+        }
+    }
+    values
+}
+
+/// Captures the fields serde asks for when deserializing a struct, and
+/// nothing else.
+struct FieldNames<'a>(&'a mut &'static [&'static str]);
+
+impl<'de> serde::Deserializer<'de> for FieldNames<'_> {
+    type Error = serde::de::value::Error;
+
+    fn deserialize_any<V: serde::de::Visitor<'de>>(self, _: V) -> Result<V::Value, Self::Error> {
+        Err(serde::de::Error::custom("not a struct"))
+    }
+
+    fn deserialize_struct<V: serde::de::Visitor<'de>>(
+        self,
+        _: &'static str,
+        fields: &'static [&'static str],
+        _: V,
+    ) -> Result<V::Value, Self::Error> {
+        *self.0 = fields;
+        Err(serde::de::Error::custom("only the fields were wanted"))
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf option unit
+        unit_struct newtype_struct seq tuple tuple_struct map enum identifier ignored_any
+    }
+}
+
+/// Checks that dotted `key` names a setting: a top-level key of [`Config`],
+/// one inside a table such as `models.gemini`, or either of those under
+/// `profile.<name>.`. Unknown keys would otherwise be quietly ignored.
+pub fn check_key(key: &str) -> anyhow::Result<()> {
+    let mut fields: &'static [&'static str] = &[];
+    let _ = Config::deserialize(FieldNames(&mut fields));
+    let setting = match key.split('.').collect::<Vec<_>>().as_slice() {
+        ["profile", _, setting, ..] => *setting,
+        [first, ..] => *first,
+        [] => "",
+    };
+    if setting == "remotes" && key.starts_with("profile.") || fields.contains(&setting) {
+        Ok(())
+    } else {
+        anyhow::bail!("Unknown config key {}; `commitgen config list` shows the ones set", key)
+    }
+}
+
+/// The keys in a config file's `table` that [`check_key`] doesn't know,
+/// looking inside its profiles and `[if.remote."<glob>"]` sections.
+pub fn unknown_keys(table: &toml::Table) -> Vec<String> {
+    let mut unknown = Vec::new();
+    for (key, value) in table {
+        match (key.as_str(), value) {
+            ("profile", toml::Value::Table(profiles)) => {
+                for (name, profile) in profiles {
+                    let keys = profile.as_table().into_iter().flat_map(|profile| profile.keys());
+                    unknown.extend(keys.map(|key| format!("profile.{}.{}", name, key)).filter(|key| check_key(key).is_err()));
+                }
+            }
+            ("if", toml::Value::Table(conditions)) => {
+                for (condition, sections) in conditions {
+                    let sections = sections.as_table().into_iter().flat_map(|sections| sections.iter());
+                    for (pattern, section) in sections {
+                        let keys = section.as_table().into_iter().flat_map(|section| section.keys());
+                        unknown.extend(
+                            keys.filter(|key| *key != "profile" && check_key(key).is_err())
+                                .map(|key| format!("if.{}.\"{}\".{}", condition, pattern, key)),
+                        );
+                    }
+                }
+            }
+            _ if check_key(key).is_err() => unknown.push(key.clone()),
+            _ => {}
+        }
+    }
+    unknown
+}
+
+/// `text` from the command line as a TOML value: `true`, `10` and
+/// `["a", "b"]` are what they look like, and anything else is a string.
+pub fn parse_value(text: &str) -> toml::Value {
+    toml::from_str::<toml::Table>(&format!("value = {}", text))
+        .ok()
+        .and_then(|mut table| table.remove("value"))
+        .unwrap_or_else(|| toml::Value::String(text.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_toml() {
+        let config = Config::from_toml("provider = \"ollama\"\n").unwrap();
+        assert_eq!(config.provider.as_deref(), Some("ollama"));
+    }
+
+    #[test]
+    fn test_from_toml_models() {
+        let config = Config::from_toml(
+            "allow_unknown_model = true\n\n[models]\ngemini = \"gemini-2.5-pro\"\nollama = \"qwen2.5-coder\"\n",
+        )
+        .unwrap();
+        assert_eq!(config.models.get("gemini").map(String::as_str), Some("gemini-2.5-pro"));
+        assert_eq!(config.models.len(), 2);
+        assert!(config.allow_unknown_model);
+    }
+
+    #[test]
+    fn test_from_toml_fallback() {
+        let config = Config::from_toml("fallback = [\"openai\", \"ollama\"]\nfallback_timeout_secs = 10\n").unwrap();
+        assert_eq!(config.fallback, ["openai", "ollama"]);
+        assert_eq!(config.fallback_timeout_secs, Some(10));
+    }
+
+    #[test]
+    fn test_generation_options() {
+        let config = Config::from_toml(
+            "temperature = 0.2\nmax_tokens = 512\n\n[generation.ollama]\ntemperature = 0.8\ntop_p = 0.9\n",
+        )
+        .unwrap();
+
+        let ollama = config.generation_options("ollama");
+        assert_eq!(ollama.temperature, Some(0.8));
+        assert_eq!(ollama.max_tokens, Some(512));
+        assert_eq!(ollama.top_p, Some(0.9));
+
+        let gemini = config.generation_options("gemini");
+        assert_eq!(gemini.temperature, Some(0.2));
+        assert_eq!(gemini.top_p, None);
+    }
+
+    #[test]
+    fn test_set_key() {
+        assert_eq!(set_key("", "provider", "\"ollama\""), "provider = \"ollama\"\n");
+        assert_eq!(
+            set_key("# mine\nprovider = \"gemini\" # fast\nsignoff = true\n", "provider", "\"claude\""),
+            "# mine\nprovider = \"claude\"\nsignoff = true\n"
+        );
+        assert_eq!(
+            set_key("signoff = true\n\n[models]\ngemini = \"x\"\n", "style", "\"gitmoji\""),
+            "signoff = true\nstyle = \"gitmoji\"\n\n[models]\ngemini = \"x\"\n"
+        );
+        assert_eq!(set_key("[models]\nstyle = \"x\"\n", "style", "\"gitmoji\""), "style = \"gitmoji\"\n\n[models]\nstyle = \"x\"\n");
+        assert_eq!(
+            set_key("signoff = true\n\n[models]\ngemini = \"x\"\n\n[generation.ollama]\ntop_p = 0.9\n", "models.ollama", "\"y\""),
+            "signoff = true\n\n[models]\ngemini = \"x\"\nollama = \"y\"\n\n[generation.ollama]\ntop_p = 0.9\n"
+        );
+        assert_eq!(
+            set_key("signoff = true\n", "profile.work.provider", "\"ollama\""),
+            "signoff = true\n\n[profile.work]\nprovider = \"ollama\"\n"
+        );
+    }
+
+    #[test]
+    fn test_unset_key() {
+        let contents = "provider = \"gemini\"\n\n[models]\ngemini = \"x\"\n";
+        assert_eq!(unset_key(contents, "models.gemini").unwrap(), "provider = \"gemini\"\n\n[models]\n");
+        assert_eq!(unset_key(contents, "provider").unwrap(), "\n[models]\ngemini = \"x\"\n");
+        assert!(unset_key(contents, "style").is_none());
+        assert!(unset_key(contents, "models.claude").is_none());
+    }
+
+    #[test]
+    fn test_lookup_and_flatten() {
+        let table: toml::Table = toml::from_str("provider = \"gemini\"\n[models]\ngemini = \"x\"\n").unwrap();
+        assert_eq!(lookup(&table, "models.gemini").and_then(toml::Value::as_str), Some("x"));
+        assert!(lookup(&table, "provider.gemini").is_none());
+        let keys: Vec<String> = flatten(&table).into_iter().map(|(key, _)| key).collect();
+        assert_eq!(keys, ["models.gemini", "provider"]);
+    }
+
+    #[test]
+    fn test_check_key() {
+        assert!(check_key("provider").is_ok());
+        assert!(check_key("models.gemini").is_ok());
+        assert!(check_key("profile.work.style").is_ok());
+        assert!(check_key("profile.work.remotes").is_ok());
+        assert!(check_key("provder").is_err());
+        assert!(check_key("remotes").is_err());
+        assert!(check_key("profile.work").is_err());
+    }
+
+    #[test]
+    fn test_unknown_keys() {
+        let table: toml::Table = toml::from_str(
+            "provder = \"openai\"\nstyle = \"kernel\"\n[models]\ngemini = \"gemini-2.5-pro\"\n\
+            [profile.work]\nremotes = [\"github.com/mycorp/*\"]\nstlye = \"angular\"\n\
+            [if.remote.\"github.com/mycorp/*\"]\nprofile = \"work\"\nprovider = \"ollama\"\nlnag = \"ja\"\n",
+        )
+        .unwrap();
+        assert_eq!(unknown_keys(&table), ["if.remote.\"github.com/mycorp/*\".lnag", "profile.work.stlye", "provder"]);
+    }
+
+    #[test]
+    fn test_env_table() {
+        let vars = [
+            ("COMMITGEN_PROVIDER", "openai"),
+            ("COMMITGEN_EXAMPLES", "5"),
+            ("COMMITGEN_STYLE", "true"),
+            ("COMMITGEN_MODELS__GEMINI", "gemini-2.5-pro"),
+            ("COMMITGEN_FALLBACK", "openai, ollama"),
+            ("COMMITGEN_COMPARE", "[\"claude\"]"),
+            ("COMMITGEN_PROFILE", "work"),
+            ("COMMITGEN_NONSENSE", "1"),
+            ("HOME", "/root"),
+        ];
+        let table = env_table(vars.iter().map(|(var, value)| (var.to_string(), value.to_string()))).unwrap();
+        let config: Config = table.try_into().unwrap();
+        assert_eq!(config.provider.as_deref(), Some("openai"));
+        assert_eq!(config.examples, Some(5));
+        assert_eq!(config.style.as_deref(), Some("true"));
+        assert_eq!(config.models.get("gemini").map(String::as_str), Some("gemini-2.5-pro"));
+        assert_eq!(config.fallback, ["openai", "ollama"]);
+        assert_eq!(config.compare, ["claude"]);
+
+        let invalid = env_table([("COMMITGEN_EXAMPLES".to_string(), "many".to_string())].into_iter());
+        assert!(invalid.unwrap_err().to_string().starts_with("Invalid COMMITGEN_EXAMPLES"));
+    }
+
+    #[test]
+    fn test_parse_value() {
+        assert_eq!(parse_value("true"), toml::Value::Boolean(true));
+        assert_eq!(parse_value("10"), toml::Value::Integer(10));
+        assert_eq!(parse_value("openai"), toml::Value::String("openai".to_string()));
+        assert_eq!(parse_value("[\"a\"]").as_array().map(Vec::len), Some(1));
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut base: toml::Table =
+            toml::from_str("provider = \"gemini\"\nfallback = [\"ollama\"]\n[models]\ngemini = \"a\"\nollama = \"b\"\n").unwrap();
+        merge(&mut base, toml::from_str("fallback = []\n[models]\nollama = \"c\"\n").unwrap());
+        let config: Config = base.try_into().unwrap();
+        assert_eq!(config.provider.as_deref(), Some("gemini"));
+        assert!(config.fallback.is_empty());
+        assert_eq!(config.models.get("gemini").map(String::as_str), Some("a"));
+        assert_eq!(config.models.get("ollama").map(String::as_str), Some("c"));
+    }
+
+    #[test]
+    fn test_choose_profile() {
+        let profiles: toml::Table = toml::from_str(
+            "[work]\nprovider = \"ollama\"\nremotes = [\"github.com/mycorp/*\"]\n\n[oss]\nlang = \"de\"\n",
+        )
+        .unwrap();
+
+        let (name, oss) = choose_profile(&profiles, Some("oss"), &[]).unwrap().unwrap();
+        assert_eq!((name.as_str(), oss.get("lang").and_then(toml::Value::as_str)), ("oss", Some("de")));
+
+        let remotes = ["git@github.com:mycorp/app.git".to_string()];
+        let (name, work) = choose_profile(&profiles, None, &remotes).unwrap().unwrap();
+        assert_eq!(name, "work");
+        assert!(!work.contains_key("remotes"));
+
+        assert!(choose_profile(&profiles, None, &[]).unwrap().is_none());
+        let error = choose_profile(&profiles, Some("home"), &[]).unwrap_err().to_string();
+        assert_eq!(error, "No profile home; the config file defines oss, work");
+    }
+
+    #[test]
+    fn test_conditional_sections() {
+        let layer = Layer {
+            origin: Origin::User(PathBuf::from("config.toml")),
+            table: toml::from_str(
+                "provider = \"gemini\"\n\n[if.remote.\"github.com/mycorp/*\"]\nprovider = \"ollama\"\nprofile = \"work\"\n\n\
+                [if.remote.\"github.com/*/*\"]\nstyle = \"gitmoji\"\n",
+            )
+            .unwrap(),
+        };
+        let sections = conditional_sections(&layer, &["git@github.com:mycorp/app.git".to_string()]).unwrap();
+        let patterns: Vec<&str> = sections.iter().map(|(pattern, _)| pattern.as_str()).collect();
+        assert_eq!(patterns, ["github.com/*/*", "github.com/mycorp/*"]);
+        assert_eq!(sections[1].1.get("profile").and_then(toml::Value::as_str), Some("work"));
+        assert!(conditional_sections(&layer, &["https://gitlab.com/mycorp/app".to_string()]).unwrap().is_empty());
+
+        let invalid = Layer { origin: layer.origin.clone(), table: toml::from_str("[if.remote.\"*\"]\nexamples = \"x\"\n").unwrap() };
+        assert!(conditional_sections(&invalid, &[]).unwrap_err().to_string().starts_with("Invalid [if.remote.\"*\"] in config.toml"));
+    }
+
+    #[test]
+    fn test_from_layers() {
+        let layer = |origin, contents: &str| Layer { origin, table: toml::from_str(contents).unwrap() };
+        let config = Config::from_layers(vec![
+            layer(Origin::User(PathBuf::from("config.toml")), "provider = \"gemini\"\nstyle = \"gitmoji\"\n"),
+            layer(Origin::Profile("work".to_string()), "provider = \"ollama\"\n"),
+        ])
+        .unwrap();
+        assert_eq!(config.profile.as_deref(), Some("work"));
+        assert_eq!((config.provider.as_deref(), config.style.as_deref()), (Some("ollama"), Some("gitmoji")));
+    }
+
+    #[test]
+    fn test_from_toml_empty() {
+        let config = Config::from_toml("").unwrap();
+        assert!(config.provider.is_none());
+    }
+
+    #[test]
+    fn test_from_toml_invalid() {
+        assert!(Config::from_toml("provider = [").is_err());
+    }
+}
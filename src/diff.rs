@@ -0,0 +1,371 @@
+use crate::config::Config;
+use crate::ignore::{self, Ignore};
+use crate::redact::Redactor;
+use crate::{git, glob, scope};
+
+/// Lockfiles and build output: long, machine-written, and uninformative
+/// line by line. `generated_files` in the config adds to these.
+pub const GENERATED_FILES: &[&str] = &[
+    "Cargo.lock",
+    "package-lock.json",
+    "npm-shrinkwrap.json",
+    "yarn.lock",
+    "pnpm-lock.yaml",
+    "bun.lockb",
+    "poetry.lock",
+    "uv.lock",
+    "Pipfile.lock",
+    "Gemfile.lock",
+    "composer.lock",
+    "go.sum",
+    "flake.lock",
+    "*.min.js",
+    "*.min.css",
+    "*.map",
+];
+
+/// Packages named in a summary before the rest are just counted.
+const MAX_NAMED_PACKAGES: usize = 5;
+
+/// With more files than this, a detailed body summarises by area rather
+/// than file by file.
+const MAX_FILE_AREAS: usize = 8;
+
+/// The part of a diff about one file, or about one area of the project.
+#[derive(Debug, PartialEq)]
+pub struct Area {
+    /// A file path, or a scope such as `parser` for a group of files.
+    pub name: String,
+    pub diff: String,
+}
+
+/// Splits a unified diff at its `diff --git` headers, returning each
+/// file's path and section.
+pub fn split_files(diff: &str) -> Vec<(String, String)> {
+    let mut files: Vec<(String, String)> = Vec::new();
+    for line in diff.split_inclusive('\n') {
+        if let Some(header) = line.strip_prefix("diff --git ") {
+            let path = header.trim_end().split_once(" b/").map_or(header.trim_end(), |(_, path)| path);
+            files.push((path.to_string(), String::new()));
+        }
+        if let Some((_, section)) = files.last_mut() {
+            section.push_str(line);
+        }
+    }
+    files
+}
+
+/// The files in `diff`, or, when there are too many to list one by one,
+/// the files grouped by the scope their path suggests.
+pub fn areas(diff: &str) -> Vec<Area> {
+    let files = split_files(diff);
+    if files.len() <= MAX_FILE_AREAS {
+        return files.into_iter().map(|(name, diff)| Area { name, diff }).collect();
+    }
+
+    let mut areas: Vec<Area> = Vec::new();
+    for (path, section) in files {
+        let name = scope::scope_of(&path).unwrap_or_else(|| "other".to_string());
+        match areas.iter_mut().find(|area| area.name == name) {
+            Some(area) => area.diff.push_str(&section),
+            None => areas.push(Area { name, diff: section }),
+        }
+    }
+    areas
+}
+
+/// A diff ready to go into a prompt: files matched by `.commitgenignore`
+/// are withheld, generated files condensed, submodule bumps spelled out
+/// and secrets redacted.
+pub fn for_prompt(diff: &str, config: &Config) -> anyhow::Result<String> {
+    let diff = describe_renames(&condense(&exclude(diff, &Ignore::load()?), &config.generated_files));
+    let diff = describe_submodules(&diff, |path, from, to| git::submodule_subjects(path, from, to).ok());
+    Redactor::from_config(config)?.apply(&diff)
+}
+
+/// Submodule commits listed for a bump before the rest are just counted.
+const MAX_SUBMODULE_COMMITS: usize = 15;
+
+/// Replaces each submodule pointer bump (`-Subproject commit abc` /
+/// `+Subproject commit def`) with the subjects of the commits it brings
+/// in, which `log` looks up for a submodule path and commit range. A
+/// submodule that isn't checked out, or moved backwards, keeps the bare
+/// hashes.
+pub fn describe_submodules(diff: &str, log: impl Fn(&str, &str, &str) -> Option<Vec<String>>) -> String {
+    let mut out = String::with_capacity(diff.len());
+    let mut rest = diff;
+    if let Some(start) = rest.find("diff --git ") {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+    }
+    for (path, section) in split_files(rest) {
+        let commit = |sign: &str| {
+            section.lines().find_map(|line| line.strip_prefix(sign)?.strip_prefix("Subproject commit "))
+                .map(|commit| commit.trim().trim_end_matches("-dirty"))
+        };
+        let (Some(from), Some(to)) = (commit("-"), commit("+")) else {
+            out.push_str(&section);
+            continue;
+        };
+        let Some(subjects) = log(&path, from, to).filter(|subjects| !subjects.is_empty()) else {
+            out.push_str(&section);
+            continue;
+        };
+
+        out.push_str(section.lines().next().unwrap_or_default());
+        out.push_str(&format!(
+            "\n[submodule {} updated {} → {}, {} commit{}:]\n",
+            path,
+            &from[..from.len().min(7)],
+            &to[..to.len().min(7)],
+            subjects.len(),
+            if subjects.len() == 1 { "" } else { "s" }
+        ));
+        for subject in subjects.iter().take(MAX_SUBMODULE_COMMITS) {
+            out.push_str(&format!("- {}\n", subject));
+        }
+        if subjects.len() > MAX_SUBMODULE_COMMITS {
+            out.push_str(&format!("- ... and {} more\n", subjects.len() - MAX_SUBMODULE_COMMITS));
+        }
+    }
+    out
+}
+
+/// Rewrites the headers of renamed and copied files (from a diff made with
+/// `--find-renames`) into a single `[moved X → Y ...]` line, so the model
+/// sees a move, with any edits as ordinary hunks after it.
+pub fn describe_renames(diff: &str) -> String {
+    let mut out = String::with_capacity(diff.len());
+    let mut rest = diff;
+    if let Some(start) = rest.find("diff --git ") {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+    }
+    for (_, section) in split_files(rest) {
+        let field = |name: &str| {
+            section.lines().find_map(|line| line.strip_prefix(name)).map(str::trim)
+        };
+        let moved = match (field("rename from "), field("rename to ")) {
+            (Some(from), Some(to)) => Some(("moved", from, to)),
+            _ => match (field("copy from "), field("copy to ")) {
+                (Some(from), Some(to)) => Some(("copied", from, to)),
+                _ => None,
+            },
+        };
+        let Some((verb, from, to)) = moved else {
+            out.push_str(&section);
+            continue;
+        };
+
+        let hunks = section.find("\n@@").map(|i| &section[i + 1..]);
+        let similarity = field("similarity index ").unwrap_or("100%");
+        let edits = if hunks.is_some() { format!("with edits, {} similar", similarity) } else { "unchanged".to_string() };
+        out.push_str(section.lines().next().unwrap_or_default());
+        out.push_str(&format!("\n[{} {} → {}, {}]\n", verb, from, to, edits));
+        if let Some(hunks) = hunks {
+            out.push_str(&format!("--- a/{}\n+++ b/{}\n{}", from, to, hunks));
+        }
+    }
+    out
+}
+
+/// Replaces the sections of ignored files with a note that they changed,
+/// so their contents never leave the machine.
+pub fn exclude(diff: &str, ignore: &Ignore) -> String {
+    let mut out = String::with_capacity(diff.len());
+    let mut rest = diff;
+    if let Some(start) = rest.find("diff --git ") {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+    }
+    for (path, section) in split_files(rest) {
+        if ignore.is_ignored(&path) {
+            let header = section.lines().next().unwrap_or_default();
+            out.push_str(&format!("{}\n[contents excluded by {}]\n", header, ignore::FILE_NAME));
+        } else {
+            out.push_str(&section);
+        }
+    }
+    out
+}
+
+/// Replaces the sections of generated files (the built-in list plus
+/// `extra` globs) with a one-line summary each, e.g.
+/// `Cargo.lock: 3 packages changed (serde, syn, tokio)`.
+pub fn condense(diff: &str, extra: &[String]) -> String {
+    let mut out = String::with_capacity(diff.len());
+    let mut rest = diff;
+    // Anything before the first file header is kept as is.
+    if let Some(start) = rest.find("diff --git ") {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+    }
+    for (path, section) in split_files(rest) {
+        let generated = GENERATED_FILES.iter().copied().chain(extra.iter().map(String::as_str));
+        if generated.into_iter().any(|pattern| glob::matches(pattern, &path)) {
+            let header = section.lines().next().unwrap_or_default();
+            out.push_str(&format!("{}\n[{}]\n", header, summarize_generated(&path, &section)));
+        } else {
+            out.push_str(&section);
+        }
+    }
+    out
+}
+
+/// A one-line account of a generated file's changes: which packages a
+/// lockfile changes, where it names them as `name = "..."` (Cargo, Poetry,
+/// uv), and otherwise how many lines changed.
+pub fn summarize_generated(path: &str, section: &str) -> String {
+    if section.lines().any(|line| line.starts_with("Binary files ") || line == "GIT binary patch") {
+        return format!("{}: binary generated file changed", path);
+    }
+
+    let (mut added, mut removed) = (0, 0);
+    let mut package: Option<&str> = None;
+    let mut packages: Vec<&str> = Vec::new();
+    for line in section.lines().skip_while(|line| !line.starts_with("@@")) {
+        let (changed, code) = match line.split_at_checked(1) {
+            Some(("+", code)) => {
+                added += 1;
+                (true, code)
+            }
+            Some(("-", code)) => {
+                removed += 1;
+                (true, code)
+            }
+            Some((_, code)) => (false, code),
+            None => continue,
+        };
+        if let Some(name) = code.trim().strip_prefix("name = \"").and_then(|name| name.strip_suffix('"')) {
+            package = Some(name);
+        }
+        if changed
+            && let Some(name) = package
+            && !packages.contains(&name)
+        {
+            packages.push(name);
+        }
+    }
+
+    if packages.is_empty() {
+        return format!("{}: generated file, {} lines added and {} removed (contents omitted)", path, added, removed);
+    }
+    let mut names = packages[..packages.len().min(MAX_NAMED_PACKAGES)].join(", ");
+    if packages.len() > MAX_NAMED_PACKAGES {
+        names.push_str(&format!(" and {} more", packages.len() - MAX_NAMED_PACKAGES));
+    }
+    let plural = if packages.len() == 1 { "" } else { "s" };
+    format!("{}: {} package{} changed ({})", path, packages.len(), plural, names)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_files() {
+        let diff = "diff --git a/src/a.rs b/src/a.rs\n+a\ndiff --git a/b.md b/b.md\n+b\n";
+        assert_eq!(
+            split_files(diff),
+            vec![
+                ("src/a.rs".to_string(), "diff --git a/src/a.rs b/src/a.rs\n+a\n".to_string()),
+                ("b.md".to_string(), "diff --git a/b.md b/b.md\n+b\n".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_condense_lockfile() {
+        let diff = "diff --git a/Cargo.lock b/Cargo.lock\n\
+            --- a/Cargo.lock\n\
+            +++ b/Cargo.lock\n\
+            @@ -1,6 +1,6 @@\n \
+            [[package]]\n \
+            name = \"serde\"\n\
+            -version = \"1.0.1\"\n\
+            +version = \"1.0.2\"\n \
+            [[package]]\n \
+            name = \"syn\"\n \
+            version = \"2.0.0\"\n\
+            diff --git a/src/a.rs b/src/a.rs\n+a\n";
+        assert_eq!(
+            condense(diff, &[]),
+            "diff --git a/Cargo.lock b/Cargo.lock\n[Cargo.lock: 1 package changed (serde)]\n\
+            diff --git a/src/a.rs b/src/a.rs\n+a\n"
+        );
+    }
+
+    #[test]
+    fn test_condense_extra_globs() {
+        let diff = "diff --git a/gen/api.rs b/gen/api.rs\n@@ -1 +1,2 @@\n-a\n+b\n+c\n";
+        assert_eq!(condense(diff, &[]), diff);
+        assert_eq!(
+            condense(diff, &["gen/**".to_string()]),
+            "diff --git a/gen/api.rs b/gen/api.rs\n[gen/api.rs: generated file, 2 lines added and 1 removed (contents omitted)]\n"
+        );
+    }
+
+    #[test]
+    fn test_exclude() {
+        let diff = "diff --git a/fixtures/keys.json b/fixtures/keys.json\n+secret\ndiff --git a/src/a.rs b/src/a.rs\n+a\n";
+        assert_eq!(
+            exclude(diff, &Ignore::parse("fixtures/\n")),
+            "diff --git a/fixtures/keys.json b/fixtures/keys.json\n[contents excluded by .commitgenignore]\n\
+            diff --git a/src/a.rs b/src/a.rs\n+a\n"
+        );
+    }
+
+    #[test]
+    fn test_describe_submodules() {
+        let diff = "diff --git a/vendor/lib b/vendor/lib\nindex 1111111..2222222 160000\n--- a/vendor/lib\n+++ b/vendor/lib\n\
+            @@ -1 +1 @@\n-Subproject commit 1111111aaaa\n+Subproject commit 2222222bbbb-dirty\n\
+            diff --git a/src/main.rs b/src/main.rs\n+x\n";
+        let described = describe_submodules(diff, |path, from, to| {
+            assert_eq!((path, from, to), ("vendor/lib", "1111111aaaa", "2222222bbbb"));
+            Some(vec!["Fix overflow".into(), "Add API".into()])
+        });
+        assert_eq!(
+            described,
+            "diff --git a/vendor/lib b/vendor/lib\n[submodule vendor/lib updated 1111111 → 2222222, 2 commits:]\n\
+            - Fix overflow\n- Add API\ndiff --git a/src/main.rs b/src/main.rs\n+x\n"
+        );
+        assert_eq!(describe_submodules(diff, |_, _, _| None), diff);
+    }
+
+    #[test]
+    fn test_describe_renames() {
+        let diff = "diff --git a/src/old.rs b/src/new.rs\n\
+            similarity index 90%\n\
+            rename from src/old.rs\n\
+            rename to src/new.rs\n\
+            index 1..2 100644\n\
+            --- a/src/old.rs\n\
+            +++ b/src/new.rs\n\
+            @@ -1 +1 @@\n-a\n+b\n\
+            diff --git a/a.txt b/b.txt\n\
+            similarity index 100%\n\
+            rename from a.txt\n\
+            rename to b.txt\n";
+        assert_eq!(
+            describe_renames(diff),
+            "diff --git a/src/old.rs b/src/new.rs\n\
+            [moved src/old.rs → src/new.rs, with edits, 90% similar]\n\
+            --- a/src/old.rs\n+++ b/src/new.rs\n@@ -1 +1 @@\n-a\n+b\n\
+            diff --git a/a.txt b/b.txt\n\
+            [moved a.txt → b.txt, unchanged]\n"
+        );
+    }
+
+    #[test]
+    fn test_areas_group_many_files() {
+        let diff: String = (0..10)
+            .map(|i| {
+                let dir = if i % 2 == 0 { "llm" } else { "auth" };
+                format!("diff --git a/src/{dir}/{i}.rs b/src/{dir}/{i}.rs\n+{i}\n")
+            })
+            .collect();
+        let areas = areas(&diff);
+        assert_eq!(areas.iter().map(|a| a.name.as_str()).collect::<Vec<_>>(), ["llm", "auth"]);
+        assert!(areas[0].diff.contains("+8\n"));
+    }
+}
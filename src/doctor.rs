@@ -0,0 +1,271 @@
+use std::path::Path;
+use std::time::Instant;
+
+use crate::config::{self, Config, Origin};
+use crate::llm::registry::{self, Provider};
+use crate::selection::Selection;
+use crate::{git, hook, http, prompt, style};
+
+/// What providers are asked to prove they work, here and in `init`.
+pub const TEST_PROMPT: &str = "Reply with the single word OK.";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Outcome {
+    Ok,
+    /// Works, but probably not as meant.
+    Warn,
+    Fail,
+}
+
+/// The result of one of `commitgen doctor`'s checks, and what to do about
+/// it when it isn't good.
+#[derive(Debug)]
+pub struct Check {
+    pub outcome: Outcome,
+    pub name: String,
+    pub detail: String,
+    pub fix: Option<String>,
+}
+
+impl Check {
+    fn ok(name: &str, detail: impl Into<String>) -> Self {
+        Self { outcome: Outcome::Ok, name: name.to_string(), detail: detail.into(), fix: None }
+    }
+
+    fn warn(name: &str, detail: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self { outcome: Outcome::Warn, name: name.to_string(), detail: detail.into(), fix: Some(fix.into()) }
+    }
+
+    /// `detail` with its causes, for errors.
+    fn fail(name: &str, detail: impl std::fmt::Display, fix: impl Into<String>) -> Self {
+        Self { outcome: Outcome::Fail, name: name.to_string(), detail: format!("{:#}", detail), fix: Some(fix.into()) }
+    }
+}
+
+/// Whether git runs, and whether there's a repository here.
+pub fn git() -> Vec<Check> {
+    let version = match git::run(&["--version"]) {
+        Ok(version) => version.trim().to_string(),
+        Err(e) => return vec![Check::fail("git", e, "Install git and make sure it's on PATH")],
+    };
+    let mut checks = vec![Check::ok("git", version)];
+    match git::run(&["rev-parse", "--show-toplevel"]) {
+        Ok(root) => checks.push(Check::ok("repository", root.trim())),
+        Err(_) => checks.push(Check::warn(
+            "repository",
+            "not in a git repository, so its .commitgen.toml and hook weren't checked",
+            "Run `commitgen doctor` inside the repository, or pass -C PATH",
+        )),
+    }
+    checks
+}
+
+/// Reads the config files, reporting keys that would be ignored, and
+/// returns the config when it loads.
+pub fn config(profile: Option<&str>) -> (Vec<Check>, Option<Config>) {
+    const EDIT: &str = "Fix it with `commitgen config edit` (`--repo` for the repository's .commitgen.toml)";
+    let layers = match config::layers(profile) {
+        Ok(layers) => layers,
+        Err(e) => return (vec![Check::fail("config", e, EDIT)], None),
+    };
+    let config = match Config::load(profile) {
+        Ok(config) => config,
+        Err(e) => return (vec![Check::fail("config", e, EDIT)], None),
+    };
+
+    let files: Vec<String> = layers
+        .iter()
+        .filter(|layer| matches!(layer.origin, Origin::User(_) | Origin::Repo(_)))
+        .map(|layer| layer.origin.to_string())
+        .collect();
+    let mut detail = if files.is_empty() { "no config file; using the defaults".to_string() } else { files.join(", ") };
+    if let Some(profile) = &config.profile {
+        detail.push_str(&format!(" (profile {})", profile));
+    }
+    let mut checks = vec![Check::ok("config", detail)];
+    for layer in layers.iter().filter(|layer| matches!(layer.origin, Origin::User(_) | Origin::Repo(_) | Origin::Env)) {
+        let unknown = config::unknown_keys(&layer.table);
+        if !unknown.is_empty() {
+            checks.push(Check::warn(
+                "config",
+                format!("{} has keys commitgen doesn't know, which are ignored: {}", layer.origin, unknown.join(", ")),
+                "Check their spelling against the README, or remove them",
+            ));
+        }
+    }
+    if let Err(e) = style::load_custom() {
+        checks.push(Check::fail("styles", e, "Fix or remove the style file"));
+    }
+    if let Some(path) = &config.prompt_template
+        && let Err(e) = prompt::load_template(path)
+    {
+        checks.push(Check::fail("template", e, "Fix the template, or unset prompt_template"));
+    }
+    (checks, Some(config))
+}
+
+/// Whether requests can be sent at all with the proxy and TLS settings.
+pub fn network(config: &Config) -> Vec<Check> {
+    const EDIT: &str = "Fix it with `commitgen config edit`";
+    if let Err(e) = http::init(config) {
+        return vec![Check::fail("network", e, EDIT)];
+    }
+    let mut checks = Vec::new();
+    if let Err(e) = http::builder().and_then(|builder| Ok(builder.build()?)) {
+        checks.push(Check::fail("network", e, "Fix `proxy` or `ca_bundle` with `commitgen config edit`"));
+    }
+    if config.insecure_skip_verify {
+        checks.push(Check::warn(
+            "network",
+            "insecure_skip_verify is set, so TLS certificates aren't checked",
+            "Trust the proxy's certificate with `ca_bundle` instead, and remove insecure_skip_verify",
+        ));
+    }
+    checks
+}
+
+/// The provider and each fallback: that each is set up, and with `call`,
+/// that it answers a one-word prompt.
+pub async fn providers(config: &Config, call: bool) -> Vec<Check> {
+    let selection = Selection::from_config(config);
+    let mut checks = Vec::new();
+    for name in selection.names() {
+        checks.push(provider(&selection, &name, call).await);
+    }
+    checks
+}
+
+async fn provider(selection: &Selection<'_>, name: &str, call: bool) -> Check {
+    let provider = match registry::lookup(name) {
+        Ok(provider) => provider,
+        Err(e) => return Check::fail(name, e, "Set `provider` or `fallback` to one of these with `commitgen config edit`"),
+    };
+    let model = selection.model(name).unwrap_or_default();
+    let client = match selection.options(name).and_then(|options| Ok((selection.client(name)?, options))) {
+        Ok(client) => client,
+        Err(e) => return Check::fail(name, &e, provider_fix(provider, &format!("{:#}", e))),
+    };
+    if !call {
+        return Check::ok(name, format!("{} is set up, but wasn't called", model));
+    }
+    let (client, options) = client;
+    let started = Instant::now();
+    match client.generate(TEST_PROMPT, &options).await {
+        Ok(_) => Check::ok(name, format!("{} answered in {:.1}s", model, started.elapsed().as_secs_f64())),
+        Err(e) => Check::fail(name, &e, provider_fix(provider, &format!("{:#}", e))),
+    }
+}
+
+/// What to do about `error` from `provider`, going by what it says.
+fn provider_fix(provider: &Provider, error: &str) -> String {
+    let lower = error.to_lowercase();
+    let has = |words: &[&str]| words.iter().any(|word| lower.contains(word));
+    match provider.key_var {
+        Some(var) if error.contains(var) && has(&["must be set"]) => {
+            return format!("Store a key with `commitgen auth set {}`, or set {}", provider.name, var);
+        }
+        _ if has(&["must be set"]) => return "Set the variable it names, in the environment or .env".to_string(),
+        _ => {}
+    }
+    if has(&["429", "rate limit", "quota"]) {
+        "The key works but is rate limited or out of quota; try again later, or check the account's plan".to_string()
+    } else if has(&["401", "403", "unauthorized", "forbidden", "api key", "permission"]) {
+        match provider.key_var {
+            Some(_) => format!("Check the key, and replace it with `commitgen auth set {}`", provider.name),
+            None => "Check the credentials it uses, and that they can reach the model".to_string(),
+        }
+    } else if has(&["unknown model", "model not found", "404", "does not exist"]) {
+        format!(
+            "`commitgen models {0}` lists its models; choose one with `commitgen config set models.{0} MODEL`",
+            provider.name
+        )
+    } else if has(&["error sending request", "timed out", "timeout", "connect", "dns", "certificate"]) {
+        "Check the network connection, and `proxy`, `ca_bundle` and `[endpoints]` in the config".to_string()
+    } else {
+        "Run `commitgen -v doctor` to see the requests".to_string()
+    }
+}
+
+/// Whether the prepare-commit-msg hook is installed and would run this
+/// commitgen; nothing outside a repository.
+pub fn hook() -> Option<Check> {
+    git::run(&["rev-parse", "--git-dir"]).ok()?;
+    let status = match hook::status() {
+        Ok(status) => status,
+        Err(e) => return Some(Check::fail("hook", e, "Check the hooks directory's permissions")),
+    };
+    Some(match status {
+        hook::Status::NotInstalled(_) => {
+            Check::ok("hook", "not installed; `commitgen hook install` fills in messages on `git commit`")
+        }
+        hook::Status::Foreign(path) => Check::warn(
+            "hook",
+            format!("{} is another prepare-commit-msg hook", path.display()),
+            "`commitgen hook install --force` replaces it, or call `commitgen hook run \"$@\"` from it",
+        ),
+        hook::Status::Installed(path) => {
+            let contents = std::fs::read_to_string(&path).unwrap_or_default();
+            let exe = std::env::current_exe().unwrap_or_default();
+            installed_hook(&path, &contents, &exe, is_executable(&path))
+        }
+    })
+}
+
+fn installed_hook(path: &Path, contents: &str, exe: &Path, executable: bool) -> Check {
+    if !executable {
+        Check::fail(
+            "hook",
+            format!("{} isn't executable, so git skips it", path.display()),
+            format!("chmod +x {}", git::shell_quote(&path.to_string_lossy())),
+        )
+    } else if contents != hook::script(exe) {
+        Check::warn(
+            "hook",
+            format!("{} runs another commitgen than this one", path.display()),
+            "`commitgen hook install --force` points it at this one",
+        )
+    } else {
+        Check::ok("hook", format!("installed at {}", path.display()))
+    }
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::metadata(path).is_ok_and(|metadata| metadata.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(_: &Path) -> bool {
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_provider_fix() {
+        let openai = registry::lookup("openai").unwrap();
+        let missing = "OPENAI_API_KEY must be set in .env or stored with `commitgen auth set`";
+        assert_eq!(provider_fix(openai, missing), "Store a key with `commitgen auth set openai`, or set OPENAI_API_KEY");
+        let unauthorized = "OpenAI API returned HTTP 401 Unauthorized: Incorrect API key provided";
+        assert!(provider_fix(openai, unauthorized).starts_with("Check the key"));
+        let rate_limited = "OpenAI API returned HTTP 429 Too Many Requests: You exceeded your current quota";
+        assert!(provider_fix(openai, rate_limited).contains("rate limited"));
+        let model = "OpenAI API returned HTTP 404 Not Found: The model `gpt-9` does not exist";
+        assert!(provider_fix(openai, model).starts_with("`commitgen models openai`"));
+        let offline = "error sending request for url (https://api.openai.com/v1/chat/completions)";
+        assert!(provider_fix(openai, offline).contains("proxy"));
+    }
+
+    #[test]
+    fn test_installed_hook() {
+        let path = Path::new("/repo/.git/hooks/prepare-commit-msg");
+        let exe = Path::new("/usr/local/bin/commitgen");
+        let script = hook::script(exe);
+        assert_eq!(installed_hook(path, &script, exe, true).outcome, Outcome::Ok);
+        assert_eq!(installed_hook(path, &script, Path::new("/old/commitgen"), true).outcome, Outcome::Warn);
+        assert_eq!(installed_hook(path, &script, exe, false).outcome, Outcome::Fail);
+    }
+}
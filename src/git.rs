@@ -0,0 +1,421 @@
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// Runs `git` with `args` in the current directory and returns stdout.
+pub fn run(args: &[&str]) -> anyhow::Result<String> {
+    run_allowing(args, &[0])
+}
+
+/// Like [`run`], but treats any of the `ok` exit codes as success; some
+/// commands (`diff --no-index`) exit 1 to report differences.
+fn run_allowing(args: &[&str], ok: &[i32]) -> anyhow::Result<String> {
+    tracing::debug!("git {}", args.join(" "));
+    let output = Command::new("git").args(args).output().map_err(|e| {
+        if e.kind() == std::io::ErrorKind::NotFound {
+            anyhow::anyhow!("git was not found on PATH")
+        } else {
+            anyhow::anyhow!("Failed to run git: {}", e)
+        }
+    })?;
+
+    if !output.status.code().is_some_and(|code| ok.contains(&code)) {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(anyhow::anyhow!("`git {}` failed: {}", args.join(" "), stderr.trim()));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// How much unchanged code surrounds each change in a diff.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Context {
+    /// Lines of context (`git diff -U`) instead of git's default of three.
+    pub lines: Option<u32>,
+    /// Show the whole function around each change (`git diff -W`).
+    pub function: bool,
+}
+
+impl Context {
+    fn args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if let Some(lines) = self.lines {
+            args.push(format!("--unified={}", lines));
+        }
+        if self.function {
+            args.push("--function-context".to_string());
+        }
+        args
+    }
+}
+
+/// Runs `git diff` with `args` (everything after `diff`) and `context`.
+fn run_diff(args: &[&str], context: &Context, ok: &[i32]) -> anyhow::Result<String> {
+    let context = context.args();
+    let mut all = vec!["diff"];
+    all.extend(context.iter().map(String::as_str));
+    all.extend(args);
+    run_allowing(&all, ok)
+}
+
+/// The changes staged for the next commit.
+pub fn staged_diff(context: &Context) -> anyhow::Result<String> {
+    let diff = run_diff(&["--cached", "--no-color", "--no-ext-diff", "--find-renames"], context, &[0])?;
+    if diff.trim().is_empty() {
+        anyhow::bail!("Nothing is staged; stage changes with `git add` or pass a description");
+    }
+    Ok(diff)
+}
+
+/// Staged and unstaged changes to tracked files, plus untracked files
+/// (minus ignored ones) when `include_untracked` is set.
+pub fn working_tree_diff(include_untracked: bool, context: &Context) -> anyhow::Result<String> {
+    let mut diff = if has_head()? {
+        run_diff(&["HEAD", "--no-color", "--no-ext-diff", "--find-renames"], context, &[0])?
+    } else {
+        // Nothing to diff against before the first commit; everything
+        // tracked is either staged or not.
+        let mut diff = run_diff(&["--cached", "--no-color", "--no-ext-diff", "--find-renames"], context, &[0])?;
+        diff.push_str(&run_diff(&["--no-color", "--no-ext-diff", "--find-renames"], context, &[0])?);
+        diff
+    };
+
+    if include_untracked {
+        let untracked = run(&["ls-files", "-z", "--others", "--exclude-standard"])?;
+        for path in untracked.split('\0').filter(|p| !p.is_empty()) {
+            diff.push_str(&run_diff(
+                &["--no-color", "--no-ext-diff", "--no-index", "--", "/dev/null", path],
+                context,
+                &[0, 1],
+            )?);
+        }
+    }
+
+    if diff.trim().is_empty() {
+        anyhow::bail!("The working tree has no changes");
+    }
+    Ok(diff)
+}
+
+/// What the last commit changed, plus anything staged since: the diff an
+/// amended commit would end up with.
+pub fn amend_diff(context: &Context) -> anyhow::Result<String> {
+    if !has_head()? {
+        anyhow::bail!("There is no commit to amend yet");
+    }
+    let parent = parent_or_empty_tree("HEAD")?;
+    run_diff(&["--cached", "--no-color", "--no-ext-diff", "--find-renames", &parent], context, &[0])
+}
+
+/// Everything the commits from `first` to `last` changed together.
+pub fn range_diff(first: &str, last: &str) -> anyhow::Result<String> {
+    run(&["diff", "--no-color", "--no-ext-diff", "--find-renames", &parent_or_empty_tree(first)?, last, "--"])
+}
+
+/// What `theirs` changed since it diverged from `ours`: the changes a
+/// merge of `theirs` would bring in.
+pub fn merge_base_diff(ours: &str, theirs: &str) -> anyhow::Result<String> {
+    run(&["diff", "--no-color", "--no-ext-diff", "--find-renames", &format!("{}...{}", ours, theirs), "--"])
+}
+
+/// The first parent of `rev`, or for a root commit the empty tree, which
+/// diffs as if every file were new.
+fn parent_or_empty_tree(rev: &str) -> anyhow::Result<String> {
+    let parent = format!("{}~1", rev);
+    if rev_exists(&parent)? {
+        Ok(parent)
+    } else {
+        Ok(run(&["hash-object", "-t", "tree", "/dev/null"])?.trim().to_string())
+    }
+}
+
+pub fn has_staged_changes() -> anyhow::Result<bool> {
+    Ok(!run(&["diff", "--cached", "--name-only"])?.trim().is_empty())
+}
+
+/// The full message of the last commit.
+pub fn head_message() -> anyhow::Result<String> {
+    commit_message("HEAD")
+}
+
+pub fn commit_message(rev: &str) -> anyhow::Result<String> {
+    Ok(run(&["log", "-1", "--format=%B", rev, "--"])?.trim().to_string())
+}
+
+/// Subjects of the last `count` non-merge commits, newest first; none
+/// before the first commit.
+pub fn recent_subjects(count: usize) -> anyhow::Result<Vec<String>> {
+    if count == 0 || !has_head()? {
+        return Ok(Vec::new());
+    }
+    let count = format!("--max-count={}", count);
+    Ok(run(&["log", &count, "--no-merges", "--format=%s", "HEAD", "--"])?
+        .lines()
+        .map(str::to_string)
+        .collect())
+}
+
+/// Full messages of the non-merge commits in `range`, oldest first.
+pub fn messages(range: &str) -> anyhow::Result<Vec<String>> {
+    rev_list(&["--reverse", "--no-merges", range, "--"])?
+        .iter()
+        .map(|sha| commit_message(sha))
+        .collect()
+}
+
+/// Subjects of the commits a submodule at `path` gained going from `from`
+/// to `to`, oldest first. Fails when the submodule isn't checked out or
+/// doesn't have the commits.
+pub fn submodule_subjects(path: &str, from: &str, to: &str) -> anyhow::Result<Vec<String>> {
+    // Diff paths are from the top of the repository, wherever we're run.
+    let path = Path::new(run(&["rev-parse", "--show-toplevel"])?.trim()).join(path);
+    let range = format!("{}..{}", from, to);
+    let out = run(&["-C", &path.to_string_lossy(), "log", "--reverse", "--no-merges", "--format=%s", &range, "--"])?;
+    Ok(out.lines().map(str::to_string).collect())
+}
+
+/// What a single commit changed, relative to its first parent.
+pub fn commit_diff(rev: &str) -> anyhow::Result<String> {
+    run(&["show", "--format=", "--no-color", "--no-ext-diff", "--find-renames", "--first-parent", rev, "--"])
+}
+
+/// Full hashes from `git rev-list` with `args`, one per line.
+pub fn rev_list(args: &[&str]) -> anyhow::Result<Vec<String>> {
+    let mut full = vec!["rev-list"];
+    full.extend_from_slice(args);
+    Ok(run(&full)?.lines().map(str::to_string).collect())
+}
+
+pub fn is_ancestor(ancestor: &str, descendant: &str) -> anyhow::Result<bool> {
+    let output = Command::new("git")
+        .args(["merge-base", "--is-ancestor", ancestor, descendant])
+        .output()?;
+    Ok(output.status.success())
+}
+
+/// Files that merging `theirs` into `ours` would leave conflicted, found
+/// with `git merge-tree` without touching the working tree. Empty when
+/// the merge is clean, or when git is too old (before 2.38) to tell.
+pub fn merge_conflicts(ours: &str, theirs: &str) -> anyhow::Result<Vec<String>> {
+    let output = Command::new("git")
+        .args(["merge-tree", "--write-tree", "--name-only", "--no-messages", ours, theirs])
+        .output()
+        .map_err(|e| anyhow::anyhow!("Failed to run git: {}", e))?;
+    if output.status.code() != Some(1) {
+        return Ok(Vec::new());
+    }
+    // The first line is the tree git would write; conflicted paths follow.
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .skip(1)
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect())
+}
+
+/// Merges `branch` into the current branch with `message`, always making
+/// a merge commit. Returns `false` when the merge stopped for conflicts;
+/// git then keeps the message for the `git commit` that concludes it.
+pub fn merge(branch: &str, message: &str) -> anyhow::Result<bool> {
+    let path = std::env::temp_dir().join(format!("commitgen-merge-{}", std::process::id()));
+    std::fs::write(&path, message)?;
+    let status = Command::new("git")
+        .args(["merge", "--no-ff", "--file"])
+        .arg(&path)
+        .arg(branch)
+        .status()
+        .map_err(|e| anyhow::anyhow!("Failed to run git: {}", e));
+    let _ = std::fs::remove_file(&path);
+    Ok(status?.success())
+}
+
+/// Creates the annotated tag `name` on `rev` with `message`, signed when
+/// `sign` is set (`git tag -s`, with git's configured signing key).
+pub fn tag(name: &str, rev: &str, message: &str, sign: bool) -> anyhow::Result<()> {
+    let path = std::env::temp_dir().join(format!("commitgen-tag-{}", std::process::id()));
+    std::fs::write(&path, message)?;
+    let status = Command::new("git")
+        .args(["tag", if sign { "--sign" } else { "--annotate" }, "--cleanup=whitespace", "--file"])
+        .arg(&path)
+        .args([name, rev])
+        .status()
+        .map_err(|e| anyhow::anyhow!("Failed to run git: {}", e));
+    let _ = std::fs::remove_file(&path);
+    if !status?.success() {
+        anyhow::bail!("git tag failed");
+    }
+    Ok(())
+}
+
+/// Runs an interactive rebase onto `base` (or from the root commit) with
+/// the todo list replaced by `todo_file`, so no editor is involved.
+pub fn scripted_rebase(base: Option<&str>, todo_file: &Path) -> anyhow::Result<()> {
+    let mut command = Command::new("git");
+    command.args(["rebase", "--interactive", "--autostash"]);
+    match base {
+        Some(base) => command.arg(base),
+        None => command.arg("--root"),
+    };
+    let status = command
+        .env("GIT_SEQUENCE_EDITOR", format!("cp {}", shell_quote(&todo_file.to_string_lossy())))
+        .env("GIT_EDITOR", "true")
+        .status()
+        .map_err(|e| anyhow::anyhow!("Failed to run git: {}", e))?;
+    if !status.success() {
+        anyhow::bail!("git rebase failed; see `git status`, and `git rebase --abort` to undo");
+    }
+    Ok(())
+}
+
+/// Applies the patch in `path` to the index only, as `git add -p` would.
+pub fn apply_cached(path: &Path) -> anyhow::Result<()> {
+    run(&["apply", "--cached", "--whitespace=nowarn", &path.to_string_lossy()])?;
+    Ok(())
+}
+
+/// Quotes `s` for a POSIX shell; git runs hooks and editors through one.
+pub fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', r"'\''"))
+}
+
+/// A value from git's configuration, or `None` when it isn't set.
+pub fn config_value(key: &str) -> anyhow::Result<Option<String>> {
+    let value = run_allowing(&["config", "--get", key], &[0, 1])?;
+    let value = value.trim();
+    Ok((!value.is_empty()).then(|| value.to_string()))
+}
+
+/// `Name <email>` from `user.name` and `user.email`, as git signs off with.
+pub fn identity() -> anyhow::Result<String> {
+    match (config_value("user.name")?, config_value("user.email")?) {
+        (Some(name), Some(email)) => Ok(format!("{} <{}>", name, email)),
+        _ => anyhow::bail!("user.name and user.email must be set in git config to sign off"),
+    }
+}
+
+/// The checked-out branch, or `None` on a detached HEAD.
+pub fn current_branch() -> anyhow::Result<Option<String>> {
+    let branch = run_allowing(&["symbolic-ref", "--quiet", "--short", "HEAD"], &[0, 1])?;
+    let branch = branch.trim();
+    Ok((!branch.is_empty()).then(|| branch.to_string()))
+}
+
+fn has_head() -> anyhow::Result<bool> {
+    rev_exists("HEAD")
+}
+
+pub fn rev_exists(rev: &str) -> anyhow::Result<bool> {
+    Ok(!run_allowing(&["rev-parse", "--verify", "--quiet", rev], &[0, 1])?.trim().is_empty())
+}
+
+/// Whether `name` is acceptable to git as a branch name.
+pub fn is_valid_branch_name(name: &str) -> anyhow::Result<bool> {
+    let output = Command::new("git")
+        .args(["check-ref-format", "--branch", name])
+        .output()
+        .map_err(|e| anyhow::anyhow!("Failed to run git: {}", e))?;
+    Ok(output.status.success())
+}
+
+pub struct CommitOptions {
+    /// Commit unstaged changes to tracked files too (`git commit --all`).
+    pub all: bool,
+    /// Replace the last commit instead of adding a new one.
+    pub amend: bool,
+    pub no_verify: bool,
+    /// Sign (`Some(true)`) or don't sign the commit regardless of
+    /// `commit.gpgsign`; `None` leaves it to git's configuration.
+    pub sign: Option<bool>,
+    /// Open git's editor on the message before committing.
+    pub edit: bool,
+}
+
+/// Runs `git commit` with `message`, leaving hooks, signing and the rest
+/// of git's own configuration in charge. git's output goes straight to
+/// the terminal.
+pub fn commit(message: &str, options: &CommitOptions) -> anyhow::Result<()> {
+    let mut command = Command::new("git");
+    command.arg("commit");
+    if options.all {
+        command.arg("--all");
+    }
+    if options.amend {
+        command.arg("--amend");
+    }
+    if options.no_verify {
+        command.arg("--no-verify");
+    }
+    match options.sign {
+        Some(true) => command.arg("--gpg-sign"),
+        Some(false) => command.arg("--no-gpg-sign"),
+        None => &mut command,
+    };
+    if options.edit {
+        return edit_commit(command, message);
+    }
+    command.args(["--file", "-", "--cleanup", "whitespace"]);
+    write_commit(command, message)
+}
+
+/// Runs a `git commit` `command` that opens git's editor on `message`,
+/// under git's usual comment lines, which are stripped again afterwards.
+fn edit_commit(mut command: Command, message: &str) -> anyhow::Result<()> {
+    let path = std::env::temp_dir().join(format!("commitgen-message-{}", std::process::id()));
+    std::fs::write(&path, message)?;
+    let status = command
+        .args(["--edit", "--cleanup", "strip", "--file"])
+        .arg(&path)
+        .status()
+        .map_err(|e| anyhow::anyhow!("Failed to run git: {}", e));
+    let _ = std::fs::remove_file(&path);
+    if !status?.success() {
+        anyhow::bail!("git commit failed");
+    }
+    Ok(())
+}
+
+/// Replaces the last commit's message with `message`, leaving its changes
+/// and the index alone.
+pub fn reword_head(message: &str) -> anyhow::Result<()> {
+    let mut command = Command::new("git");
+    command.args(["commit", "--amend", "--only", "--quiet", "--file", "-", "--cleanup", "whitespace"]);
+    write_commit(command, message)
+}
+
+/// Runs a `git commit` `command` that reads its message from stdin.
+fn write_commit(mut command: Command, message: &str) -> anyhow::Result<()> {
+    // git's stdin is the message, so gpg's pinentry can't find the terminal
+    // to ask for a passphrase on unless it's told.
+    if std::env::var_os("GPG_TTY").is_none()
+        && let Some(tty) = terminal()
+    {
+        command.env("GPG_TTY", tty);
+    }
+    let mut child = command
+        .stdin(Stdio::piped())
+        .spawn()
+        .map_err(|e| anyhow::anyhow!("Failed to run git: {}", e))?;
+    child
+        .stdin
+        .take()
+        .expect("stdin is piped")
+        .write_all(message.as_bytes())?;
+
+    if !child.wait()?.success() {
+        anyhow::bail!("git commit failed");
+    }
+    Ok(())
+}
+
+/// The terminal on our stdin, when there is one and it can be named.
+fn terminal() -> Option<std::path::PathBuf> {
+    use std::io::IsTerminal;
+
+    if !std::io::stdin().is_terminal() {
+        return None;
+    }
+    std::fs::read_link("/proc/self/fd/0").ok().or_else(|| {
+        let output = Command::new("tty").stdin(Stdio::inherit()).output().ok()?;
+        let tty = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        (output.status.success() && !tty.is_empty()).then(|| tty.into())
+    })
+}
@@ -0,0 +1,67 @@
+/// Matches `path` against a gitignore-style glob: `*` and `?` stay within
+/// a path segment, `**` spans any number of them. A pattern without a `/`
+/// matches a file or directory of that name anywhere, and with only a
+/// trailing `/`, a directory of that name anywhere; any other `/` anchors
+/// the pattern at the repository root.
+pub fn matches(pattern: &str, path: &str) -> bool {
+    let segments: Vec<&str> = path.split('/').collect();
+    if let Some(anchored) = pattern.strip_prefix('/') {
+        return match_segments(&anchored.split('/').collect::<Vec<_>>(), &segments);
+    }
+    match pattern.strip_suffix('/') {
+        Some(dir) if !dir.contains('/') => {
+            let dirs = &segments[..segments.len() - 1];
+            dirs.iter().any(|segment| match_segment(dir.as_bytes(), segment.as_bytes()))
+        }
+        _ if pattern.contains('/') => match_segments(&pattern.split('/').collect::<Vec<_>>(), &segments),
+        _ => segments.iter().any(|segment| match_segment(pattern.as_bytes(), segment.as_bytes())),
+    }
+}
+
+fn match_segments(pattern: &[&str], path: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((&"**", rest)) => (0..=path.len()).any(|skip| match_segments(rest, &path[skip..])),
+        // A trailing `dir/` matches everything under the directory.
+        Some((&"", [])) => !path.is_empty(),
+        Some((first, rest)) => match path.split_first() {
+            Some((segment, path)) => match_segment(first.as_bytes(), segment.as_bytes()) && match_segments(rest, path),
+            None => false,
+        },
+    }
+}
+
+fn match_segment(pattern: &[u8], name: &[u8]) -> bool {
+    match pattern.split_first() {
+        None => name.is_empty(),
+        Some((b'*', rest)) => (0..=name.len()).any(|skip| match_segment(rest, &name[skip..])),
+        Some((b'?', rest)) => !name.is_empty() && match_segment(rest, &name[1..]),
+        Some((c, rest)) => name.first() == Some(c) && match_segment(rest, &name[1..]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_basename_patterns() {
+        assert!(matches("Cargo.lock", "Cargo.lock"));
+        assert!(matches("Cargo.lock", "crates/cli/Cargo.lock"));
+        assert!(matches("*.min.js", "web/static/app.min.js"));
+        assert!(!matches("*.min.js", "web/static/app.js"));
+        assert!(matches("fixtures", "tests/fixtures/a.json"));
+        assert!(matches("fixtures/", "tests/fixtures/a.json"));
+        assert!(!matches("fixtures/", "tests/fixtures"));
+    }
+
+    #[test]
+    fn test_anchored_patterns() {
+        assert!(matches("vendor/**", "vendor/a/b.c"));
+        assert!(matches("/vendor/", "vendor/a/b.c"));
+        assert!(!matches("vendor/**", "src/vendor/a.c"));
+        assert!(matches("**/generated/*.rs", "src/generated/api.rs"));
+        assert!(matches("src/?.rs", "src/a.rs"));
+        assert!(!matches("src/*.rs", "src/llm/a.rs"));
+    }
+}
@@ -0,0 +1,103 @@
+use std::process::Command;
+
+use crate::git;
+
+/// Runs `hg` with `args` in the current directory and returns stdout.
+pub fn run(args: &[&str]) -> anyhow::Result<String> {
+    let output = Command::new("hg").args(args).env("HGPLAIN", "1").output().map_err(|e| {
+        if e.kind() == std::io::ErrorKind::NotFound {
+            anyhow::anyhow!("hg was not found on PATH")
+        } else {
+            anyhow::anyhow!("Failed to run hg: {}", e)
+        }
+    })?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(anyhow::anyhow!("`hg {}` failed: {}", args.join(" "), stderr.trim()));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// The working directory's uncommitted changes, as a git-style diff. hg
+/// has no staging area, so this is what `hg commit` would record.
+pub fn working_diff(context: &git::Context) -> anyhow::Result<String> {
+    let diff = diff_from(None, context)?;
+    if diff.trim().is_empty() {
+        anyhow::bail!("The working directory has no changes; `hg add` new files, or pass a description");
+    }
+    Ok(diff)
+}
+
+/// What the working directory's parent changed plus anything uncommitted:
+/// the diff `hg commit --amend` would end up with.
+pub fn amend_diff(context: &git::Context) -> anyhow::Result<String> {
+    diff_from(Some(".^"), context)
+}
+
+/// The working directory against `rev` (its parent by default). hg has
+/// nothing like git's `--function-context`, so only the line count of
+/// `context` applies.
+fn diff_from(rev: Option<&str>, context: &git::Context) -> anyhow::Result<String> {
+    let lines = context.lines.map(|lines| lines.to_string());
+    let mut args = vec!["diff", "--git"];
+    if let Some(rev) = rev {
+        args.extend(["--rev", rev]);
+    }
+    if let Some(lines) = &lines {
+        args.extend(["--unified", lines]);
+    }
+    run(&args)
+}
+
+/// What the changeset `rev` changed, as a git-style diff.
+pub fn diff(rev: &str) -> anyhow::Result<String> {
+    run(&["diff", "--git", "--change", rev])
+}
+
+/// The full description of `rev`.
+pub fn description(rev: &str) -> anyhow::Result<String> {
+    run(&["log", "--rev", rev, "--template", "{desc}"])
+}
+
+/// First lines of the last `count` non-merge changesets, newest first.
+pub fn recent_subjects(count: usize) -> anyhow::Result<Vec<String>> {
+    let out = run(&["log", "--no-merges", "--limit", &count.to_string(), "--template", "{desc|firstline}\\n"])?;
+    Ok(out.lines().filter(|line| !line.is_empty()).map(str::to_string).collect())
+}
+
+/// Commits the working directory's changes with `message`, read from a
+/// file with `hg commit -l` so it keeps its line breaks, and opened in
+/// hg's editor first with `edit`. hg's output goes straight to the
+/// terminal.
+pub fn commit(message: &str, amend: bool, edit: bool) -> anyhow::Result<()> {
+    let path = std::env::temp_dir().join(format!("commitgen-hg-{}", std::process::id()));
+    std::fs::write(&path, message)?;
+    let mut command = Command::new("hg");
+    command.arg("commit").arg("--logfile").arg(&path);
+    if amend {
+        command.arg("--amend");
+    }
+    if edit {
+        command.arg("--edit");
+    }
+    let status = command.status().map_err(|e| anyhow::anyhow!("Failed to run hg: {}", e));
+    let _ = std::fs::remove_file(&path);
+    if !status?.success() {
+        anyhow::bail!("hg commit failed");
+    }
+    Ok(())
+}
+
+/// Replaces the message of the working directory's parent, which has to
+/// be `rev`; with uncommitted changes an amend would take those too.
+pub fn describe(rev: &str, message: &str) -> anyhow::Result<()> {
+    let node = |rev: &str| run(&["log", "--rev", rev, "--template", "{node}"]);
+    if node(rev)? != node(".")? {
+        anyhow::bail!("hg can only redescribe the working directory's parent (.)");
+    }
+    if !run(&["status", "--modified", "--added", "--removed", "--deleted"])?.trim().is_empty() {
+        anyhow::bail!("The working directory has uncommitted changes, which amending would take in");
+    }
+    commit(message, true, false)
+}
@@ -0,0 +1,171 @@
+use std::env;
+use std::io::Write;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{git, notes};
+
+/// Older entries are dropped once the file holds this many; each carries
+/// its whole prompt, diff included.
+pub const MAX_ENTRIES: usize = 500;
+
+/// One generated message, with what it took to make it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Entry {
+    pub id: String,
+    /// Seconds since the Unix epoch.
+    pub time: i64,
+    /// The repository it was generated in, if any.
+    pub repo: Option<String>,
+    pub provider: String,
+    pub model: Option<String>,
+    pub prompt_sha256: String,
+    pub prompt: String,
+    /// Every message offered when there was a choice (`-n`, `--interactive`).
+    #[serde(default)]
+    pub candidates: Vec<String>,
+    /// The message that was chosen.
+    pub message: String,
+    /// Estimated, as in git notes.
+    pub prompt_tokens: usize,
+    pub output_tokens: usize,
+}
+
+impl Entry {
+    pub fn new(provider: &str, model: Option<String>, prompt: &str, candidates: Vec<String>, message: &str) -> Self {
+        let metadata = notes::Metadata::new(provider, model, prompt, message);
+        let now = chrono::Utc::now();
+        let id = hex::encode(Sha256::digest(format!("{}\n{}", now.timestamp_nanos_opt().unwrap_or_default(), message)));
+        Self {
+            id: id[..8].to_string(),
+            time: now.timestamp(),
+            repo: git::run(&["rev-parse", "--show-toplevel"]).ok().map(|root| root.trim().to_string()),
+            provider: metadata.provider,
+            model: metadata.model,
+            prompt_sha256: metadata.prompt_sha256,
+            prompt: prompt.to_string(),
+            candidates,
+            message: message.to_string(),
+            prompt_tokens: metadata.prompt_tokens,
+            output_tokens: metadata.output_tokens,
+        }
+    }
+
+    /// Local time, to the minute.
+    pub fn date(&self) -> String {
+        chrono::DateTime::from_timestamp(self.time, 0)
+            .map(|time| time.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or_default()
+    }
+}
+
+/// `$XDG_DATA_HOME/commitgen/history.jsonl`, falling back to
+/// `~/.local/share` (or the platform data dir on Windows and macOS).
+pub fn path() -> Option<PathBuf> {
+    let base = match env::var("XDG_DATA_HOME") {
+        Ok(dir) if !dir.is_empty() => Some(PathBuf::from(dir)),
+        _ if cfg!(unix) && !cfg!(target_os = "macos") => dirs::home_dir().map(|home| home.join(".local").join("share")),
+        _ => dirs::data_dir(),
+    };
+    base.map(|dir| dir.join("commitgen").join("history.jsonl"))
+}
+
+/// Entries one JSON object a line, oldest first. Lines that don't parse,
+/// such as one cut short by a crash, are skipped.
+fn parse(contents: &str) -> Vec<Entry> {
+    contents.lines().filter_map(|line| serde_json::from_str(line).ok()).collect()
+}
+
+/// Every entry, oldest first; none when nothing has been recorded yet.
+pub fn load() -> anyhow::Result<Vec<Entry>> {
+    let Some(path) = path() else {
+        return Ok(Vec::new());
+    };
+    match std::fs::read_to_string(&path) {
+        Ok(contents) => Ok(parse(&contents)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(anyhow::anyhow!("Failed to read {}: {}", path.display(), e)),
+    }
+}
+
+/// Appends `entry`, dropping the oldest entries beyond [`MAX_ENTRIES`].
+pub fn record(entry: &Entry) -> anyhow::Result<()> {
+    let path = path().ok_or_else(|| anyhow::anyhow!("No data directory to keep the history in"))?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let line = serde_json::to_string(entry)?;
+    let entries = load()?;
+    if entries.len() >= MAX_ENTRIES {
+        let mut contents = String::new();
+        for kept in &entries[entries.len() + 1 - MAX_ENTRIES..] {
+            contents.push_str(&serde_json::to_string(kept)?);
+            contents.push('\n');
+        }
+        contents.push_str(&line);
+        contents.push('\n');
+        std::fs::write(&path, contents)?;
+    } else {
+        let mut file = std::fs::OpenOptions::new().create(true).append(true).open(&path)?;
+        writeln!(file, "{}", line)?;
+    }
+    Ok(())
+}
+
+/// The entry whose id starts with `id`, which must pick out just one.
+pub fn find<'a>(entries: &'a [Entry], id: &str) -> anyhow::Result<&'a Entry> {
+    let matches: Vec<&Entry> = entries.iter().filter(|entry| entry.id.starts_with(id)).collect();
+    match matches.as_slice() {
+        [entry] => Ok(entry),
+        [] => anyhow::bail!("No generation {} in the history; `commitgen history` lists them", id),
+        _ => anyhow::bail!("{} matches {} generations; give more of the id", id, matches.len()),
+    }
+}
+
+/// Deletes the history file.
+pub fn clear() -> anyhow::Result<()> {
+    let Some(path) = path() else {
+        return Ok(());
+    };
+    match std::fs::remove_file(&path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(id: &str) -> Entry {
+        Entry {
+            id: id.to_string(),
+            time: 0,
+            repo: None,
+            provider: "ollama".to_string(),
+            model: None,
+            prompt_sha256: String::new(),
+            prompt: "p".to_string(),
+            candidates: Vec::new(),
+            message: "feat: x".to_string(),
+            prompt_tokens: 1,
+            output_tokens: 2,
+        }
+    }
+
+    #[test]
+    fn test_parse_skips_broken_lines() {
+        let contents = format!("{}\n{{\"id\": \"trunc\n", serde_json::to_string(&entry("ab12cd34")).unwrap());
+        assert_eq!(parse(&contents), [entry("ab12cd34")]);
+    }
+
+    #[test]
+    fn test_find() {
+        let entries = [entry("ab12cd34"), entry("ab99ef00")];
+        assert_eq!(find(&entries, "ab12").unwrap().id, "ab12cd34");
+        assert!(find(&entries, "ab").unwrap_err().to_string().contains("matches 2 generations"));
+        assert!(find(&entries, "ff").is_err());
+    }
+}
@@ -0,0 +1,159 @@
+use std::path::{Path, PathBuf};
+
+use crate::git;
+
+/// Identifies hooks written by `commitgen hook install`, so they can be
+/// told apart from hooks the user wrote.
+const MARKER: &str = "# Installed by commitgen";
+const HOOK_NAME: &str = "prepare-commit-msg";
+
+pub enum Status {
+    NotInstalled(PathBuf),
+    Installed(PathBuf),
+    /// Some other prepare-commit-msg hook is in the way.
+    Foreign(PathBuf),
+}
+
+/// Where git looks for hooks, honouring `core.hooksPath`.
+pub fn hook_path() -> anyhow::Result<PathBuf> {
+    let dir = git::run(&["rev-parse", "--git-path", "hooks"])?;
+    Ok(PathBuf::from(dir.trim()).join(HOOK_NAME))
+}
+
+pub fn status() -> anyhow::Result<Status> {
+    let path = hook_path()?;
+    Ok(match std::fs::read_to_string(&path) {
+        Ok(contents) if contents.contains(MARKER) => Status::Installed(path),
+        Ok(_) => Status::Foreign(path),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Status::NotInstalled(path),
+        Err(e) => return Err(anyhow::anyhow!("Failed to read {}: {}", path.display(), e)),
+    })
+}
+
+/// Writes the hook, pointing it at the running executable. An existing
+/// hook that commitgen didn't write is only replaced with `force`.
+pub fn install(force: bool) -> anyhow::Result<PathBuf> {
+    if let Status::Foreign(path) = status()?
+        && !force
+    {
+        anyhow::bail!(
+            "{} already exists and wasn't installed by commitgen; pass --force to replace it",
+            path.display()
+        );
+    }
+
+    let path = hook_path()?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let exe = std::env::current_exe()?;
+    std::fs::write(&path, script(&exe))
+        .map_err(|e| anyhow::anyhow!("Failed to write {}: {}", path.display(), e))?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755))?;
+    }
+    Ok(path)
+}
+
+/// Removes the hook if commitgen installed it. Returns the path when
+/// something was removed.
+pub fn uninstall() -> anyhow::Result<Option<PathBuf>> {
+    match status()? {
+        Status::Installed(path) => {
+            std::fs::remove_file(&path)?;
+            Ok(Some(path))
+        }
+        Status::Foreign(path) => Err(anyhow::anyhow!(
+            "{} wasn't installed by commitgen; leaving it alone",
+            path.display()
+        )),
+        Status::NotInstalled(_) => Ok(None),
+    }
+}
+
+/// The hook never blocks a commit: if commitgen fails, git carries on
+/// with the usual empty message.
+pub fn script(exe: &Path) -> String {
+    format!(
+        "#!/bin/sh\n\
+        {MARKER}; remove with `commitgen hook uninstall`.\n\
+        {exe} hook run \"$@\" </dev/null || true\n",
+        exe = git::shell_quote(&exe.to_string_lossy())
+    )
+}
+
+/// Sources (git's second hook argument) whose message is already decided:
+/// `-m`/`-F`, a merge, `--squash`, and `-c`/`-C`/`--amend`.
+const DECIDED_SOURCES: &[&str] = &["message", "merge", "squash", "commit"];
+
+/// Subject prefixes `git rebase --autosquash` looks for.
+const AUTOSQUASH_PREFIXES: &[&str] = &["fixup! ", "squash! ", "amend! "];
+
+/// Whether the hook should leave the buffer alone for this `source`, so
+/// merges, amends and autosquash commits keep the message git gave them.
+pub fn passes_through(source: Option<&str>, buffer: &str) -> bool {
+    if source.is_some_and(|source| DECIDED_SOURCES.contains(&source)) {
+        return true;
+    }
+    let first = buffer.lines().find(|line| !line.trim().is_empty() && !line.starts_with('#'));
+    first.is_some_and(|line| AUTOSQUASH_PREFIXES.iter().any(|prefix| line.starts_with(prefix)))
+}
+
+/// Whether the message buffer already holds something besides comments
+/// and blank lines (a `-m` message, say, or a template someone filled in).
+pub fn has_message(buffer: &str) -> bool {
+    buffer
+        .lines()
+        .any(|line| !line.trim().is_empty() && !line.starts_with('#'))
+}
+
+/// Puts `message` at the top of the buffer, keeping git's comments below.
+pub fn prefill(buffer: &str, message: &str) -> String {
+    let comments = buffer.trim_start_matches('\n');
+    if comments.is_empty() {
+        format!("{}\n", message)
+    } else {
+        format!("{}\n\n{}", message, comments)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_script_quotes_path() {
+        let script = script(Path::new("/opt/it's here/commitgen"));
+        assert!(script.starts_with("#!/bin/sh\n"));
+        assert!(script.contains(MARKER));
+        assert!(script.contains(r"'/opt/it'\''s here/commitgen' hook run"));
+    }
+
+    #[test]
+    fn test_has_message() {
+        assert!(!has_message("\n# Please enter the commit message for your changes.\n#\n"));
+        assert!(has_message("fix: typo\n\n# Please enter the commit message\n"));
+    }
+
+    #[test]
+    fn test_passes_through() {
+        assert!(passes_through(Some("merge"), "Merge branch 'x'\n"));
+        assert!(passes_through(Some("commit"), ""));
+        assert!(passes_through(None, "\nfixup! feat: add hook\n# comment\n"));
+        assert!(!passes_through(None, "\n# Please enter the commit message\n"));
+        assert!(!passes_through(Some("template"), "# Summary\n"));
+    }
+
+    #[test]
+    fn test_prefill() {
+        let buffer = "\n# Please enter the commit message for your changes.\n";
+        assert_eq!(
+            prefill(buffer, "feat: add hook"),
+            "feat: add hook\n\n# Please enter the commit message for your changes.\n"
+        );
+        assert_eq!(prefill("", "feat: add hook"), "feat: add hook\n");
+    }
+}
@@ -0,0 +1,154 @@
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use crate::config::Config;
+
+/// How requests reach providers, from the config.
+#[derive(Debug, Default, Clone)]
+struct Settings {
+    proxy: Option<String>,
+    ca_bundle: Option<PathBuf>,
+    insecure_skip_verify: bool,
+    /// `None` waits as long as the server takes.
+    timeout: Option<Duration>,
+    retries: Retries,
+}
+
+/// How failed requests are tried again: connection failures, timeouts,
+/// 429s and 5xx responses get `max` more attempts, waiting `backoff` and
+/// then twice as long each time, or as long as `Retry-After` says.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Retries {
+    pub max: u32,
+    pub backoff: Duration,
+}
+
+impl Default for Retries {
+    fn default() -> Self {
+        Self { max: DEFAULT_MAX_RETRIES, backoff: Duration::from_secs_f64(DEFAULT_BACKOFF_SECS) }
+    }
+}
+
+impl Retries {
+    /// The wait before retry number `attempt` (from 0).
+    pub fn delay(&self, attempt: u32) -> Duration {
+        self.backoff.saturating_mul(1 << attempt.min(16))
+    }
+}
+
+/// How long a request may go without hearing from the server, connecting
+/// or between reads, before it's given up on.
+pub const DEFAULT_TIMEOUT_SECS: u64 = 120;
+pub const DEFAULT_MAX_RETRIES: u32 = 2;
+pub const DEFAULT_BACKOFF_SECS: f64 = 1.0;
+
+static SETTINGS: OnceLock<Settings> = OnceLock::new();
+static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+
+/// Hosts an explicit `proxy` doesn't apply to when `NO_PROXY` isn't set,
+/// so Ollama on this machine is still reached directly.
+const DEFAULT_NO_PROXY: &str = "localhost,127.0.0.1,::1";
+
+/// Takes the proxy, TLS, timeout and retry settings from the config. The
+/// proxy and certificates aren't read or checked until the first request,
+/// so a bad `ca_bundle` doesn't stop `commitgen config edit` from fixing it.
+pub fn init(config: &Config) -> anyhow::Result<()> {
+    let backoff = config.backoff.unwrap_or(DEFAULT_BACKOFF_SECS);
+    let backoff = Duration::try_from_secs_f64(backoff)
+        .map_err(|_| anyhow::anyhow!("backoff must be a number of seconds, not {}", backoff))?;
+    let _ = SETTINGS.set(Settings {
+        proxy: config.proxy.clone(),
+        ca_bundle: config.ca_bundle.clone(),
+        insecure_skip_verify: config.insecure_skip_verify,
+        timeout: match config.timeout_secs.unwrap_or(DEFAULT_TIMEOUT_SECS) {
+            0 => None,
+            secs => Some(Duration::from_secs(secs)),
+        },
+        retries: Retries { max: config.max_retries.unwrap_or(DEFAULT_MAX_RETRIES), backoff },
+    });
+    Ok(())
+}
+
+/// The retry policy for provider requests.
+pub fn retries() -> Retries {
+    SETTINGS.get().map(|settings| settings.retries).unwrap_or_default()
+}
+
+/// A client builder with the configured proxy, certificates and timeouts, for
+/// requests that need more, such as a shorter timeout.
+pub fn builder() -> anyhow::Result<reqwest::ClientBuilder> {
+    builder_for(SETTINGS.get().cloned().unwrap_or_default())
+}
+
+/// The client every provider sends with, built on first use.
+pub fn client() -> anyhow::Result<reqwest::Client> {
+    if let Some(client) = CLIENT.get() {
+        return Ok(client.clone());
+    }
+    let client = builder()?.build()?;
+    Ok(CLIENT.get_or_init(|| client).clone())
+}
+
+/// Without `proxy`, reqwest already uses `HTTPS_PROXY`, `HTTP_PROXY`,
+/// `ALL_PROXY` and `NO_PROXY` (in either case).
+fn builder_for(settings: Settings) -> anyhow::Result<reqwest::ClientBuilder> {
+    let mut builder = reqwest::Client::builder();
+    if let Some(timeout) = settings.timeout {
+        // A limit between reads rather than on the whole request, so a
+        // long streamed message isn't cut off while it's still arriving.
+        builder = builder.connect_timeout(timeout).read_timeout(timeout);
+    }
+    if let Some(url) = settings.proxy.as_deref() {
+        let no_proxy = reqwest::NoProxy::from_env().or_else(|| reqwest::NoProxy::from_string(DEFAULT_NO_PROXY));
+        let proxy = reqwest::Proxy::all(url).map_err(|e| anyhow::anyhow!("Invalid proxy {}: {}", url, e))?;
+        builder = builder.proxy(proxy.no_proxy(no_proxy));
+    }
+    if let Some(path) = &settings.ca_bundle {
+        let pem = std::fs::read(path).map_err(|e| anyhow::anyhow!("Cannot read ca_bundle {}: {}", path.display(), e))?;
+        let certificates = reqwest::Certificate::from_pem_bundle(&pem)
+            .map_err(|e| anyhow::anyhow!("Invalid ca_bundle {}: {}", path.display(), e))?;
+        if certificates.is_empty() {
+            anyhow::bail!("No certificates in ca_bundle {}", path.display());
+        }
+        for certificate in certificates {
+            builder = builder.add_root_certificate(certificate);
+        }
+    }
+    if settings.insecure_skip_verify {
+        tracing::warn!("insecure_skip_verify is set: TLS certificates are not being checked");
+        builder = builder.danger_accept_invalid_certs(true);
+    }
+    Ok(builder)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builder_for() {
+        assert!(builder_for(Settings::default()).unwrap().build().is_ok());
+        let timeout = Settings { timeout: Some(Duration::from_secs(5)), ..Settings::default() };
+        assert!(builder_for(timeout).unwrap().build().is_ok());
+        let proxy = Settings { proxy: Some("http://proxy.example.com:3128".into()), ..Settings::default() };
+        assert!(builder_for(proxy).unwrap().build().is_ok());
+
+        let missing = Settings { ca_bundle: Some("/nonexistent/ca.pem".into()), ..Settings::default() };
+        assert!(builder_for(missing).unwrap_err().to_string().starts_with("Cannot read ca_bundle /nonexistent/ca.pem"));
+
+        let path = std::env::temp_dir().join(format!("commitgen-ca-{}.pem", std::process::id()));
+        std::fs::write(&path, "not a certificate\n").unwrap();
+        let empty = Settings { ca_bundle: Some(path.clone()), ..Settings::default() };
+        assert!(builder_for(empty).is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_retry_delay() {
+        let retries = Retries { max: 3, backoff: Duration::from_millis(500) };
+        assert_eq!(retries.delay(0), Duration::from_millis(500));
+        assert_eq!(retries.delay(2), Duration::from_secs(2));
+        assert_eq!(Retries { max: 1, backoff: Duration::MAX }.delay(5), Duration::MAX);
+    }
+}
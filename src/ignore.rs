@@ -0,0 +1,69 @@
+use crate::{git, glob};
+
+/// The ignore file's name, at the top of the repository.
+pub const FILE_NAME: &str = ".commitgenignore";
+
+/// Paths kept out of prompts, read from `.commitgenignore` with the same
+/// syntax as `.gitignore`: one glob per line, `#` for comments, and `!` to
+/// bring back something an earlier line excluded.
+#[derive(Debug, Default)]
+pub struct Ignore {
+    /// Patterns with whether they negate, in file order; the last match wins.
+    rules: Vec<(String, bool)>,
+}
+
+impl Ignore {
+    pub fn parse(contents: &str) -> Self {
+        let rules = contents
+            .lines()
+            .map(str::trim_end)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(|line| match line.strip_prefix('!') {
+                Some(pattern) => (pattern.to_string(), true),
+                None => (line.strip_prefix('\\').unwrap_or(line).to_string(), false),
+            })
+            .collect();
+        Self { rules }
+    }
+
+    /// The repository's ignore file, or no rules when there is none (or
+    /// no repository).
+    pub fn load() -> anyhow::Result<Self> {
+        let Ok(root) = git::run(&["rev-parse", "--show-toplevel"]) else {
+            return Ok(Self::default());
+        };
+        let path = std::path::Path::new(root.trim()).join(FILE_NAME);
+        match std::fs::read_to_string(&path) {
+            Ok(contents) => Ok(Self::parse(&contents)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(anyhow::anyhow!("Failed to read {}: {}", path.display(), e)),
+        }
+    }
+
+    pub fn is_ignored(&self, path: &str) -> bool {
+        self.rules
+            .iter()
+            .rev()
+            .find(|(pattern, _)| glob::matches(pattern, path))
+            .is_some_and(|(_, negated)| !negated)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_ignored() {
+        let ignore = Ignore::parse("# vendored code\nvendor/\n*.secret.json\n!public.secret.json\n\n");
+        assert!(ignore.is_ignored("vendor/lib/a.c"));
+        assert!(ignore.is_ignored("tests/fixtures/db.secret.json"));
+        assert!(!ignore.is_ignored("public.secret.json"));
+        assert!(!ignore.is_ignored("src/main.rs"));
+    }
+
+    #[test]
+    fn test_empty() {
+        assert!(!Ignore::default().is_ignored("anything"));
+    }
+}
@@ -0,0 +1,119 @@
+use std::path::{Path, PathBuf};
+
+use crate::llm::registry::PROVIDERS;
+
+/// Variables naming where a provider's server is, and the provider whose
+/// `[endpoints]` entry takes their place.
+const ENDPOINT_VARS: &[(&str, &str)] = &[
+    ("OLLAMA_HOST", "ollama"),
+    ("AZURE_OPENAI_RESOURCE", "azure"),
+    ("OPENAI_COMPAT_BASE_URL", "openai-compatible"),
+    ("HF_ENDPOINT_URL", "huggingface"),
+];
+
+/// Where an imported variable goes.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Target {
+    /// The keyring, or the encrypted credentials file.
+    Secret,
+    /// A key in the config file, e.g. `models.openai`.
+    Config(String),
+}
+
+/// A variable `commitgen config import-env` can move out of `.env`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Import {
+    pub var: String,
+    pub value: String,
+    pub target: Target,
+    /// Read from the `.env` file, rather than the environment.
+    pub from_file: bool,
+}
+
+/// The `.env` that was loaded at startup: the first one in this directory
+/// or those above it.
+pub fn find_dotenv() -> Option<PathBuf> {
+    let dir = std::env::current_dir().ok()?;
+    dir.ancestors().map(|dir| dir.join(".env")).find(|path| path.is_file())
+}
+
+/// The pairs in the `.env` file at `path`, parsed as they were at startup.
+// dotenv has no other way to read a file without loading it into the
+// environment, which has happened already.
+#[allow(deprecated)]
+pub fn read(path: &Path) -> anyhow::Result<Vec<(String, String)>> {
+    dotenv::from_path_iter(path)
+        .and_then(|pairs| pairs.collect())
+        .map_err(|e| anyhow::anyhow!("Failed to read {}: {}", path.display(), e))
+}
+
+/// The variables commitgen can take over: each provider's key and model,
+/// and those naming a server.
+fn targets() -> Vec<(&'static str, Target)> {
+    let mut targets = Vec::new();
+    for provider in PROVIDERS {
+        if let Some(var) = provider.key_var {
+            targets.push((var, Target::Secret));
+        }
+        if let Some(var) = provider.model_var {
+            targets.push((var, Target::Config(format!("models.{}", provider.name))));
+        }
+    }
+    for (var, provider) in ENDPOINT_VARS {
+        targets.push((*var, Target::Config(format!("endpoints.{}", provider))));
+    }
+    targets
+}
+
+/// What there is to import: each variable set in `file`, the `.env`
+/// file's pairs, or else in the environment as `env` reads it.
+pub fn plan(file: &[(String, String)], env: impl Fn(&str) -> Option<String>) -> Vec<Import> {
+    targets()
+        .into_iter()
+        .filter_map(|(var, target)| {
+            let in_file = file.iter().rev().find(|(name, _)| name == var).map(|(_, value)| value.clone());
+            let from_file = in_file.is_some();
+            let value = in_file.or_else(|| env(var))?.trim().to_string();
+            (!value.is_empty()).then(|| Import { var: var.to_string(), value, target, from_file })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plan() {
+        let file = [
+            ("OPENAI_API_KEY".to_string(), "sk-file".to_string()),
+            ("OPENAI_MODEL".to_string(), "gpt-4o".to_string()),
+            ("UNRELATED".to_string(), "x".to_string()),
+            ("OLLAMA_HOST".to_string(), "".to_string()),
+        ];
+        let env = |var: &str| match var {
+            "OPENAI_API_KEY" => Some("sk-env".to_string()),
+            "GEMINI_API_KEY" => Some("AIza-env".to_string()),
+            _ => None,
+        };
+        let plan = plan(&file, env);
+        assert_eq!(
+            plan,
+            [
+                Import {
+                    var: "GEMINI_API_KEY".into(),
+                    value: "AIza-env".into(),
+                    target: Target::Secret,
+                    from_file: false
+                },
+                Import { var: "OPENAI_API_KEY".into(), value: "sk-file".into(), target: Target::Secret, from_file: true },
+                Import {
+                    var: "OPENAI_MODEL".into(),
+                    value: "gpt-4o".into(),
+                    target: Target::Config("models.openai".into()),
+                    from_file: true
+                },
+            ]
+        );
+    }
+}
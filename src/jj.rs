@@ -0,0 +1,52 @@
+use std::process::Command;
+
+/// Runs `jj` with `args` in the current directory and returns stdout.
+pub fn run(args: &[&str]) -> anyhow::Result<String> {
+    let output = Command::new("jj").args(args).output().map_err(|e| {
+        if e.kind() == std::io::ErrorKind::NotFound {
+            anyhow::anyhow!("jj was not found on PATH")
+        } else {
+            anyhow::anyhow!("Failed to run jj: {}", e)
+        }
+    })?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(anyhow::anyhow!("`jj {}` failed: {}", args.join(" "), stderr.trim()));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// What the change `rev` changed, as a git-style diff.
+pub fn diff(rev: &str) -> anyhow::Result<String> {
+    run(&["diff", "--git", "--color", "never", "-r", rev])
+}
+
+/// The current description of `rev`; empty for an undescribed change.
+pub fn description(rev: &str) -> anyhow::Result<String> {
+    run(&["log", "--no-graph", "--color", "never", "-r", rev, "-T", "description"])
+}
+
+/// First lines of the last `count` described ancestors of the working
+/// copy, newest first.
+pub fn recent_subjects(count: usize) -> anyhow::Result<Vec<String>> {
+    let out = run(&[
+        "log",
+        "--no-graph",
+        "--color",
+        "never",
+        "-r",
+        "::@- & ~description(exact:\"\")",
+        "-n",
+        &count.to_string(),
+        "-T",
+        "description.first_line() ++ \"\\n\"",
+    ])?;
+    Ok(out.lines().filter(|line| !line.is_empty()).map(str::to_string).collect())
+}
+
+/// Sets the description of `rev` to `message`.
+pub fn describe(rev: &str, message: &str) -> anyhow::Result<()> {
+    run(&["describe", "--quiet", "-r", rev, "-m", message])?;
+    Ok(())
+}
@@ -0,0 +1,99 @@
+use serde_json::Value;
+
+use super::openai::{chat_request_body, parse_chat_error};
+use super::{GenerationOptions, LLMClient, OpenAIClient, SendLogged};
+
+/// Azure OpenAI routes requests by deployment rather than by model name, and
+/// authenticates with an `api-key` header instead of a bearer token.
+pub struct AzureOpenAIClient {
+    api_key: String,
+    endpoint: String,
+}
+
+impl AzureOpenAIClient {
+    pub const DEFAULT_API_VERSION: &'static str = "2024-10-21";
+
+    pub fn new(api_key: String, resource: &str, deployment: &str, api_version: &str) -> Self {
+        Self {
+            api_key,
+            endpoint: Self::build_endpoint(resource, deployment, api_version),
+        }
+    }
+
+    /// `resource` may be the bare resource name or the full
+    /// `https://<resource>.openai.azure.com` URL shown in the Azure portal.
+    pub fn build_endpoint(resource: &str, deployment: &str, api_version: &str) -> String {
+        let resource = resource.trim().trim_end_matches('/');
+        let base = if resource.contains("://") {
+            resource.to_string()
+        } else {
+            format!("https://{}.openai.azure.com", resource)
+        };
+        format!(
+            "{}/openai/deployments/{}/chat/completions?api-version={}",
+            base, deployment, api_version
+        )
+    }
+
+    pub fn parse_error(status: reqwest::StatusCode, body: &str) -> anyhow::Error {
+        if status == reqwest::StatusCode::NOT_FOUND {
+            return anyhow::anyhow!(
+                "Azure OpenAI returned HTTP {}: deployment not found; check AZURE_OPENAI_RESOURCE and AZURE_OPENAI_DEPLOYMENT\n{}",
+                status,
+                body
+            );
+        }
+        parse_chat_error("Azure OpenAI", "AZURE_OPENAI_API_KEY", status, body)
+    }
+}
+
+#[async_trait::async_trait]
+impl LLMClient for AzureOpenAIClient {
+    async fn generate(&self, prompt: &str, options: &GenerationOptions) -> anyhow::Result<String> {
+        let client = crate::http::client()?;
+        let resp = client
+            .post(&self.endpoint)
+            .header("api-key", &self.api_key)
+            .json(&chat_request_body(None, prompt, options))
+            .send_logged()
+            .await?;
+
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let text = resp.text().await?;
+            return Err(Self::parse_error(status, &text));
+        }
+
+        let v: Value = resp.json().await?;
+        OpenAIClient::parse_response_json(&v)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_endpoint_from_resource_name() {
+        let endpoint = AzureOpenAIClient::build_endpoint("mycorp", "gpt4o", "2024-10-21");
+        assert_eq!(
+            endpoint,
+            "https://mycorp.openai.azure.com/openai/deployments/gpt4o/chat/completions?api-version=2024-10-21"
+        );
+    }
+
+    #[test]
+    fn test_build_endpoint_from_full_url() {
+        let endpoint =
+            AzureOpenAIClient::build_endpoint("https://mycorp.openai.azure.com/", "gpt4o", "2024-10-21");
+        assert!(endpoint.starts_with("https://mycorp.openai.azure.com/openai/deployments/gpt4o/"));
+    }
+
+    #[test]
+    fn test_parse_error_names_azure_key() {
+        let body = r#"{"error": {"code": "401", "message": "Access denied due to invalid subscription key."}}"#;
+
+        let err = AzureOpenAIClient::parse_error(reqwest::StatusCode::UNAUTHORIZED, body);
+        assert!(err.to_string().contains("AZURE_OPENAI_API_KEY"));
+    }
+}
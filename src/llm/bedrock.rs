@@ -0,0 +1,199 @@
+use serde_json::Value;
+
+use super::{GenerationOptions, LLMClient, SendLogged};
+use crate::auth::aws::{self, AwsCredentials, SigningRequest};
+
+/// Talks to Bedrock through the model-agnostic Converse API, so the same
+/// request shape works for Anthropic Claude, Amazon Titan and the other
+/// text models Bedrock hosts. Credentials are resolved per request so that
+/// short-lived role credentials are always fresh.
+pub struct BedrockClient {
+    region: String,
+    model: String,
+    /// `https://host` to send to instead of the region's public endpoint.
+    base_url: Option<String>,
+}
+
+impl BedrockClient {
+    pub const DEFAULT_MODEL: &'static str = "anthropic.claude-3-haiku-20240307-v1:0";
+
+    pub fn new(region: String, model: String) -> Self {
+        Self { region, model, base_url: None }
+    }
+
+    /// Sends to another endpoint than the region's, such as a VPC
+    /// interface endpoint. Only the scheme and host are used.
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.base_url = Some(super::trim_base_url(base_url));
+        self
+    }
+
+    fn host(&self) -> String {
+        match &self.base_url {
+            Some(url) => {
+                let rest = url.split_once("://").map_or(url.as_str(), |(_, rest)| rest);
+                rest.split('/').next().unwrap_or(rest).to_string()
+            }
+            None => format!("bedrock-runtime.{}.amazonaws.com", self.region),
+        }
+    }
+
+    fn scheme(&self) -> &str {
+        self.base_url.as_deref().and_then(|url| url.split_once("://")).map_or("https", |(scheme, _)| scheme)
+    }
+
+    fn path(&self) -> String {
+        format!("/model/{}/converse", aws::uri_encode(&self.model, true))
+    }
+
+    pub fn parse_response_json(v: &serde_json::Value) -> anyhow::Result<String> {
+        let text: Vec<&str> = v
+            .get("output")
+            .and_then(|o| o.get("message"))
+            .and_then(|m| m.get("content"))
+            .and_then(|c| c.as_array())
+            .map(|blocks| {
+                blocks
+                    .iter()
+                    .filter_map(|b| b.get("text").and_then(|t| t.as_str()))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        if text.is_empty() {
+            return Err(anyhow::anyhow!("Failed to extract message. Response: {}", v));
+        }
+
+        Ok(text.concat().trim().to_string())
+    }
+
+    /// Bedrock errors carry the exception name in `x-amzn-ErrorType` and a
+    /// `{"message": "..."}` body.
+    pub fn parse_error(status: reqwest::StatusCode, error_type: Option<&str>, body: &str) -> anyhow::Error {
+        let message = serde_json::from_str::<Value>(body)
+            .ok()
+            .and_then(|v| v.get("message")?.as_str().map(str::to_string))
+            .unwrap_or_else(|| body.to_string());
+        let error_type = error_type.map(|t| t.split(':').next().unwrap_or(t));
+
+        match error_type {
+            Some("AccessDeniedException") => anyhow::anyhow!(
+                "Bedrock returned HTTP {}: {} [check the IAM policy and that model access is enabled in this region]",
+                status,
+                message
+            ),
+            Some(kind) => anyhow::anyhow!("Bedrock returned HTTP {}: {} ({})", status, message, kind),
+            None => anyhow::anyhow!("Bedrock returned HTTP {}:\n{}", status, message),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl LLMClient for BedrockClient {
+    async fn generate(&self, prompt: &str, options: &GenerationOptions) -> anyhow::Result<String> {
+        let body = serde_json::to_vec(&serde_json::json!({
+            "messages": [{ "role": "user", "content": [{ "text": prompt }] }],
+            "inferenceConfig": options.params(["temperature", "maxTokens", "topP"])
+        }))?;
+
+        let credentials = AwsCredentials::from_default_chain().await?;
+        let host = self.host();
+        let path = self.path();
+        let amz_date = chrono::Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+
+        let mut headers = vec![("content-type", "application/json"), ("x-amz-date", amz_date.as_str())];
+        if let Some(token) = &credentials.session_token {
+            headers.push(("x-amz-security-token", token.as_str()));
+        }
+
+        let authorization = aws::sign(
+            &credentials,
+            &self.region,
+            "bedrock",
+            &amz_date,
+            &SigningRequest {
+                method: "POST",
+                host: &host,
+                path: &path,
+                headers: &headers,
+                payload: &body,
+            },
+        );
+
+        let client = crate::http::client()?;
+        let mut req = client
+            .post(format!("{}://{}{}", self.scheme(), host, path))
+            .header("authorization", authorization)
+            .body(body);
+        for (name, value) in &headers {
+            req = req.header(*name, *value);
+        }
+        let resp = req.send_logged().await?;
+
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let error_type = resp
+                .headers()
+                .get("x-amzn-errortype")
+                .and_then(|h| h.to_str().ok())
+                .map(str::to_string);
+            let text = resp.text().await?;
+            return Err(Self::parse_error(status, error_type.as_deref(), &text));
+        }
+
+        let v: Value = resp.json().await?;
+        Self::parse_response_json(&v)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_path_encodes_model_id() {
+        let client = BedrockClient::new(
+            "eu-west-1".to_string(),
+            "anthropic.claude-3-haiku-20240307-v1:0".to_string(),
+        );
+
+        assert_eq!(client.host(), "bedrock-runtime.eu-west-1.amazonaws.com");
+        assert_eq!(client.path(), "/model/anthropic.claude-3-haiku-20240307-v1%3A0/converse");
+    }
+
+    #[test]
+    fn test_with_base_url() {
+        let client = BedrockClient::new("eu-west-1".to_string(), "m".to_string())
+            .with_base_url("http://vpce-123.bedrock-runtime.eu-west-1.vpce.amazonaws.com/");
+        assert_eq!(client.host(), "vpce-123.bedrock-runtime.eu-west-1.vpce.amazonaws.com");
+        assert_eq!(client.scheme(), "http");
+    }
+
+    #[test]
+    fn test_parse_response_json_success() {
+        let data = json!({
+            "output": { "message": { "role": "assistant", "content": [{ "text": "docs: update README\n" }] } },
+            "stopReason": "end_turn"
+        });
+
+        let result = BedrockClient::parse_response_json(&data).unwrap();
+        assert_eq!(result, "docs: update README");
+    }
+
+    #[test]
+    fn test_parse_response_json_missing_output() {
+        let result = BedrockClient::parse_response_json(&json!({}));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_parse_error_access_denied() {
+        let err = BedrockClient::parse_error(
+            reqwest::StatusCode::FORBIDDEN,
+            Some("AccessDeniedException:http://internal.amazon.com/coral/com.amazon.bedrock/"),
+            r#"{"message": "You don't have access to the model with the specified model ID."}"#,
+        );
+        assert!(err.to_string().contains("model access is enabled"));
+    }
+}
@@ -0,0 +1,143 @@
+use serde_json::Value;
+
+use super::openai::chat_request_body;
+use super::{GenerationOptions, LLMClient, SendLogged};
+
+const ANTHROPIC_VERSION: &str = "2023-06-01";
+
+pub struct ClaudeClient {
+    api_key: String,
+    endpoint: String,
+    model: String,
+}
+
+impl ClaudeClient {
+    pub const DEFAULT_MODEL: &'static str = "claude-3-5-haiku-latest";
+
+    pub fn new(api_key: String, model: String) -> Self {
+        Self {
+            api_key,
+            endpoint: "https://api.anthropic.com/v1/messages".to_string(),
+            model,
+        }
+    }
+
+    /// Sends to another API root, e.g. `https://gateway.example.com/anthropic/v1`,
+    /// or a full `/messages` URL.
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        let base_url = super::trim_base_url(base_url);
+        self.endpoint = if base_url.ends_with("/messages") { base_url } else { format!("{}/messages", base_url) };
+        self
+    }
+
+    /// Joins the `text` blocks of a Messages API response. Non-text blocks
+    /// (e.g. `thinking`) are skipped.
+    pub fn parse_response_json(v: &serde_json::Value) -> anyhow::Result<String> {
+        let text: Vec<&str> = v
+            .get("content")
+            .and_then(|c| c.as_array())
+            .map(|blocks| {
+                blocks
+                    .iter()
+                    .filter(|b| b.get("type").and_then(|t| t.as_str()) == Some("text"))
+                    .filter_map(|b| b.get("text").and_then(|t| t.as_str()))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        if text.is_empty() {
+            return Err(anyhow::anyhow!("Failed to extract message. Response: {}", v));
+        }
+
+        Ok(text.concat().trim().to_string())
+    }
+
+    /// Turns a non-2xx response into a readable error, using the
+    /// `{"type": "error", "error": {"type", "message"}}` body Anthropic returns.
+    pub fn parse_error(status: reqwest::StatusCode, body: &str) -> anyhow::Error {
+        let detail = serde_json::from_str::<Value>(body).ok().and_then(|v| {
+            let err = v.get("error")?;
+            let message = err.get("message")?.as_str()?;
+            Some(match err.get("type").and_then(|t| t.as_str()) {
+                Some(kind) => format!("{} ({})", message, kind),
+                None => message.to_string(),
+            })
+        });
+
+        let hint = match status.as_u16() {
+            401 => Some("check that ANTHROPIC_API_KEY is valid"),
+            429 => Some("rate limit exceeded"),
+            529 => Some("the API is temporarily overloaded"),
+            _ => None,
+        };
+
+        match (detail, hint) {
+            (Some(detail), Some(hint)) => {
+                anyhow::anyhow!("Anthropic API returned HTTP {}: {} [{}]", status, detail, hint)
+            }
+            (Some(detail), None) => anyhow::anyhow!("Anthropic API returned HTTP {}: {}", status, detail),
+            (None, _) => anyhow::anyhow!("Anthropic API returned HTTP {}:\n{}", status, body),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl LLMClient for ClaudeClient {
+    async fn generate(&self, prompt: &str, options: &GenerationOptions) -> anyhow::Result<String> {
+        let client = crate::http::client()?;
+        let resp = client
+            .post(&self.endpoint)
+            .header("x-api-key", &self.api_key)
+            .header("anthropic-version", ANTHROPIC_VERSION)
+            .json(&chat_request_body(Some(&self.model), prompt, options))
+            .send_logged()
+            .await?;
+
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let text = resp.text().await?;
+            return Err(Self::parse_error(status, &text));
+        }
+
+        let v: Value = resp.json().await?;
+        Self::parse_response_json(&v)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_response_json_success() {
+        let data = json!({
+            "content": [
+                { "type": "thinking", "thinking": "..." },
+                { "type": "text", "text": "feat: add Claude provider\n" }
+            ],
+            "stop_reason": "end_turn"
+        });
+
+        let result = ClaudeClient::parse_response_json(&data).unwrap();
+        assert_eq!(result, "feat: add Claude provider");
+    }
+
+    #[test]
+    fn test_parse_response_json_no_text_blocks() {
+        let data = json!({ "content": [] });
+
+        let result = ClaudeClient::parse_response_json(&data);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_parse_error_uses_error_body() {
+        let body = r#"{"type": "error", "error": {"type": "overloaded_error", "message": "Overloaded"}}"#;
+
+        let err = ClaudeClient::parse_error(reqwest::StatusCode::from_u16(529).unwrap(), body);
+        let msg = err.to_string();
+        assert!(msg.contains("Overloaded (overloaded_error)"));
+        assert!(msg.contains("overloaded"));
+    }
+}
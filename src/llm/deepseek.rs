@@ -0,0 +1,46 @@
+use super::{GenerationOptions, LLMClient, ModelInfo, OpenAIClient};
+use super::strip_think_blocks;
+
+/// DeepSeek speaks the OpenAI chat format; its reasoning models may inline
+/// their chain of thought, which must never end up in a commit message.
+pub struct DeepSeekClient {
+    inner: OpenAIClient,
+}
+
+impl DeepSeekClient {
+    pub const DEFAULT_MODEL: &'static str = "deepseek-chat";
+
+    pub fn new(api_key: String, model: String) -> Self {
+        Self {
+            inner: OpenAIClient::for_provider(
+                "DeepSeek",
+                "DEEPSEEK_API_KEY",
+                "https://api.deepseek.com/chat/completions",
+                Some(api_key),
+                model,
+            ),
+        }
+    }
+
+    /// Sends to another API root; see [`OpenAIClient::with_base_url`].
+    pub fn with_base_url(self, base_url: &str) -> Self {
+        Self { inner: self.inner.with_base_url(base_url) }
+    }
+}
+
+#[async_trait::async_trait]
+impl LLMClient for DeepSeekClient {
+    async fn generate(&self, prompt: &str, options: &GenerationOptions) -> anyhow::Result<String> {
+        let message = strip_think_blocks(&self.inner.generate(prompt, options).await?);
+        if message.is_empty() {
+            return Err(anyhow::anyhow!(
+                "DeepSeek returned only reasoning and no commit message"
+            ));
+        }
+        Ok(message)
+    }
+
+    async fn list_models(&self) -> anyhow::Result<Vec<ModelInfo>> {
+        self.inner.list_models().await
+    }
+}
@@ -0,0 +1,238 @@
+use std::time::Duration;
+
+use futures::StreamExt;
+
+use super::{ClientResult, GenerationOptions, TokenStream};
+
+/// An ordered list of providers to try until one succeeds. Clients are
+/// built up front, but a build failure (say, a missing API key for a
+/// fallback) only counts against that entry when it is reached. Each
+/// entry carries its own generation options.
+pub struct FallbackChain {
+    entries: Vec<(String, ClientResult, GenerationOptions)>,
+    timeout: Duration,
+}
+
+/// A successful generation, with the failures that preceded it.
+pub struct Generation {
+    pub provider: String,
+    pub message: String,
+    pub failures: Vec<(String, anyhow::Error)>,
+}
+
+impl FallbackChain {
+    pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+    /// `timeout` bounds every attempt except the last, so a hanging
+    /// provider doesn't stop the chain from moving on.
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            entries: Vec::new(),
+            timeout,
+        }
+    }
+
+    pub fn push(
+        &mut self,
+        provider: impl Into<String>,
+        client: ClientResult,
+        options: GenerationOptions,
+    ) {
+        self.entries.push((provider.into(), client, options));
+    }
+
+    pub async fn generate(&self, prompt: &str) -> anyhow::Result<Generation> {
+        self.generate_streaming(prompt, |_| {}).await
+    }
+
+    /// Like [`generate`](Self::generate), but hands each token to
+    /// `on_token` as it arrives. The chain only moves on while nothing has
+    /// been emitted yet; once a provider has started answering, a later
+    /// error ends the generation. The timeout covers opening the stream
+    /// and receiving its first token.
+    pub async fn generate_streaming(
+        &self,
+        prompt: &str,
+        mut on_token: impl FnMut(&str),
+    ) -> anyhow::Result<Generation> {
+        let mut failures = Vec::new();
+
+        for (i, (provider, client, options)) in self.entries.iter().enumerate() {
+            let client = match client {
+                Ok(client) => client,
+                Err(e) => {
+                    tracing::info!("Skipping {}: {}", provider, e);
+                    failures.push((provider.clone(), anyhow::anyhow!("{}", e)));
+                    continue;
+                }
+            };
+
+            tracing::debug!("Asking {}", provider);
+            let started = std::time::Instant::now();
+            let is_last = i + 1 == self.entries.len();
+            let first = async {
+                let mut stream = client.generate_stream(prompt, options).await?;
+                let first = stream.next().await.transpose()?;
+                Ok::<_, anyhow::Error>((stream, first))
+            };
+            let result = if is_last {
+                first.await
+            } else {
+                match tokio::time::timeout(self.timeout, first).await {
+                    Ok(result) => result,
+                    Err(_) => Err(anyhow::anyhow!("timed out after {}s", self.timeout.as_secs())),
+                }
+            };
+
+            let (stream, first): (TokenStream<'_>, Option<String>) = match result {
+                Ok(started) => started,
+                Err(e) => {
+                    tracing::info!("{} failed after {:.1}s: {}", provider, started.elapsed().as_secs_f64(), e);
+                    failures.push((provider.clone(), e));
+                    continue;
+                }
+            };
+
+            let mut message = String::new();
+            if let Some(token) = first {
+                on_token(&token);
+                message.push_str(&token);
+            }
+            let mut stream = stream;
+            while let Some(token) = stream.next().await {
+                let token = token?;
+                on_token(&token);
+                message.push_str(&token);
+            }
+
+            tracing::info!("{} answered in {:.1}s", provider, started.elapsed().as_secs_f64());
+            return Ok(Generation {
+                provider: provider.clone(),
+                message: message.trim().to_string(),
+                failures,
+            });
+        }
+
+        Err(Self::combine(failures))
+    }
+
+    fn combine(mut failures: Vec<(String, anyhow::Error)>) -> anyhow::Error {
+        if failures.len() == 1 {
+            return failures.pop().unwrap().1;
+        }
+
+        let details: Vec<String> = failures
+            .iter()
+            .map(|(provider, e)| format!("  {}: {}", provider, e))
+            .collect();
+        anyhow::anyhow!("All providers failed:\n{}", details.join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::LLMClient;
+    use anyhow::Result;
+    use async_trait::async_trait;
+
+    struct Reply(&'static str);
+    #[async_trait]
+    impl LLMClient for Reply {
+        async fn generate(&self, _prompt: &str, _options: &GenerationOptions) -> Result<String> {
+            Ok(self.0.into())
+        }
+    }
+
+    struct Fail;
+    #[async_trait]
+    impl LLMClient for Fail {
+        async fn generate(&self, _prompt: &str, _options: &GenerationOptions) -> Result<String> {
+            Err(anyhow::anyhow!("HTTP 500"))
+        }
+    }
+
+    struct Hang;
+    #[async_trait]
+    impl LLMClient for Hang {
+        async fn generate(&self, _prompt: &str, _options: &GenerationOptions) -> Result<String> {
+            tokio::time::sleep(Duration::from_secs(60)).await;
+            Ok("too late".into())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_primary_success() {
+        let mut chain = FallbackChain::new(FallbackChain::DEFAULT_TIMEOUT);
+        chain.push("gemini", Ok(Box::new(Reply("feat: a"))), GenerationOptions::default());
+        chain.push("openai", Ok(Box::new(Reply("feat: b"))), GenerationOptions::default());
+
+        let generation = chain.generate("prompt").await.unwrap();
+        assert_eq!(generation.provider, "gemini");
+        assert_eq!(generation.message, "feat: a");
+        assert!(generation.failures.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_falls_back_on_error_and_build_failure() {
+        let mut chain = FallbackChain::new(FallbackChain::DEFAULT_TIMEOUT);
+        chain.push("gemini", Ok(Box::new(Fail)), GenerationOptions::default());
+        chain.push("openai", Err(anyhow::anyhow!("OPENAI_API_KEY must be set in .env")), GenerationOptions::default());
+        chain.push("ollama", Ok(Box::new(Reply("fix: c"))), GenerationOptions::default());
+
+        let generation = chain.generate("prompt").await.unwrap();
+        assert_eq!(generation.provider, "ollama");
+        let failed: Vec<&str> = generation.failures.iter().map(|(p, _)| p.as_str()).collect();
+        assert_eq!(failed, ["gemini", "openai"]);
+    }
+
+    #[tokio::test]
+    async fn test_falls_back_on_timeout() {
+        let mut chain = FallbackChain::new(Duration::from_millis(10));
+        chain.push("slow", Ok(Box::new(Hang)), GenerationOptions::default());
+        chain.push("fast", Ok(Box::new(Reply("perf: d"))), GenerationOptions::default());
+
+        let generation = chain.generate("prompt").await.unwrap();
+        assert_eq!(generation.provider, "fast");
+        assert!(generation.failures[0].1.to_string().contains("timed out"));
+    }
+
+    #[tokio::test]
+    async fn test_all_failed_lists_each_provider() {
+        let mut chain = FallbackChain::new(FallbackChain::DEFAULT_TIMEOUT);
+        chain.push("gemini", Ok(Box::new(Fail)), GenerationOptions::default());
+        chain.push("openai", Ok(Box::new(Fail)), GenerationOptions::default());
+
+        let err = chain.generate("prompt").await.err().unwrap().to_string();
+        assert!(err.contains("All providers failed"));
+        assert!(err.contains("gemini: HTTP 500"));
+        assert!(err.contains("openai: HTTP 500"));
+    }
+
+    #[tokio::test]
+    async fn test_streaming_falls_back_before_first_token() {
+        let mut chain = FallbackChain::new(Duration::from_millis(10));
+        chain.push("gemini", Ok(Box::new(Fail)), GenerationOptions::default());
+        chain.push("slow", Ok(Box::new(Hang)), GenerationOptions::default());
+        chain.push("ollama", Ok(Box::new(Reply(" feat: e\n"))), GenerationOptions::default());
+
+        let mut emitted = String::new();
+        let generation = chain
+            .generate_streaming("prompt", |token| emitted.push_str(token))
+            .await
+            .unwrap();
+        assert_eq!(generation.provider, "ollama");
+        assert_eq!(generation.message, "feat: e");
+        assert_eq!(emitted, " feat: e\n");
+        assert_eq!(generation.failures.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_single_provider_error_is_passed_through() {
+        let mut chain = FallbackChain::new(FallbackChain::DEFAULT_TIMEOUT);
+        chain.push("gemini", Ok(Box::new(Fail)), GenerationOptions::default());
+
+        let err = chain.generate("prompt").await.err().unwrap().to_string();
+        assert_eq!(err, "HTTP 500");
+    }
+}
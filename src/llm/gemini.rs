@@ -0,0 +1,326 @@
+use std::collections::VecDeque;
+
+use futures::StreamExt;
+use serde_json::Value;
+
+use super::sse::SseParser;
+use super::{GenerationOptions, LLMClient, ModelInfo, SendLogged, TokenStream};
+
+/// Gemini's names for the generation parameters, shared with Vertex AI.
+pub(super) const GEMINI_PARAMS: [&str; 3] = ["temperature", "maxOutputTokens", "topP"];
+
+pub struct GeminiClient {
+    api_key: String,
+    base_url: String,
+    model: String,
+}
+
+impl GeminiClient {
+    pub const DEFAULT_MODEL: &'static str = "gemini-2.5-flash";
+    pub const DEFAULT_BASE_URL: &'static str = "https://generativelanguage.googleapis.com/v1beta";
+
+    pub fn new(api_key: String, model: &str) -> Self {
+        Self { api_key, base_url: Self::DEFAULT_BASE_URL.to_string(), model: model.to_string() }
+    }
+
+    /// Sends to another API root than AI Studio's, such as a regional
+    /// endpoint or a gateway, e.g. `https://gateway.example.com/gemini/v1beta`.
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.base_url = super::trim_base_url(base_url);
+        self
+    }
+
+    fn url(&self, method: &str) -> String {
+        format!("{}/models/{}:{}", self.base_url, self.model, method)
+    }
+
+    fn request_body(prompt: &str, options: &GenerationOptions) -> Value {
+        serde_json::json!({
+            "contents": [{ "parts": [{ "text": prompt }] }],
+            "generationConfig": options.params(GEMINI_PARAMS)
+        })
+    }
+
+    /// One page of `ListModels`, keeping only models that can generate
+    /// text, along with the token for the next page.
+    pub fn parse_models_json(v: &serde_json::Value) -> anyhow::Result<(Vec<ModelInfo>, Option<String>)> {
+        let models = v
+            .get("models")
+            .and_then(|m| m.as_array())
+            .ok_or_else(|| anyhow::anyhow!("Failed to extract models. Response: {}", v))?
+            .iter()
+            .filter(|m| {
+                m.get("supportedGenerationMethods")
+                    .and_then(|s| s.as_array())
+                    .is_some_and(|s| s.iter().any(|m| m == "generateContent"))
+            })
+            .filter_map(|m| {
+                let name = m.get("name")?.as_str()?;
+                Some(ModelInfo {
+                    id: name.strip_prefix("models/").unwrap_or(name).to_string(),
+                    context_window: m.get("inputTokenLimit").and_then(|l| l.as_u64()),
+                })
+            })
+            .collect();
+        let next = v
+            .get("nextPageToken")
+            .and_then(|t| t.as_str())
+            .filter(|t| !t.is_empty())
+            .map(str::to_string);
+        Ok((models, next))
+    }
+
+    /// Each streamed event is a partial `generateContent` response. The
+    /// final one may carry only a `finishReason` and no text.
+    pub fn parse_stream_chunk(v: &serde_json::Value) -> anyhow::Result<Option<String>> {
+        if let Some(error) = v.get("error") {
+            return Err(anyhow::anyhow!("Gemini API stream error: {}", error));
+        }
+
+        let text: String = v
+            .get("candidates")
+            .and_then(|c| c.get(0))
+            .and_then(|c| c.get("content"))
+            .and_then(|c| c.get("parts"))
+            .and_then(|p| p.as_array())
+            .map(|parts| {
+                parts
+                    .iter()
+                    .filter_map(|p| p.get("text").and_then(|t| t.as_str()))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Ok((!text.is_empty()).then_some(text))
+    }
+    
+    pub fn parse_response_json(v: &serde_json::Value) -> anyhow::Result<String> {
+        v.get("candidates")
+            .and_then(|c| c.get(0))
+            .and_then(|c| c.get("content"))
+            .and_then(|c| c.get("parts"))
+            .and_then(|p| p.get(0))
+            .and_then(|p| p.get("text"))
+            .and_then(|t| t.as_str())
+            .map(|s| s.trim().to_string())
+            .ok_or_else(|| anyhow::anyhow!("Failed to extract message. Response: {}", v))
+    }
+}
+
+#[async_trait::async_trait]
+impl LLMClient for GeminiClient {
+    async fn generate(&self, prompt: &str, options: &GenerationOptions) -> anyhow::Result<String> {
+        let client = crate::http::client()?;
+        let resp = client
+            .post(self.url("generateContent"))
+            .header("x-goog-api-key", &self.api_key)
+            .json(&Self::request_body(prompt, options))
+            .send_logged()
+            .await?;
+
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let text = resp.text().await?;
+            return Err(anyhow::anyhow!(
+                "Gemini API returned HTTP {}:\n{}", status, text
+            ));
+        }
+
+        let v: Value = resp.json().await?;
+        Self::parse_response_json(&v)
+    }
+
+    async fn generate_stream(
+        &self,
+        prompt: &str,
+        options: &GenerationOptions,
+    ) -> anyhow::Result<TokenStream<'_>> {
+        let client = crate::http::client()?;
+        let resp = client
+            .post(self.url("streamGenerateContent"))
+            .query(&[("alt", "sse")])
+            .header("x-goog-api-key", &self.api_key)
+            .json(&Self::request_body(prompt, options))
+            .send_logged()
+            .await?;
+
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let text = resp.text().await?;
+            return Err(anyhow::anyhow!(
+                "Gemini API returned HTTP {}:\n{}", status, text
+            ));
+        }
+
+        let state = (resp.bytes_stream(), SseParser::default(), VecDeque::new());
+        let stream = futures::stream::unfold(state, |(mut bytes, mut parser, mut pending)| async move {
+            loop {
+                if let Some(item) = pending.pop_front() {
+                    return Some((item, (bytes, parser, pending)));
+                }
+                match bytes.next().await {
+                    Some(Ok(chunk)) => {
+                        for data in parser.push(&chunk) {
+                            let item = serde_json::from_str::<Value>(&data)
+                                .map_err(anyhow::Error::from)
+                                .and_then(|v| Self::parse_stream_chunk(&v));
+                            match item {
+                                Ok(Some(text)) => pending.push_back(Ok(text)),
+                                Ok(None) => {}
+                                Err(e) => pending.push_back(Err(e)),
+                            }
+                        }
+                    }
+                    Some(Err(e)) => return Some((Err(e.into()), (bytes, parser, pending))),
+                    None => return None,
+                }
+            }
+        });
+
+        Ok(Box::pin(stream))
+    }
+
+    async fn list_models(&self) -> anyhow::Result<Vec<ModelInfo>> {
+        let client = crate::http::client()?;
+        let mut models = Vec::new();
+        let mut page_token: Option<String> = None;
+        loop {
+            let mut req = client
+                .get(format!("{}/models", self.base_url))
+                .header("x-goog-api-key", &self.api_key)
+                .query(&[("pageSize", "1000")]);
+            if let Some(token) = &page_token {
+                req = req.query(&[("pageToken", token)]);
+            }
+            let resp = req.send_logged().await?;
+
+            if !resp.status().is_success() {
+                let status = resp.status();
+                let text = resp.text().await?;
+                return Err(anyhow::anyhow!(
+                    "Gemini API returned HTTP {}:\n{}", status, text
+                ));
+            }
+
+            let v: Value = resp.json().await?;
+            let (page, next) = Self::parse_models_json(&v)?;
+            models.extend(page);
+            match next {
+                Some(token) => page_token = Some(token),
+                None => return Ok(models),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_url() {
+        let client = GeminiClient::new("key".to_string(), "gemini-2.5-flash");
+        assert_eq!(
+            client.url("generateContent"),
+            "https://generativelanguage.googleapis.com/v1beta/models/gemini-2.5-flash:generateContent"
+        );
+        let client = client.with_base_url("https://gateway.example.com/gemini/v1beta/");
+        assert_eq!(
+            client.url("streamGenerateContent"),
+            "https://gateway.example.com/gemini/v1beta/models/gemini-2.5-flash:streamGenerateContent"
+        );
+    }
+
+    #[test]
+    fn test_parse_response_json_success() {
+        let data = json!({
+            "candidates": [{
+                "content": {
+                    "parts": [{
+                        "text": "Commit message here"
+                    }]
+                }
+            }]
+        });
+
+        let result = GeminiClient::parse_response_json(&data).unwrap();
+        assert_eq!(result, "Commit message here");
+    }
+
+    #[test]
+    fn test_parse_models_json() {
+        let data = json!({
+            "models": [
+                {
+                    "name": "models/gemini-2.5-flash",
+                    "inputTokenLimit": 1048576,
+                    "supportedGenerationMethods": ["generateContent", "countTokens"]
+                },
+                {
+                    "name": "models/text-embedding-004",
+                    "inputTokenLimit": 2048,
+                    "supportedGenerationMethods": ["embedContent"]
+                }
+            ],
+            "nextPageToken": "abc"
+        });
+
+        let (models, next) = GeminiClient::parse_models_json(&data).unwrap();
+        assert_eq!(
+            models,
+            [ModelInfo { id: "gemini-2.5-flash".into(), context_window: Some(1048576) }]
+        );
+        assert_eq!(next.as_deref(), Some("abc"));
+    }
+
+    #[test]
+    fn test_parse_stream_chunk() {
+        let data = json!({
+            "candidates": [{ "content": { "parts": [{ "text": "feat: add " }], "role": "model" } }]
+        });
+        assert_eq!(GeminiClient::parse_stream_chunk(&data).unwrap().as_deref(), Some("feat: add "));
+
+        let last = json!({ "candidates": [{ "finishReason": "STOP" }] });
+        assert_eq!(GeminiClient::parse_stream_chunk(&last).unwrap(), None);
+
+        let error = json!({ "error": { "code": 429, "message": "Resource exhausted" } });
+        assert!(GeminiClient::parse_stream_chunk(&error).is_err());
+    }
+
+    #[test]
+    fn test_parse_response_json_missing_fields() {
+        let data = json!({
+            "wrong_key": []
+        });
+
+        let result = GeminiClient::parse_response_json(&data);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_parse_response_json_empty_candidates() {
+        let data = json!({
+            "candidates": []
+        });
+
+        let result = GeminiClient::parse_response_json(&data);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_parse_response_json_text_not_string() {
+        let data = json!({
+            "candidates": [{
+                "content": {
+                    "parts": [{
+                        "text": 1234
+                    }]
+                }
+            }]
+        });
+
+        let result = GeminiClient::parse_response_json(&data);
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,35 @@
+use super::{GenerationOptions, LLMClient, OpenAIClient};
+
+/// GitHub Models exposes an OpenAI-compatible endpoint authenticated with
+/// an ordinary GitHub token, so no separate API key is needed.
+pub struct GitHubModelsClient {
+    inner: OpenAIClient,
+}
+
+impl GitHubModelsClient {
+    pub const DEFAULT_MODEL: &'static str = "openai/gpt-4o-mini";
+
+    pub fn new(token: String, model: String) -> Self {
+        Self {
+            inner: OpenAIClient::for_provider(
+                "GitHub Models",
+                "GITHUB_TOKEN",
+                "https://models.github.ai/inference/chat/completions",
+                Some(token),
+                model,
+            ),
+        }
+    }
+
+    /// Sends to another API root; see [`OpenAIClient::with_base_url`].
+    pub fn with_base_url(self, base_url: &str) -> Self {
+        Self { inner: self.inner.with_base_url(base_url) }
+    }
+}
+
+#[async_trait::async_trait]
+impl LLMClient for GitHubModelsClient {
+    async fn generate(&self, prompt: &str, options: &GenerationOptions) -> anyhow::Result<String> {
+        self.inner.generate(prompt, options).await
+    }
+}
@@ -0,0 +1,60 @@
+use serde_json::Value;
+
+use super::openai::{chat_request_body, parse_chat_error};
+use super::{GenerationOptions, LLMClient, OpenAIClient, SendLogged, retry_after};
+
+/// Groq serves an OpenAI-compatible API with tight per-minute limits. 429
+/// responses are retried after the server-provided `retry-after` by
+/// [`SendLogged`]; one still limited after that says how long to wait.
+pub struct GroqClient {
+    api_key: String,
+    endpoint: String,
+    model: String,
+}
+
+impl GroqClient {
+    pub const DEFAULT_MODEL: &'static str = "llama-3.1-8b-instant";
+
+    pub fn new(api_key: String, model: String) -> Self {
+        Self {
+            api_key,
+            endpoint: "https://api.groq.com/openai/v1/chat/completions".to_string(),
+            model,
+        }
+    }
+
+    /// Sends to another API root, or a full `/chat/completions` URL.
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.endpoint = OpenAIClient::chat_completions_url(base_url);
+        self
+    }
+}
+
+#[async_trait::async_trait]
+impl LLMClient for GroqClient {
+    async fn generate(&self, prompt: &str, options: &GenerationOptions) -> anyhow::Result<String> {
+        let client = crate::http::client()?;
+        let resp = client
+            .post(&self.endpoint)
+            .bearer_auth(&self.api_key)
+            .json(&chat_request_body(Some(&self.model), prompt, options))
+            .send_logged()
+            .await?;
+
+        if resp.status() == reqwest::StatusCode::TOO_MANY_REQUESTS
+            && let Some(wait) = retry_after(resp.headers())
+        {
+            let text = resp.text().await?;
+            return Err(anyhow::anyhow!("Groq rate limit hit; retry in {:.1}s\n{}", wait.as_secs_f64(), text));
+        }
+
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let text = resp.text().await?;
+            return Err(parse_chat_error("Groq", "GROQ_API_KEY", status, &text));
+        }
+
+        let v: Value = resp.json().await?;
+        OpenAIClient::parse_response_json(&v)
+    }
+}
@@ -0,0 +1,209 @@
+use std::time::Duration;
+
+use serde_json::Value;
+
+use super::{GenerationOptions, LLMClient, SendLogged};
+
+const MAX_LOADING_RETRIES: u32 = 6;
+const MAX_LOADING_WAIT: Duration = Duration::from_secs(30);
+/// Serverless text generation caps output well below the chat APIs.
+const DEFAULT_MAX_NEW_TOKENS: u32 = 512;
+
+/// The Hugging Face text-generation API, served either by the shared
+/// serverless Inference API or by a dedicated Inference Endpoint. Cold
+/// models answer 503 while they load, so those responses are retried.
+pub struct HuggingFaceClient {
+    token: Option<String>,
+    endpoint: String,
+}
+
+impl HuggingFaceClient {
+    pub const DEFAULT_MODEL: &'static str = "mistralai/Mistral-7B-Instruct-v0.3";
+
+    pub fn serverless(token: Option<String>, model: &str) -> Self {
+        Self {
+            token,
+            endpoint: format!("https://api-inference.huggingface.co/models/{}", model),
+        }
+    }
+
+    pub fn dedicated(token: Option<String>, url: &str) -> Self {
+        Self {
+            token,
+            endpoint: url.trim().trim_end_matches('/').to_string(),
+        }
+    }
+
+    /// Text generation returns `[{"generated_text": ...}]`, though some
+    /// dedicated endpoints answer with the bare object.
+    pub fn parse_response_json(v: &serde_json::Value) -> anyhow::Result<String> {
+        let item = v.get(0).unwrap_or(v);
+        item.get("generated_text")
+            .and_then(|t| t.as_str())
+            .map(|s| s.trim().to_string())
+            .ok_or_else(|| anyhow::anyhow!("Failed to extract message. Response: {}", v))
+    }
+
+    /// Text generation only samples when asked to; greedy decoding rejects
+    /// `temperature` and `top_p`, so they're sent only alongside `do_sample`.
+    pub fn parameters(options: &GenerationOptions) -> Value {
+        let mut params = serde_json::json!({
+            "max_new_tokens": options.max_tokens.unwrap_or(DEFAULT_MAX_NEW_TOKENS),
+            "do_sample": false,
+            "return_full_text": false
+        });
+        if options.temperature() > 0.0 || options.top_p.is_some() {
+            params["do_sample"] = true.into();
+            if options.temperature() > 0.0 {
+                params["temperature"] = options.temperature().into();
+            }
+            if let Some(p) = options.top_p {
+                params["top_p"] = p.into();
+            }
+        }
+        params
+    }
+
+    /// How long a 503 body says the model needs to finish loading.
+    pub fn loading_wait(body: &str) -> Option<Duration> {
+        let v: Value = serde_json::from_str(body).ok()?;
+        let error = v.get("error")?.as_str()?;
+        if !error.contains("loading") {
+            return None;
+        }
+        let secs = v
+            .get("estimated_time")
+            .and_then(|t| t.as_f64())
+            .filter(|secs| secs.is_finite() && *secs > 0.0)
+            .unwrap_or(5.0);
+        Some(Duration::from_secs_f64(secs).clamp(Duration::from_secs(1), MAX_LOADING_WAIT))
+    }
+
+    /// Errors are `{"error": "..."}`, or a list of messages for
+    /// validation failures.
+    pub fn parse_error(status: reqwest::StatusCode, body: &str) -> anyhow::Error {
+        let detail = serde_json::from_str::<Value>(body).ok().and_then(|v| match v.get("error")? {
+            Value::String(s) => Some(s.clone()),
+            Value::Array(items) => Some(
+                items
+                    .iter()
+                    .filter_map(|i| i.as_str())
+                    .collect::<Vec<_>>()
+                    .join("; "),
+            ),
+            _ => None,
+        });
+
+        let hint = match status.as_u16() {
+            401 | 403 => Some("check that HF_TOKEN is valid and can access this model"),
+            429 => Some("rate limit exceeded"),
+            _ => None,
+        };
+
+        match (detail, hint) {
+            (Some(detail), Some(hint)) => {
+                anyhow::anyhow!("Hugging Face returned HTTP {}: {} [{}]", status, detail, hint)
+            }
+            (Some(detail), None) => anyhow::anyhow!("Hugging Face returned HTTP {}: {}", status, detail),
+            (None, _) => anyhow::anyhow!("Hugging Face returned HTTP {}:\n{}", status, body),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl LLMClient for HuggingFaceClient {
+    async fn generate(&self, prompt: &str, options: &GenerationOptions) -> anyhow::Result<String> {
+        let client = crate::http::client()?;
+        let body = serde_json::json!({
+            "inputs": prompt,
+            "parameters": Self::parameters(options)
+        });
+
+        let mut attempt = 0;
+        loop {
+            let mut req = client.post(&self.endpoint).json(&body);
+            if let Some(token) = &self.token {
+                req = req.bearer_auth(token);
+            }
+            let resp = req.send_logged().await?;
+
+            if !resp.status().is_success() {
+                let status = resp.status();
+                let text = resp.text().await?;
+                if status == reqwest::StatusCode::SERVICE_UNAVAILABLE
+                    && attempt < MAX_LOADING_RETRIES
+                    && let Some(wait) = Self::loading_wait(&text)
+                {
+                    attempt += 1;
+                    tracing::info!("Model is loading; retry {} of {} in {}s", attempt, MAX_LOADING_RETRIES, wait.as_secs());
+                    tokio::time::sleep(wait).await;
+                    continue;
+                }
+                return Err(Self::parse_error(status, &text));
+            }
+
+            let v: Value = resp.json().await?;
+            return Self::parse_response_json(&v);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_response_json_list_and_object() {
+        let list = json!([{ "generated_text": " chore: bump deps\n" }]);
+        assert_eq!(HuggingFaceClient::parse_response_json(&list).unwrap(), "chore: bump deps");
+
+        let object = json!({ "generated_text": "chore: bump deps" });
+        assert_eq!(HuggingFaceClient::parse_response_json(&object).unwrap(), "chore: bump deps");
+
+        assert!(HuggingFaceClient::parse_response_json(&json!([])).is_err());
+    }
+
+    #[test]
+    fn test_loading_wait() {
+        let body = r#"{"error": "Model mistralai/Mistral-7B-Instruct-v0.3 is currently loading", "estimated_time": 12.5}"#;
+        assert_eq!(HuggingFaceClient::loading_wait(body), Some(Duration::from_secs_f64(12.5)));
+
+        let body = r#"{"error": "Model is currently loading", "estimated_time": 400.0}"#;
+        assert_eq!(HuggingFaceClient::loading_wait(body), Some(MAX_LOADING_WAIT));
+
+        assert_eq!(HuggingFaceClient::loading_wait(r#"{"error": "Service Unavailable"}"#), None);
+    }
+
+    #[test]
+    fn test_parameters() {
+        let greedy = HuggingFaceClient::parameters(&GenerationOptions::default());
+        assert_eq!(greedy, json!({ "max_new_tokens": 512, "do_sample": false, "return_full_text": false }));
+
+        let sampled = HuggingFaceClient::parameters(&GenerationOptions {
+            temperature: Some(0.5),
+            max_tokens: Some(128),
+            top_p: None,
+        });
+        assert_eq!(sampled["do_sample"], true);
+        assert_eq!(sampled["temperature"], 0.5);
+        assert_eq!(sampled["max_new_tokens"], 128);
+        assert!(sampled.get("top_p").is_none());
+    }
+
+    #[test]
+    fn test_parse_error_list() {
+        let body = r#"{"error": ["Input validation error: `inputs` must have less than 4096 tokens"]}"#;
+        let err = HuggingFaceClient::parse_error(reqwest::StatusCode::UNPROCESSABLE_ENTITY, body);
+        assert!(err.to_string().contains("Input validation error"));
+    }
+
+    #[test]
+    fn test_endpoints() {
+        let serverless = HuggingFaceClient::serverless(None, "bigcode/starcoder2-15b");
+        assert_eq!(serverless.endpoint, "https://api-inference.huggingface.co/models/bigcode/starcoder2-15b");
+
+        let dedicated = HuggingFaceClient::dedicated(None, "https://abc123.us-east-1.aws.endpoints.huggingface.cloud/");
+        assert_eq!(dedicated.endpoint, "https://abc123.us-east-1.aws.endpoints.huggingface.cloud");
+    }
+}
@@ -0,0 +1,196 @@
+use serde_json::Value;
+
+use super::openai::chat_request_body;
+use super::{GenerationOptions, LLMClient, OpenAIClient, SendLogged};
+
+pub struct MistralClient {
+    api_key: String,
+    endpoint: String,
+    model: String,
+}
+
+impl MistralClient {
+    pub const DEFAULT_MODEL: &'static str = "mistral-small-latest";
+
+    pub fn new(api_key: String, model: String) -> Self {
+        Self {
+            api_key,
+            endpoint: "https://api.mistral.ai/v1/chat/completions".to_string(),
+            model,
+        }
+    }
+
+    /// Sends to another API root, or a full `/chat/completions` URL.
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.endpoint = OpenAIClient::chat_completions_url(base_url);
+        self
+    }
+
+    /// Mistral's chat format mirrors OpenAI's, but `content` may also be a
+    /// list of typed chunks, and the finish reasons `length`,
+    /// `model_length` and `error` mean the message can't be trusted.
+    pub fn parse_response_json(v: &serde_json::Value) -> anyhow::Result<String> {
+        let choice = v.get("choices").and_then(|c| c.get(0));
+
+        match choice
+            .and_then(|c| c.get("finish_reason"))
+            .and_then(|f| f.as_str())
+        {
+            Some("length") | Some("model_length") => {
+                return Err(anyhow::anyhow!("Mistral stopped early: the response hit the token limit"));
+            }
+            Some("error") => {
+                return Err(anyhow::anyhow!("Mistral reported an error while generating. Response: {}", v));
+            }
+            _ => {}
+        }
+
+        let content = choice
+            .and_then(|c| c.get("message"))
+            .and_then(|m| m.get("content"));
+
+        let text = match content {
+            Some(Value::String(s)) => Some(s.clone()),
+            Some(Value::Array(chunks)) => Some(
+                chunks
+                    .iter()
+                    .filter(|c| c.get("type").and_then(|t| t.as_str()) == Some("text"))
+                    .filter_map(|c| c.get("text").and_then(|t| t.as_str()))
+                    .collect::<String>(),
+            ),
+            _ => None,
+        };
+
+        text.map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+            .ok_or_else(|| anyhow::anyhow!("Failed to extract message. Response: {}", v))
+    }
+
+    /// Mistral uses a flat `{"message", "type", "code"}` body for most
+    /// errors, and FastAPI-style `{"detail": [{"loc", "msg"}]}` for
+    /// request validation failures (422).
+    pub fn parse_error(status: reqwest::StatusCode, body: &str) -> anyhow::Error {
+        let detail = serde_json::from_str::<Value>(body).ok().and_then(|v| {
+            if let Some(issues) = v.get("detail").and_then(|d| d.as_array()) {
+                let msgs: Vec<String> = issues
+                    .iter()
+                    .filter_map(|issue| {
+                        let msg = issue.get("msg")?.as_str()?;
+                        let loc = issue
+                            .get("loc")
+                            .and_then(|l| l.as_array())
+                            .map(|l| {
+                                l.iter()
+                                    .map(|p| p.as_str().map(str::to_string).unwrap_or_else(|| p.to_string()))
+                                    .collect::<Vec<_>>()
+                                    .join(".")
+                            });
+                        Some(match loc {
+                            Some(loc) => format!("{}: {}", loc, msg),
+                            None => msg.to_string(),
+                        })
+                    })
+                    .collect();
+                return (!msgs.is_empty()).then(|| msgs.join("; "));
+            }
+
+            let message = v.get("message")?.as_str()?;
+            Some(match v.get("type").and_then(|t| t.as_str()) {
+                Some(kind) => format!("{} ({})", message, kind),
+                None => message.to_string(),
+            })
+        });
+
+        let hint = match status.as_u16() {
+            401 => Some("check that MISTRAL_API_KEY is valid"),
+            429 => Some("rate limit exceeded"),
+            _ => None,
+        };
+
+        match (detail, hint) {
+            (Some(detail), Some(hint)) => {
+                anyhow::anyhow!("Mistral API returned HTTP {}: {} [{}]", status, detail, hint)
+            }
+            (Some(detail), None) => anyhow::anyhow!("Mistral API returned HTTP {}: {}", status, detail),
+            (None, _) => anyhow::anyhow!("Mistral API returned HTTP {}:\n{}", status, body),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl LLMClient for MistralClient {
+    async fn generate(&self, prompt: &str, options: &GenerationOptions) -> anyhow::Result<String> {
+        let client = crate::http::client()?;
+        let resp = client
+            .post(&self.endpoint)
+            .bearer_auth(&self.api_key)
+            .json(&chat_request_body(Some(&self.model), prompt, options))
+            .send_logged()
+            .await?;
+
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let text = resp.text().await?;
+            return Err(Self::parse_error(status, &text));
+        }
+
+        let v: Value = resp.json().await?;
+        Self::parse_response_json(&v)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_response_json_string_content() {
+        let data = json!({
+            "choices": [{ "message": { "content": "perf: cache lookups" }, "finish_reason": "stop" }]
+        });
+
+        assert_eq!(MistralClient::parse_response_json(&data).unwrap(), "perf: cache lookups");
+    }
+
+    #[test]
+    fn test_parse_response_json_chunked_content() {
+        let data = json!({
+            "choices": [{
+                "message": { "content": [
+                    { "type": "thinking", "thinking": [] },
+                    { "type": "text", "text": "perf: cache lookups" }
+                ] },
+                "finish_reason": "stop"
+            }]
+        });
+
+        assert_eq!(MistralClient::parse_response_json(&data).unwrap(), "perf: cache lookups");
+    }
+
+    #[test]
+    fn test_parse_response_json_truncated() {
+        let data = json!({
+            "choices": [{ "message": { "content": "perf: cache" }, "finish_reason": "model_length" }]
+        });
+
+        let err = MistralClient::parse_response_json(&data).unwrap_err();
+        assert!(err.to_string().contains("token limit"));
+    }
+
+    #[test]
+    fn test_parse_error_flat_body() {
+        let body = r#"{"object": "error", "message": "Invalid model: foo", "type": "invalid_model", "param": null, "code": "1500"}"#;
+
+        let err = MistralClient::parse_error(reqwest::StatusCode::BAD_REQUEST, body);
+        assert!(err.to_string().contains("Invalid model: foo (invalid_model)"));
+    }
+
+    #[test]
+    fn test_parse_error_validation_detail() {
+        let body = r#"{"detail": [{"loc": ["body", "temperature"], "msg": "Input should be less than or equal to 1.5", "type": "less_than_equal"}]}"#;
+
+        let err = MistralClient::parse_error(reqwest::StatusCode::UNPROCESSABLE_ENTITY, body);
+        assert!(err.to_string().contains("body.temperature: Input should be less than or equal to 1.5"));
+    }
+}
@@ -0,0 +1,266 @@
+mod azure;
+mod bedrock;
+mod claude;
+mod deepseek;
+mod fallback;
+mod gemini;
+mod github;
+mod groq;
+mod huggingface;
+mod mistral;
+mod ollama;
+mod openai;
+mod openrouter;
+mod options;
+pub mod registry;
+mod sse;
+mod vertex;
+mod xai;
+
+pub use azure::AzureOpenAIClient;
+pub use bedrock::BedrockClient;
+pub use claude::ClaudeClient;
+pub use deepseek::DeepSeekClient;
+pub use fallback::{FallbackChain, Generation};
+pub use gemini::GeminiClient;
+pub use github::GitHubModelsClient;
+pub use groq::GroqClient;
+pub use huggingface::HuggingFaceClient;
+pub use mistral::MistralClient;
+pub use ollama::OllamaClient;
+pub use openai::OpenAIClient;
+pub use openrouter::OpenRouterClient;
+pub use options::GenerationOptions;
+pub use vertex::VertexClient;
+pub use xai::XaiClient;
+
+use std::pin::Pin;
+use std::time::{Duration, Instant};
+
+use futures::Stream;
+
+/// Pieces of a message in the order they are produced.
+pub type TokenStream<'a> = Pin<Box<dyn Stream<Item = anyhow::Result<String>> + Send + 'a>>;
+
+/// A model offered by a provider, as reported by its listing endpoint.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModelInfo {
+    pub id: String,
+    /// Input context size in tokens, when the provider reports it.
+    pub context_window: Option<u64>,
+}
+
+/// A provider's client, or the reason it couldn't be built.
+pub type ClientResult = anyhow::Result<Box<dyn LLMClient>>;
+
+#[async_trait::async_trait]
+pub trait LLMClient: Send + Sync {
+    async fn generate(&self, prompt: &str, options: &GenerationOptions) -> anyhow::Result<String>;
+
+    /// Streams the message as it is generated. Providers without a
+    /// streaming API yield the whole message as a single item.
+    async fn generate_stream(
+        &self,
+        prompt: &str,
+        options: &GenerationOptions,
+    ) -> anyhow::Result<TokenStream<'_>> {
+        let message = self.generate(prompt, options).await?;
+        Ok(Box::pin(futures::stream::once(async move { Ok(message) })))
+    }
+
+    /// The models available to this client's credentials.
+    async fn list_models(&self) -> anyhow::Result<Vec<ModelInfo>> {
+        Err(anyhow::anyhow!("this provider has no model listing endpoint"))
+    }
+}
+
+/// An API root from the config, without the trailing slash the paths
+/// after it start with.
+pub(crate) fn trim_base_url(base_url: &str) -> String {
+    base_url.trim().trim_end_matches('/').to_string()
+}
+
+/// Removes `<think>...</think>` reasoning blocks that reasoning models
+/// (DeepSeek-R1 and its distills) emit before their answer. An unclosed
+/// block means the answer never arrived, so everything after it is dropped.
+pub fn strip_think_blocks(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("<think>") {
+        out.push_str(&rest[..start]);
+        match rest[start..].find("</think>") {
+            Some(end) => rest = &rest[start + end + "</think>".len()..],
+            None => {
+                rest = "";
+                break;
+            }
+        }
+    }
+    out.push_str(rest);
+    out.trim().to_string()
+}
+
+/// Response headers worth logging when diagnosing rate limits.
+const RATE_LIMIT_HEADERS: &[&str] = &["retry-after", "x-ratelimit-", "anthropic-ratelimit-", "ratelimit"];
+
+/// Longer waits than this are reported instead of slept through; a
+/// commit message isn't worth blocking the terminal for minutes.
+const MAX_RETRY_WAIT: Duration = Duration::from_secs(30);
+
+/// `send` for provider requests, logging each request's status and timing
+/// and, at debug level, any rate-limit headers in the response. The URL's
+/// query string is left out of the log, and the whole URL out of errors,
+/// since some providers carry keys in them.
+/// Connection failures, timeouts, 429s and 5xx responses are retried as
+/// [`crate::http::retries`] says, before any of the body is read.
+pub(crate) trait SendLogged {
+    fn send_logged(self) -> impl Future<Output = reqwest::Result<reqwest::Response>> + Send;
+}
+
+impl SendLogged for reqwest::RequestBuilder {
+    async fn send_logged(self) -> reqwest::Result<reqwest::Response> {
+        let (client, request) = self.build_split();
+        let mut request = request?;
+        let target = format!(
+            "{} {}{}",
+            request.method(),
+            request.url().host_str().unwrap_or(""),
+            request.url().path()
+        );
+        let retries = crate::http::retries();
+        let mut attempt = 0;
+        loop {
+            let spare = if attempt < retries.max { request.try_clone() } else { None };
+            let started = Instant::now();
+            let result = client.execute(request).await.map_err(reqwest::Error::without_url);
+            let elapsed_ms = started.elapsed().as_millis() as u64;
+            match &result {
+                Ok(resp) => {
+                    tracing::info!(status = resp.status().as_u16(), elapsed_ms, "{}", target);
+                    for (name, value) in resp.headers() {
+                        if RATE_LIMIT_HEADERS.iter().any(|prefix| name.as_str().starts_with(prefix)) {
+                            tracing::debug!("{}: {}", name, value.to_str().unwrap_or("<binary>"));
+                        }
+                    }
+                }
+                Err(e) => tracing::info!(elapsed_ms, "{} failed: {}", target, e),
+            }
+
+            let Some(spare) = spare else { return result };
+            let wait = match &result {
+                Ok(resp) if is_retryable(resp.status()) => {
+                    retry_after(resp.headers()).unwrap_or_else(|| retries.delay(attempt))
+                }
+                Err(e) if e.is_timeout() || e.is_connect() => retries.delay(attempt),
+                _ => return result,
+            };
+            if wait > MAX_RETRY_WAIT {
+                return result;
+            }
+            attempt += 1;
+            tracing::info!("{}: retry {} of {} in {:.1}s", target, attempt, retries.max, wait.as_secs_f64());
+            tokio::time::sleep(wait).await;
+            request = spare;
+        }
+    }
+}
+
+/// Rate limits and server-side failures, which may pass; not other client
+/// errors such as a bad key, which won't.
+fn is_retryable(status: reqwest::StatusCode) -> bool {
+    status == reqwest::StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+/// `retry-after` as a number of seconds; some providers send fractions.
+pub(crate) fn retry_after(headers: &reqwest::header::HeaderMap) -> Option<Duration> {
+    headers
+        .get(reqwest::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse::<f64>()
+        .ok()
+        .filter(|secs| secs.is_finite() && *secs >= 0.0)
+        .map(Duration::from_secs_f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use async_trait::async_trait;
+    use reqwest::header::{HeaderMap, HeaderValue, RETRY_AFTER};
+
+    struct FakeClient;
+    #[async_trait]
+    impl LLMClient for FakeClient {
+        async fn generate(&self, _prompt: &str, _options: &GenerationOptions) -> Result<String> {
+            Ok("chore: add unit tests".into())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_generate_with_fake_client() {
+        let fake = FakeClient;
+        let result = fake.generate("test prompt", &GenerationOptions::default()).await.unwrap();
+        assert_eq!(result, "chore: add unit tests");
+    }
+
+    #[tokio::test]
+    async fn test_default_generate_stream_yields_whole_message() {
+        use futures::StreamExt;
+
+        let fake = FakeClient;
+        let chunks: Vec<String> = fake
+            .generate_stream("test prompt", &GenerationOptions::default())
+            .await
+            .unwrap()
+            .map(|chunk| chunk.unwrap())
+            .collect()
+            .await;
+        assert_eq!(chunks, ["chore: add unit tests"]);
+    }
+
+    #[test]
+    fn test_strip_think_blocks() {
+        let text = "<think>\nThe user changed the parser...\n</think>\n\nfix(parser): handle empty input";
+        assert_eq!(strip_think_blocks(text), "fix(parser): handle empty input");
+    }
+
+    #[test]
+    fn test_strip_think_blocks_without_block() {
+        assert_eq!(strip_think_blocks("  feat: add flag \n"), "feat: add flag");
+    }
+
+    #[test]
+    fn test_strip_think_blocks_unclosed() {
+        assert_eq!(strip_think_blocks("<think>still reasoning when the tokens ran out"), "");
+    }
+
+    #[test]
+    fn test_retry_after_parses_seconds() {
+        let mut headers = HeaderMap::new();
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("2"));
+        assert_eq!(retry_after(&headers), Some(Duration::from_secs(2)));
+
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("0.5"));
+        assert_eq!(retry_after(&headers), Some(Duration::from_millis(500)));
+    }
+
+    #[test]
+    fn test_retry_after_missing_or_invalid() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(retry_after(&headers), None);
+
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"));
+        assert_eq!(retry_after(&headers), None);
+    }
+
+    #[test]
+    fn test_is_retryable() {
+        assert!(is_retryable(reqwest::StatusCode::TOO_MANY_REQUESTS));
+        assert!(is_retryable(reqwest::StatusCode::BAD_GATEWAY));
+        assert!(!is_retryable(reqwest::StatusCode::UNAUTHORIZED));
+        assert!(!is_retryable(reqwest::StatusCode::OK));
+    }
+}
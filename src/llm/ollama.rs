@@ -0,0 +1,228 @@
+use serde_json::Value;
+
+use super::{GenerationOptions, LLMClient, ModelInfo, SendLogged};
+
+const DEFAULT_PORT: u16 = 11434;
+
+pub struct OllamaClient {
+    base_url: String,
+    model: String,
+}
+
+impl OllamaClient {
+    pub const DEFAULT_HOST: &'static str = "127.0.0.1:11434";
+    pub const DEFAULT_MODEL: &'static str = "llama3.2";
+
+    pub fn new(host: &str, model: String) -> Self {
+        Self {
+            base_url: Self::normalize_host(host),
+            model,
+        }
+    }
+
+    /// Accepts the same forms as the `OLLAMA_HOST` variable understood by
+    /// Ollama itself: `host` or `host:port`, which get the default port if
+    /// they have none, or a full `http(s)://` URL, possibly with a path
+    /// behind a gateway, which is used as given.
+    pub fn normalize_host(host: &str) -> String {
+        let host = host.trim().trim_end_matches('/');
+        if host.contains("://") {
+            return host.to_string();
+        }
+        let host = if host.is_empty() { "127.0.0.1" } else { host };
+        let (authority, path) = host.split_at(host.find('/').unwrap_or(host.len()));
+        let has_port = authority
+            .rsplit_once(':')
+            .is_some_and(|(_, port)| port.parse::<u16>().is_ok());
+
+        if has_port {
+            format!("http://{}{}", authority, path)
+        } else {
+            format!("http://{}:{}{}", authority, DEFAULT_PORT, path)
+        }
+    }
+
+    pub fn parse_response_json(v: &serde_json::Value) -> anyhow::Result<String> {
+        v.get("message")
+            .and_then(|m| m.get("content"))
+            .and_then(|t| t.as_str())
+            .map(|s| s.trim().to_string())
+            .ok_or_else(|| anyhow::anyhow!("Failed to extract message. Response: {}", v))
+    }
+
+    /// Names of the locally pulled models from `/api/tags`.
+    pub fn parse_tags_json(v: &serde_json::Value) -> anyhow::Result<Vec<String>> {
+        let models = v
+            .get("models")
+            .and_then(|m| m.as_array())
+            .ok_or_else(|| anyhow::anyhow!("Failed to extract models. Response: {}", v))?;
+        Ok(models
+            .iter()
+            .filter_map(|m| m.get("name")?.as_str().map(str::to_string))
+            .collect())
+    }
+
+    /// `/api/show` reports the context length under an
+    /// architecture-specific key such as `llama.context_length`.
+    pub fn parse_context_length(v: &serde_json::Value) -> Option<u64> {
+        v.get("model_info")?
+            .as_object()?
+            .iter()
+            .find(|(key, _)| key.ends_with(".context_length"))
+            .and_then(|(_, length)| length.as_u64())
+    }
+
+    /// Ollama reports failures as `{"error": "..."}`.
+    pub fn parse_error(status: reqwest::StatusCode, body: &str) -> anyhow::Error {
+        let detail = serde_json::from_str::<Value>(body)
+            .ok()
+            .and_then(|v| v.get("error")?.as_str().map(str::to_string));
+
+        match detail {
+            Some(detail) if status == reqwest::StatusCode::NOT_FOUND => anyhow::anyhow!(
+                "Ollama returned HTTP {}: {} [pull it with `ollama pull <model>`]",
+                status,
+                detail
+            ),
+            Some(detail) => anyhow::anyhow!("Ollama returned HTTP {}: {}", status, detail),
+            None => anyhow::anyhow!("Ollama returned HTTP {}:\n{}", status, body),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl LLMClient for OllamaClient {
+    async fn generate(&self, prompt: &str, options: &GenerationOptions) -> anyhow::Result<String> {
+        let client = crate::http::client()?;
+        let resp = client
+            .post(format!("{}/api/chat", self.base_url))
+            .json(&serde_json::json!({
+                "model": self.model,
+                "messages": [{ "role": "user", "content": prompt }],
+                "stream": false,
+                "options": options.params(["temperature", "num_predict", "top_p"])
+            }))
+            .send_logged()
+            .await
+            .map_err(|e| {
+                if e.is_connect() {
+                    anyhow::anyhow!(
+                        "Could not connect to Ollama at {}; is `ollama serve` running?",
+                        self.base_url
+                    )
+                } else {
+                    e.into()
+                }
+            })?;
+
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let text = resp.text().await?;
+            return Err(Self::parse_error(status, &text));
+        }
+
+        let v: Value = resp.json().await?;
+        Self::parse_response_json(&v)
+    }
+
+    async fn list_models(&self) -> anyhow::Result<Vec<ModelInfo>> {
+        let client = crate::http::client()?;
+        let resp = client
+            .get(format!("{}/api/tags", self.base_url))
+            .send_logged()
+            .await
+            .map_err(|e| {
+                if e.is_connect() {
+                    anyhow::anyhow!(
+                        "Could not connect to Ollama at {}; is `ollama serve` running?",
+                        self.base_url
+                    )
+                } else {
+                    e.into()
+                }
+            })?;
+
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let text = resp.text().await?;
+            return Err(Self::parse_error(status, &text));
+        }
+
+        let v: Value = resp.json().await?;
+        let mut models = Vec::new();
+        for id in Self::parse_tags_json(&v)? {
+            // The context length is only a nicety; a failed lookup just
+            // leaves it blank.
+            let context_window = match client
+                .post(format!("{}/api/show", self.base_url))
+                .json(&serde_json::json!({ "model": id }))
+                .send_logged()
+                .await
+            {
+                Ok(resp) if resp.status().is_success() => resp
+                    .json::<Value>()
+                    .await
+                    .ok()
+                    .and_then(|v| Self::parse_context_length(&v)),
+                _ => None,
+            };
+            models.push(ModelInfo { id, context_window });
+        }
+        Ok(models)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_normalize_host() {
+        assert_eq!(OllamaClient::normalize_host("localhost"), "http://localhost:11434");
+        assert_eq!(OllamaClient::normalize_host("10.0.0.5:8080"), "http://10.0.0.5:8080");
+        assert_eq!(OllamaClient::normalize_host("https://ollama.internal/"), "https://ollama.internal");
+        assert_eq!(OllamaClient::normalize_host("https://gw.corp/ollama"), "https://gw.corp/ollama");
+        assert_eq!(OllamaClient::normalize_host("http://gw.corp:8080/ollama/"), "http://gw.corp:8080/ollama");
+        assert_eq!(OllamaClient::normalize_host("gw.corp/ollama"), "http://gw.corp:11434/ollama");
+        assert_eq!(OllamaClient::normalize_host(""), "http://127.0.0.1:11434");
+    }
+
+    #[test]
+    fn test_parse_tags_and_context_length() {
+        let tags = json!({ "models": [{ "name": "llama3.2:latest", "size": 2019393189 }, { "name": "qwen2.5-coder:7b" }] });
+        assert_eq!(OllamaClient::parse_tags_json(&tags).unwrap(), ["llama3.2:latest", "qwen2.5-coder:7b"]);
+
+        let show = json!({ "model_info": { "general.architecture": "llama", "llama.context_length": 131072 } });
+        assert_eq!(OllamaClient::parse_context_length(&show), Some(131072));
+        assert_eq!(OllamaClient::parse_context_length(&json!({})), None);
+    }
+
+    #[test]
+    fn test_parse_response_json_success() {
+        let data = json!({
+            "model": "llama3.2",
+            "message": { "role": "assistant", "content": "fix: handle empty input\n" },
+            "done": true
+        });
+
+        let result = OllamaClient::parse_response_json(&data).unwrap();
+        assert_eq!(result, "fix: handle empty input");
+    }
+
+    #[test]
+    fn test_parse_response_json_missing_message() {
+        let data = json!({ "done": true });
+
+        let result = OllamaClient::parse_response_json(&data);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_parse_error_model_not_found() {
+        let body = r#"{"error": "model \"mistral\" not found, try pulling it first"}"#;
+
+        let err = OllamaClient::parse_error(reqwest::StatusCode::NOT_FOUND, body);
+        assert!(err.to_string().contains("ollama pull"));
+    }
+}
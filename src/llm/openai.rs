@@ -0,0 +1,357 @@
+use serde_json::Value;
+
+use super::options::CHAT_PARAMS;
+use super::{GenerationOptions, LLMClient, ModelInfo, SendLogged};
+
+pub struct OpenAIClient {
+    api_key: Option<String>,
+    endpoint: String,
+    model: String,
+    provider: &'static str,
+    key_var: &'static str,
+}
+
+impl OpenAIClient {
+    pub const DEFAULT_MODEL: &'static str = "gpt-4o-mini";
+
+    pub fn new(api_key: String, model: String) -> Self {
+        Self {
+            api_key: Some(api_key),
+            endpoint: "https://api.openai.com/v1/chat/completions".to_string(),
+            model,
+            provider: "OpenAI",
+            key_var: "OPENAI_API_KEY",
+        }
+    }
+
+    /// Any server speaking the chat-completions format (vLLM, LM Studio,
+    /// LiteLLM, llama.cpp, ...). Local servers often need no key at all.
+    pub fn compatible(base_url: &str, api_key: Option<String>, model: String) -> Self {
+        Self::for_provider(
+            "OpenAI-compatible endpoint",
+            "OPENAI_COMPAT_API_KEY",
+            &Self::chat_completions_url(base_url),
+            api_key,
+            model,
+        )
+    }
+
+    /// For hosted providers that reuse the OpenAI wire format; `provider`
+    /// and `key_var` only affect error messages.
+    pub(super) fn for_provider(
+        provider: &'static str,
+        key_var: &'static str,
+        endpoint: &str,
+        api_key: Option<String>,
+        model: String,
+    ) -> Self {
+        Self {
+            api_key,
+            endpoint: endpoint.to_string(),
+            model,
+            provider,
+            key_var,
+        }
+    }
+
+    /// Sends to another API root, e.g. `https://gateway.example.com/openai/v1`,
+    /// or a full `/chat/completions` URL.
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.endpoint = Self::chat_completions_url(base_url);
+        self
+    }
+
+    /// Accepts either the API root (`http://localhost:8000/v1`) or the full
+    /// `/chat/completions` URL.
+    pub fn chat_completions_url(base_url: &str) -> String {
+        let base_url = base_url.trim().trim_end_matches('/');
+        if base_url.ends_with("/chat/completions") {
+            base_url.to_string()
+        } else {
+            format!("{}/chat/completions", base_url)
+        }
+    }
+
+    /// `/models` lives next to `/chat/completions` under the API root.
+    pub fn models_url(&self) -> String {
+        let root = self.endpoint.strip_suffix("/chat/completions").unwrap_or(&self.endpoint);
+        format!("{}/models", root)
+    }
+
+    /// OpenAI itself doesn't report context sizes, but several compatible
+    /// servers add one under a field of their own choosing.
+    pub fn parse_models_json(v: &serde_json::Value) -> anyhow::Result<Vec<ModelInfo>> {
+        let data = v
+            .get("data")
+            .and_then(|d| d.as_array())
+            .ok_or_else(|| anyhow::anyhow!("Failed to extract models. Response: {}", v))?;
+        Ok(data
+            .iter()
+            .filter_map(|m| {
+                let id = m.get("id")?.as_str()?.to_string();
+                let context_window = ["context_window", "context_length", "max_context_length", "max_model_len"]
+                    .iter()
+                    .find_map(|field| m.get(*field).and_then(|l| l.as_u64()));
+                Some(ModelInfo { id, context_window })
+            })
+            .collect())
+    }
+
+    pub fn parse_response_json(v: &serde_json::Value) -> anyhow::Result<String> {
+        let message = v
+            .get("choices")
+            .and_then(|c| c.get(0))
+            .and_then(|c| c.get("message"));
+
+        if let Some(refusal) = message
+            .and_then(|m| m.get("refusal"))
+            .and_then(|r| r.as_str())
+        {
+            return Err(anyhow::anyhow!("The model refused the request: {}", refusal));
+        }
+
+        let finish_reason = v
+            .get("choices")
+            .and_then(|c| c.get(0))
+            .and_then(|c| c.get("finish_reason"))
+            .and_then(|f| f.as_str());
+        if finish_reason == Some("content_filter") {
+            return Err(anyhow::anyhow!("The response was blocked by the content filter"));
+        }
+
+        message
+            .and_then(|m| m.get("content"))
+            .and_then(|t| t.as_str())
+            .map(|s| s.trim().to_string())
+            .ok_or_else(|| anyhow::anyhow!("Failed to extract message. Response: {}", v))
+    }
+
+    /// Turns a non-2xx response into a readable error, using the
+    /// `{"error": {"message", "type", "code"}}` body OpenAI returns.
+    pub fn parse_error(&self, status: reqwest::StatusCode, body: &str) -> anyhow::Error {
+        parse_chat_error(self.provider, self.key_var, status, body)
+    }
+}
+
+/// Request body shared by every backend that speaks the OpenAI
+/// chat-completions wire format.
+pub(super) fn chat_request_body(model: Option<&str>, prompt: &str, options: &GenerationOptions) -> Value {
+    let mut body = options.params(CHAT_PARAMS);
+    if model.is_some_and(is_reasoning_model) {
+        // Reasoning models reject `temperature` and `top_p`, and take their
+        // limit, which covers the reasoning too, as `max_completion_tokens`.
+        body.remove("temperature");
+        body.remove("top_p");
+        if let Some(max_tokens) = body.remove("max_tokens") {
+            body.insert("max_completion_tokens".into(), max_tokens);
+        }
+    }
+    if let Some(model) = model {
+        body.insert("model".into(), model.into());
+    }
+    body.insert("messages".into(), serde_json::json!([{ "role": "user", "content": prompt }]));
+    Value::Object(body)
+}
+
+/// OpenAI's o-series and GPT-5 models, also under an OpenRouter-style
+/// `openai/` prefix. `gpt-5-chat` isn't one.
+fn is_reasoning_model(model: &str) -> bool {
+    let model = model.rsplit('/').next().unwrap_or(model);
+    let o_series = model.strip_prefix('o').is_some_and(|rest| rest.starts_with(|c: char| c.is_ascii_digit()));
+    o_series || (model.starts_with("gpt-5") && !model.starts_with("gpt-5-chat"))
+}
+
+/// Error mapping shared by every backend that speaks the OpenAI
+/// chat-completions wire format.
+pub(super) fn parse_chat_error(
+    provider: &str,
+    key_var: &str,
+    status: reqwest::StatusCode,
+    body: &str,
+) -> anyhow::Error {
+    let detail = serde_json::from_str::<Value>(body).ok().and_then(|v| {
+        let err = v.get("error")?;
+        let message = err.get("message")?.as_str()?.to_string();
+        let code = err
+            .get("code")
+            .and_then(|c| c.as_str())
+            .or_else(|| err.get("type").and_then(|t| t.as_str()));
+        Some(match code {
+            Some(code) => format!("{} ({})", message, code),
+            None => message,
+        })
+    });
+
+    let hint = match status.as_u16() {
+        401 => Some(format!("check that {} is valid", key_var)),
+        429 => Some("rate limit or quota exceeded".to_string()),
+        _ => None,
+    };
+
+    match (detail, hint) {
+        (Some(detail), Some(hint)) => {
+            anyhow::anyhow!("{} API returned HTTP {}: {} [{}]", provider, status, detail, hint)
+        }
+        (Some(detail), None) => anyhow::anyhow!("{} API returned HTTP {}: {}", provider, status, detail),
+        (None, _) => anyhow::anyhow!("{} API returned HTTP {}:\n{}", provider, status, body),
+    }
+}
+
+#[async_trait::async_trait]
+impl LLMClient for OpenAIClient {
+    async fn generate(&self, prompt: &str, options: &GenerationOptions) -> anyhow::Result<String> {
+        let client = crate::http::client()?;
+        let mut req = client.post(&self.endpoint);
+        if let Some(api_key) = &self.api_key {
+            req = req.bearer_auth(api_key);
+        }
+        let resp = req
+            .json(&chat_request_body(Some(&self.model), prompt, options))
+            .send_logged()
+            .await?;
+
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let text = resp.text().await?;
+            return Err(self.parse_error(status, &text));
+        }
+
+        let v: Value = resp.json().await?;
+        Self::parse_response_json(&v)
+    }
+
+    async fn list_models(&self) -> anyhow::Result<Vec<ModelInfo>> {
+        let client = crate::http::client()?;
+        let mut req = client.get(self.models_url());
+        if let Some(api_key) = &self.api_key {
+            req = req.bearer_auth(api_key);
+        }
+        let resp = req.send_logged().await?;
+
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let text = resp.text().await?;
+            return Err(self.parse_error(status, &text));
+        }
+
+        let v: Value = resp.json().await?;
+        let mut models = Self::parse_models_json(&v)?;
+        models.sort_by(|a, b| a.id.cmp(&b.id));
+        Ok(models)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_chat_request_body() {
+        let options = GenerationOptions { top_p: Some(0.5), ..Default::default() };
+        let body = |model| {
+            let mut body = chat_request_body(Some(model), "hi", &options);
+            body.as_object_mut().unwrap().remove("messages");
+            body
+        };
+        assert_eq!(body("gpt-4o"), json!({ "model": "gpt-4o", "temperature": 0.0, "max_tokens": 4096, "top_p": 0.5 }));
+        assert_eq!(body("gpt-5-mini"), json!({ "model": "gpt-5-mini", "max_completion_tokens": 4096 }));
+        assert_eq!(body("o4-mini"), json!({ "model": "o4-mini", "max_completion_tokens": 4096 }));
+        assert_eq!(body("openai/o3"), json!({ "model": "openai/o3", "max_completion_tokens": 4096 }));
+        assert_eq!(body("gpt-5-chat-latest")["max_tokens"], 4096);
+        assert_eq!(body("open-mistral-nemo")["max_tokens"], 4096);
+    }
+
+    #[test]
+    fn test_chat_completions_url() {
+        assert_eq!(
+            OpenAIClient::chat_completions_url("http://localhost:8000/v1/"),
+            "http://localhost:8000/v1/chat/completions"
+        );
+        assert_eq!(
+            OpenAIClient::chat_completions_url("http://localhost:1234/v1/chat/completions"),
+            "http://localhost:1234/v1/chat/completions"
+        );
+    }
+
+    #[test]
+    fn test_models_url_and_parse_models_json() {
+        let client = OpenAIClient::compatible("http://localhost:8000/v1", None, "local".into());
+        assert_eq!(client.models_url(), "http://localhost:8000/v1/models");
+
+        let data = json!({
+            "object": "list",
+            "data": [
+                { "id": "gpt-4o-mini", "object": "model", "owned_by": "system" },
+                { "id": "llama-3.1-8b-instant", "context_window": 131072 }
+            ]
+        });
+        let models = OpenAIClient::parse_models_json(&data).unwrap();
+        assert_eq!(models[0], ModelInfo { id: "gpt-4o-mini".into(), context_window: None });
+        assert_eq!(models[1].context_window, Some(131072));
+    }
+
+    #[test]
+    fn test_parse_response_json_success() {
+        let data = json!({
+            "choices": [{
+                "message": { "role": "assistant", "content": "  feat: add OpenAI provider\n" },
+                "finish_reason": "stop"
+            }]
+        });
+
+        let result = OpenAIClient::parse_response_json(&data).unwrap();
+        assert_eq!(result, "feat: add OpenAI provider");
+    }
+
+    #[test]
+    fn test_parse_response_json_empty_choices() {
+        let data = json!({ "choices": [] });
+
+        let result = OpenAIClient::parse_response_json(&data);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_parse_response_json_refusal() {
+        let data = json!({
+            "choices": [{
+                "message": { "role": "assistant", "content": null, "refusal": "I can't help with that." }
+            }]
+        });
+
+        let err = OpenAIClient::parse_response_json(&data).unwrap_err();
+        assert!(err.to_string().contains("refused"));
+    }
+
+    #[test]
+    fn test_parse_response_json_content_filter() {
+        let data = json!({
+            "choices": [{
+                "message": { "role": "assistant", "content": null },
+                "finish_reason": "content_filter"
+            }]
+        });
+
+        let err = OpenAIClient::parse_response_json(&data).unwrap_err();
+        assert!(err.to_string().contains("content filter"));
+    }
+
+    #[test]
+    fn test_parse_error_uses_error_body() {
+        let body = r#"{"error": {"message": "Incorrect API key provided", "type": "invalid_request_error", "code": "invalid_api_key"}}"#;
+
+        let client = OpenAIClient::new("sk-test".into(), OpenAIClient::DEFAULT_MODEL.into());
+        let err = client.parse_error(reqwest::StatusCode::UNAUTHORIZED, body);
+        let msg = err.to_string();
+        assert!(msg.contains("Incorrect API key provided (invalid_api_key)"));
+        assert!(msg.contains("OPENAI_API_KEY"));
+    }
+
+    #[test]
+    fn test_parse_error_falls_back_to_raw_body() {
+        let client = OpenAIClient::compatible("http://localhost:8000/v1", None, "llama".into());
+        let err = client.parse_error(reqwest::StatusCode::BAD_GATEWAY, "upstream down");
+        assert!(err.to_string().contains("upstream down"));
+    }
+}
@@ -0,0 +1,164 @@
+use serde_json::Value;
+
+use super::openai::chat_request_body;
+use super::{GenerationOptions, LLMClient, OpenAIClient, SendLogged};
+
+/// OpenRouter fronts many upstream providers behind one chat-completions
+/// API; the model slug (`anthropic/claude-3.5-haiku`, `openrouter/auto`, ...)
+/// decides where the request is routed.
+pub struct OpenRouterClient {
+    api_key: String,
+    endpoint: String,
+    model: String,
+}
+
+impl OpenRouterClient {
+    pub const DEFAULT_MODEL: &'static str = "openrouter/auto";
+
+    pub fn new(api_key: String, model: String) -> Self {
+        Self {
+            api_key,
+            endpoint: "https://openrouter.ai/api/v1/chat/completions".to_string(),
+            model,
+        }
+    }
+
+    /// Sends to another API root, or a full `/chat/completions` URL.
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.endpoint = OpenAIClient::chat_completions_url(base_url);
+        self
+    }
+
+    /// Upstream failures can arrive with a 200 status, either as a
+    /// top-level `error` or on the choice itself.
+    pub fn parse_response_json(v: &serde_json::Value) -> anyhow::Result<String> {
+        let error = v.get("error").or_else(|| {
+            v.get("choices")
+                .and_then(|c| c.get(0))
+                .and_then(|c| c.get("error"))
+        });
+        if let Some(error) = error {
+            return Err(anyhow::anyhow!("OpenRouter routing failed: {}", describe_error(error)));
+        }
+
+        OpenAIClient::parse_response_json(v)
+    }
+
+    pub fn parse_error(&self, status: reqwest::StatusCode, body: &str) -> anyhow::Error {
+        let detail = serde_json::from_str::<Value>(body)
+            .ok()
+            .and_then(|v| v.get("error").map(describe_error));
+
+        let hint = match status.as_u16() {
+            401 => Some("check that OPENROUTER_API_KEY is valid".to_string()),
+            402 => Some("the account is out of credits".to_string()),
+            404 => Some(format!(
+                "no provider can serve '{}'; check the slug at https://openrouter.ai/models",
+                self.model
+            )),
+            429 => Some("rate limit exceeded".to_string()),
+            _ => None,
+        };
+
+        match (detail, hint) {
+            (Some(detail), Some(hint)) => {
+                anyhow::anyhow!("OpenRouter returned HTTP {}: {} [{}]", status, detail, hint)
+            }
+            (Some(detail), None) => anyhow::anyhow!("OpenRouter returned HTTP {}: {}", status, detail),
+            (None, _) => anyhow::anyhow!("OpenRouter returned HTTP {}:\n{}", status, body),
+        }
+    }
+}
+
+/// Formats `{"message", "metadata": {"provider_name", "raw"}}`, surfacing
+/// which upstream provider failed when OpenRouter tells us.
+fn describe_error(error: &Value) -> String {
+    let message = error
+        .get("message")
+        .and_then(|m| m.as_str())
+        .unwrap_or("unknown error");
+    let metadata = error.get("metadata");
+    let provider = metadata
+        .and_then(|m| m.get("provider_name"))
+        .and_then(|p| p.as_str());
+    let raw = metadata.and_then(|m| m.get("raw")).and_then(|r| r.as_str());
+
+    let mut out = message.to_string();
+    if let Some(provider) = provider {
+        out.push_str(&format!(" (upstream: {})", provider));
+    }
+    if let Some(raw) = raw {
+        out.push_str(&format!(": {}", raw));
+    }
+    out
+}
+
+#[async_trait::async_trait]
+impl LLMClient for OpenRouterClient {
+    async fn generate(&self, prompt: &str, options: &GenerationOptions) -> anyhow::Result<String> {
+        let client = crate::http::client()?;
+        let resp = client
+            .post(&self.endpoint)
+            .bearer_auth(&self.api_key)
+            .header("HTTP-Referer", "https://github.com/bokney/commitgen")
+            .header("X-Title", "commitgen")
+            .json(&chat_request_body(Some(&self.model), prompt, options))
+            .send_logged()
+            .await?;
+
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let text = resp.text().await?;
+            return Err(self.parse_error(status, &text));
+        }
+
+        let v: Value = resp.json().await?;
+        Self::parse_response_json(&v)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_response_json_success() {
+        let data = json!({
+            "model": "anthropic/claude-3.5-haiku",
+            "choices": [{ "message": { "role": "assistant", "content": "refactor: split module" } }]
+        });
+
+        let result = OpenRouterClient::parse_response_json(&data).unwrap();
+        assert_eq!(result, "refactor: split module");
+    }
+
+    #[test]
+    fn test_parse_response_json_upstream_error_with_ok_status() {
+        let data = json!({
+            "choices": [{
+                "error": {
+                    "code": 502,
+                    "message": "Provider returned error",
+                    "metadata": { "provider_name": "Together", "raw": "model overloaded" }
+                }
+            }]
+        });
+
+        let err = OpenRouterClient::parse_response_json(&data).unwrap_err();
+        let msg = err.to_string();
+        assert!(msg.contains("upstream: Together"));
+        assert!(msg.contains("model overloaded"));
+    }
+
+    #[test]
+    fn test_parse_error_unknown_model() {
+        let client = OpenRouterClient::new("key".into(), "nope/not-a-model".into());
+        let body = r#"{"error": {"code": 404, "message": "No endpoints found for nope/not-a-model."}}"#;
+
+        let err = client.parse_error(reqwest::StatusCode::NOT_FOUND, body);
+        let msg = err.to_string();
+        assert!(msg.contains("No endpoints found"));
+        assert!(msg.contains("'nope/not-a-model'"));
+    }
+}
@@ -0,0 +1,104 @@
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+/// Sampling parameters for a request. Unset fields fall back to the
+/// provider's own defaults in this crate (deterministic output with a
+/// generous token budget).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct GenerationOptions {
+    pub temperature: Option<f32>,
+    pub max_tokens: Option<u32>,
+    pub top_p: Option<f32>,
+}
+
+/// Parameter names used by OpenAI-style chat APIs.
+pub const CHAT_PARAMS: [&str; 3] = ["temperature", "max_tokens", "top_p"];
+
+impl GenerationOptions {
+    pub const DEFAULT_TEMPERATURE: f32 = 0.0;
+    pub const DEFAULT_MAX_TOKENS: u32 = 4096;
+
+    /// Fields set on `self` win; the rest come from `other`.
+    pub fn or(self, other: Self) -> Self {
+        Self {
+            temperature: self.temperature.or(other.temperature),
+            max_tokens: self.max_tokens.or(other.max_tokens),
+            top_p: self.top_p.or(other.top_p),
+        }
+    }
+
+    pub fn temperature(&self) -> f32 {
+        self.temperature.unwrap_or(Self::DEFAULT_TEMPERATURE)
+    }
+
+    pub fn max_tokens(&self) -> u32 {
+        self.max_tokens.unwrap_or(Self::DEFAULT_MAX_TOKENS)
+    }
+
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if let Some(t) = self.temperature
+            && !(0.0..=2.0).contains(&t)
+        {
+            return Err(anyhow::anyhow!("temperature must be between 0 and 2, got {}", t));
+        }
+        if let Some(p) = self.top_p
+            && !(p > 0.0 && p <= 1.0)
+        {
+            return Err(anyhow::anyhow!("top_p must be greater than 0 and at most 1, got {}", p));
+        }
+        if self.max_tokens == Some(0) {
+            return Err(anyhow::anyhow!("max_tokens must be at least 1"));
+        }
+        Ok(())
+    }
+
+    /// The parameters as a JSON object, under the provider's names for
+    /// temperature, max tokens and top-p. `top_p` is left out unless set.
+    pub fn params(&self, names: [&str; 3]) -> Map<String, Value> {
+        let [temperature, max_tokens, top_p] = names;
+        let mut params = Map::new();
+        params.insert(temperature.into(), self.temperature().into());
+        params.insert(max_tokens.into(), self.max_tokens().into());
+        if let Some(p) = self.top_p {
+            params.insert(top_p.into(), p.into());
+        }
+        params
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_or_prefers_self() {
+        let flags = GenerationOptions { temperature: Some(0.7), ..Default::default() };
+        let config = GenerationOptions { temperature: Some(0.2), max_tokens: Some(256), top_p: None };
+        assert_eq!(
+            flags.or(config),
+            GenerationOptions { temperature: Some(0.7), max_tokens: Some(256), top_p: None }
+        );
+    }
+
+    #[test]
+    fn test_params() {
+        let options = GenerationOptions::default();
+        assert_eq!(Value::Object(options.params(CHAT_PARAMS)), json!({ "temperature": 0.0, "max_tokens": 4096 }));
+
+        let options = GenerationOptions { top_p: Some(0.5), max_tokens: Some(100), ..Default::default() };
+        assert_eq!(
+            Value::Object(options.params(["temperature", "maxOutputTokens", "topP"])),
+            json!({ "temperature": 0.0, "maxOutputTokens": 100, "topP": 0.5 })
+        );
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(GenerationOptions::default().validate().is_ok());
+        assert!(GenerationOptions { temperature: Some(2.5), ..Default::default() }.validate().is_err());
+        assert!(GenerationOptions { top_p: Some(0.0), ..Default::default() }.validate().is_err());
+        assert!(GenerationOptions { max_tokens: Some(0), ..Default::default() }.validate().is_err());
+    }
+}
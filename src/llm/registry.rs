@@ -0,0 +1,481 @@
+use std::env;
+
+use super::{
+    AzureOpenAIClient, BedrockClient, ClaudeClient, ClientResult, DeepSeekClient, GeminiClient, GitHubModelsClient,
+    GroqClient, HuggingFaceClient, LLMClient, MistralClient, OllamaClient, OpenAIClient, OpenRouterClient,
+    VertexClient, XaiClient,
+};
+use crate::auth::{aws, github, keyring};
+use crate::auth::google::GoogleCredentials;
+
+/// Settings that apply across providers; each builder decides what it can
+/// honour.
+#[derive(Default)]
+pub struct ProviderOptions<'a> {
+    /// Explicit model from `--model` or the config file.
+    pub model: Option<&'a str>,
+    /// Skip the known-model check, for models released after this build.
+    pub allow_unknown_model: bool,
+    /// The API root from `[endpoints]`, in place of the provider's own.
+    pub endpoint: Option<&'a str>,
+}
+
+pub struct Provider {
+    pub name: &'static str,
+    pub aliases: &'static [&'static str],
+    pub description: &'static str,
+    /// The API key's environment variable, which also names its keyring
+    /// entry for `commitgen auth set`.
+    pub key_var: Option<&'static str>,
+    /// Environment variable consulted when no model is given explicitly.
+    pub model_var: Option<&'static str>,
+    pub default_model: Option<&'static str>,
+    /// Models accepted without `--allow-unknown-model`. Empty for providers
+    /// whose catalogue is open-ended (local servers, routers, deployments).
+    pub known_models: &'static [&'static str],
+    build: fn(Option<String>, Option<&str>) -> ClientResult,
+}
+
+impl Provider {
+    pub fn build(&self, opts: &ProviderOptions) -> anyhow::Result<Box<dyn LLMClient>> {
+        let model = self.resolve_model(opts.model);
+        if let Some(model) = &model
+            && !opts.allow_unknown_model
+        {
+            self.validate_model(model)?;
+        }
+        (self.build)(model, opts.endpoint)
+    }
+
+    /// `--model`/config wins over the provider's `*_MODEL` variable, which
+    /// wins over the provider default.
+    pub fn resolve_model(&self, explicit: Option<&str>) -> Option<String> {
+        explicit
+            .map(str::to_string)
+            .or_else(|| self.model_var.and_then(|var| env::var(var).ok()))
+            .or_else(|| self.default_model.map(str::to_string))
+    }
+
+    pub fn validate_model(&self, model: &str) -> anyhow::Result<()> {
+        if self.known_models.is_empty() || self.known_models.contains(&model) {
+            return Ok(());
+        }
+        Err(anyhow::anyhow!(
+            "Unknown model '{}' for provider '{}'. Known models: {}\n\
+            If this is a newly released model, pass --allow-unknown-model to use it anyway.",
+            model,
+            self.name,
+            self.known_models.join(", ")
+        ))
+    }
+}
+
+pub const DEFAULT_PROVIDER: &str = "gemini";
+
+const GEMINI_MODELS: &[&str] = &[
+    "gemini-2.5-pro",
+    "gemini-2.5-flash",
+    "gemini-2.5-flash-lite",
+    "gemini-2.0-flash",
+    "gemini-2.0-flash-lite",
+];
+
+pub static PROVIDERS: &[Provider] = &[
+    Provider {
+        name: "gemini",
+        aliases: &[],
+        description: "Google Gemini (GEMINI_API_KEY)",
+        key_var: Some("GEMINI_API_KEY"),
+        model_var: Some("GEMINI_MODEL"),
+        default_model: Some(GeminiClient::DEFAULT_MODEL),
+        known_models: GEMINI_MODELS,
+        build: |model, endpoint| {
+            let client = GeminiClient::new(required_secret("GEMINI_API_KEY")?, &required_model(model)?);
+            at(client, endpoint, GeminiClient::with_base_url)
+        },
+    },
+    Provider {
+        name: "openai",
+        aliases: &[],
+        description: "OpenAI (OPENAI_API_KEY)",
+        key_var: Some("OPENAI_API_KEY"),
+        model_var: Some("OPENAI_MODEL"),
+        default_model: Some(OpenAIClient::DEFAULT_MODEL),
+        known_models: &[
+            "gpt-4o", "gpt-4o-mini", "gpt-4.1", "gpt-4.1-mini", "gpt-4.1-nano",
+            "gpt-5", "gpt-5-mini", "gpt-5-nano", "o3", "o3-mini", "o4-mini",
+        ],
+        build: |model, endpoint| {
+            let client = OpenAIClient::new(required_secret("OPENAI_API_KEY")?, required_model(model)?);
+            at(client, endpoint, OpenAIClient::with_base_url)
+        },
+    },
+    Provider {
+        name: "claude",
+        aliases: &["anthropic"],
+        description: "Anthropic Claude (ANTHROPIC_API_KEY)",
+        key_var: Some("ANTHROPIC_API_KEY"),
+        model_var: Some("ANTHROPIC_MODEL"),
+        default_model: Some(ClaudeClient::DEFAULT_MODEL),
+        known_models: &[
+            "claude-3-5-haiku-latest", "claude-3-7-sonnet-latest",
+            "claude-sonnet-4-0", "claude-opus-4-0", "claude-opus-4-1",
+        ],
+        build: |model, endpoint| {
+            let client = ClaudeClient::new(required_secret("ANTHROPIC_API_KEY")?, required_model(model)?);
+            at(client, endpoint, ClaudeClient::with_base_url)
+        },
+    },
+    Provider {
+        name: "ollama",
+        aliases: &[],
+        description: "Local Ollama server (OLLAMA_HOST)",
+        key_var: None,
+        model_var: Some("OLLAMA_MODEL"),
+        default_model: Some(OllamaClient::DEFAULT_MODEL),
+        known_models: &[],
+        build: |model, endpoint| {
+            let host = endpoint.map(str::to_string).unwrap_or_else(ollama_host);
+            Ok(Box::new(OllamaClient::new(&host, required_model(model)?)))
+        },
+    },
+    Provider {
+        name: "azure",
+        aliases: &[],
+        description: "Azure OpenAI (AZURE_OPENAI_API_KEY, AZURE_OPENAI_RESOURCE, AZURE_OPENAI_DEPLOYMENT)",
+        key_var: Some("AZURE_OPENAI_API_KEY"),
+        model_var: None,
+        default_model: None,
+        known_models: &[],
+        build: |_, endpoint| {
+            let api_version = env::var("AZURE_OPENAI_API_VERSION")
+                .unwrap_or_else(|_| AzureOpenAIClient::DEFAULT_API_VERSION.to_string());
+            let resource = match endpoint {
+                Some(url) => url.to_string(),
+                None => required_env("AZURE_OPENAI_RESOURCE")?,
+            };
+            Ok(Box::new(AzureOpenAIClient::new(
+                required_secret("AZURE_OPENAI_API_KEY")?,
+                &resource,
+                &required_env("AZURE_OPENAI_DEPLOYMENT")?,
+                &api_version,
+            )))
+        },
+    },
+    Provider {
+        name: "bedrock",
+        aliases: &[],
+        description: "AWS Bedrock (default AWS credential chain)",
+        key_var: None,
+        model_var: Some("BEDROCK_MODEL"),
+        default_model: Some(BedrockClient::DEFAULT_MODEL),
+        known_models: &[],
+        build: |model, endpoint| {
+            let client = BedrockClient::new(aws::default_region(), required_model(model)?);
+            at(client, endpoint, BedrockClient::with_base_url)
+        },
+    },
+    Provider {
+        name: "vertex",
+        aliases: &[],
+        description: "Gemini on Vertex AI (Application Default Credentials)",
+        key_var: None,
+        model_var: Some("VERTEX_MODEL"),
+        default_model: Some(GeminiClient::DEFAULT_MODEL),
+        known_models: GEMINI_MODELS,
+        build: |model, endpoint| {
+            let credentials = GoogleCredentials::from_default_chain()?;
+            let project = env::var("GOOGLE_CLOUD_PROJECT")
+                .ok()
+                .or_else(|| credentials.project_id().map(str::to_string))
+                .ok_or_else(|| anyhow::anyhow!("GOOGLE_CLOUD_PROJECT must be set to use Vertex AI"))?;
+            let location = env::var("GOOGLE_CLOUD_LOCATION").unwrap_or_else(|_| VertexClient::DEFAULT_LOCATION.to_string());
+            let client = VertexClient::new(credentials, &project, &location, &required_model(model)?);
+            at(client, endpoint, VertexClient::with_base_url)
+        },
+    },
+    Provider {
+        name: "openai-compatible",
+        aliases: &[],
+        description: "Any OpenAI-compatible server (OPENAI_COMPAT_BASE_URL)",
+        key_var: Some("OPENAI_COMPAT_API_KEY"),
+        model_var: Some("OPENAI_COMPAT_MODEL"),
+        default_model: None,
+        known_models: &[],
+        build: |model, endpoint| {
+            let model = model.ok_or_else(|| {
+                anyhow::anyhow!("OPENAI_COMPAT_MODEL must be set in .env or passed with --model")
+            })?;
+            let base_url = match endpoint {
+                Some(url) => url.to_string(),
+                None => required_env("OPENAI_COMPAT_BASE_URL")?,
+            };
+            Ok(Box::new(OpenAIClient::compatible(
+                &base_url,
+                keyring::secret("OPENAI_COMPAT_API_KEY"),
+                model,
+            )))
+        },
+    },
+    Provider {
+        name: "openrouter",
+        aliases: &[],
+        description: "OpenRouter (OPENROUTER_API_KEY)",
+        key_var: Some("OPENROUTER_API_KEY"),
+        model_var: Some("OPENROUTER_MODEL"),
+        default_model: Some(OpenRouterClient::DEFAULT_MODEL),
+        known_models: &[],
+        build: |model, endpoint| {
+            let client = OpenRouterClient::new(required_secret("OPENROUTER_API_KEY")?, required_model(model)?);
+            at(client, endpoint, OpenRouterClient::with_base_url)
+        },
+    },
+    Provider {
+        name: "mistral",
+        aliases: &[],
+        description: "Mistral (MISTRAL_API_KEY)",
+        key_var: Some("MISTRAL_API_KEY"),
+        model_var: Some("MISTRAL_MODEL"),
+        default_model: Some(MistralClient::DEFAULT_MODEL),
+        known_models: &[
+            "mistral-small-latest", "mistral-medium-latest", "mistral-large-latest",
+            "codestral-latest", "ministral-8b-latest", "open-mistral-nemo",
+        ],
+        build: |model, endpoint| {
+            let client = MistralClient::new(required_secret("MISTRAL_API_KEY")?, required_model(model)?);
+            at(client, endpoint, MistralClient::with_base_url)
+        },
+    },
+    Provider {
+        name: "groq",
+        aliases: &[],
+        description: "Groq (GROQ_API_KEY)",
+        key_var: Some("GROQ_API_KEY"),
+        model_var: Some("GROQ_MODEL"),
+        default_model: Some(GroqClient::DEFAULT_MODEL),
+        known_models: &[
+            "llama-3.1-8b-instant", "llama-3.3-70b-versatile",
+            "openai/gpt-oss-20b", "openai/gpt-oss-120b", "qwen/qwen3-32b",
+        ],
+        build: |model, endpoint| {
+            let client = GroqClient::new(required_secret("GROQ_API_KEY")?, required_model(model)?);
+            at(client, endpoint, GroqClient::with_base_url)
+        },
+    },
+    Provider {
+        name: "deepseek",
+        aliases: &[],
+        description: "DeepSeek (DEEPSEEK_API_KEY)",
+        key_var: Some("DEEPSEEK_API_KEY"),
+        model_var: Some("DEEPSEEK_MODEL"),
+        default_model: Some(DeepSeekClient::DEFAULT_MODEL),
+        known_models: &["deepseek-chat", "deepseek-reasoner"],
+        build: |model, endpoint| {
+            let client = DeepSeekClient::new(required_secret("DEEPSEEK_API_KEY")?, required_model(model)?);
+            at(client, endpoint, DeepSeekClient::with_base_url)
+        },
+    },
+    Provider {
+        name: "xai",
+        aliases: &["grok"],
+        description: "xAI Grok (XAI_API_KEY)",
+        key_var: Some("XAI_API_KEY"),
+        model_var: Some("XAI_MODEL"),
+        default_model: Some(XaiClient::DEFAULT_MODEL),
+        known_models: &["grok-3", "grok-3-mini", "grok-4"],
+        build: |model, endpoint| {
+            let client = XaiClient::new(required_secret("XAI_API_KEY")?, required_model(model)?);
+            at(client, endpoint, XaiClient::with_base_url)
+        },
+    },
+    Provider {
+        name: "github",
+        aliases: &["github-models"],
+        description: "GitHub Models (GITHUB_TOKEN or `gh auth login`)",
+        key_var: Some("GITHUB_TOKEN"),
+        model_var: Some("GITHUB_MODELS_MODEL"),
+        default_model: Some(GitHubModelsClient::DEFAULT_MODEL),
+        known_models: &[],
+        build: |model, endpoint| {
+            let client = GitHubModelsClient::new(github::token()?, required_model(model)?);
+            at(client, endpoint, GitHubModelsClient::with_base_url)
+        },
+    },
+    Provider {
+        name: "huggingface",
+        aliases: &["hf"],
+        description: "Hugging Face Inference API or a dedicated endpoint (HF_TOKEN, HF_ENDPOINT_URL)",
+        key_var: Some("HF_TOKEN"),
+        model_var: Some("HF_MODEL"),
+        default_model: Some(HuggingFaceClient::DEFAULT_MODEL),
+        known_models: &[],
+        build: |model, endpoint| {
+            let token = keyring::secret("HF_TOKEN");
+            match endpoint.map(str::to_string).ok_or(()).or_else(|_| env::var("HF_ENDPOINT_URL")) {
+                Ok(url) => Ok(Box::new(HuggingFaceClient::dedicated(token, &url))),
+                Err(_) => {
+                    let token = token.ok_or_else(|| missing_secret("HF_TOKEN"))?;
+                    Ok(Box::new(HuggingFaceClient::serverless(Some(token), &required_model(model)?)))
+                }
+            }
+        },
+    },
+];
+
+/// Finds a provider by name or alias, case-insensitively.
+pub fn lookup(name: &str) -> anyhow::Result<&'static Provider> {
+    let wanted = name.trim().to_ascii_lowercase();
+    PROVIDERS
+        .iter()
+        .find(|p| p.name == wanted || p.aliases.contains(&wanted.as_str()))
+        .ok_or_else(|| {
+            let available: Vec<String> = PROVIDERS
+                .iter()
+                .map(|p| format!("  {:<18} {}", p.name, p.description))
+                .collect();
+            anyhow::anyhow!(
+                "Unknown provider '{}'. Available providers:\n{}",
+                name,
+                available.join("\n")
+            )
+        })
+}
+
+/// Whether requests to provider `name` stay on this machine: a local
+/// server (Ollama, or an OpenAI-compatible one) on a loopback address, or
+/// any provider whose `endpoint` from `[endpoints]` is one.
+pub fn is_local(name: &str, endpoint: Option<&str>) -> bool {
+    let url = match (endpoint, lookup(name).map(|p| p.name)) {
+        (Some(endpoint), _) => endpoint.to_string(),
+        (None, Ok("ollama")) => ollama_host(),
+        (None, Ok("openai-compatible")) => env::var("OPENAI_COMPAT_BASE_URL").unwrap_or_default(),
+        _ => return false,
+    };
+    is_loopback(&url)
+}
+
+fn ollama_host() -> String {
+    env::var("OLLAMA_HOST").unwrap_or_else(|_| OllamaClient::DEFAULT_HOST.to_string())
+}
+
+/// Whether `url` (a full URL, or just `host:port`) names this machine.
+fn is_loopback(url: &str) -> bool {
+    let rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let authority = rest.split('/').next().unwrap_or_default();
+    let host = match authority.strip_prefix('[') {
+        Some(bracketed) => bracketed.split(']').next().unwrap_or_default(),
+        None => authority.split(':').next().unwrap_or_default(),
+    };
+    host.eq_ignore_ascii_case("localhost")
+        || host.parse::<std::net::IpAddr>().is_ok_and(|ip| ip.is_loopback())
+}
+
+pub fn build(name: &str, opts: &ProviderOptions) -> anyhow::Result<Box<dyn LLMClient>> {
+    lookup(name)?.build(opts)
+}
+
+/// `client`, sent to `endpoint` instead of its default API root if there is one.
+fn at<C: LLMClient + 'static>(
+    client: C,
+    endpoint: Option<&str>,
+    with_base_url: fn(C, &str) -> C,
+) -> ClientResult {
+    Ok(Box::new(match endpoint {
+        Some(url) => with_base_url(client, url),
+        None => client,
+    }))
+}
+
+fn required_env(var: &str) -> anyhow::Result<String> {
+    env::var(var).map_err(|_| anyhow::anyhow!("{} must be set in .env", var))
+}
+
+fn required_secret(var: &str) -> anyhow::Result<String> {
+    keyring::secret(var).ok_or_else(|| missing_secret(var))
+}
+
+fn missing_secret(var: &str) -> anyhow::Error {
+    anyhow::anyhow!("{} must be set in .env or stored with `commitgen auth set`", var)
+}
+
+/// Every provider with a `default_model` always resolves to some model;
+/// this only guards against a table entry missing one.
+fn required_model(model: Option<String>) -> anyhow::Result<String> {
+    model.ok_or_else(|| anyhow::anyhow!("No model configured; pass one with --model"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_loopback() {
+        assert!(is_loopback("127.0.0.1:11434"));
+        assert!(is_loopback("http://localhost:8080/v1"));
+        assert!(is_loopback("http://[::1]:8080"));
+        assert!(!is_loopback("https://api.example.com/v1"));
+        assert!(!is_loopback("http://10.0.0.5:11434"));
+        assert!(!is_local("gemini", None));
+        assert!(is_local("gemini", Some("http://127.0.0.1:8080/v1beta")));
+        assert!(!is_local("ollama", Some("https://ollama.example.com")));
+    }
+
+    #[test]
+    fn test_lookup_by_name_and_alias() {
+        assert_eq!(lookup("openai").unwrap().name, "openai");
+        assert_eq!(lookup("Grok").unwrap().name, "xai");
+        assert_eq!(lookup(DEFAULT_PROVIDER).unwrap().name, "gemini");
+    }
+
+    #[test]
+    fn test_lookup_unknown_lists_available() {
+        let err = lookup("watson").err().unwrap().to_string();
+        assert!(err.contains("Unknown provider 'watson'"));
+        for provider in PROVIDERS {
+            assert!(err.contains(provider.name));
+        }
+    }
+
+    #[test]
+    fn test_validate_model() {
+        let gemini = lookup("gemini").unwrap();
+        assert!(gemini.validate_model("gemini-2.5-pro").is_ok());
+
+        let err = gemini.validate_model("gemini-9-ultra").unwrap_err().to_string();
+        assert!(err.contains("Unknown model 'gemini-9-ultra'"));
+        assert!(err.contains("--allow-unknown-model"));
+    }
+
+    #[test]
+    fn test_validate_model_open_catalogue() {
+        let ollama = lookup("ollama").unwrap();
+        assert!(ollama.validate_model("qwen2.5-coder:7b").is_ok());
+    }
+
+    #[test]
+    fn test_resolve_model_prefers_explicit() {
+        let openai = lookup("openai").unwrap();
+        assert_eq!(openai.resolve_model(Some("gpt-4.1")).as_deref(), Some("gpt-4.1"));
+    }
+
+    #[test]
+    fn test_default_models_are_known() {
+        for provider in PROVIDERS {
+            if let Some(model) = provider.default_model {
+                assert!(provider.validate_model(model).is_ok(), "{}", provider.name);
+            }
+        }
+    }
+
+    #[test]
+    fn test_names_are_unique() {
+        let mut all: Vec<&str> = PROVIDERS
+            .iter()
+            .flat_map(|p| std::iter::once(p.name).chain(p.aliases.iter().copied()))
+            .collect();
+        let len = all.len();
+        all.sort();
+        all.dedup();
+        assert_eq!(all.len(), len);
+    }
+}
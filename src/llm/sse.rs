@@ -0,0 +1,67 @@
+/// Incremental parser for `text/event-stream` bodies. Bytes are fed in as
+/// they arrive; complete events come back as their joined `data:` payloads.
+/// Events are only decoded once whole, since a chunk can end partway
+/// through a character.
+#[derive(Default)]
+pub struct SseParser {
+    buffer: Vec<u8>,
+}
+
+impl SseParser {
+    pub fn push(&mut self, chunk: &[u8]) -> Vec<String> {
+        self.buffer.extend_from_slice(chunk);
+        if self.buffer.contains(&b'\r') {
+            let mut bytes = self.buffer.iter().peekable();
+            let mut normalized = Vec::with_capacity(self.buffer.len());
+            while let Some(&b) = bytes.next() {
+                if b != b'\r' || bytes.peek() != Some(&&b'\n') {
+                    normalized.push(b);
+                }
+            }
+            self.buffer = normalized;
+        }
+
+        let mut events = Vec::new();
+        while let Some(end) = self.buffer.windows(2).position(|pair| pair == b"\n\n") {
+            let event: Vec<u8> = self.buffer.drain(..end + 2).collect();
+            let event = String::from_utf8_lossy(&event);
+            let data: Vec<&str> = event
+                .lines()
+                .filter_map(|line| line.strip_prefix("data:"))
+                .map(|data| data.strip_prefix(' ').unwrap_or(data))
+                .collect();
+            if !data.is_empty() {
+                events.push(data.join("\n"));
+            }
+        }
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_push_split_across_chunks() {
+        let mut parser = SseParser::default();
+        assert!(parser.push(b"data: {\"a\":").is_empty());
+        assert_eq!(parser.push(b" 1}\n\ndata: {\"b\": 2}\n\n"), ["{\"a\": 1}", "{\"b\": 2}"]);
+    }
+
+    #[test]
+    fn test_push_character_split_across_chunks() {
+        let mut parser = SseParser::default();
+        let event = "data: コミット\r\n\r\n".as_bytes();
+        // Partway through the second character's three bytes.
+        assert!(parser.push(&event[..10]).is_empty());
+        assert_eq!(parser.push(&event[10..]), ["コミット"]);
+    }
+
+    #[test]
+    fn test_push_ignores_comments_and_joins_multiline_data() {
+        let mut parser = SseParser::default();
+        let events = parser.push(b": keep-alive\n\nevent: message\r\ndata: line one\r\ndata: line two\r\n\r\n");
+        assert_eq!(events, ["line one\nline two"]);
+    }
+}
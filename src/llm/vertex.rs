@@ -0,0 +1,92 @@
+use serde_json::Value;
+
+use super::gemini::GEMINI_PARAMS;
+use super::{GeminiClient, GenerationOptions, LLMClient, SendLogged};
+use crate::auth::google::GoogleCredentials;
+
+/// Gemini served through Vertex AI, authenticated with Application Default
+/// Credentials instead of an AI Studio API key.
+pub struct VertexClient {
+    credentials: GoogleCredentials,
+    endpoint: String,
+}
+
+impl VertexClient {
+    pub const DEFAULT_LOCATION: &'static str = "us-central1";
+
+    pub fn new(credentials: GoogleCredentials, project: &str, location: &str, model: &str) -> Self {
+        Self {
+            credentials,
+            endpoint: Self::build_endpoint(project, location, model),
+        }
+    }
+
+    /// Sends to another host than the location's, such as a Private
+    /// Service Connect endpoint, e.g. `https://vertex.corp.example.com`;
+    /// the `/v1/projects/...` path stays the same.
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        let path = self.endpoint.find("/v1/").map_or("", |start| &self.endpoint[start..]);
+        self.endpoint = format!("{}{}", super::trim_base_url(base_url), path);
+        self
+    }
+
+    pub fn build_endpoint(project: &str, location: &str, model: &str) -> String {
+        let host = if location == "global" {
+            "aiplatform.googleapis.com".to_string()
+        } else {
+            format!("{}-aiplatform.googleapis.com", location)
+        };
+        format!(
+            "https://{}/v1/projects/{}/locations/{}/publishers/google/models/{}:generateContent",
+            host, project, location, model
+        )
+    }
+}
+
+#[async_trait::async_trait]
+impl LLMClient for VertexClient {
+    async fn generate(&self, prompt: &str, options: &GenerationOptions) -> anyhow::Result<String> {
+        let client = crate::http::client()?;
+        let token = self.credentials.access_token(&client).await?;
+        let resp = client
+            .post(&self.endpoint)
+            .bearer_auth(token)
+            .json(&serde_json::json!({
+                "contents": [{ "role": "user", "parts": [{ "text": prompt }] }],
+                "generationConfig": options.params(GEMINI_PARAMS)
+            }))
+            .send_logged()
+            .await?;
+
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let text = resp.text().await?;
+            return Err(anyhow::anyhow!(
+                "Vertex AI returned HTTP {}:\n{}", status, text
+            ));
+        }
+
+        let v: Value = resp.json().await?;
+        GeminiClient::parse_response_json(&v)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_endpoint_regional() {
+        let endpoint = VertexClient::build_endpoint("my-project", "europe-west4", "gemini-2.5-flash");
+        assert_eq!(
+            endpoint,
+            "https://europe-west4-aiplatform.googleapis.com/v1/projects/my-project/locations/europe-west4/publishers/google/models/gemini-2.5-flash:generateContent"
+        );
+    }
+
+    #[test]
+    fn test_build_endpoint_global() {
+        let endpoint = VertexClient::build_endpoint("my-project", "global", "gemini-2.5-flash");
+        assert!(endpoint.starts_with("https://aiplatform.googleapis.com/v1/projects/my-project/locations/global/"));
+    }
+}
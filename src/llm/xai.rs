@@ -0,0 +1,117 @@
+use serde_json::Value;
+
+use super::openai::chat_request_body;
+use super::{GenerationOptions, LLMClient, OpenAIClient, SendLogged};
+
+pub struct XaiClient {
+    api_key: String,
+    endpoint: String,
+    model: String,
+}
+
+impl XaiClient {
+    pub const DEFAULT_MODEL: &'static str = "grok-3-mini";
+
+    pub fn new(api_key: String, model: String) -> Self {
+        Self {
+            api_key,
+            endpoint: "https://api.x.ai/v1/chat/completions".to_string(),
+            model,
+        }
+    }
+
+    /// Sends to another API root, or a full `/chat/completions` URL.
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.endpoint = OpenAIClient::chat_completions_url(base_url);
+        self
+    }
+
+    /// Grok reasoning models return their thinking in a separate
+    /// `reasoning_content` field, so only `content` is used.
+    pub fn parse_response_json(v: &serde_json::Value) -> anyhow::Result<String> {
+        OpenAIClient::parse_response_json(v)
+    }
+
+    /// xAI errors are a flat `{"code": "...", "error": "..."}` object rather
+    /// than OpenAI's nested `error` body.
+    pub fn parse_error(status: reqwest::StatusCode, body: &str) -> anyhow::Error {
+        let detail = serde_json::from_str::<Value>(body).ok().and_then(|v| {
+            let error = v.get("error")?;
+            let message = error
+                .as_str()
+                .or_else(|| error.get("message").and_then(|m| m.as_str()))?;
+            Some(match v.get("code").and_then(|c| c.as_str()) {
+                Some(code) => format!("{} ({})", message, code),
+                None => message.to_string(),
+            })
+        });
+
+        let hint = match status.as_u16() {
+            400 | 401 | 403 if body.contains("API key") => Some("check that XAI_API_KEY is valid"),
+            429 => Some("rate limit exceeded"),
+            _ => None,
+        };
+
+        match (detail, hint) {
+            (Some(detail), Some(hint)) => {
+                anyhow::anyhow!("xAI API returned HTTP {}: {} [{}]", status, detail, hint)
+            }
+            (Some(detail), None) => anyhow::anyhow!("xAI API returned HTTP {}: {}", status, detail),
+            (None, _) => anyhow::anyhow!("xAI API returned HTTP {}:\n{}", status, body),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl LLMClient for XaiClient {
+    async fn generate(&self, prompt: &str, options: &GenerationOptions) -> anyhow::Result<String> {
+        let client = crate::http::client()?;
+        let resp = client
+            .post(&self.endpoint)
+            .bearer_auth(&self.api_key)
+            .json(&chat_request_body(Some(&self.model), prompt, options))
+            .send_logged()
+            .await?;
+
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let text = resp.text().await?;
+            return Err(Self::parse_error(status, &text));
+        }
+
+        let v: Value = resp.json().await?;
+        Self::parse_response_json(&v)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_response_json_ignores_reasoning() {
+        let data = json!({
+            "choices": [{
+                "message": {
+                    "role": "assistant",
+                    "reasoning_content": "Let me look at the diff...",
+                    "content": "build: bump tokio"
+                }
+            }]
+        });
+
+        assert_eq!(XaiClient::parse_response_json(&data).unwrap(), "build: bump tokio");
+    }
+
+    #[test]
+    fn test_parse_error_flat_body() {
+        let body = r#"{"code": "Client specified an invalid argument", "error": "Incorrect API key provided: xa***. You can obtain an API key from https://console.x.ai."}"#;
+
+        let err = XaiClient::parse_error(reqwest::StatusCode::BAD_REQUEST, body);
+        let msg = err.to_string();
+        assert!(msg.contains("Incorrect API key provided"));
+        assert!(msg.contains("(Client specified an invalid argument)"));
+        assert!(msg.contains("XAI_API_KEY"));
+    }
+}
@@ -0,0 +1,173 @@
+use std::fmt::Write as _;
+use std::io::Write as _;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use colored::*;
+use tracing::field::{Field, Visit};
+use tracing::level_filters::LevelFilter;
+use tracing::{Event, Level, Metadata, Subscriber, span};
+
+/// Which events are written: the level for the most specific matching
+/// target prefix, or the default level for everything else.
+#[derive(Debug, PartialEq)]
+pub struct Filter {
+    default: LevelFilter,
+    targets: Vec<(String, LevelFilter)>,
+}
+
+impl Filter {
+    /// Warnings only by default; `-v` adds commitgen's info events (request
+    /// timing, retries, fallbacks) and `-vv` its debug events (rate-limit
+    /// headers and the like). Dependencies stay at warnings either way.
+    pub fn from_verbosity(verbose: u8) -> Self {
+        let level = match verbose {
+            0 => LevelFilter::WARN,
+            1 => LevelFilter::INFO,
+            2 => LevelFilter::DEBUG,
+            _ => LevelFilter::TRACE,
+        };
+        Self { default: LevelFilter::WARN, targets: vec![("commitgen".to_string(), level)] }
+    }
+
+    /// Parses `RUST_LOG`-style directives: comma-separated `level` or
+    /// `target=level` entries, such as `info,commitgen::llm=trace`.
+    /// Unparseable entries are skipped rather than failing the run.
+    pub fn parse(directives: &str) -> Self {
+        let mut filter = Self { default: LevelFilter::ERROR, targets: Vec::new() };
+        for directive in directives.split(',').map(str::trim).filter(|d| !d.is_empty()) {
+            match directive.split_once('=') {
+                Some((target, level)) => {
+                    if let Ok(level) = level.trim().parse() {
+                        filter.targets.push((target.trim().to_string(), level));
+                    }
+                }
+                None => match directive.parse() {
+                    Ok(level) => filter.default = level,
+                    // A bare target turns everything on for it, as in env_logger.
+                    Err(_) => filter.targets.push((directive.to_string(), LevelFilter::TRACE)),
+                },
+            }
+        }
+        filter
+    }
+
+    fn level_for(&self, target: &str) -> LevelFilter {
+        self.targets
+            .iter()
+            .filter(|(prefix, _)| {
+                target == prefix || target.strip_prefix(prefix.as_str()).is_some_and(|rest| rest.starts_with("::"))
+            })
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map_or(self.default, |(_, level)| *level)
+    }
+
+    fn max_level(&self) -> LevelFilter {
+        self.targets.iter().map(|(_, level)| *level).fold(self.default, std::cmp::max)
+    }
+}
+
+/// Writes events to stderr, one line each. Spans aren't shown; commitgen
+/// only uses events.
+struct StderrSubscriber {
+    filter: Filter,
+    next_span: AtomicU64,
+}
+
+impl Subscriber for StderrSubscriber {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        *metadata.level() <= self.filter.level_for(metadata.target())
+    }
+
+    fn max_level_hint(&self) -> Option<LevelFilter> {
+        Some(self.filter.max_level())
+    }
+
+    fn new_span(&self, _span: &span::Attributes<'_>) -> span::Id {
+        span::Id::from_u64(self.next_span.fetch_add(1, Ordering::Relaxed))
+    }
+
+    fn record(&self, _span: &span::Id, _values: &span::Record<'_>) {}
+
+    fn record_follows_from(&self, _span: &span::Id, _follows: &span::Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let _ = writeln!(std::io::stderr().lock(), "{}", format_event(event));
+    }
+
+    fn enter(&self, _span: &span::Id) {}
+
+    fn exit(&self, _span: &span::Id) {}
+}
+
+/// Collects an event's message and its other fields as `name=value`.
+#[derive(Default)]
+struct Fields {
+    message: String,
+    rest: String,
+}
+
+impl Visit for Fields {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message.push_str(value);
+        } else {
+            let _ = write!(self.rest, " {}={}", field.name(), value);
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            let _ = write!(self.message, "{:?}", value);
+        } else {
+            let _ = write!(self.rest, " {}={:?}", field.name(), value);
+        }
+    }
+}
+
+fn format_event(event: &Event<'_>) -> String {
+    let metadata = event.metadata();
+    let mut fields = Fields::default();
+    event.record(&mut fields);
+    let level = match *metadata.level() {
+        Level::ERROR => "ERROR".red(),
+        Level::WARN => " WARN".yellow(),
+        Level::INFO => " INFO".green(),
+        Level::DEBUG => "DEBUG".blue(),
+        Level::TRACE => "TRACE".purple(),
+    };
+    format!("{} {} {}{}", level, format!("{}:", metadata.target()).dimmed(), fields.message, fields.rest.dimmed())
+}
+
+/// Installs the stderr logger: `RUST_LOG` when it's set, otherwise the
+/// level `-v` asks for.
+pub fn init(verbose: u8) {
+    let filter = match std::env::var("RUST_LOG") {
+        Ok(directives) if !directives.trim().is_empty() => Filter::parse(&directives),
+        _ => Filter::from_verbosity(verbose),
+    };
+    let subscriber = StderrSubscriber { filter, next_span: AtomicU64::new(1) };
+    let _ = tracing::subscriber::set_global_default(subscriber);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let filter = Filter::parse("info, commitgen::llm=trace,reqwest");
+        assert_eq!(filter.level_for("commitgen"), LevelFilter::INFO);
+        assert_eq!(filter.level_for("commitgen::llm::groq"), LevelFilter::TRACE);
+        assert_eq!(filter.level_for("commitgen::llmx"), LevelFilter::INFO);
+        assert_eq!(filter.level_for("reqwest::connect"), LevelFilter::TRACE);
+        assert_eq!(filter.max_level(), LevelFilter::TRACE);
+        assert_eq!(Filter::parse("commitgen=nonsense"), Filter::parse(""));
+    }
+
+    #[test]
+    fn test_from_verbosity() {
+        assert_eq!(Filter::from_verbosity(0).level_for("commitgen::llm"), LevelFilter::WARN);
+        assert_eq!(Filter::from_verbosity(1).level_for("commitgen::llm"), LevelFilter::INFO);
+        assert_eq!(Filter::from_verbosity(2).level_for("hyper"), LevelFilter::WARN);
+    }
+}
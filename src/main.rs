@@ -1,197 +1,3075 @@
+mod auth;
+mod body;
+mod branch;
+mod breaking;
+mod changelog;
+mod check;
+mod clarify;
+mod clipboard;
+mod commit_template;
+mod compare;
+mod completions;
+mod config;
+mod diff;
+mod doctor;
+mod git;
+mod glob;
+mod hg;
+mod history;
+mod http;
+mod hook;
+mod ignore;
+mod import;
+mod jj;
+mod llm;
+mod logging;
+mod man;
+mod notes;
+mod outbound;
+mod output;
+mod pr;
+mod prompt;
+mod revert;
+mod redact;
+mod regex;
+mod remote;
+mod reword;
+mod scope;
+mod selection;
+mod split;
+mod style;
+mod template;
+mod terminal;
+mod ticket;
+mod trailers;
+mod tui;
+mod vcs;
+mod workspace;
+mod wrap;
 
-use std::env;
 use colored::*;
-use clap::Parser;
+use clap::{ArgGroup, Args, CommandFactory, FromArgMatches, Parser, Subcommand};
 use dotenv::dotenv;
-use serde_json::Value;
+use std::io::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::Duration;
 use indicatif::{ProgressBar, ProgressStyle};
 
-#[async_trait::async_trait]
-pub trait LLMClient {
-    async fn generate(&self, prompt: &str) -> anyhow::Result<String>;
+use body::BodyMode;
+use config::Config;
+use selection::{ProviderArgs, Selection, parse_provider};
+use llm::{ClientResult, GenerationOptions};
+use llm::registry::{self, ProviderOptions};
+use vcs::Vcs;
+
+/// Without a subcommand, commitgen runs `generate`, so `commitgen "<description>"`
+/// and `commitgen --commit` keep working.
+#[derive(Parser)]
+#[command(author, version, about = "Generate Git commit messages with LLMs")]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Run as if commitgen was started in PATH, like `git -C`; goes before any subcommand
+    #[arg(short = 'C', long = "repo", value_name = "PATH")]
+    repo: Option<std::path::PathBuf>,
+
+    /// Log request timing, retries and fallbacks to stderr; `-vv` adds rate-limit headers. `RUST_LOG`
+    /// overrides this, e.g. `RUST_LOG=commitgen::llm=trace`
+    #[arg(short, long, action = clap::ArgAction::Count, global = true)]
+    verbose: u8,
+
+    /// Use the `[profile.NAME]` settings from the config file, instead of the profile picked by
+    /// the repository's remotes
+    #[arg(long, value_name = "NAME", global = true)]
+    profile: Option<String>,
+
+    /// Seconds a request may wait to connect or for more of the answer; 0 waits forever
+    /// [default: `timeout_secs` in the config file, or 120]
+    #[arg(long, value_name = "SECS", global = true)]
+    timeout: Option<u64>,
+
+    /// Times a request that fails to connect, times out, is rate limited or hits a server error is
+    /// tried again [default: `max_retries` in the config file, or 2]
+    #[arg(long, value_name = "N", global = true)]
+    max_retries: Option<u32>,
+
+    /// Seconds before the first retry, doubling for each after it, unless the server says how long
+    /// [default: `backoff` in the config file, or 1]
+    #[arg(long, value_name = "SECS", global = true)]
+    backoff: Option<f64>,
+
+    #[command(flatten)]
+    generate: GenerateArgs,
+}
+
+#[derive(Args)]
+#[command(group(ArgGroup::new("committing").args(["commit", "amend"]).multiple(true)))]
+struct GenerateArgs {
+    /// What changed, or `-` to read it from stdin [default: anything piped in, otherwise the staged
+    /// diff, from `git diff --cached`]
+    description: Option<String>,
+
+    /// Message style: a preset such as `angular` or `kernel`, or any description
+    /// [default: `style` in the config file, or conventional]
+    #[arg(short, long)]
+    style: Option<String>,
+
+    /// Language to write the message in: a code such as `ja` or `pt-BR`, or a language name
+    /// [default: `lang` in the config file, or English]
+    #[arg(long)]
+    lang: Option<String>,
+
+    /// Jinja template to write the prompt from in place of the built-in one, using `{{ diff }}`,
+    /// `{{ files }}`, `{{ branch }}`, `{{ recent_commits }}`, `{{ style_rules }}` and so on [default:
+    /// `prompt_template` in the config file]
+    #[arg(long, value_name = "PATH")]
+    prompt_template: Option<std::path::PathBuf>,
+
+    /// Read the diff from stdin rather than git, e.g. `git diff main | commitgen --stdin`
+    #[arg(long, conflicts_with_all = ["description", "all", "amend", "commit"])]
+    stdin: bool,
+
+    /// Describe the whole working tree, not just what is staged
+    #[arg(short, long)]
+    all: bool,
+
+    /// With --all, also include untracked files that aren't ignored
+    #[arg(short = 'u', long, requires = "all")]
+    include_untracked: bool,
+
+    /// Lines of unchanged code shown around each change; fewer makes large changes cheaper to
+    /// describe, more gives the model more to go on [default: 3, as git does]
+    #[arg(long, value_name = "N", conflicts_with_all = ["description", "stdin"])]
+    context_lines: Option<u32>,
+
+    /// Show the model the whole function around each change (`git diff --function-context`)
+    #[arg(long, conflicts_with_all = ["description", "stdin"])]
+    function_context: bool,
+
+    /// Create the commit with the generated message once confirmed
+    #[arg(short, long)]
+    commit: bool,
+
+    /// Rewrite the last commit's message, describing HEAD plus anything newly staged
+    #[arg(long, conflicts_with = "all")]
+    amend: bool,
+
+    /// Don't ask before committing (with --commit or --amend), or before sending the diff to a
+    /// cloud provider
+    #[arg(short, long)]
+    yes: bool,
+
+    /// With --commit or --amend, open the message in git's editor (`GIT_EDITOR`, `core.editor`,
+    /// `VISUAL` or `EDITOR`) for final changes before committing
+    #[arg(short, long, requires = "committing")]
+    edit: bool,
+
+    /// With --commit or --amend, bypass the pre-commit and commit-msg hooks
+    #[arg(long, requires = "committing")]
+    no_verify: bool,
+
+    /// With --commit or --amend, sign the commit even if `commit.gpgsign` isn't set; GPG or SSH
+    /// signing follows `gpg.format` and `user.signingkey` as in `git commit -S`
+    #[arg(short = 'S', long, requires = "committing", conflicts_with = "no_gpg_sign")]
+    gpg_sign: bool,
+
+    /// With --commit or --amend, don't sign the commit even if `commit.gpgsign` is set
+    #[arg(long, requires = "committing")]
+    no_gpg_sign: bool,
+
+    /// With --commit or --amend, record the provider, model, prompt hash and estimated token usage
+    /// in a git note under refs/notes/commitgen [default: `notes` in the config file]
+    #[arg(long, requires = "committing")]
+    note: bool,
+
+    /// How the message body is written: `detailed` summarises each changed file (or area, for large
+    /// changes) in a bullet of its own [default: `body` in the config file, or normal]
+    #[arg(long, value_enum)]
+    body: Option<BodyMode>,
+
+    /// Write the subject line alone, the same as `--body none`
+    #[arg(long, conflicts_with = "body")]
+    no_body: bool,
+
+    /// Allow at most N paragraphs in the body, counting each bullet point as one [default:
+    /// `max_body_items` in the config file]
+    #[arg(long, value_name = "N")]
+    max_body_items: Option<usize>,
+
+    /// Leave out any trailers the model writes (those from --signoff, --co-author and the ticket are
+    /// still added)
+    #[arg(long)]
+    no_footers: bool,
+
+    /// What to do with a subject over the style's length limit [default: `long_subject` in the
+    /// config file, or truncate]
+    #[arg(long, value_enum, value_name = "MODE")]
+    long_subject: Option<wrap::LongSubject>,
+
+    /// Hard-wrap body lines at this column; 0 leaves them as written [default: `wrap_column` in the
+    /// config file, or 72]
+    #[arg(long, value_name = "COLUMN")]
+    wrap: Option<usize>,
+
+    /// Add a `Co-authored-by` trailer for `Name <email>`, or an alias from `[co_authors]` in the
+    /// config file; repeatable
+    #[arg(long, value_name = "AUTHOR")]
+    co_author: Vec<String>,
+
+    /// Add a `Signed-off-by` trailer (Developer Certificate of Origin) from git's user.name and
+    /// user.email [default: `signoff` in the config file]
+    #[arg(long)]
+    signoff: bool,
+
+    /// Mark the message as a breaking change even if none was detected
+    #[arg(long, conflicts_with = "no_breaking")]
+    breaking: bool,
+
+    /// Don't mark the message as a breaking change, even if removed or changed public APIs were detected
+    #[arg(long)]
+    no_breaking: bool,
+
+    /// Don't add the ticket found in the branch name (e.g. `JIRA-1234`) to the message
+    #[arg(long)]
+    no_ticket: bool,
+
+    /// Recent commit subjects to show the model as examples of the repo's conventions; 0 turns
+    /// this off [default: 10, or `examples` in the config file]
+    #[arg(long, value_name = "N")]
+    examples: Option<usize>,
+
+    #[command(flatten)]
+    providers: ProviderArgs,
+
+    /// Also put the message on the clipboard; over SSH, the local terminal's clipboard (OSC 52)
+    #[arg(long)]
+    copy: bool,
+
+    /// Also write the message to PATH, replacing it in one step, e.g. a hook's message file
+    #[arg(short, long, value_name = "PATH", conflicts_with_all = ["dry_run", "compare"])]
+    output: Option<std::path::PathBuf>,
+
+    /// Print only the message, with no spinner, colour or blank lines, e.g. for
+    /// `git commit -m "$(commitgen --quiet)"`
+    #[arg(short, long, visible_alias = "plain", conflicts_with_all = ["interactive", "candidates", "compare", "json"])]
+    quiet: bool,
+
+    /// Print the prompt that would be sent, with an estimate of its tokens, without calling the model
+    #[arg(long, conflicts_with_all = ["committing", "copy", "interactive", "candidates", "json"])]
+    dry_run: bool,
+
+    /// Print the message as JSON (subject, body, footers, provider, model and estimated tokens)
+    /// instead of as text, for scripts and editor plugins
+    #[arg(long, conflicts_with_all = ["interactive", "candidates", "compare"])]
+    json: bool,
+
+    /// After generating, offer to edit, regenerate or refine the message with an instruction
+    /// ("shorter", "mention the migration") until it's accepted
+    #[arg(short, long)]
+    interactive: bool,
+
+    /// Before generating, let the model ask up to N questions about the change (such as why
+    /// something was removed) and answer them at the prompt [default N: 3]
+    #[arg(long, value_name = "N", num_args = 0..=1, require_equals = true, default_missing_value = "3",
+        value_parser = clap::value_parser!(u8).range(1..=9), conflicts_with_all = ["quiet", "json", "dry_run", "compare"])]
+    interactive_context: Option<u8>,
+
+    /// Generate N messages at once and pick one of them
+    #[arg(short = 'n', long = "candidates", value_name = "N", default_value_t = 1,
+        value_parser = clap::value_parser!(u8).range(1..=9), conflicts_with = "compare")]
+    candidates: u8,
+
+    /// Ask several providers at once and pick between their messages
+    /// [default list: `compare` in the config file]
+    #[arg(long, num_args = 0.., value_delimiter = ',', value_parser = parse_provider)]
+    compare: Option<Vec<String>>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Generate a message for the staged changes or a description (the default command)
+    Generate(GenerateArgs),
+    /// Generate a message and commit with it once confirmed, as `generate --commit`
+    Commit(GenerateArgs),
+    /// Set up commitgen: choose a provider, store and test its API key, and pick a style
+    Init,
+    /// Check the config, git, the hook and each provider, and say how to fix what's wrong
+    Doctor {
+        /// Don't call the providers, only check that they're set up
+        #[arg(long)]
+        offline: bool,
+    },
+    /// Browse past generations, reuse their messages or send their prompts again
+    History {
+        #[command(subcommand)]
+        action: Option<HistoryCommand>,
+    },
+    /// Show where the config file lives, or edit it
+    Config {
+        #[command(subcommand)]
+        action: ConfigCommand,
+    },
+    /// List the models the active provider offers
+    Models {
+        /// Provider to query [default: gemini, or `provider` in the config file]
+        #[arg(short, long, value_parser = parse_provider)]
+        provider: Option<String>,
+    },
+    /// Manage API keys stored in the OS keyring
+    Auth {
+        #[command(subcommand)]
+        action: AuthCommand,
+    },
+    /// Validate an existing commit message, e.g. from a commit-msg hook
+    Check {
+        /// File holding the message, such as the one git passes to commit-msg
+        message_file: std::path::PathBuf,
+
+        /// Message style: a preset such as `angular` or `kernel`, or any description
+        /// [default: `style` in the config file, or conventional]
+        #[arg(short, long)]
+        style: Option<String>,
+
+        /// Longest subject line allowed [default: the style's limit, or 72]
+        #[arg(long)]
+        max_subject_length: Option<usize>,
+    },
+    /// Regenerate the messages of a range of commits and rewrite them after review
+    Reword {
+        /// Commits to reword, e.g. `main..HEAD`; a single revision means from there to HEAD
+        range: String,
+
+        /// Message style: a preset such as `angular` or `kernel`, or any description
+        /// [default: `style` in the config file, or conventional]
+        #[arg(short, long)]
+        style: Option<String>,
+
+        /// Rewrite every commit without asking
+        #[arg(short, long)]
+        yes: bool,
+
+        #[command(flatten)]
+        providers: ProviderArgs,
+    },
+    /// Write one message for a range of commits being squashed together
+    Squash {
+        /// Commits to squash, e.g. `main..HEAD`; a single revision means from there to HEAD
+        range: String,
+
+        /// Message style: a preset such as `angular` or `kernel`, or any description
+        /// [default: `style` in the config file, or conventional]
+        #[arg(short, long)]
+        style: Option<String>,
+
+        /// Squash the range into a single commit with the message once confirmed
+        #[arg(short, long)]
+        commit: bool,
+
+        /// With --commit, skip the confirmation prompt
+        #[arg(short, long, requires = "commit")]
+        yes: bool,
+
+        #[command(flatten)]
+        providers: ProviderArgs,
+    },
+    /// Suggest a branch name for a change, and optionally switch to it
+    Branch {
+        /// What the branch is for [default: the staged diff]
+        description: Option<String>,
+
+        /// Name pattern using `{type}`, `{scope}` and `{description}`
+        /// [default: `branch_pattern` in the config file, or `{type}/{scope}-{description}`]
+        #[arg(long)]
+        pattern: Option<String>,
+
+        /// Create the branch and switch to it once confirmed
+        #[arg(short, long)]
+        create: bool,
+
+        /// With --create, skip the confirmation prompt
+        #[arg(short, long, requires = "create")]
+        yes: bool,
+
+        #[command(flatten)]
+        providers: ProviderArgs,
+    },
+    /// Suggest how to split the staged changes into separate commits
+    Split {
+        /// Message style: a preset such as `angular` or `kernel`, or any description
+        /// [default: `style` in the config file, or conventional]
+        #[arg(short, long)]
+        style: Option<String>,
+
+        /// Stage each group in turn and create its commit, once confirmed
+        #[arg(long)]
+        apply: bool,
+
+        /// With --apply, skip the confirmation prompt
+        #[arg(short, long, requires = "apply")]
+        yes: bool,
+
+        #[command(flatten)]
+        providers: ProviderArgs,
+    },
+    /// Write a descriptive message for merging a branch, and optionally merge it
+    Merge {
+        /// Branch to merge into the current one
+        branch: String,
+
+        /// Message style: a preset such as `angular` or `kernel`, or any description
+        /// [default: `style` in the config file, or conventional]
+        #[arg(short, long)]
+        style: Option<String>,
+
+        /// Merge with the message (always creating a merge commit) once confirmed
+        #[arg(short, long)]
+        commit: bool,
+
+        /// With --commit, skip the confirmation prompt
+        #[arg(short, long, requires = "commit")]
+        yes: bool,
+
+        #[command(flatten)]
+        providers: ProviderArgs,
+    },
+    /// Undo the last commit if commitgen made it, keeping its changes staged (`git reset --soft HEAD~`)
+    Undo {
+        /// Undo without asking first
+        #[arg(short, long)]
+        yes: bool,
+
+        /// Undo even if the commit has been pushed, or commitgen can't tell that it made it
+        #[arg(long)]
+        force: bool,
+    },
+    /// Write a message for reverting a commit, and optionally revert it
+    Revert {
+        /// Commit to revert
+        #[arg(value_name = "COMMIT")]
+        rev: String,
+
+        /// Why it is being reverted [default: inferred from the change]
+        #[arg(short, long)]
+        reason: Option<String>,
+
+        /// Message style: a preset such as `angular` or `kernel`, or any description
+        /// [default: `style` in the config file, or conventional]
+        #[arg(short, long)]
+        style: Option<String>,
+
+        /// Revert the commit with the message once confirmed
+        #[arg(short, long)]
+        commit: bool,
+
+        /// With --commit, skip the confirmation prompt
+        #[arg(short, long, requires = "commit")]
+        yes: bool,
+
+        #[command(flatten)]
+        providers: ProviderArgs,
+    },
+    /// Explain in plain English what a commit, or the staged changes, does and why it might have been made
+    Explain {
+        /// Commit to explain [default: HEAD]
+        #[arg(value_name = "COMMIT", conflicts_with = "staged")]
+        rev: Option<String>,
+
+        /// Explain the staged changes instead of a commit
+        #[arg(long)]
+        staged: bool,
+
+        /// Language to explain in: a code such as `ja`, or a language name [default: `lang` in the
+        /// config file, or English]
+        #[arg(long)]
+        lang: Option<String>,
+
+        /// Send the diff to a cloud provider without asking first
+        #[arg(short, long)]
+        yes: bool,
+
+        #[command(flatten)]
+        providers: ProviderArgs,
+    },
+    /// Write a description for a jj change (or the last git commit) and set it
+    Describe {
+        /// Change to describe [default: @ in jj, HEAD in git]
+        #[arg(short, long)]
+        revision: Option<String>,
+
+        /// Message style: a preset such as `angular` or `kernel`, or any description
+        /// [default: `style` in the config file, or conventional]
+        #[arg(short, long)]
+        style: Option<String>,
+
+        /// Set the description without asking for confirmation
+        #[arg(short, long)]
+        yes: bool,
+
+        #[command(flatten)]
+        providers: ProviderArgs,
+    },
+    /// Stash the working tree's changes under a generated description
+    Stash {
+        /// Stash untracked files too, as `git stash push --include-untracked`
+        #[arg(short = 'u', long)]
+        include_untracked: bool,
+
+        /// Stash, and send the diff to a cloud provider, without asking for confirmation
+        #[arg(short, long)]
+        yes: bool,
+
+        #[command(flatten)]
+        providers: ProviderArgs,
+    },
+    /// Review the staged changes and candidate messages side by side in a full-screen view
+    Review {
+        /// Message style: a preset such as `angular` or `kernel`, or any description
+        /// [default: `style` in the config file, or conventional]
+        #[arg(short, long)]
+        style: Option<String>,
+
+        #[command(flatten)]
+        providers: ProviderArgs,
+    },
+    /// Write a pull request title and description for the current branch
+    Pr {
+        /// Branch the pull request targets [default: the remote's default branch, or main/master]
+        #[arg(short, long)]
+        base: Option<String>,
+
+        /// Open the pull request with `gh pr create` once confirmed
+        #[arg(short, long)]
+        create: bool,
+
+        /// With --create, skip the confirmation prompt
+        #[arg(short, long, requires = "create")]
+        yes: bool,
+
+        #[command(flatten)]
+        providers: ProviderArgs,
+    },
+    /// Write a Keep a Changelog section for the commits between two revisions
+    Changelog {
+        /// Start of the range, exclusive [default: the tag before --to]
+        #[arg(long)]
+        from: Option<String>,
+
+        /// End of the range, inclusive
+        #[arg(long, default_value = "HEAD")]
+        to: String,
+
+        /// List every commit instead of having the model summarise long runs
+        #[arg(long)]
+        no_summarize: bool,
+
+        /// Send the commit subjects to a cloud provider without asking first
+        #[arg(short, long)]
+        yes: bool,
+
+        #[command(flatten)]
+        providers: ProviderArgs,
+    },
+    /// Write user-facing release notes for the commits between two tags
+    ReleaseNotes {
+        /// Tags to cover, e.g. `v1.2.0..v1.3.0`; a single tag means from there to HEAD
+        range: String,
+
+        /// Send the commit messages to a cloud provider without asking first
+        #[arg(short, long)]
+        yes: bool,
+
+        #[command(flatten)]
+        providers: ProviderArgs,
+    },
+    /// Write an annotated tag message summarising the commits since the previous tag, and optionally tag
+    Tag {
+        /// Name of the new tag, e.g. `v1.4.0`
+        name: String,
+
+        /// Commit to tag
+        #[arg(value_name = "COMMIT", default_value = "HEAD")]
+        rev: String,
+
+        /// Tag or commit to summarise from [default: the previous tag, or the whole history without one]
+        #[arg(long)]
+        from: Option<String>,
+
+        /// Create the annotated tag with the message once confirmed
+        #[arg(short, long)]
+        create: bool,
+
+        /// With --create, make a signed tag (`git tag --sign`)
+        #[arg(long, requires = "create")]
+        sign: bool,
+
+        /// Don't ask before sending the commit messages to a cloud provider, or with --create, before
+        /// tagging
+        #[arg(short, long)]
+        yes: bool,
+
+        #[command(flatten)]
+        providers: ProviderArgs,
+    },
+    /// Manage the prepare-commit-msg hook that pre-fills commit messages
+    Hook {
+        #[command(subcommand)]
+        action: HookCommand,
+    },
+    /// Print a shell completion script for bash, zsh, fish, elvish or PowerShell
+    Completions {
+        #[arg(value_enum)]
+        shell: completions::Shell,
+    },
+    /// Write roff man pages for commitgen and each of its subcommands, for packagers
+    #[command(hide = true)]
+    Man {
+        /// Write every page into DIR (as commitgen.1, commitgen-hook-install.1, ...) instead of
+        /// printing commitgen.1
+        #[arg(long, value_name = "DIR")]
+        out_dir: Option<std::path::PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+enum ConfigCommand {
+    /// Print the config file's path
+    Path {
+        /// The repository's `.commitgen.toml` instead, whose settings win over yours
+        #[arg(long)]
+        repo: bool,
+    },
+    /// Open the config file in your editor, creating it if need be; it's checked before saving
+    Edit {
+        /// The repository's `.commitgen.toml` instead, whose settings win over yours
+        #[arg(long)]
+        repo: bool,
+    },
+    /// Print the value in effect for KEY, e.g. `provider` or `models.gemini`
+    Get { key: String },
+    /// Set KEY to VALUE in the config file: `true`, numbers and `["a", "b"]` are TOML, anything
+    /// else a string
+    Set {
+        key: String,
+        value: String,
+
+        /// In the repository's `.commitgen.toml` instead
+        #[arg(long)]
+        repo: bool,
+    },
+    /// Remove KEY from the config file
+    Unset {
+        key: String,
+
+        /// From the repository's `.commitgen.toml` instead
+        #[arg(long)]
+        repo: bool,
+    },
+    /// List every setting in effect
+    List {
+        /// Also show which file or profile each one comes from
+        #[arg(long)]
+        origin: bool,
+    },
+    /// Move API keys from `.env` and the environment into the keyring, and models and server
+    /// addresses into the config file
+    ImportEnv {
+        /// The file to read [default: the `.env` in this directory or the nearest one above it]
+        #[arg(long)]
+        file: Option<std::path::PathBuf>,
+
+        /// Store the keys in the passphrase-protected file instead of the keyring
+        #[arg(long)]
+        encrypted: bool,
+
+        /// Import without asking first
+        #[arg(short, long)]
+        yes: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum HistoryCommand {
+    /// List past generations, newest first (the default)
+    List {
+        /// How many to list
+        #[arg(short = 'n', long, default_value_t = 20)]
+        limit: usize,
+
+        /// Only those generated in this repository
+        #[arg(long)]
+        here: bool,
+    },
+    /// Show a generation in full: its message, the candidates offered, and the provider and tokens
+    Show {
+        /// The generation's id, or the start of it
+        id: String,
+
+        /// Print the prompt that was sent too
+        #[arg(long)]
+        prompt: bool,
+    },
+    /// Print a past message again, or commit the staged changes with it
+    Use {
+        /// The generation's id, or the start of it
+        id: String,
+
+        /// Commit with the message once confirmed
+        #[arg(short, long)]
+        commit: bool,
+
+        /// With --commit, skip the confirmation prompt
+        #[arg(short, long, requires = "commit")]
+        yes: bool,
+
+        /// Also put the message on the clipboard
+        #[arg(long)]
+        copy: bool,
+    },
+    /// Send a past prompt again, to the same provider or another, and print the model's reply
+    Rerun {
+        /// The generation's id, or the start of it
+        id: String,
+
+        /// Send the prompt to a cloud provider without asking first
+        #[arg(short, long)]
+        yes: bool,
+
+        #[command(flatten)]
+        providers: ProviderArgs,
+    },
+    /// Delete the history
+    Clear,
+}
+
+#[derive(Subcommand)]
+enum HookCommand {
+    /// Install the hook into this repository's hooks directory
+    Install {
+        /// Replace an existing prepare-commit-msg hook
+        #[arg(long)]
+        force: bool,
+    },
+    /// Remove the hook, if commitgen installed it
+    Uninstall,
+    /// Show whether the hook is installed
+    Status,
+    /// Called by the hook itself with git's prepare-commit-msg arguments
+    #[command(hide = true)]
+    Run {
+        message_file: std::path::PathBuf,
+        source: Option<String>,
+        commit: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum AuthCommand {
+    /// Store a provider's API key, read from a hidden prompt or stdin
+    Set {
+        #[arg(value_parser = parse_provider)]
+        provider: String,
+        /// Store it in a passphrase-protected file instead of the keyring, for machines without one
+        #[arg(long)]
+        encrypted: bool,
+    },
+    /// Remove a provider's stored API key
+    Delete {
+        #[arg(value_parser = parse_provider)]
+        provider: String,
+        /// Remove it from the passphrase-protected file instead of the keyring
+        #[arg(long)]
+        encrypted: bool,
+    },
+}
+
+/// Set by `--quiet`, which leaves nothing on the terminal but the message.
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+fn spinner(message: impl Into<std::borrow::Cow<'static, str>>) -> anyhow::Result<ProgressBar> {
+    // Log lines would be drawn over by the spinner, and where there's no
+    // terminal to draw it on, its frames end up in logs.
+    if QUIET.load(Ordering::Relaxed) || tracing::enabled!(tracing::Level::INFO) || !terminal::animates() {
+        return Ok(ProgressBar::hidden());
+    }
+    let spinner = ProgressBar::new_spinner();
+    spinner.set_style(
+        ProgressStyle::default_spinner()
+            .tick_strings(&["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"])
+            .template("{spinner:.green} {msg}")?,
+    );
+    spinner.set_message(message);
+    spinner.enable_steady_tick(Duration::from_millis(80));
+    Ok(spinner)
+}
+
+async fn run_compare(
+    clients: Vec<(String, ClientResult, GenerationOptions)>,
+    prompt: &str,
+) -> anyhow::Result<()> {
+    let spinner = spinner("Generating commit messages...")?;
+    let candidates = compare::generate_all(clients, prompt).await;
+    spinner.finish_and_clear();
+
+    let columns: Vec<(String, String)> = candidates
+        .iter()
+        .enumerate()
+        .map(|(i, c)| {
+            let header = format!("[{}] {}", i + 1, c.provider).bold().to_string();
+            let body = match &c.result {
+                Ok(message) => message.cyan().to_string(),
+                Err(e) => format!("error: {}", e).red().to_string(),
+            };
+            (header, body)
+        })
+        .collect();
+    let width = console::Term::stdout().size().1 as usize;
+    println!();
+    print!("{}", compare::render(&columns, width));
+    println!();
+
+    let succeeded: Vec<&compare::Candidate> = candidates.iter().filter(|c| c.result.is_ok()).collect();
+    if succeeded.is_empty() {
+        eprintln!("{} every provider failed", "Error:".red().bold());
+        std::process::exit(1);
+    }
+    if !console::user_attended() {
+        return Ok(());
+    }
+
+    let labels: Vec<&str> = succeeded.iter().map(|c| c.provider.as_str()).collect();
+    let choice = dialoguer::Select::new()
+        .with_prompt("Pick a message")
+        .items(&labels)
+        .default(0)
+        .interact_opt()?;
+    if let Some(choice) = choice
+        && let Ok(message) = &succeeded[choice].result
+    {
+        println!();
+        println!("{}", message.cyan());
+        println!();
+    }
+
+    Ok(())
+}
+
+/// `1048576` -> `1M`, `131072` -> `128k`; odd sizes are printed as-is.
+fn format_tokens(tokens: u64) -> String {
+    match tokens {
+        0 => "0".to_string(),
+        n if n % 1_000_000 == 0 => format!("{}M", n / 1_000_000),
+        n if n % 1000 == 0 => format!("{}k", n / 1000),
+        n if n % (1 << 20) == 0 => format!("{}M", n >> 20),
+        n if n % 1024 == 0 => format!("{}k", n >> 10),
+        n => n.to_string(),
+    }
+}
+
+async fn run_models(provider: Option<String>, config: &Config) -> anyhow::Result<()> {
+    let provider = provider
+        .or_else(|| config.provider.clone())
+        .unwrap_or_else(|| registry::DEFAULT_PROVIDER.to_string());
+    let endpoint = config.endpoints.get(registry::lookup(&provider)?.name).map(String::as_str);
+    let client = registry::build(&provider, &ProviderOptions { model: None, allow_unknown_model: true, endpoint })?;
+
+    let spinner = spinner("Fetching models...")?;
+    let result = client.list_models().await;
+    spinner.finish_and_clear();
+
+    let models = result.map_err(|e| anyhow::anyhow!("Could not list {} models: {}", provider, e))?;
+    if models.is_empty() {
+        println!("{} reports no available models", provider);
+        return Ok(());
+    }
+
+    let width = models.iter().map(|m| m.id.len()).max().unwrap_or(0);
+    for model in &models {
+        match model.context_window {
+            Some(tokens) => println!("{:<width$}  {}", model.id, format_tokens(tokens).dimmed(), width = width),
+            None => println!("{}", model.id),
+        }
+    }
+    Ok(())
+}
+
+fn run_auth(action: AuthCommand) -> anyhow::Result<()> {
+    let (AuthCommand::Set { provider, encrypted } | AuthCommand::Delete { provider, encrypted }) = &action;
+    let encrypted = *encrypted;
+    let place = match auth::vault::path() {
+        Some(path) if encrypted => path.display().to_string(),
+        None if encrypted => anyhow::bail!("Cannot find a config directory"),
+        _ => "the keyring".to_string(),
+    };
+    let provider = registry::lookup(provider)?;
+    let var = provider.key_var.ok_or_else(|| {
+        anyhow::anyhow!("{} doesn't use an API key; see the README for its setup", provider.name)
+    })?;
+
+    match action {
+        AuthCommand::Set { .. } => {
+            let secret = if console::user_attended_stderr() {
+                dialoguer::Password::new()
+                    .with_prompt(format!("{} ({})", provider.name, var))
+                    .interact()?
+            } else {
+                let mut line = String::new();
+                std::io::stdin().read_line(&mut line)?;
+                line
+            };
+            let secret = secret.trim();
+            if secret.is_empty() {
+                anyhow::bail!("No key given; nothing was stored");
+            }
+            if encrypted {
+                auth::vault::set(var, secret)?;
+            } else {
+                auth::keyring::set(var, secret)?;
+            }
+            println!("Stored {} in {}", var, place);
+        }
+        AuthCommand::Delete { .. } => {
+            let deleted = if encrypted { auth::vault::delete(var)? } else { auth::keyring::delete(var)? };
+            if deleted {
+                println!("Removed {} from {}", var, place);
+            } else {
+                println!("No {} was stored in {}", var, place);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Walks through choosing a provider, storing its key in the keyring and
+/// picking a style, then writes both to the config file. The key is only
+/// kept once a test request with it succeeds.
+async fn run_init(config: &Config) -> anyhow::Result<()> {
+    if !console::user_attended_stderr() {
+        anyhow::bail!("`init` asks questions, so it needs a terminal");
+    }
+    let path = Config::default_path().ok_or_else(|| anyhow::anyhow!("Cannot find a config directory"))?;
+
+    let current = config.provider.as_deref().and_then(|name| registry::lookup(name).ok()).map_or(registry::DEFAULT_PROVIDER, |p| p.name);
+    let items: Vec<String> = registry::PROVIDERS.iter().map(|p| format!("{:<18} {}", p.name, p.description)).collect();
+    let Some(choice) = dialoguer::Select::new()
+        .with_prompt("Which provider should write your messages?")
+        .items(&items)
+        .default(registry::PROVIDERS.iter().position(|p| p.name == current).unwrap_or(0))
+        .interact_opt()?
+    else {
+        return Ok(());
+    };
+    let provider = &registry::PROVIDERS[choice];
+
+    // The key goes into the keyring before the test, since that's where the
+    // provider reads it from; a key that doesn't work is taken out again.
+    let previous = provider.key_var.and_then(auth::keyring::get);
+    loop {
+        if let Some(var) = provider.key_var {
+            let existing = auth::keyring::secret(var).is_some();
+            let prompt = if existing { format!("{} (leave empty to keep the current one)", var) } else { var.to_string() };
+            let key = dialoguer::Password::new().with_prompt(prompt).allow_empty_password(existing).interact()?;
+            if !key.trim().is_empty() {
+                auth::keyring::set(var, key.trim())?;
+            }
+        }
+
+        let spinner = spinner(format!("Testing {}...", provider.name))?;
+        let options = ProviderOptions {
+            model: config.models.get(provider.name).map(String::as_str),
+            allow_unknown_model: true,
+            endpoint: config.endpoints.get(provider.name).map(String::as_str),
+        };
+        let result = match provider.build(&options) {
+            Ok(client) => client.generate(doctor::TEST_PROMPT, &config.generation_options(provider.name)).await,
+            Err(e) => Err(e),
+        };
+        spinner.finish_and_clear();
+        match result {
+            Ok(_) => {
+                println!("{} {} works", "✓".green().bold(), provider.name);
+                break;
+            }
+            Err(e) => {
+                eprintln!("{} {}", "Error:".red().bold(), e);
+                const ACTIONS: &[&str] = &["Try again", "Save the settings anyway", "Give up"];
+                match dialoguer::Select::new().with_prompt("What now?").items(ACTIONS).default(0).interact_opt()? {
+                    Some(0) => continue,
+                    Some(1) => break,
+                    _ => {
+                        if let Some(var) = provider.key_var {
+                            match &previous {
+                                Some(key) => auth::keyring::set(var, key)?,
+                                None => {
+                                    auth::keyring::delete(var)?;
+                                }
+                            }
+                        }
+                        println!("Nothing was changed");
+                        return Ok(());
+                    }
+                }
+            }
+        }
+    }
+
+    let mut styles: Vec<String> = style::PRESETS.iter().map(|p| format!("{:<18} {}", p.name, p.description)).collect();
+    styles.push("something else...".to_string());
+    let current = style::lookup(&config.style(None)).map_or("", |preset| preset.name);
+    let Some(choice) = dialoguer::Select::new()
+        .with_prompt("Which message style?")
+        .items(&styles)
+        .default(style::PRESETS.iter().position(|p| p.name == current).unwrap_or(0))
+        .interact_opt()?
+    else {
+        return Ok(());
+    };
+    let style = if let Some(preset) = style::PRESETS.get(choice) {
+        preset.name.to_string()
+    } else {
+        dialoguer::Input::<String>::new().with_prompt("Describe the style").interact_text()?
+    };
+
+    let contents = match std::fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => anyhow::bail!("Failed to read {}: {}", path.display(), e),
+    };
+    let contents = config::set_key(&contents, "provider", &toml::Value::String(provider.name.to_string()).to_string());
+    let contents = config::set_key(&contents, "style", &toml::Value::String(style).to_string());
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(&path, contents)?;
+    println!("Saved {}", path.display());
+    println!("Stage some changes and run `commitgen` to write your first message.");
+    Ok(())
+}
+
+async fn run_doctor(profile: Option<&str>, offline: bool) -> anyhow::Result<()> {
+    let mut checks = doctor::git();
+    let (config_checks, config) = doctor::config(profile);
+    checks.extend(config_checks);
+    if let Some(config) = &config {
+        auth::vault::init(config);
+        checks.extend(doctor::network(config));
+    }
+    checks.extend(doctor::hook());
+    print_checks(&checks);
+    if let Some(config) = &config {
+        let spinner = spinner(if offline { "Checking providers..." } else { "Calling providers..." })?;
+        let provider_checks = doctor::providers(config, !offline).await;
+        spinner.finish_and_clear();
+        print_checks(&provider_checks);
+        checks.extend(provider_checks);
+    }
+
+    let failed = checks.iter().filter(|check| check.outcome == doctor::Outcome::Fail).count();
+    let warned = checks.iter().filter(|check| check.outcome == doctor::Outcome::Warn).count();
+    match (failed, warned) {
+        (0, 0) => println!("No problems found"),
+        (0, _) => println!("{} warning(s), no problems", warned),
+        _ => anyhow::bail!("{} problem(s) found", failed),
+    }
+    Ok(())
+}
+
+fn print_checks(checks: &[doctor::Check]) {
+    for check in checks {
+        let mark = match check.outcome {
+            doctor::Outcome::Ok => "✓".green().bold(),
+            doctor::Outcome::Warn => "!".yellow().bold(),
+            doctor::Outcome::Fail => "✗".red().bold(),
+        };
+        println!("{} {:<12} {}", mark, check.name, check.detail);
+        if let Some(fix) = &check.fix {
+            println!("  {} {}", "→".dimmed(), fix);
+        }
+    }
+}
+
+fn run_config(action: ConfigCommand, profile: Option<&str>) -> anyhow::Result<()> {
+    let repo = match action {
+        ConfigCommand::Path { repo } | ConfigCommand::Edit { repo } => repo,
+        ConfigCommand::Set { repo, .. } | ConfigCommand::Unset { repo, .. } => repo,
+        ConfigCommand::Get { .. } | ConfigCommand::List { .. } | ConfigCommand::ImportEnv { .. } => false,
+    };
+    let path = if repo {
+        Config::repo_path().ok_or_else(|| anyhow::anyhow!("--repo needs a git repository"))?
+    } else {
+        Config::default_path().ok_or_else(|| anyhow::anyhow!("Cannot find a config directory"))?
+    };
+    let read = || match std::fs::read_to_string(&path) {
+        Ok(contents) => Ok(contents),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(String::new()),
+        Err(e) => Err(anyhow::anyhow!("Failed to read {}: {}", path.display(), e)),
+    };
+    let save = |contents: &str| -> anyhow::Result<()> {
+        if let Err(e) = Config::from_toml(contents) {
+            anyhow::bail!("That would make {} invalid: {}", path.display(), e);
+        }
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        Ok(std::fs::write(&path, contents)?)
+    };
+    match action {
+        ConfigCommand::Path { .. } => println!("{}", path.display()),
+        ConfigCommand::Get { key } => {
+            let mut merged = toml::Table::new();
+            for layer in config::layers(profile)? {
+                config::merge(&mut merged, layer.table);
+            }
+            match config::lookup(&merged, &key) {
+                Some(toml::Value::String(value)) => println!("{}", value),
+                Some(value) => println!("{}", value),
+                None => {
+                    config::check_key(&key)?;
+                    anyhow::bail!("{} isn't set", key);
+                }
+            }
+        }
+        ConfigCommand::Set { key, value, .. } => {
+            config::check_key(&key)?;
+            save(&config::set_key(&read()?, &key, &config::parse_value(&value).to_string()))?;
+        }
+        ConfigCommand::Unset { key, .. } => match config::unset_key(&read()?, &key) {
+            Some(contents) => save(&contents)?,
+            None => eprintln!("{} isn't set in {}", key, path.display()),
+        },
+        ConfigCommand::List { origin } => {
+            let mut values: Vec<(String, toml::Value, config::Origin)> = Vec::new();
+            for layer in config::layers(profile)? {
+                for (key, value) in config::flatten(&layer.table) {
+                    values.retain(|(existing, _, _)| *existing != key);
+                    values.push((key, value, layer.origin.clone()));
+                }
+            }
+            values.sort_by(|a, b| a.0.cmp(&b.0));
+            for (key, value, from) in values {
+                if origin {
+                    println!("{} = {}  {}", key, value, format!("# {}", from).dimmed());
+                } else {
+                    println!("{} = {}", key, value);
+                }
+            }
+        }
+        ConfigCommand::Edit { .. } => {
+            let mut contents = match std::fs::read_to_string(&path) {
+                Ok(contents) => contents,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+                Err(e) => anyhow::bail!("Failed to read {}: {}", path.display(), e),
+            };
+            loop {
+                let Some(edited) = dialoguer::Editor::new().extension(".toml").edit(&contents)? else {
+                    println!("Config left unchanged");
+                    return Ok(());
+                };
+                match Config::from_toml(&edited) {
+                    Ok(_) => {
+                        contents = edited;
+                        break;
+                    }
+                    Err(e) => {
+                        eprintln!("{} {}", "Invalid config:".red().bold(), e);
+                        if !confirm("Edit it again?")? {
+                            println!("Config left unchanged");
+                            return Ok(());
+                        }
+                        contents = edited;
+                    }
+                }
+            }
+            if let Some(dir) = path.parent() {
+                std::fs::create_dir_all(dir)?;
+            }
+            std::fs::write(&path, contents)?;
+            println!("Saved {}", path.display());
+        }
+        ConfigCommand::ImportEnv { file, encrypted, yes } => run_import_env(file, encrypted, yes, &path)?,
+    }
+    Ok(())
+}
+
+/// Moves what [`import::plan`] finds into the keyring (or the encrypted
+/// file) and the config file at `path`, leaving alone anything already
+/// stored there, then warns about the plaintext copies left behind.
+fn run_import_env(file: Option<std::path::PathBuf>, encrypted: bool, yes: bool, path: &std::path::Path) -> anyhow::Result<()> {
+    let file = file.or_else(import::find_dotenv);
+    let pairs = match &file {
+        Some(file) => import::read(file)?,
+        None => Vec::new(),
+    };
+    let source = file.as_ref().map_or(".env".to_string(), |file| file.display().to_string());
+    let imports = import::plan(&pairs, |var| std::env::var(var).ok());
+    if imports.is_empty() {
+        println!("Nothing to import: no provider keys, models or servers in {} or the environment", source);
+        return Ok(());
+    }
+
+    let place = match auth::vault::path() {
+        Some(vault) if encrypted => vault.display().to_string(),
+        None if encrypted => anyhow::bail!("Cannot find a config directory"),
+        _ => "the keyring".to_string(),
+    };
+    let mut contents = match std::fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => anyhow::bail!("Failed to read {}: {}", path.display(), e),
+    };
+    let table: toml::Table = toml::from_str(&contents).map_err(|e| anyhow::anyhow!("Invalid {}: {}", path.display(), e))?;
+    let mut pending = Vec::new();
+    for import in &imports {
+        let (stored, to) = match &import.target {
+            import::Target::Secret => {
+                let stored = if encrypted { auth::vault::has(&import.var) } else { auth::keyring::get(&import.var).is_some() };
+                (stored, place.clone())
+            }
+            import::Target::Config(key) => (config::lookup(&table, key).is_some(), format!("{} in {}", key, path.display())),
+        };
+        let from = if import.from_file { source.as_str() } else { "the environment" };
+        if stored {
+            println!("  {} {}", import.var, format!("(from {}) is already set in {}; left as is", from, to).dimmed());
+        } else {
+            println!("  {} {} {} {}", import.var, format!("(from {})", from).dimmed(), "→".dimmed(), to);
+            pending.push(import);
+        }
+    }
+
+    if !pending.is_empty() {
+        if !yes && !confirm("Import them?")? {
+            println!("Nothing was imported");
+            return Ok(());
+        }
+        let mut config_changed = false;
+        for import in &pending {
+            match &import.target {
+                import::Target::Secret if encrypted => auth::vault::set(&import.var, &import.value)?,
+                import::Target::Secret => auth::keyring::set(&import.var, &import.value)?,
+                import::Target::Config(key) => {
+                    contents = config::set_key(&contents, key, &toml::Value::String(import.value.clone()).to_string());
+                    config_changed = true;
+                }
+            }
+        }
+        if config_changed {
+            Config::from_toml(&contents).map_err(|e| anyhow::anyhow!("That would make {} invalid: {}", path.display(), e))?;
+            if let Some(dir) = path.parent() {
+                std::fs::create_dir_all(dir)?;
+            }
+            std::fs::write(path, &contents)?;
+        }
+        println!("Imported {} variable(s)", pending.len());
+    }
+
+    // Keys stored now win over these, but they're still readable by
+    // anything that can read the file or the process's environment.
+    let secrets = |from_file: bool| -> Vec<&str> {
+        imports
+            .iter()
+            .filter(|import| import.target == import::Target::Secret && import.from_file == from_file)
+            .map(|import| import.var.as_str())
+            .collect()
+    };
+    let (in_file, in_env) = (secrets(true), secrets(false));
+    if !in_file.is_empty() {
+        eprintln!(
+            "{} {} still holds {} in plaintext; delete those lines now that the keys are in {}",
+            "Warning:".yellow().bold(),
+            source,
+            in_file.join(", "),
+            place
+        );
+    }
+    if !in_env.is_empty() {
+        eprintln!(
+            "{} {} {} still set in the environment; remove them from wherever they're exported",
+            "Warning:".yellow().bold(),
+            in_env.join(", "),
+            if in_env.len() == 1 { "is" } else { "are" }
+        );
+    }
+    Ok(())
+}
+
+fn run_check(message_file: &std::path::Path, style: &str, max_subject_length: usize) -> anyhow::Result<()> {
+    let buffer = std::fs::read_to_string(message_file)
+        .map_err(|e| anyhow::anyhow!("Failed to read {}: {}", message_file.display(), e))?;
+    let mut problems = check::check(&buffer, check::Style::from_name(style), max_subject_length);
+    problems.extend(style::missing_footers(&check::clean(&buffer), style));
+    if problems.is_empty() {
+        return Ok(());
+    }
+
+    eprintln!("{} commit message doesn't follow the '{}' style:", "Error:".red().bold(), style);
+    for problem in &problems {
+        eprintln!("  - {}", problem.message);
+        eprintln!("    {}", problem.hint.dimmed());
+    }
+    std::process::exit(1);
+}
+
+fn subject(message: &str) -> &str {
+    message.lines().next().unwrap_or("")
+}
+
+async fn run_reword(
+    range: &str,
+    style: &str,
+    yes: bool,
+    providers: &ProviderArgs,
+    config: &Config,
+) -> anyhow::Result<()> {
+    let range = reword::normalize_range(range);
+    let commits = reword::commits(&range)?;
+    let selection = providers.selection(config);
+    let (first, last) = (&commits[0].sha, &commits[commits.len() - 1].sha);
+    confirm_send(&diff::for_prompt(&git::range_diff(first, last)?, config)?, &selection.names(), yes, config)?;
+    let chain = selection.chain()?;
+
+    let mut rewrites = Vec::new();
+    for (i, commit) in commits.iter().enumerate() {
+        let spinner = spinner(format!("Rewording commit {}/{}...", i + 1, commits.len()))?;
+        let result = async {
+            let diff = diff::for_prompt(&git::commit_diff(&commit.sha)?, config)?;
+            let prompt = prompt::for_amend(style, &diff, &commit.message);
+            chain.generate(&prompt).await
+        }
+        .await;
+        spinner.finish_and_clear();
+        let generation = result.map_err(|e| anyhow::anyhow!("Failed to reword {}: {}", &commit.sha[..7], e))?;
+        rewrites.push(generation.message);
+    }
+
+    let changed: Vec<usize> = (0..commits.len()).filter(|&i| rewrites[i] != commits[i].message).collect();
+    if changed.is_empty() {
+        println!("The generated messages match the existing ones; nothing to reword");
+        return Ok(());
+    }
+    for &i in &changed {
+        println!("{} {}", commits[i].sha[..7].yellow(), subject(&commits[i].message).dimmed());
+        println!("        {}", subject(&rewrites[i]).cyan());
+    }
+    println!();
+
+    let chosen: Vec<usize> = if yes {
+        changed
+    } else {
+        if !console::user_attended_stderr() {
+            anyhow::bail!("Not asking which commits to reword without a terminal; pass --yes to rewrite them all");
+        }
+        let labels: Vec<String> = changed
+            .iter()
+            .map(|&i| format!("{} {}", &commits[i].sha[..7], subject(&rewrites[i])))
+            .collect();
+        dialoguer::MultiSelect::new()
+            .with_prompt("Commits to reword (space toggles, enter confirms)")
+            .items(&labels)
+            .defaults(&vec![true; labels.len()])
+            .interact()?
+            .into_iter()
+            .map(|choice| changed[choice])
+            .collect()
+    };
+    if chosen.is_empty() {
+        println!("Nothing reworded");
+        return Ok(());
+    }
+
+    let selected: Vec<(String, String)> = chosen
+        .iter()
+        .map(|&i| (commits[i].sha.clone(), rewrites[i].clone()))
+        .collect();
+    reword::apply(&commits[0].sha, &selected)?;
+    println!("Reworded {} commit{}", selected.len(), if selected.len() == 1 { "" } else { "s" });
+    Ok(())
+}
+
+async fn run_squash(
+    range: &str,
+    style: &str,
+    commit: bool,
+    yes: bool,
+    providers: &ProviderArgs,
+    config: &Config,
+) -> anyhow::Result<()> {
+    let range = reword::normalize_range(range);
+    let commits = reword::commits(&range)?;
+    let (first, last) = (&commits[0].sha, &commits[commits.len() - 1].sha);
+    if commit {
+        if git::run(&["rev-parse", "HEAD"])?.trim() != last {
+            anyhow::bail!("--commit can only squash a range that ends at HEAD");
+        }
+        if !git::rev_exists(&format!("{}~1", first))? {
+            anyhow::bail!("--commit can't squash the root commit");
+        }
+        if git::has_staged_changes()? {
+            anyhow::bail!("There are staged changes; commit or stash them before squashing");
+        }
+    }
+
+    let diff = diff::for_prompt(&git::range_diff(first, last)?, config)?;
+    let selection = providers.selection(config);
+    confirm_send(&diff, &selection.names(), yes, config)?;
+    let chain = selection.chain()?;
+    let messages: Vec<String> = commits.iter().map(|c| c.message.clone()).collect();
+    let spinner = spinner(format!("Summarising {} commits...", commits.len()))?;
+    let result = chain.generate(&prompt::for_squash(style, &diff, &messages)).await;
+    spinner.finish_and_clear();
+    let generation = result?;
+
+    println!();
+    println!("{}", generation.message.cyan());
+    println!();
+
+    if commit {
+        if !yes && !confirm(&format!("Squash {} commits with this message?", commits.len()))? {
+            eprintln!("Not squashed");
+            return Ok(());
+        }
+        git::run(&["reset", "--soft", &format!("{}~1", first)])?;
+        git::commit(
+            &generation.message,
+            &git::CommitOptions { all: false, amend: false, no_verify: false, sign: None, edit: false },
+        )?;
+    }
+    Ok(())
+}
+
+async fn run_branch(
+    description: Option<&str>,
+    pattern: Option<&str>,
+    create: bool,
+    yes: bool,
+    providers: &ProviderArgs,
+    config: &Config,
+) -> anyhow::Result<()> {
+    let selection = providers.selection(config);
+    let prompt = match description {
+        Some(description) => prompt::branch_from_description(description),
+        None => {
+            let diff = diff::for_prompt(&git::staged_diff(&git::Context::default())?, config)?;
+            confirm_send(&diff, &selection.names(), yes, config)?;
+            prompt::branch_from_diff(&diff)
+        }
+    };
+    let pattern = pattern
+        .or(config.branch_pattern.as_deref())
+        .unwrap_or(branch::DEFAULT_PATTERN);
+
+    let chain = selection.chain()?;
+    let spinner = spinner("Naming branch...")?;
+    let result = chain.generate(&prompt).await;
+    spinner.finish_and_clear();
+    let name = branch::render(pattern, &branch::parse(&result?.message));
+    if name.is_empty() || !git::is_valid_branch_name(&name)? {
+        anyhow::bail!("Couldn't make a valid branch name from the reply (got `{}`)", name);
+    }
+
+    println!("{}", name.cyan());
+    if create {
+        if !yes && !confirm(&format!("Create and switch to {}?", name))? {
+            eprintln!("Not created");
+            return Ok(());
+        }
+        git::run(&["switch", "--create", &name])?;
+        eprintln!("Switched to a new branch '{}'", name);
+    }
+    Ok(())
+}
+
+async fn run_split(
+    style: &str,
+    apply: bool,
+    yes: bool,
+    providers: &ProviderArgs,
+    config: &Config,
+) -> anyhow::Result<()> {
+    let staged = split::staged()?;
+    let hunks = split::hunks(&staged);
+    let selection = providers.selection(config);
+    confirm_send(&diff::for_prompt(&staged, config)?, &selection.names(), yes, config)?;
+    let chain = selection.chain()?;
+    let spinner = spinner(format!("Planning commits for {} hunks...", hunks.len()))?;
+    let listing = split::listing(&hunks, &ignore::Ignore::load()?, &redact::Redactor::from_config(config)?)?;
+    let result = chain.generate(&prompt::for_split(style, &listing)).await;
+    spinner.finish_and_clear();
+    let groups = split::parse_plan(&result?.message, &hunks)?;
+
+    for (i, group) in groups.iter().enumerate() {
+        println!();
+        println!("{}", format!("Commit {} of {}", i + 1, groups.len()).bold());
+        println!("{}", group.message.cyan());
+        for &index in &group.hunks {
+            println!("  {}", format!("{} {}", hunks[index].path, hunks[index].range()).dimmed());
+        }
+    }
+    println!();
+
+    if !apply {
+        return Ok(());
+    }
+    if !git::rev_exists("HEAD")? {
+        anyhow::bail!("--apply needs an existing commit to split on top of");
+    }
+    if !yes && !confirm(&format!("Create these {} commits?", groups.len()))? {
+        eprintln!("Nothing committed");
+        return Ok(());
+    }
+
+    let dir = std::env::temp_dir().join(format!("commitgen-split-{}", std::process::id()));
+    std::fs::create_dir_all(&dir)?;
+    let result = apply_split(&dir, &staged, &hunks, &groups);
+    let _ = std::fs::remove_dir_all(&dir);
+    result
+}
+
+/// Unstages everything, then stages and commits each group in turn. If a
+/// group can't be applied or committed, the hunks not yet committed are
+/// staged again.
+fn apply_split(
+    dir: &std::path::Path,
+    staged: &str,
+    hunks: &[split::Hunk],
+    groups: &[split::Group],
+) -> anyhow::Result<()> {
+    git::run(&["reset", "--quiet"])?;
+
+    for (i, group) in groups.iter().enumerate() {
+        let patch = dir.join(format!("{}.patch", i));
+        std::fs::write(&patch, split::patch(hunks, &group.hunks))?;
+        let result = git::apply_cached(&patch).and_then(|()| {
+            git::commit(&group.message, &git::CommitOptions { all: false, amend: false, no_verify: false, sign: None, edit: false })
+        });
+        let Err(e) = result else { continue };
+
+        let mut rest: Vec<usize> = groups[i..].iter().flat_map(|g| g.hunks.iter().copied()).collect();
+        rest.sort_unstable();
+        let remaining = if i == 0 { staged.to_string() } else { split::patch(hunks, &rest) };
+        let remaining_file = dir.join("remaining.patch");
+        std::fs::write(&remaining_file, remaining)?;
+        git::run(&["reset", "--quiet"])?;
+        if let Err(restore) = git::apply_cached(&remaining_file) {
+            anyhow::bail!(
+                "Commit {} of {} failed ({}), and the uncommitted changes couldn't be staged again ({}); \
+                they are still in the working tree",
+                i + 1, groups.len(), e, restore
+            );
+        }
+        anyhow::bail!("Commit {} of {} failed, and its changes are staged again: {}", i + 1, groups.len(), e);
+    }
+    Ok(())
+}
+
+async fn run_merge(
+    branch: &str,
+    style: &str,
+    commit: bool,
+    yes: bool,
+    providers: &ProviderArgs,
+    config: &Config,
+) -> anyhow::Result<()> {
+    let messages = git::messages(&format!("HEAD..{}", branch))?;
+    if messages.is_empty() {
+        anyhow::bail!("{} has nothing that isn't already merged", branch);
+    }
+    let target = git::current_branch()?.unwrap_or_else(|| "HEAD".to_string());
+    let conflicts = git::merge_conflicts("HEAD", branch)?;
+    let diff = diff::for_prompt(&git::merge_base_diff("HEAD", branch)?, config)?;
+
+    let selection = providers.selection(config);
+    confirm_send(&diff, &selection.names(), yes, config)?;
+    let chain = selection.chain()?;
+    let spinner = spinner(format!("Describing {} commits from {}...", messages.len(), branch))?;
+    let result = chain.generate(&prompt::for_merge(style, branch, &target, &diff, &messages, &conflicts)).await;
+    spinner.finish_and_clear();
+    let generation = result?;
+
+    println!();
+    println!("{}", generation.message.cyan());
+    println!();
+    for path in &conflicts {
+        eprintln!("{} {} will conflict", "Warning:".yellow().bold(), path);
+    }
+
+    if commit {
+        if !yes && !confirm(&format!("Merge {} with this message?", branch))? {
+            eprintln!("Not merged");
+            return Ok(());
+        }
+        if !git::merge(branch, &generation.message)? {
+            anyhow::bail!("The merge stopped; resolve the conflicts and run `git commit`, which will use this message");
+        }
+    }
+    Ok(())
+}
+
+/// Soft-resets HEAD when commitgen made it: it has commitgen's git note,
+/// or its message is one commitgen generated (from the history). Merges,
+/// root commits and pushed commits are refused; `force` allows the last
+/// two checks to be skipped.
+fn run_undo(yes: bool, force: bool) -> anyhow::Result<()> {
+    if Vcs::detect()? != Vcs::Git {
+        anyhow::bail!("undo works in git repositories");
+    }
+    let sha = git::run(&["rev-parse", "--verify", "HEAD"])?.trim().to_string();
+    let parents = git::run(&["rev-list", "--parents", "-n", "1", "HEAD"])?.split_whitespace().count() - 1;
+    match parents {
+        0 => anyhow::bail!("{} is the first commit; there is nothing to reset to", &sha[..7]),
+        1 => {}
+        _ => anyhow::bail!("{} is a merge; undo it with `git reset` yourself", &sha[..7]),
+    }
+    let message = git::head_message()?;
+    let noted = notes::read("HEAD")?.is_some_and(|note| notes::is_generated(&note));
+    let remembered = history::load()?.iter().any(|entry| entry.message.trim() == message.trim());
+    if !noted && !remembered && !force {
+        anyhow::bail!(
+            "{} doesn't look like a commit commitgen made (no commitgen note, and its message isn't in the history); \
+            pass --force to undo it anyway",
+            &sha[..7]
+        );
+    }
+    let pushed = git::run(&["rev-parse", "--abbrev-ref", "--symbolic-full-name", "@{upstream}"])
+        .is_ok_and(|upstream| git::is_ancestor("HEAD", upstream.trim()).unwrap_or(false));
+    if pushed && !force {
+        anyhow::bail!("{} has already been pushed; undoing it would rewrite published history (--force to do it anyway)", &sha[..7]);
+    }
+
+    println!("{} {}", sha[..7].yellow(), subject(&message));
+    if !yes && !confirm("Undo this commit, keeping its changes staged?")? {
+        eprintln!("Nothing was undone");
+        return Ok(());
+    }
+    git::run(&["reset", "--soft", "HEAD~"])?;
+    eprintln!(
+        "Undid {}; its changes are staged again. `git reset --soft {}` puts it back.",
+        &sha[..7],
+        &sha[..7]
+    );
+    Ok(())
+}
+
+async fn run_revert(
+    rev: &str,
+    reason: Option<&str>,
+    style: &str,
+    commit: bool,
+    yes: bool,
+    providers: &ProviderArgs,
+    config: &Config,
+) -> anyhow::Result<()> {
+    let sha = git::run(&["rev-parse", "--verify", &format!("{}^{{commit}}", rev)])?.trim().to_string();
+    let original = git::commit_message(&sha)?;
+    let diff = diff::for_prompt(&git::commit_diff(&sha)?, config)?;
+
+    let selection = providers.selection(config);
+    confirm_send(&diff, &selection.names(), yes, config)?;
+    let chain = selection.chain()?;
+    let spinner = spinner(format!("Explaining the revert of {}...", &sha[..7]))?;
+    let result = chain.generate(&prompt::for_revert(&original, &diff, reason)).await;
+    spinner.finish_and_clear();
+    let revert_subject = revert::subject(subject(&original), check::Style::from_name(style));
+    let message = revert::message(&revert_subject, &result?.message, &sha);
+
+    println!();
+    println!("{}", message.cyan());
+    println!();
+
+    if commit {
+        if !yes && !confirm(&format!("Revert {} with this message?", &sha[..7]))? {
+            eprintln!("Not reverted");
+            return Ok(());
+        }
+        if git::run(&["revert", "--no-commit", &sha]).is_err() {
+            anyhow::bail!("The revert stopped on conflicts; resolve them, then commit with the message above");
+        }
+        git::commit(&message, &git::CommitOptions { all: false, amend: false, no_verify: false, sign: None, edit: false })?;
+    }
+    Ok(())
+}
+
+async fn run_history(action: HistoryCommand, config: &Config) -> anyhow::Result<()> {
+    let entries = history::load()?;
+    match action {
+        HistoryCommand::List { limit, here } => {
+            let repo = if here { Some(git::run(&["rev-parse", "--show-toplevel"])?.trim().to_string()) } else { None };
+            let listed: Vec<&history::Entry> = entries
+                .iter()
+                .rev()
+                .filter(|entry| repo.is_none() || entry.repo == repo)
+                .take(limit)
+                .collect();
+            if listed.is_empty() {
+                eprintln!("No generations recorded yet");
+            }
+            for entry in listed {
+                println!(
+                    "{}  {}  {:<10}  {}",
+                    entry.id.yellow(),
+                    entry.date().dimmed(),
+                    entry.provider,
+                    subject(&entry.message)
+                );
+            }
+        }
+        HistoryCommand::Show { id, prompt } => {
+            let entry = history::find(&entries, &id)?;
+            println!("{} {}", "Generated:".bold(), entry.date());
+            if let Some(repo) = &entry.repo {
+                println!("{} {}", "Repository:".bold(), repo);
+            }
+            let model = entry.model.as_deref().map(|model| format!(" ({})", model)).unwrap_or_default();
+            println!("{} {}{}", "Provider:".bold(), entry.provider, model);
+            println!("{} ~{} in, ~{} out", "Tokens:".bold(), entry.prompt_tokens, entry.output_tokens);
+            println!();
+            println!("{}", entry.message.cyan());
+            for (i, candidate) in entry.candidates.iter().enumerate() {
+                println!();
+                println!("{}", format!("Candidate {}:", i + 1).bold());
+                println!("{}", candidate);
+            }
+            if prompt {
+                println!();
+                println!("{}", "Prompt:".bold());
+                println!("{}", entry.prompt);
+            }
+        }
+        HistoryCommand::Use { id, commit, yes, copy } => {
+            let entry = history::find(&entries, &id)?;
+            println!("{}", entry.message);
+            if copy {
+                match clipboard::copy(&entry.message) {
+                    Ok(via) => eprintln!("{}", format!("Copied to the clipboard via {}", via).dimmed()),
+                    Err(e) => eprintln!("{} couldn't copy the message: {}", "Warning:".yellow().bold(), e),
+                }
+            }
+            if commit {
+                if !git::has_staged_changes()? {
+                    anyhow::bail!("Nothing is staged to commit");
+                }
+                if !yes && !confirm("Commit with this message?")? {
+                    eprintln!("Not committed");
+                    return Ok(());
+                }
+                git::commit(
+                    &entry.message,
+                    &git::CommitOptions { all: false, amend: false, no_verify: false, sign: None, edit: false },
+                )?;
+            }
+        }
+        HistoryCommand::Rerun { id, yes, providers } => {
+            let entry = history::find(&entries, &id)?;
+            let mut selection = providers.selection(config);
+            if providers.provider.is_none() {
+                selection.provider = entry.provider.clone();
+            }
+            confirm_send(&entry.prompt, &selection.names(), yes, config)?;
+            let chain = selection.chain()?;
+            let spinner = spinner("Generating commit message...")?;
+            let result = chain.generate(&entry.prompt).await;
+            spinner.finish_and_clear();
+            let generation = result?;
+            println!("{}", generation.message.cyan());
+            if config.history.unwrap_or(true) {
+                let model = selection.model(&generation.provider);
+                history::record(&history::Entry::new(&generation.provider, model, &entry.prompt, Vec::new(), &generation.message))?;
+            }
+        }
+        HistoryCommand::Clear => {
+            history::clear()?;
+            eprintln!("History cleared");
+        }
+    }
+    Ok(())
 }
 
-pub struct GeminiClient {
-    api_key: String,
-    endpoint: String,
+async fn run_explain(
+    rev: Option<&str>,
+    staged: bool,
+    lang: Option<&str>,
+    yes: bool,
+    providers: &ProviderArgs,
+    config: &Config,
+) -> anyhow::Result<()> {
+    let (what, message, diff) = if staged {
+        ("the staged changes".to_string(), None, git::staged_diff(&git::Context::default())?)
+    } else {
+        let rev = rev.unwrap_or("HEAD");
+        let sha = git::run(&["rev-parse", "--verify", &format!("{}^{{commit}}", rev)])?.trim().to_string();
+        (sha[..7].to_string(), Some(git::commit_message(&sha)?), git::commit_diff(&sha)?)
+    };
+    if diff.trim().is_empty() {
+        anyhow::bail!("There are no changes in {} to explain", what);
+    }
+    let diff = diff::for_prompt(&diff, config)?;
+    let prompt = prompt::for_explanation(&diff, message.as_deref(), lang);
+
+    let selection = providers.selection(config);
+    confirm_send(&diff, &selection.names(), yes, config)?;
+    let chain = selection.chain()?;
+    let spinner = spinner(format!("Explaining {}...", what))?;
+    let result = chain.generate(&prompt).await;
+    spinner.finish_and_clear();
+    println!("{}", result?.message);
+    Ok(())
 }
 
-impl GeminiClient {
-    pub fn new(api_key: String) -> Self {
-        let endpoint = format!(
-            "https://generativelanguage.googleapis.com/v1beta/models/gemini-2.5-flash:generateContent?key={}",
-            api_key
-        );
-        Self { api_key, endpoint }
+async fn run_describe(
+    revision: Option<&str>,
+    style: &str,
+    yes: bool,
+    providers: &ProviderArgs,
+    config: &Config,
+) -> anyhow::Result<()> {
+    let vcs = Vcs::detect()?;
+    let rev = revision.unwrap_or(vcs.default_rev());
+    let diff = vcs.diff(rev)?;
+    if diff.trim().is_empty() {
+        anyhow::bail!("{} has no changes to describe", rev);
+    }
+    let diff = diff::for_prompt(&diff, config)?;
+    let examples = config.examples.unwrap_or(prompt::DEFAULT_EXAMPLES);
+    let mut subjects = vcs.recent_subjects(examples).unwrap_or_default();
+    let prompt = opening(style, Some(&diff), None, config.prompt_template.as_deref(), &mut subjects)?;
+    let prompt = prompt::with_examples(prompt, &subjects);
+
+    let selection = providers.selection(config);
+    confirm_send(&diff, &selection.names(), yes, config)?;
+    let chain = selection.chain()?;
+    let spinner = spinner(format!("Describing {}...", rev))?;
+    let result = chain.generate(&prompt).await;
+    spinner.finish_and_clear();
+    let message = result?.message;
+
+    println!();
+    println!("{}", message.cyan());
+    println!();
+
+    let replacing = !vcs.description(rev)?.trim().is_empty();
+    let question = if replacing {
+        format!("Replace the description of {}?", rev)
+    } else {
+        format!("Describe {} with this?", rev)
+    };
+    if !yes && !confirm(&question)? {
+        eprintln!("Not described");
+        return Ok(());
+    }
+    vcs.describe(rev, &message)
+}
+
+async fn run_stash(include_untracked: bool, yes: bool, providers: &ProviderArgs, config: &Config) -> anyhow::Result<()> {
+    let diff = diff::for_prompt(&git::working_tree_diff(include_untracked, &git::Context::default())?, config)?;
+
+    let selection = providers.selection(config);
+    confirm_send(&diff, &selection.names(), yes, config)?;
+    let chain = selection.chain()?;
+    let spinner = spinner("Describing the work in progress...")?;
+    let result = chain.generate(&prompt::for_stash(&diff)).await;
+    spinner.finish_and_clear();
+    let description = subject(&result?.message).trim().to_string();
+
+    println!();
+    println!("{}", description.cyan());
+    println!();
+
+    if !yes && !confirm("Stash the changes with this description?")? {
+        eprintln!("Not stashed");
+        return Ok(());
     }
-    
-    pub fn parse_response_json(v: &serde_json::Value) -> anyhow::Result<String> {
-        v.get("candidates")
-            .and_then(|c| c.get(0))
-            .and_then(|c| c.get("content"))
-            .and_then(|c| c.get("parts"))
-            .and_then(|p| p.get(0))
-            .and_then(|p| p.get("text"))
-            .and_then(|t| t.as_str())
-            .map(|s| s.trim().to_string())
-            .ok_or_else(|| anyhow::anyhow!("Failed to extract message. Response: {}", v))
+    let mut args = vec!["stash", "push", "--message", &description];
+    if include_untracked {
+        args.push("--include-untracked");
     }
+    git::run(&args)?;
+    Ok(())
 }
 
-#[async_trait::async_trait]
-impl LLMClient for GeminiClient {
-    async fn generate(&self, prompt: &str) -> anyhow::Result<String> {
-        let client = reqwest::Client::new();
-        let resp = client
-            .post(&self.endpoint)
-            .header("x-goog-api-key", &self.api_key)
-            .json(&serde_json::json!({
-                "contents": [{ "parts": [{ "text": prompt }] }],
-                "generationConfig": { "temperature": 0.0, "maxOutputTokens": 4096 }
-            }))
-            .send()
-            .await?;
+async fn run_review(style: &str, providers: &ProviderArgs, config: &Config) -> anyhow::Result<()> {
+    let term = console::Term::stdout();
+    if !term.is_term() || !console::user_attended() {
+        anyhow::bail!("review needs a terminal");
+    }
+    let raw = git::staged_diff(&git::Context::default())?;
+    let diff = diff::for_prompt(&raw, config)?;
+    let finish = Finish {
+        style,
+        ticket: config.ticket.unwrap_or(true),
+        breaking: breaking::Verdict::new(None, Some(&raw)),
+        co_authors: Vec::new(),
+        signoff: if config.signoff || style::signs_off(style) { Some(git::identity()?) } else { None },
+        max_subject_length: style::max_subject_length(style),
+        long_subject: config.long_subject.unwrap_or_default(),
+        wrap_column: config.wrap_column.unwrap_or(wrap::DEFAULT_WRAP_COLUMN),
+        shape: config.shape(),
+    };
+    let examples = config.examples.unwrap_or(prompt::DEFAULT_EXAMPLES);
+    let mut subjects = git::recent_subjects(examples).unwrap_or_default();
+    let prompt = opening(style, Some(&diff), None, config.prompt_template.as_deref(), &mut subjects)?;
+    let prompt = prompt::with_examples(
+        prompt::with_language(
+            prompt::with_shape(prompt::with_breaking(prompt, &finish.breaking), &finish.shape),
+            config.lang.as_deref(),
+            style::max_subject_length(style),
+        ),
+        &subjects,
+    );
 
-        if !resp.status().is_success() {
-            let status = resp.status();
-            let text = resp.text().await?;
-            return Err(anyhow::anyhow!(
-                "Gemini API returned HTTP {}:\n{}", status, text
-            ));
+    // `p` cycles through the provider and its fallbacks, then the config's `compare` list.
+    let selection = providers.selection(config);
+    let mut names = vec![selection.provider.clone()];
+    for name in config.fallback.iter().chain(&config.compare) {
+        if !names.contains(name) {
+            names.push(name.clone());
+        }
+    }
+    confirm_send(&diff, &names, false, config)?;
+    let review = tui::Review::new(&raw, selection.compare(names)?);
+    match tui::run(&term, review, &prompt, |message| finish_message(message.to_string(), &finish, config)).await? {
+        tui::Outcome::Commit(message) => git::commit(
+            &message,
+            &git::CommitOptions { all: false, amend: false, no_verify: false, sign: None, edit: false },
+        ),
+        tui::Outcome::Quit => {
+            eprintln!("Not committed");
+            Ok(())
         }
+    }
+}
+
+async fn run_pr(
+    base: Option<String>,
+    create: bool,
+    yes: bool,
+    providers: &ProviderArgs,
+    config: &Config,
+) -> anyhow::Result<()> {
+    let base = match base {
+        Some(base) => base,
+        None => pr::default_base()?,
+    };
+    let diff = diff::for_prompt(&pr::diff(&base)?, config)?;
+    let prompt = prompt::for_pr(&diff, &pr::messages(&base)?);
+
+    let selection = providers.selection(config);
+    confirm_send(&diff, &selection.names(), yes, config)?;
+    let chain = selection.chain()?;
+    let spinner = spinner("Writing pull request...")?;
+    let result = chain.generate(&prompt).await;
+    spinner.finish_and_clear();
+    let (title, body) = pr::split(&result?.message);
 
-        let v: Value = resp.json().await?;
-        Self::parse_response_json(&v)
+    if !create {
+        println!("{}\n\n{}", title, body);
+        return Ok(());
     }
+    println!();
+    println!("{}", title.cyan().bold());
+    println!();
+    println!("{}", body.cyan());
+    println!();
+    if !yes && !confirm(&format!("Open a pull request against {}?", base))? {
+        eprintln!("Not opened");
+        return Ok(());
+    }
+
+    // gh wants the branch name, not a remote-tracking ref.
+    let base_branch = base.strip_prefix("origin/").unwrap_or(&base);
+    let mut child = std::process::Command::new("gh")
+        .args(["pr", "create", "--base", base_branch, "--title", &title, "--body-file", "-"])
+        .stdin(std::process::Stdio::piped())
+        .spawn()
+        .map_err(|e| anyhow::anyhow!("Failed to run gh (is the GitHub CLI installed?): {}", e))?;
+    child.stdin.take().expect("stdin is piped").write_all(body.as_bytes())?;
+    if !child.wait()?.success() {
+        anyhow::bail!("gh pr create failed");
+    }
+    Ok(())
 }
 
-#[derive(Parser)]
-#[command(author, version, about = "Generate Git commit messages with Gemini API")]
-struct CLI {
-    description: String,
-    
-    #[arg(short, long, default_value = "conventional commit")]
-    style: String,
+async fn run_changelog(
+    from: Option<String>,
+    to: &str,
+    no_summarize: bool,
+    yes: bool,
+    providers: &ProviderArgs,
+    config: &Config,
+) -> anyhow::Result<()> {
+    let from = match from {
+        Some(from) => from,
+        None => changelog::previous_tag(to)?,
+    };
+    let groups = changelog::group(&changelog::subjects(&from, to)?);
+    let noisy = |group: &changelog::Group| !no_summarize && group.items.len() >= changelog::NOISY_RUN;
+    let summarised: usize = groups.iter().filter(|group| noisy(group)).map(|group| group.items.len()).sum();
+    let chain = if summarised > 0 {
+        let selection = providers.selection(config);
+        let summary = format!("  {} commit subjects, in runs long enough to summarise", summarised);
+        confirm_sending("commit subjects", &summary, &selection.names(), yes, config)?;
+        Some(selection.chain()?)
+    } else {
+        None
+    };
+
+    let mut bullets = Vec::new();
+    for group in &groups {
+        let scope = group.scope.as_deref();
+        let lines = match &chain {
+            Some(chain) if noisy(group) => {
+                let spinner = spinner(format!("Summarising {} {} commits...", group.items.len(), group.section))?;
+                let result = chain.generate(&prompt::for_changelog_group(group.section, scope, &group.items)).await;
+                spinner.finish_and_clear();
+                let summary = result?.message;
+                vec![changelog::bullet(scope, summary.trim_start_matches(['-', '*', ' ']))]
+            }
+            _ => group.items.iter().map(|item| changelog::bullet(scope, item)).collect(),
+        };
+        bullets.push((group.section, lines));
+    }
+
+    print!("{}", changelog::render(&changelog::version(to), &changelog::date(to)?, &bullets));
+    Ok(())
+}
+
+async fn run_release_notes(range: &str, yes: bool, providers: &ProviderArgs, config: &Config) -> anyhow::Result<()> {
+    let range = reword::normalize_range(range);
+    let messages = git::messages(&range)?;
+    if messages.is_empty() {
+        anyhow::bail!("No commits in {}", range);
+    }
+    let (_, to) = range.split_once("..").expect("normalized ranges contain ..");
+    let release = if to.is_empty() || to == "HEAD" { "the upcoming release" } else { to.trim_start_matches('.') };
+    let stat = git::run(&["diff", "--stat", "--no-color", &range, "--"])?;
+
+    let selection = providers.selection(config);
+    confirm_sending("commit messages", &messages_summary(messages.len(), &stat), &selection.names(), yes, config)?;
+    let chain = selection.chain()?;
+    let spinner = spinner(format!("Writing release notes for {} commits...", messages.len()))?;
+    let result = chain.generate(&prompt::for_release_notes(release, &messages, &stat)).await;
+    spinner.finish_and_clear();
+    println!("{}", result?.message);
+    Ok(())
+}
+
+async fn run_tag(args: TagArgs, providers: &ProviderArgs, config: &Config) -> anyhow::Result<()> {
+    if git::rev_exists(&format!("refs/tags/{}", args.name))? {
+        anyhow::bail!("The tag {} already exists", args.name);
+    }
+    let from = match args.from {
+        Some(from) => Some(from),
+        None => changelog::previous_tag(&args.rev).ok(),
+    };
+    let (messages, stat) = match &from {
+        Some(from) => {
+            let range = format!("{}..{}", from, args.rev);
+            (git::messages(&range)?, git::run(&["diff", "--stat", "--no-color", &range, "--"])?)
+        }
+        None => (git::messages(&args.rev)?, String::new()),
+    };
+    if messages.is_empty() {
+        anyhow::bail!("No commits since {}", from.as_deref().unwrap_or(&args.rev));
+    }
+
+    let selection = providers.selection(config);
+    confirm_sending("commit messages", &messages_summary(messages.len(), &stat), &selection.names(), args.yes, config)?;
+    let chain = selection.chain()?;
+    let spinner = spinner(format!("Summarising {} commits for {}...", messages.len(), args.name))?;
+    let result = chain.generate(&prompt::for_tag(&args.name, &messages, &stat)).await;
+    spinner.finish_and_clear();
+    let message = result?.message;
+
+    println!();
+    println!("{}", message.cyan());
+    println!();
+
+    if args.create {
+        if !args.yes && !confirm(&format!("Create the tag {} with this message?", args.name))? {
+            eprintln!("Not tagged");
+            return Ok(());
+        }
+        git::tag(&args.name, &args.rev, &message, args.sign)?;
+    }
+    Ok(())
+}
+
+/// What sending `count` commit messages and the diffstat `stat` means, for
+/// [`confirm_sending`].
+fn messages_summary(count: usize, stat: &str) -> String {
+    let files = stat.lines().count().saturating_sub(1);
+    match files {
+        0 => format!("  {} commit message{}", count, if count == 1 { "" } else { "s" }),
+        _ => format!("  {} commit message{}, and the diffstat of {} files", count, if count == 1 { "" } else { "s" }, files),
+    }
+}
+
+/// The options of `commitgen tag`.
+struct TagArgs {
+    name: String,
+    rev: String,
+    from: Option<String>,
+    create: bool,
+    sign: bool,
+    yes: bool,
+}
+
+async fn run_hook(action: HookCommand, config: &Config) -> anyhow::Result<()> {
+    match action {
+        HookCommand::Install { force } => {
+            let path = hook::install(force)?;
+            println!("Installed {}", path.display());
+        }
+        HookCommand::Uninstall => match hook::uninstall()? {
+            Some(path) => println!("Removed {}", path.display()),
+            None => println!("No commitgen hook is installed"),
+        },
+        HookCommand::Status => match hook::status()? {
+            hook::Status::Installed(path) => println!("Installed at {}", path.display()),
+            hook::Status::NotInstalled(path) => println!("Not installed (would be {})", path.display()),
+            hook::Status::Foreign(path) => {
+                println!("Not installed; {} is another hook", path.display())
+            }
+        },
+        HookCommand::Run { message_file, source, .. } => {
+            let buffer = std::fs::read_to_string(&message_file)?;
+            if hook::passes_through(source.as_deref(), &buffer) {
+                return Ok(());
+            }
+            let template = commit_template::load().ok().flatten();
+            // git has put the commit.template in the buffer; it gets filled
+            // in rather than counting as a message.
+            let filling = source.as_deref() == Some("template")
+                && template.as_deref().is_some_and(|template| buffer.starts_with(template));
+            if !filling && hook::has_message(&buffer) {
+                return Ok(());
+            }
+            let rest = match &template {
+                Some(template) if filling => &buffer[template.len()..],
+                _ => buffer.as_str(),
+            };
+            // Anything going wrong here must not get in the way of the
+            // commit, so failures are reported and the buffer left as is.
+            let style = config.style(None);
+            let mut finish = Finish {
+                style: &style,
+                ticket: config.ticket.unwrap_or(true),
+                breaking: breaking::Verdict::Unknown,
+                co_authors: Vec::new(),
+                signoff: if config.signoff || style::signs_off(&style) { git::identity().ok() } else { None },
+                max_subject_length: style::max_subject_length(&style),
+                long_subject: config.long_subject.unwrap_or_default(),
+                wrap_column: config.wrap_column.unwrap_or(wrap::DEFAULT_WRAP_COLUMN),
+                shape: config.shape(),
+            };
+            let generated = async {
+                let diff = git::staged_diff(&git::Context::default())?;
+                finish.breaking = breaking::Verdict::new(None, Some(&diff));
+                let diff = diff::for_prompt(&diff, config)?;
+                let mut subjects = git::recent_subjects(config.examples.unwrap_or(prompt::DEFAULT_EXAMPLES))?;
+                let prompt = opening(finish.style, Some(&diff), None, config.prompt_template.as_deref(), &mut subjects)?;
+                let prompt = prompt::with_template(prompt::with_breaking(prompt, &finish.breaking), template.as_deref());
+                let prompt = prompt::with_language(
+                    prompt::with_shape(prompt, &finish.shape),
+                    config.lang.as_deref(),
+                    style::max_subject_length(finish.style),
+                );
+                let prompt = prompt::with_examples(prompt, &subjects);
+                let selection = Selection::from_config(config);
+                confirm_send(&diff, &selection.names(), false, config)?;
+                let chain = selection.chain()?;
+                let spinner = spinner("Generating commit message...")?;
+                let result = chain.generate(&prompt).await;
+                spinner.finish_and_clear();
+                result.map(|generation| (prompt, generation))
+            };
+            match generated.await {
+                Ok((prompt, generation)) => {
+                    let message = finish_message(generation.message, &finish, config);
+                    if config.history.unwrap_or(true) {
+                        let model = Selection::from_config(config).model(&generation.provider);
+                        let entry = history::Entry::new(&generation.provider, model, &prompt, Vec::new(), &message);
+                        if let Err(e) = history::record(&entry) {
+                            eprintln!("commitgen: couldn't save the message to the history: {}", e);
+                        }
+                    }
+                    output::write(&message_file, &hook::prefill(rest, &message))?
+                }
+                Err(e) => eprintln!("commitgen: {}", e),
+            }
+        }
+    }
+    Ok(())
 }
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     dotenv().ok();
-    let api_key = env::var("GEMINI_API_KEY").expect("GEMINI_API_KEY must be set in .env");
+    completions::complete(cli);
+
+    let (repos, argv) = take_repo_args(std::env::args_os().collect());
+    let args = Cli::from_arg_matches(&cli().get_matches_from(argv)).unwrap_or_else(|e| e.exit());
+    terminal::init();
+    logging::init(args.verbose);
+    // Every git command, and every relative path, then resolves against it.
+    for repo in repos.iter().chain(&args.repo) {
+        std::env::set_current_dir(repo).map_err(|e| anyhow::anyhow!("Cannot use {}: {}", repo.display(), e))?;
+    }
 
-    let args = CLI::parse();
+    if let Some(Command::Doctor { offline }) = args.command {
+        // Before the config is loaded, so that one which doesn't load is
+        // reported rather than stopping it.
+        return run_doctor(args.profile.as_deref(), offline).await;
+    }
 
-    let prompt = format!(
-        "You are an expert programmer writing a git commit message.\n\
-        Your task is to generate a single, git commit message in the '{style}' style for the following change description.\n\n\
-        VERY IMPORTANT: Your entire response must be only the commit message itself. Do not include any surrounding text, explanations, apologies, or markdown formatting like ```.\n\n\
-        Change Description: \"{description}\"",
-        style = args.style,
-        description = args.description
-    );
+    let mut config = Config::load(args.profile.as_deref())?;
+    config.timeout_secs = args.timeout.or(config.timeout_secs);
+    config.max_retries = args.max_retries.or(config.max_retries);
+    config.backoff = args.backoff.or(config.backoff);
+    http::init(&config)?;
+    auth::vault::init(&config);
+    style::load_custom()?;
+    match args.command.unwrap_or(Command::Generate(args.generate)) {
+        Command::Generate(args) => run_generate(args, &config).await,
+        Command::Commit(mut args) => {
+            args.commit = true;
+            run_generate(args, &config).await
+        }
+        Command::Init => run_init(&config).await,
+        Command::Doctor { .. } => unreachable!("handled before the config is loaded"),
+        Command::History { action } => {
+            run_history(action.unwrap_or(HistoryCommand::List { limit: 20, here: false }), &config).await
+        }
+        Command::Config { action } => run_config(action, args.profile.as_deref()),
+        Command::Models { provider } => run_models(provider, &config).await,
+        Command::Auth { action } => run_auth(action),
+        Command::Check { message_file, style, max_subject_length } => {
+            let style = config.style(style);
+            run_check(&message_file, &style, max_subject_length.unwrap_or_else(|| style::max_subject_length(&style)))
+        }
+        Command::Reword { range, style, yes, providers } => {
+            run_reword(&range, &config.style(style), yes, &providers, &config).await
+        }
+        Command::Squash { range, style, commit, yes, providers } => {
+            run_squash(&range, &config.style(style), commit, yes, &providers, &config).await
+        }
+        Command::Branch { description, pattern, create, yes, providers } => {
+            run_branch(description.as_deref(), pattern.as_deref(), create, yes, &providers, &config).await
+        }
+        Command::Split { style, apply, yes, providers } => {
+            run_split(&config.style(style), apply, yes, &providers, &config).await
+        }
+        Command::Merge { branch, style, commit, yes, providers } => {
+            run_merge(&branch, &config.style(style), commit, yes, &providers, &config).await
+        }
+        Command::Undo { yes, force } => run_undo(yes, force),
+        Command::Revert { rev, reason, style, commit, yes, providers } => {
+            run_revert(&rev, reason.as_deref(), &config.style(style), commit, yes, &providers, &config).await
+        }
+        Command::Explain { rev, staged, lang, yes, providers } => {
+            let lang = lang.or_else(|| config.lang.clone());
+            run_explain(rev.as_deref(), staged, lang.as_deref(), yes, &providers, &config).await
+        }
+        Command::Describe { revision, style, yes, providers } => {
+            run_describe(revision.as_deref(), &config.style(style), yes, &providers, &config).await
+        }
+        Command::Stash { include_untracked, yes, providers } => {
+            run_stash(include_untracked, yes, &providers, &config).await
+        }
+        Command::Review { style, providers } => run_review(&config.style(style), &providers, &config).await,
+        Command::Pr { base, create, yes, providers } => run_pr(base, create, yes, &providers, &config).await,
+        Command::Changelog { from, to, no_summarize, yes, providers } => {
+            run_changelog(from, &to, no_summarize, yes, &providers, &config).await
+        }
+        Command::ReleaseNotes { range, yes, providers } => run_release_notes(&range, yes, &providers, &config).await,
+        Command::Tag { name, rev, from, create, sign, yes, providers } => {
+            run_tag(TagArgs { name, rev, from, create, sign, yes }, &providers, &config).await
+        }
+        Command::Hook { action } => run_hook(action, &config).await,
+        Command::Completions { shell } => {
+            print!("{}", completions::script(shell, cli().get_name()));
+            Ok(())
+        }
+        Command::Man { out_dir } => {
+            let pages = man::pages(&cli())?;
+            match out_dir {
+                Some(dir) => {
+                    std::fs::create_dir_all(&dir)?;
+                    for (name, page) in &pages {
+                        std::fs::write(dir.join(name), page)?;
+                    }
+                    eprintln!("Wrote {} man pages to {}", pages.len(), dir.display());
+                }
+                None => print!("{}", pages[0].1),
+            }
+            Ok(())
+        }
+    }
+}
 
-    let llm: Box<dyn LLMClient> = Box::new(GeminiClient::new(api_key));
+/// The command line, with `commit` adjusted to always commit: `--commit`
+/// is implied, so the flags that need it are allowed on their own.
+fn cli() -> clap::Command {
+    Cli::command().mut_subcommand("commit", |command| {
+        ["yes", "edit", "no_verify", "gpg_sign", "no_gpg_sign", "note"]
+            .into_iter()
+            .fold(command, |command, id| command.mut_arg(id, |arg| arg.requires(clap::builder::Resettable::Reset)))
+            .mut_arg("commit", |arg| arg.hide(true))
+            .mut_arg("stdin", |arg| arg.hide(true).conflicts_with("description"))
+    })
+}
 
-    let spinner = ProgressBar::new_spinner();
-    spinner.set_style(
-        ProgressStyle::default_spinner()
-            .tick_strings(&["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"])
-            .template("{spinner:.green} {msg}")?,
-    );
-    spinner.set_message("Generating commit message...");
-    spinner.enable_steady_tick(Duration::from_millis(80));
+async fn run_generate(mut args: GenerateArgs, config: &Config) -> anyhow::Result<()> {
+    if args.stdin && args.commit {
+        anyhow::bail!("A diff from --stdin can't be committed; use `commitgen generate --stdin`");
+    }
+    // A description piped in stands in for the argument, unless the flags
+    // ask for a particular diff.
+    if args.description.as_deref() == Some("-") {
+        args.description = Some(stdin_description()?.ok_or_else(|| {
+            anyhow::anyhow!("`-` reads the description piped in, e.g. `echo \"fix the login\" | commitgen -`")
+        })?);
+    } else if args.description.is_none() && !(args.stdin || args.amend || args.all) {
+        args.description = stdin_description()?;
+    }
+    if args.quiet {
+        QUIET.store(true, Ordering::Relaxed);
+        colored::control::set_override(false);
+    }
+    // The config files stand in for flags that weren't given, where they'd
+    // make sense with the ones that were.
+    args.context_lines = args.context_lines.or(config.context_lines);
+    args.function_context |= config.function_context;
+    args.include_untracked |= args.all && config.include_untracked;
+    args.no_ticket |= config.ticket == Some(false);
+    args.copy |= config.copy && !args.dry_run;
+    if args.commit || args.amend {
+        args.edit |= config.edit;
+        args.no_verify |= config.no_verify;
+        if !args.gpg_sign && !args.no_gpg_sign {
+            args.gpg_sign = config.gpg_sign == Some(true);
+            args.no_gpg_sign = config.gpg_sign == Some(false);
+        }
+    }
+    if !(args.quiet || args.json || args.dry_run || args.compare.is_some()) && console::user_attended() {
+        args.interactive |= config.interactive;
+        if args.candidates == 1 {
+            args.candidates = config.candidates.unwrap_or(1).clamp(1, 9);
+        }
+    }
+    let style = config.style(args.style.take());
+    let lang = args.lang.take().or_else(|| config.lang.clone());
+    let body_mode = if args.no_body { BodyMode::None } else { args.body.or(config.body).unwrap_or_default() };
+    let context = git::Context { lines: args.context_lines, function: args.function_context };
+    let vcs = Vcs::detect()?;
+    let mercurial = vcs == Vcs::Mercurial;
+    let diff = match &args.description {
+        Some(_) => None,
+        None if args.stdin => Some(stdin_diff()?),
+        None if mercurial && args.amend => Some(hg::amend_diff(&context)?),
+        None if mercurial => Some(hg::working_diff(&context)?),
+        None if args.amend => Some(git::amend_diff(&context)?),
+        None if args.all => Some(git::working_tree_diff(args.include_untracked, &context)?),
+        None => Some(git::staged_diff(&context)?),
+    };
+    let diff = diff.map(|diff| diff::for_prompt(&diff, config)).transpose()?;
+    let examples = args.examples.or(config.examples).unwrap_or(prompt::DEFAULT_EXAMPLES);
+    // Outside a repository there's simply nothing to learn from.
+    let mut subjects = vcs.recent_subjects(examples).unwrap_or_default();
+    let prompt_template = args.prompt_template.take().or_else(|| config.prompt_template.clone());
+    let prompt = match (&args.description, &diff) {
+        (None, Some(diff)) if args.amend => {
+            let head_message = if mercurial { hg::description(".")? } else { git::head_message()? };
+            prompt::for_amend(&style, diff, &head_message)
+        }
+        (description, diff) => {
+            opening(&style, diff.as_deref(), description.as_deref(), prompt_template.as_deref(), &mut subjects)?
+        }
+    };
+
+    let force_breaking = if args.breaking { Some(true) } else if args.no_breaking { Some(false) } else { None };
+    let finish = Finish {
+        style: &style,
+        ticket: !args.no_ticket,
+        breaking: breaking::Verdict::new(force_breaking, diff.as_deref()),
+        co_authors: args
+            .co_author
+            .iter()
+            .map(|value| trailers::co_author(value, &config.co_authors))
+            .collect::<anyhow::Result<_>>()?,
+        signoff: if args.signoff || config.signoff || style::signs_off(&style) { Some(git::identity()?) } else { None },
+        max_subject_length: style::max_subject_length(&style),
+        long_subject: args.long_subject.or(config.long_subject).unwrap_or_default(),
+        wrap_column: args.wrap.or(config.wrap_column).unwrap_or(wrap::DEFAULT_WRAP_COLUMN),
+        shape: body::Shape {
+            body: body_mode != BodyMode::None,
+            max_items: args.max_body_items.or(config.max_body_items),
+            footers: !args.no_footers && config.footers.unwrap_or(true),
+        },
+    };
+    let template = commit_template::load()?;
+    let compose = |prompt: String| {
+        let prompt = prompt::with_template(prompt::with_breaking(prompt, &finish.breaking), template.as_deref());
+        let prompt = prompt::with_language(prompt, lang.as_deref(), style::max_subject_length(&style));
+        prompt::with_examples(prompt::with_shape(prompt, &finish.shape), &subjects)
+    };
+    let selection = args.providers.selection(config);
 
-    let result = llm.generate(&prompt).await;
+    if args.dry_run {
+        if body_mode == BodyMode::Detailed {
+            anyhow::bail!("--dry-run can't preview --body detailed, which asks the model about each area first");
+        }
+        let prompt = compose(prompt);
+        println!("{}", prompt);
+        let model = selection.model(&selection.provider).unwrap_or_else(|| "its default model".to_string());
+        eprintln!(
+            "{}",
+            format!("~{} tokens for {} ({}); nothing was sent", notes::estimate_tokens(&prompt), selection.provider, model)
+                .dimmed()
+        );
+        return Ok(());
+    }
+
+    if let Some(diff) = &diff {
+        confirm_send(diff, &args.compare.clone().unwrap_or_else(|| selection.names()), args.yes, config)?;
+    }
+    if let Some(compare) = args.compare {
+        if body_mode == BodyMode::Detailed {
+            anyhow::bail!("--body detailed can't be combined with --compare");
+        }
+        return run_compare(selection.compare(compare)?, &compose(prompt)).await;
+    }
+
+    let chain = selection.chain()?;
+    let (prompt, body) = match (body_mode, &diff) {
+        (BodyMode::Normal | BodyMode::None, _) => (compose(prompt), None),
+        (BodyMode::Detailed, None) => anyhow::bail!("--body detailed works from a diff, not a description"),
+        (BodyMode::Detailed, Some(diff)) => {
+            let summaries = summarise_areas(&chain, diff).await?;
+            let body: Vec<String> = summaries.iter().map(|(area, summary)| format!("- {}: {}", area, summary)).collect();
+            (compose(prompt::subject_from_summaries(&style, &summaries)), Some(body.join("\n")))
+        }
+    };
+    let prompt = match args.interactive_context {
+        Some(max) => clarify(&chain, prompt, max).await?,
+        None => prompt,
+    };
+    let spinner = spinner("Generating commit message...")?;
+
+    // On a terminal, tokens are printed as they arrive; leading whitespace
+    // is held back so the output matches the trimmed message.
+    let mut started = false;
+    let mut shown = String::new();
+    let mut offered = Vec::new();
+    let result = if args.candidates > 1 {
+        spinner.finish_and_clear();
+        pick_candidate(&chain, &prompt, args.candidates, &mut offered).await
+    } else if args.json || args.quiet || !terminal::redraws() {
+        chain.generate(&prompt).await
+    } else {
+        chain
+            .generate_streaming(&prompt, |token| {
+                let token = if started { token } else { token.trim_start() };
+                if token.is_empty() {
+                    return;
+                }
+                if !started {
+                    spinner.finish_and_clear();
+                    println!();
+                    started = true;
+                }
+                print!("{}", token.cyan());
+                let _ = std::io::stdout().flush();
+                shown.push_str(token);
+            })
+            .await
+    };
 
     spinner.finish_and_clear();
-    match result {
-        Ok(message) => {
-            println!();
-            println!("{}", message.cyan());
-            println!();
+    let result = match result {
+        Ok(mut generation) if finish.long_subject == wrap::LongSubject::Rewrite => {
+            generation.message = shorten_subject(&chain, generation.message, finish.max_subject_length).await;
+            Ok(generation)
+        }
+        result => result,
+    };
+    let polish = |message: &str| {
+        let message = match &body {
+            Some(body) => format!("{}\n\n{}", subject(message), body),
+            None => message.to_string(),
+        };
+        // Committed without an editor, so git won't strip the template's comments.
+        let message = match &template {
+            Some(_) => commit_template::strip_comments(&message, commit_template::comment_char()),
+            None => message,
+        };
+        finish_message(message, &finish, config)
+    };
+    let mut generation = match result {
+        Ok(mut generation) => {
+            let message = polish(&generation.message);
+            if args.json {
+                println!("{}", json_output(&message, &generation.provider, selection.model(&generation.provider), &prompt));
+            } else if args.quiet {
+                println!("{}", message);
+            } else {
+                if !started {
+                    println!();
+                    print!("{}", message.cyan());
+                } else if message != generation.message {
+                    erase(&shown);
+                    print!("{}", message.cyan());
+                }
+                println!();
+                println!();
+            }
+            generation.message = message;
+            for (provider, e) in generation.failures.iter().filter(|_| !args.quiet) {
+                eprintln!("{} {} failed: {}", "Warning:".yellow().bold(), provider, e);
+            }
+            if !generation.failures.is_empty() && !args.quiet {
+                eprintln!("{}", format!("(generated by {})", generation.provider).dimmed());
+            }
+            if !args.quiet {
+                warn_style_problems(&generation.message, &style);
+            }
+            generation
         }
         Err(e) => {
             eprintln!("\n{} {}", "Error:".red().bold(), e);
             std::process::exit(1);
         }
+    };
+
+    if args.interactive {
+        let rewrite_over =
+            (finish.long_subject == wrap::LongSubject::Rewrite).then_some(finish.max_subject_length);
+        match refine(&chain, &prompt, generation, rewrite_over, &polish, &mut offered).await? {
+            Some(accepted) => generation = accepted,
+            None => {
+                eprintln!("Discarded");
+                return Ok(());
+            }
+        }
+    }
+
+    if config.history.unwrap_or(true) {
+        // Only a real choice is worth keeping the alternatives for.
+        let candidates = if offered.len() > 1 { offered } else { Vec::new() };
+        let entry = history::Entry::new(
+            &generation.provider,
+            selection.model(&generation.provider),
+            &prompt,
+            candidates,
+            &generation.message,
+        );
+        if let Err(e) = history::record(&entry) {
+            eprintln!("{} couldn't save the message to the history: {}", "Warning:".yellow().bold(), e);
+        }
+    }
+
+    if let Some(path) = &args.output {
+        output::write(path, &generation.message)?;
+    }
+
+    if args.copy {
+        match clipboard::copy(&generation.message) {
+            Ok(via) if !args.quiet => eprintln!("{}", format!("Copied to the clipboard via {}", via).dimmed()),
+            Ok(_) => {}
+            Err(e) => eprintln!("{} couldn't copy the message: {}", "Warning:".yellow().bold(), e),
+        }
+    }
+
+    if args.commit || args.amend {
+        let question = if args.amend { "Amend the last commit with this message?" } else { "Commit with this message?" };
+        // Accepting the message in --interactive already answered this.
+        if !args.yes && !args.interactive && !confirm(question)? {
+            eprintln!("Not committed");
+            return Ok(());
+        }
+        if mercurial {
+            hg::commit(&generation.message, args.amend, args.edit)?;
+            if args.note {
+                eprintln!("{} git notes aren't available in Mercurial; nothing was recorded", "Warning:".yellow().bold());
+            }
+            return Ok(());
+        }
+        if args.include_untracked {
+            git::run(&["add", "--all"])?;
+        }
+        git::commit(
+            &generation.message,
+            &git::CommitOptions {
+                all: args.all,
+                amend: args.amend,
+                no_verify: args.no_verify,
+                sign: if args.gpg_sign { Some(true) } else if args.no_gpg_sign { Some(false) } else { None },
+                edit: args.edit,
+            },
+        )?;
+        if args.note || config.notes {
+            let metadata = notes::Metadata::new(
+                &generation.provider,
+                selection.model(&generation.provider),
+                &prompt,
+                &generation.message,
+            );
+            if let Err(e) = notes::attach("HEAD", &metadata) {
+                eprintln!("{} couldn't record the git note: {}", "Warning:".yellow().bold(), e);
+            }
+        }
     }
 
     Ok(())
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use anyhow::Result;
-    use serde_json::json;
-    use async_trait::async_trait;
+/// Offers to use, edit, regenerate or refine `generation` until it's
+/// accepted (returned) or dismissed (`None`), adding each message shown
+/// to `offered`. Every regeneration sees the messages so far and what was
+/// asked of each, and is shortened (when `rewrite_over` is a subject
+/// limit) and `polish`ed like the first.
+async fn refine(
+    chain: &llm::FallbackChain,
+    prompt: &str,
+    mut generation: llm::Generation,
+    rewrite_over: Option<usize>,
+    polish: impl Fn(&str) -> String,
+    offered: &mut Vec<String>,
+) -> anyhow::Result<Option<llm::Generation>> {
+    const ACTIONS: &[&str] = &["Use this message", "Edit it", "Regenerate", "Refine with an instruction"];
+    if !console::user_attended() {
+        anyhow::bail!("--interactive needs a terminal");
+    }
+    if offered.is_empty() {
+        offered.push(generation.message.clone());
+    }
+    let mut turns: Vec<(String, String)> = Vec::new();
+    loop {
+        let Some(action) = dialoguer::Select::new().with_prompt("What now?").items(ACTIONS).default(0).interact_opt()?
+        else {
+            return Ok(None);
+        };
+        let instruction = match action {
+            0 => return Ok(Some(generation)),
+            1 => {
+                if let Some(edited) = dialoguer::Editor::new().edit(&generation.message)? {
+                    generation.message = edited.trim().to_string();
+                    println!();
+                    println!("{}", generation.message.cyan());
+                    println!();
+                }
+                continue;
+            }
+            2 => "Write a different message for the same change.".to_string(),
+            _ => dialoguer::Input::<String>::new().with_prompt("How should it change?").interact_text()?,
+        };
+        turns.push((generation.message.clone(), instruction));
 
-    struct FakeClient;
-    #[async_trait]
-    impl LLMClient for FakeClient {
-        async fn generate(&self, _prompt: &str) -> Result<String> {
-            Ok("chore: add unit tests".into())
+        let spinner = spinner("Regenerating commit message...")?;
+        let result = chain.generate(&prompt::for_refinement(prompt, &turns)).await;
+        spinner.finish_and_clear();
+        match result {
+            Ok(mut next) => {
+                if let Some(max) = rewrite_over {
+                    next.message = shorten_subject(chain, next.message, max).await;
+                }
+                generation = llm::Generation { message: polish(&next.message), ..next };
+                offered.push(generation.message.clone());
+                println!();
+                println!("{}", generation.message.cyan());
+                println!();
+            }
+            Err(e) => {
+                turns.pop();
+                eprintln!("{} {}", "Error:".red().bold(), e);
+            }
+        }
+    }
+}
+
+/// Lets the model ask up to `max` questions about the change before it
+/// writes the message, and adds the answers to `prompt`. Questions left
+/// unanswered are dropped; if the model has none, `prompt` is unchanged.
+async fn clarify(chain: &llm::FallbackChain, prompt: String, max: u8) -> anyhow::Result<String> {
+    if !console::user_attended() {
+        anyhow::bail!("--interactive-context needs a terminal to answer the questions at");
+    }
+    let spinner = spinner("Looking for questions about the change...")?;
+    let result = chain.generate(&prompt::for_questions(&prompt, max)).await;
+    spinner.finish_and_clear();
+    let questions = match result {
+        Ok(generation) => clarify::questions(&generation.message, max as usize),
+        Err(e) => {
+            eprintln!("{} no questions were asked: {}", "Warning:".yellow().bold(), e);
+            return Ok(prompt);
         }
+    };
+    if questions.is_empty() {
+        eprintln!("{}", "The model had no questions about the change".dimmed());
+        return Ok(prompt);
     }
+    eprintln!("{}", "A few questions about the change (leave one empty to skip it):".bold());
+    let mut answers = Vec::new();
+    for question in questions {
+        let answer = dialoguer::Input::<String>::new().with_prompt(&question).allow_empty(true).interact_text()?;
+        if !answer.trim().is_empty() {
+            answers.push((question, answer.trim().to_string()));
+        }
+    }
+    Ok(prompt::with_answers(prompt, &answers))
+}
 
-    #[tokio::test]
-    async fn test_generate_with_fake_client() {
-        let fake = FakeClient;
-        let result = fake.generate("test prompt").await.unwrap();
-        assert_eq!(result, "chore: add unit tests");
+/// Generates `count` messages for `prompt` at once and asks which to use.
+/// Identical messages are only offered once, and all of them are added to
+/// `offered`.
+async fn pick_candidate(
+    chain: &llm::FallbackChain,
+    prompt: &str,
+    count: u8,
+    offered: &mut Vec<String>,
+) -> anyhow::Result<llm::Generation> {
+    if !console::user_attended() {
+        anyhow::bail!("Not asking which message to use without a terminal; leave out -n");
     }
+    let spinner = spinner(format!("Generating {} commit messages...", count))?;
+    let results = futures::future::join_all((0..count).map(|_| chain.generate(prompt))).await;
+    spinner.finish_and_clear();
 
-    #[test]
-    fn test_parse_response_json_success() {
-        let data = json!({
-            "candidates": [{
-                "content": {
-                    "parts": [{
-                        "text": "Commit message here"
-                    }]
-                }
-            }]
-        });
+    let mut candidates: Vec<llm::Generation> = Vec::new();
+    let mut last_error = None;
+    for result in results {
+        match result {
+            Ok(generation) if !candidates.iter().any(|c| c.message == generation.message) => candidates.push(generation),
+            Ok(_) => {}
+            Err(e) => last_error = Some(e),
+        }
+    }
+    if candidates.is_empty() {
+        return Err(last_error.unwrap_or_else(|| anyhow::anyhow!("No messages were generated")));
+    }
 
-        let result = GeminiClient::parse_response_json(&data).unwrap();
-        assert_eq!(result, "Commit message here");
+    println!();
+    for (i, candidate) in candidates.iter().enumerate() {
+        println!("{}", format!("[{}]", i + 1).bold());
+        println!("{}", candidate.message.cyan());
+        println!();
     }
+    offered.extend(candidates.iter().map(|candidate| candidate.message.clone()));
+    let labels: Vec<String> =
+        candidates.iter().enumerate().map(|(i, c)| format!("[{}] {}", i + 1, subject(&c.message))).collect();
+    let choice = dialoguer::Select::new()
+        .with_prompt("Pick a message")
+        .items(&labels)
+        .default(0)
+        .interact_opt()?
+        .ok_or_else(|| anyhow::anyhow!("No message picked"))?;
+    Ok(candidates.swap_remove(choice))
+}
 
-    #[test]
-    fn test_parse_response_json_missing_fields() {
-        let data = json!({
-            "wrong_key": []
-        });
+/// The `--json` form of a generated `message`, taken apart for scripts
+/// and editor plugins. Token counts are estimates, as in git notes.
+fn json_output(message: &str, provider: &str, model: Option<String>, prompt: &str) -> String {
+    let parts = trailers::split(message);
+    serde_json::json!({
+        "subject": parts.subject,
+        "body": parts.body,
+        "footers": parts.footers,
+        "provider": provider,
+        "model": model,
+        "tokens": {
+            "prompt": notes::estimate_tokens(prompt),
+            "output": notes::estimate_tokens(message),
+        },
+    })
+    .to_string()
+}
+
+/// The start of the prompt for a message about `diff`, or `description`:
+/// the user's prompt template filled in when there is one, or else the
+/// built-in prompt. A template shows `subjects` where it puts
+/// `{{ recent_commits }}`, so it takes them rather than leaving them to be
+/// added at the end.
+fn opening(
+    style: &str,
+    diff: Option<&str>,
+    description: Option<&str>,
+    template: Option<&std::path::Path>,
+    subjects: &mut Vec<String>,
+) -> anyhow::Result<String> {
+    Ok(match (template, description, diff) {
+        (Some(path), _, _) => {
+            let template = prompt::load_template(path)?;
+            prompt::from_template(&template, style, diff, description, &std::mem::take(subjects))?
+        }
+        (None, Some(description), _) => prompt::from_description(style, description),
+        (None, None, Some(diff)) => prompt::from_diff(style, diff),
+        (None, None, None) => unreachable!("a diff is read whenever there is no description"),
+    })
+}
 
-        let result = GeminiClient::parse_response_json(&data);
-        assert!(result.is_err());
+/// A diff piped in for `--stdin`, from any tool that writes unified diffs.
+fn stdin_diff() -> anyhow::Result<String> {
+    use std::io::{IsTerminal, Read};
+
+    let mut stdin = std::io::stdin();
+    if stdin.is_terminal() {
+        anyhow::bail!("--stdin reads a diff piped in, e.g. `git diff --cached | commitgen --stdin`");
+    }
+    let mut diff = String::new();
+    stdin.read_to_string(&mut diff).map_err(|e| anyhow::anyhow!("Failed to read the diff from stdin: {}", e))?;
+    if diff.trim().is_empty() {
+        anyhow::bail!("The diff on stdin is empty");
+    }
+    Ok(diff)
+}
+
+/// The description on stdin, when something is piped in; `None` for a
+/// terminal or empty input.
+fn stdin_description() -> anyhow::Result<Option<String>> {
+    use std::io::{IsTerminal, Read};
+
+    let mut stdin = std::io::stdin();
+    if stdin.is_terminal() {
+        return Ok(None);
+    }
+    let mut description = String::new();
+    stdin
+        .read_to_string(&mut description)
+        .map_err(|e| anyhow::anyhow!("Failed to read the description from stdin: {}", e))?;
+    let description = description.trim();
+    Ok((!description.is_empty()).then(|| description.to_string()))
+}
+
+/// One-line summaries of each file or area in `diff`, for a detailed body.
+/// A few are generated at a time to stay clear of rate limits.
+async fn summarise_areas(chain: &llm::FallbackChain, diff: &str) -> anyhow::Result<Vec<(String, String)>> {
+    use futures::StreamExt;
+
+    let areas = diff::areas(diff);
+    let spinner = spinner(format!("Summarising {} changed areas...", areas.len()))?;
+    let summaries: Vec<anyhow::Result<(String, String)>> = futures::stream::iter(areas)
+        .map(|area| async move {
+            let generation = chain.generate(&prompt::for_area_summary(&area.name, &area.diff)).await?;
+            Ok((area.name, subject(&generation.message).trim_end_matches('.').to_string()))
+        })
+        .buffered(4)
+        .collect()
+        .await;
+    spinner.finish_and_clear();
+    summaries.into_iter().collect()
+}
+
+/// What [`finish_message`] does to a generated message.
+struct Finish<'a> {
+    style: &'a str,
+    /// Add the ticket named by the branch.
+    ticket: bool,
+    breaking: breaking::Verdict,
+    co_authors: Vec<String>,
+    /// The identity to sign off as.
+    signoff: Option<String>,
+    max_subject_length: usize,
+    long_subject: wrap::LongSubject,
+    /// 0 leaves the body's lines alone.
+    wrap_column: usize,
+    shape: body::Shape,
+}
+
+/// Post-processing every generated message goes through before it is
+/// shown as final.
+fn finish_message(mut message: String, finish: &Finish, config: &Config) -> String {
+    if check::Style::from_name(finish.style).is_conventional_form() {
+        message = match &finish.breaking {
+            breaking::Verdict::Breaking(changes) => breaking::mark(&message, changes),
+            breaking::Verdict::NotBreaking => breaking::unmark(&message),
+            breaking::Verdict::Unknown => message,
+        };
+    }
+    message = finish.shape.apply(&message);
+
+    let template = config.ticket_template.as_deref().unwrap_or(ticket::DEFAULT_TEMPLATE);
+    if finish.ticket
+        && !template.is_empty()
+        && let Some(ticket) = git::current_branch().ok().flatten().and_then(|branch| ticket::find(&branch))
+    {
+        message = ticket::apply(&message, &ticket, template);
+    }
+    for co_author in &finish.co_authors {
+        message = trailers::append(&message, &format!("Co-authored-by: {}", co_author));
+    }
+    if let Some(identity) = &finish.signoff {
+        message = trailers::append(&message, &format!("Signed-off-by: {}", identity));
+    }
+    // A rewrite has already happened by now; whatever is still too long is cut.
+    if finish.long_subject != wrap::LongSubject::Keep {
+        message = wrap::with_subject(&message, &wrap::truncate_subject(subject(&message), finish.max_subject_length));
+    }
+    if finish.wrap_column > 0 {
+        message = wrap::wrap_body(&message, finish.wrap_column);
+    }
+    message
+}
+
+/// Asks the model for a shorter subject when `message`'s is over `max`
+/// columns. If that fails the message is returned as it was, for
+/// [`finish_message`] to truncate.
+async fn shorten_subject(chain: &llm::FallbackChain, message: String, max: usize) -> String {
+    let long = subject(&message);
+    if check::width(long) <= max {
+        return message;
+    }
+    let Ok(spinner) = spinner("Shortening the subject...") else {
+        return message;
+    };
+    let result = chain.generate(&prompt::for_shorter_subject(long, max)).await;
+    spinner.finish_and_clear();
+    match result {
+        Ok(generation) => match generation.message.lines().map(str::trim).find(|line| !line.is_empty()) {
+            Some(short) => wrap::with_subject(&message, short),
+            None => message,
+        },
+        Err(e) => {
+            tracing::warn!("couldn't shorten the subject: {}", e);
+            message
+        }
+    }
+}
+
+/// Points out where a generated message breaks its style's rules, so it
+/// can be fixed before committing rather than by a commit-msg hook after.
+fn warn_style_problems(message: &str, style: &str) {
+    let mut problems = check::check(message, check::Style::from_name(style), style::max_subject_length(style));
+    problems.extend(style::missing_footers(message, style));
+    for problem in problems {
+        eprintln!("{} {} ({})", "Warning:".yellow().bold(), problem.message, problem.hint.dimmed());
     }
+}
+
+/// Clears a streamed message off the terminal, leaving the cursor where
+/// it began, so the finished message can be printed in its place.
+fn erase(shown: &str) {
+    let term = console::Term::stdout();
+    let width = (term.size().1 as usize).max(1);
+    let rows: usize = shown
+        .split('\n')
+        .map(|line| console::measure_text_width(line).max(1).div_ceil(width))
+        .sum();
+    println!();
+    let _ = term.clear_last_lines(rows);
+}
+
+/// Takes `-C <path>`/`--repo <path>` off the front of the command line, as
+/// git does, so they can come before a subcommand (which clap otherwise
+/// won't allow alongside top-level arguments). Like git's, repeated ones
+/// each apply relative to the last.
+fn take_repo_args(mut argv: Vec<std::ffi::OsString>) -> (Vec<std::path::PathBuf>, Vec<std::ffi::OsString>) {
+    let mut repos = Vec::new();
+    while argv.len() > 2 {
+        let arg = argv[1].to_string_lossy();
+        if arg == "-C" || arg == "--repo" {
+            repos.push(argv.remove(2).into());
+            argv.remove(1);
+        } else if let Some(path) = arg.strip_prefix("--repo=") {
+            repos.push(path.into());
+            argv.remove(1);
+        } else {
+            break;
+        }
+    }
+    (repos, argv)
+}
+
+/// Shows what `diff` would send off the machine and asks before it goes,
+/// when any of `providers` is a cloud one. `yes`, or `confirm_send = false`
+/// in the config, sends it without asking.
+fn confirm_send(diff: &str, providers: &[String], yes: bool, config: &Config) -> anyhow::Result<()> {
+    if diff.trim().is_empty() {
+        return Ok(());
+    }
+    confirm_sending("the diff", &outbound::summary(diff), providers, yes, config)
+}
+
+/// [`confirm_send`] for what isn't a diff, such as commit messages:
+/// `what` is sent, and `summary` says how much of it there is.
+fn confirm_sending(what: &str, summary: &str, providers: &[String], yes: bool, config: &Config) -> anyhow::Result<()> {
+    let cloud: Vec<&str> = providers
+        .iter()
+        .map(String::as_str)
+        .filter(|name| !registry::is_local(name, config.endpoints.get(*name).map(String::as_str)))
+        .collect();
+    if yes || !config.confirm_send.unwrap_or(true) || cloud.is_empty() {
+        return Ok(());
+    }
+    if !console::user_attended_stderr() {
+        anyhow::bail!(
+            "Not sending {} to {} without asking, and there's no terminal to ask at; pass --yes, or set \
+            `confirm_send = false` in the config file",
+            what,
+            cloud.join(", ")
+        );
+    }
+    eprintln!("{}", format!("About to send {} to {}:", what, cloud.join(" or ")).bold());
+    eprintln!("{}", summary);
+    if !dialoguer::Confirm::new().with_prompt("Send it?").default(true).interact()? {
+        anyhow::bail!("Nothing was sent");
+    }
+    Ok(())
+}
+
+/// Asks a yes/no question on the terminal. Without one there is nobody to
+/// ask, so the caller has to opt in with `--yes` instead.
+fn confirm(question: &str) -> anyhow::Result<bool> {
+    if !console::user_attended_stderr() {
+        anyhow::bail!("Not asking for confirmation without a terminal; pass --yes to go ahead");
+    }
+    Ok(dialoguer::Confirm::new()
+        .with_prompt(question)
+        .default(true)
+        .interact()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
     #[test]
-    fn test_parse_response_json_empty_candidates() {
-        let data = json!({
-            "candidates": []
-        });
+    fn test_json_output() {
+        let json = json_output("feat: add x\n\nBecause.\n\nRefs: #1", "gemini", Some("gemini-2.5-flash".into()), "12345678");
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["subject"], "feat: add x");
+        assert_eq!(value["body"], "Because.");
+        assert_eq!(value["footers"], serde_json::json!(["Refs: #1"]));
+        assert_eq!(value["model"], "gemini-2.5-flash");
+        assert_eq!(value["tokens"]["prompt"], 2);
+    }
+
+    #[test]
+    fn test_take_repo_args() {
+        let argv = |args: &[&str]| args.iter().map(std::ffi::OsString::from).collect::<Vec<_>>();
+        let (repos, rest) = take_repo_args(argv(&["commitgen", "-C", "a", "--repo=b", "models", "-C", "c"]));
+        assert_eq!(repos, [std::path::PathBuf::from("a"), "b".into()]);
+        assert_eq!(rest, argv(&["commitgen", "models", "-C", "c"]));
 
-        let result = GeminiClient::parse_response_json(&data);
-        assert!(result.is_err());
+        let (repos, rest) = take_repo_args(argv(&["commitgen", "-C"]));
+        assert!(repos.is_empty());
+        assert_eq!(rest, argv(&["commitgen", "-C"]));
     }
 
     #[test]
-    fn test_parse_response_json_text_not_string() {
-        let data = json!({
-            "candidates": [{
-                "content": {
-                    "parts": [{
-                        "text": 1234
-                    }]
-                }
-            }]
-        });
+    fn test_cli_commit_implies_committing() {
+        assert!(cli().try_get_matches_from(["commitgen", "commit", "--yes", "--edit"]).is_ok());
+        assert!(cli().try_get_matches_from(["commitgen", "generate", "--edit"]).is_err());
+        assert!(cli().try_get_matches_from(["commitgen", "generate", "--yes"]).is_ok());
+        let args = Cli::from_arg_matches(&cli().get_matches_from(["commitgen", "add login", "-c"])).unwrap();
+        assert!(args.command.is_none());
+        assert_eq!(args.generate.description.as_deref(), Some("add login"));
+    }
 
-        let result = GeminiClient::parse_response_json(&data);
-        assert!(result.is_err());
+    #[test]
+    fn test_format_tokens() {
+        assert_eq!(format_tokens(1048576), "1M");
+        assert_eq!(format_tokens(2_000_000), "2M");
+        assert_eq!(format_tokens(131072), "128k");
+        assert_eq!(format_tokens(128000), "128k");
+        assert_eq!(format_tokens(4097), "4097");
     }
 }
-
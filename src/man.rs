@@ -0,0 +1,82 @@
+use clap_mangen::Man;
+use clap_mangen::roff::{Roff, bold, roman};
+
+/// Man pages in roff, rendered by clap_mangen from the CLI definition so
+/// they can't drift from `--help`: one for the command itself and one for
+/// each subcommand, named as git names its own (`commitgen-hook-install.1`).
+pub fn pages(command: &clap::Command) -> anyhow::Result<Vec<(String, String)>> {
+    let mut command = command.clone().disable_help_subcommand(true);
+    command.build();
+    let source = format!("{} {}", command.get_name(), command.get_version().unwrap_or_default());
+    let mut pages = Vec::new();
+    collect(&command, None, &source, &mut pages)?;
+    Ok(pages)
+}
+
+fn collect(
+    command: &clap::Command,
+    parent: Option<&str>,
+    source: &str,
+    pages: &mut Vec<(String, String)>,
+) -> anyhow::Result<()> {
+    let man = Man::new(command.clone()).source(source);
+    let mut page = Vec::new();
+    man.render(&mut page)?;
+    let mut page = String::from_utf8(page)?;
+    // Plain roff, since the preamble `render` starts with is already there.
+    page.push_str(&see_also(parent).to_roff());
+    pages.push((man.get_filename(), page));
+
+    let name = command.get_display_name().unwrap_or_else(|| command.get_name());
+    for sub in command.get_subcommands().filter(|sub| !sub.is_hide_set()) {
+        collect(sub, Some(name), source, pages)?;
+    }
+    Ok(())
+}
+
+/// git-commit(1), and the page of the command a subcommand belongs to.
+fn see_also(parent: Option<&str>) -> Roff {
+    let mut text = vec![bold("git-commit"), roman("(1)")];
+    if let Some(parent) = parent {
+        text.extend([roman(", "), bold(parent), roman("(1)")]);
+    }
+    let mut roff = Roff::new();
+    roff.control("SH", ["SEE ALSO"]).text(text);
+    roff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::{Arg, Command};
+
+    fn command() -> Command {
+        Command::new("tool")
+            .version("1.2.3")
+            .about("Does things")
+            .arg(Arg::new("style").long("style").short('s').value_name("NAME").help("Message style, e.g. .conventional"))
+            .subcommand(
+                Command::new("hook")
+                    .about("Manage the hook")
+                    .subcommand(Command::new("install").about("Install it").arg(Arg::new("force").long("force").num_args(0))),
+            )
+            .subcommand(Command::new("secret").hide(true))
+    }
+
+    #[test]
+    fn test_pages() {
+        let pages = pages(&command()).unwrap();
+        let names: Vec<&str> = pages.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, ["tool.1", "tool-hook.1", "tool-hook-install.1"]);
+
+        let main = &pages[0].1;
+        assert!(main.contains(".TH tool 1  \"tool 1.2.3\" \n.SH NAME\ntool \\- Does things\n"), "{}", main);
+        assert!(main.contains("\\fB\\-s\\fR, \\fB\\-\\-style\\fR \\fI<NAME>\\fR\nMessage style, e.g. .conventional\n"), "{}", main);
+        assert!(main.contains("tool\\-hook(1)\nManage the hook\n"), "{}", main);
+        assert!(!main.contains("secret"));
+        assert!(main.ends_with(".SH \"SEE ALSO\"\n\\fBgit\\-commit\\fR(1)\n"), "{}", main);
+        let install = &pages[2].1;
+        assert!(install.contains(".SH SYNOPSIS\n\\fBtool hook install\\fR"), "{}", install);
+        assert!(install.contains("\\fBtool\\-hook\\fR(1)"), "{}", install);
+    }
+}
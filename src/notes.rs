@@ -0,0 +1,91 @@
+use sha2::{Digest, Sha256};
+
+use crate::git;
+
+/// Notes live under their own ref, `refs/notes/commitgen`, so they don't
+/// mix with the user's own notes. Show them with
+/// `git log --notes=commitgen`.
+pub const REF: &str = "commitgen";
+
+/// How a commit's message was generated.
+#[derive(Debug, PartialEq)]
+pub struct Metadata {
+    pub provider: String,
+    pub model: Option<String>,
+    pub prompt_sha256: String,
+    /// Providers don't all report usage, so token counts are estimated
+    /// from the text at about four bytes a token.
+    pub prompt_tokens: usize,
+    pub output_tokens: usize,
+}
+
+impl Metadata {
+    pub fn new(provider: &str, model: Option<String>, prompt: &str, message: &str) -> Self {
+        Self {
+            provider: provider.to_string(),
+            model,
+            prompt_sha256: hex::encode(Sha256::digest(prompt.as_bytes())),
+            prompt_tokens: estimate_tokens(prompt),
+            output_tokens: estimate_tokens(message),
+        }
+    }
+
+    /// The note's text, one `Key: value` line per field.
+    pub fn render(&self) -> String {
+        let mut note = format!("Generated-by: commitgen {}\nProvider: {}\n", env!("CARGO_PKG_VERSION"), self.provider);
+        if let Some(model) = &self.model {
+            note.push_str(&format!("Model: {}\n", model));
+        }
+        note.push_str(&format!(
+            "Prompt-SHA256: {}\nEstimated-prompt-tokens: {}\nEstimated-output-tokens: {}\n",
+            self.prompt_sha256, self.prompt_tokens, self.output_tokens
+        ));
+        note
+    }
+}
+
+pub fn estimate_tokens(text: &str) -> usize {
+    text.len().div_ceil(4)
+}
+
+/// Attaches `metadata` to `rev`, replacing any earlier commitgen note.
+pub fn attach(rev: &str, metadata: &Metadata) -> anyhow::Result<()> {
+    git::run(&["notes", "--ref", REF, "add", "--force", "--message", &metadata.render(), rev])?;
+    Ok(())
+}
+
+/// The commitgen note on `rev`, if it has one.
+pub fn read(rev: &str) -> anyhow::Result<Option<String>> {
+    // `git notes show` fails the same way for a missing note as for
+    // anything else, so check the list first.
+    if git::run(&["notes", "--ref", REF, "list", rev]).is_err() {
+        return Ok(None);
+    }
+    Ok(Some(git::run(&["notes", "--ref", REF, "show", rev])?))
+}
+
+/// Whether `note` is one commitgen wrote with [`attach`].
+pub fn is_generated(note: &str) -> bool {
+    note.starts_with("Generated-by: commitgen")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let metadata = Metadata::new("ollama", Some("qwen2.5-coder".into()), "abc", "feat: add notes");
+        assert_eq!(
+            metadata.render(),
+            format!(
+                "Generated-by: commitgen {}\nProvider: ollama\nModel: qwen2.5-coder\n\
+                Prompt-SHA256: ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad\n\
+                Estimated-prompt-tokens: 1\nEstimated-output-tokens: 4\n",
+                env!("CARGO_PKG_VERSION")
+            )
+        );
+        assert!(is_generated(&metadata.render()));
+        assert!(!is_generated("Reviewed in standup\n"));
+    }
+}
@@ -0,0 +1,110 @@
+use crate::diff::split_files;
+use crate::ignore;
+use crate::prompt::MAX_DIFF_BYTES;
+use crate::redact;
+
+/// One file in a diff about to be sent, and how much of it goes.
+#[derive(Debug, PartialEq)]
+pub struct File {
+    pub path: String,
+    pub added: usize,
+    pub removed: usize,
+    /// Why its contents aren't sent as they are, if they aren't.
+    pub withheld: Option<&'static str>,
+    /// Secrets replaced with placeholders.
+    pub redacted: usize,
+}
+
+/// The files in a prompt-ready diff (from [`crate::diff::for_prompt`]),
+/// with their line counts and which were withheld or condensed.
+pub fn files(diff: &str) -> Vec<File> {
+    split_files(diff)
+        .into_iter()
+        .map(|(path, section)| {
+            let mut file = File { path, added: 0, removed: 0, withheld: None, redacted: 0 };
+            for line in section.lines().skip(1) {
+                file.redacted += line.matches(redact::PLACEHOLDER).count();
+                if line.starts_with("+++") || line.starts_with("---") {
+                    continue;
+                } else if line.starts_with('+') {
+                    file.added += 1;
+                } else if line.starts_with('-') {
+                    file.removed += 1;
+                } else if line == format!("[contents excluded by {}]", ignore::FILE_NAME) {
+                    file.withheld = Some("excluded by .commitgenignore");
+                } else if file.withheld.is_none() && line.starts_with('[') && line.ends_with(']') && !line.starts_with("[moved") {
+                    file.withheld = Some("generated, summarised in a line");
+                }
+            }
+            file
+        })
+        .collect()
+}
+
+/// What sending `diff` means, one line per file and then the totals, for
+/// the question before it goes.
+pub fn summary(diff: &str) -> String {
+    let files = files(diff);
+    let mut lines: Vec<String> = files
+        .iter()
+        .map(|file| match file.withheld {
+            Some(why) => format!("  {} ({})", file.path, why),
+            None if file.redacted > 0 => {
+                format!("  {} (+{} -{}, {})", file.path, file.added, file.removed, secrets(file.redacted))
+            }
+            None => format!("  {} (+{} -{})", file.path, file.added, file.removed),
+        })
+        .collect();
+    let added: usize = files.iter().map(|file| file.added).sum();
+    let removed: usize = files.iter().map(|file| file.removed).sum();
+    let withheld = files.iter().filter(|file| file.withheld.is_some()).count();
+    let redacted: usize = files.iter().map(|file| file.redacted).sum();
+    let mut total = format!(
+        "{} file{}, +{} -{} lines",
+        files.len(),
+        if files.len() == 1 { "" } else { "s" },
+        added,
+        removed
+    );
+    if withheld > 0 {
+        total.push_str(&format!(", {} withheld or summarised", withheld));
+    }
+    if redacted > 0 {
+        total.push_str(&format!(", {}", secrets(redacted)));
+    }
+    if diff.len() > MAX_DIFF_BYTES {
+        total.push_str(&format!(", cut off at {} KB", MAX_DIFF_BYTES / 1000));
+    }
+    lines.push(total);
+    lines.join("\n")
+}
+
+fn secrets(count: usize) -> String {
+    format!("{} secret{} redacted", count, if count == 1 { "" } else { "s" })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DIFF: &str = "diff --git a/src/a.rs b/src/a.rs\n--- a/src/a.rs\n+++ b/src/a.rs\n@@ -1,2 +1,2 @@\n-old\n+new\n+more = \"[REDACTED aws-key]\"\n\
+        diff --git a/secrets.env b/secrets.env\n[contents excluded by .commitgenignore]\n\
+        diff --git a/Cargo.lock b/Cargo.lock\n[Cargo.lock: 1 package changed (serde)]\n";
+
+    #[test]
+    fn test_files() {
+        let files = files(DIFF);
+        assert_eq!(files[0], File { path: "src/a.rs".to_string(), added: 2, removed: 1, withheld: None, redacted: 1 });
+        assert_eq!(files[1].withheld, Some("excluded by .commitgenignore"));
+        assert_eq!(files[2].withheld, Some("generated, summarised in a line"));
+    }
+
+    #[test]
+    fn test_summary() {
+        assert_eq!(
+            summary(DIFF),
+            "  src/a.rs (+2 -1, 1 secret redacted)\n  secrets.env (excluded by .commitgenignore)\n  Cargo.lock (generated, summarised in a line)\n\
+            3 files, +2 -1 lines, 2 withheld or summarised, 1 secret redacted"
+        );
+    }
+}
@@ -0,0 +1,81 @@
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Writes `message` to `path` with a trailing newline, through a temporary
+/// file beside it and a rename, so whoever reads `path` (git reading a
+/// hook's message file, an editor plugin) never sees half a message.
+pub fn write(path: &Path, message: &str) -> anyhow::Result<()> {
+    replace(path, message, false)
+}
+
+/// As [`write`], for files only their owner should read: on unix the
+/// temporary file is created readable by no one else, before anything is
+/// written to it.
+pub fn write_private(path: &Path, contents: &str) -> anyhow::Result<()> {
+    replace(path, contents, true)
+}
+
+fn replace(path: &Path, message: &str, private: bool) -> anyhow::Result<()> {
+    let temporary = temporary_path(path);
+    let result = (|| -> std::io::Result<()> {
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        if private {
+            use std::os::unix::fs::OpenOptionsExt;
+            // A leftover file would keep its own mode.
+            let _ = std::fs::remove_file(&temporary);
+            options.mode(0o600);
+        }
+        let mut file = options.open(&temporary)?;
+        writeln!(file, "{}", message.trim_end())?;
+        file.sync_all()?;
+        if !private && let Ok(metadata) = std::fs::metadata(path) {
+            std::fs::set_permissions(&temporary, metadata.permissions())?;
+        }
+        std::fs::rename(&temporary, path)
+    })();
+    if let Err(e) = result {
+        let _ = std::fs::remove_file(&temporary);
+        anyhow::bail!("Failed to write {}: {}", path.display(), e);
+    }
+    Ok(())
+}
+
+/// `.NAME.commitgen-PID` in the same directory, so the rename doesn't cross
+/// file systems.
+fn temporary_path(path: &Path) -> PathBuf {
+    let name = path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+    path.with_file_name(format!(".{}.commitgen-{}", name, std::process::id()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_replaces_contents() {
+        let dir = std::env::temp_dir().join(format!("commitgen-output-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("COMMIT_EDITMSG");
+        std::fs::write(&path, "# a much longer template the message replaces\n").unwrap();
+        write(&path, "feat: add x\n\n").unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "feat: add x\n");
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_write_private() {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = std::env::temp_dir().join(format!("commitgen-private-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("credentials.enc");
+        std::fs::write(&path, "old").unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o644)).unwrap();
+        write_private(&path, "{}").unwrap();
+        assert_eq!(std::fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,60 @@
+use crate::git;
+
+/// The branch a pull request would target: the remote's default branch
+/// when `origin/HEAD` is set, otherwise a local `main` or `master`.
+pub fn default_base() -> anyhow::Result<String> {
+    if let Ok(head) = git::run(&["symbolic-ref", "--quiet", "--short", "refs/remotes/origin/HEAD"]) {
+        let head = head.trim();
+        if !head.is_empty() {
+            return Ok(head.to_string());
+        }
+    }
+    for candidate in ["main", "master"] {
+        if git::rev_exists(candidate)? {
+            return Ok(candidate.to_string());
+        }
+    }
+    anyhow::bail!("Couldn't work out the base branch; pass --base")
+}
+
+/// The messages of the commits on HEAD that aren't on `base`, oldest first.
+pub fn messages(base: &str) -> anyhow::Result<Vec<String>> {
+    let messages = git::messages(&format!("{}..HEAD", base))?;
+    if messages.is_empty() {
+        anyhow::bail!("HEAD has no commits that aren't already on {}", base);
+    }
+    Ok(messages)
+}
+
+/// Everything HEAD changed since it branched off `base`.
+pub fn diff(base: &str) -> anyhow::Result<String> {
+    git::merge_base_diff(base, "HEAD")
+}
+
+/// Splits the model's reply into a title (its first line) and the body
+/// after it. Markdown heading marks on the title are dropped.
+pub fn split(reply: &str) -> (String, String) {
+    let reply = reply.trim();
+    let (title, body) = reply.split_once('\n').unwrap_or((reply, ""));
+    let title = title.trim().trim_start_matches('#').trim();
+    let title = title.strip_prefix("Title:").unwrap_or(title).trim();
+    (title.to_string(), body.trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split() {
+        let (title, body) = split("Add login support\n\n## Summary\nUsers can log in.\n");
+        assert_eq!(title, "Add login support");
+        assert_eq!(body, "## Summary\nUsers can log in.");
+    }
+
+    #[test]
+    fn test_split_strips_heading_and_label() {
+        assert_eq!(split("# Title: Add login").0, "Add login");
+        assert_eq!(split("Add login"), ("Add login".to_string(), String::new()));
+    }
+}
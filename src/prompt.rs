@@ -0,0 +1,779 @@
+use std::collections::BTreeMap;
+
+use crate::body::Shape;
+use crate::breaking::Verdict;
+use crate::template::Template;
+use crate::{check, git, scope, style, workspace};
+
+/// How many recent subjects are shown as examples unless configured.
+pub const DEFAULT_EXAMPLES: usize = 10;
+
+/// Per-file diffs for a detailed body are cut off sooner than whole ones;
+/// a one-line summary doesn't need every hunk.
+pub const MAX_AREA_DIFF_BYTES: usize = 20_000;
+
+/// Diffs beyond this many bytes are cut off; the start of a diff says the
+/// most about what changed, and huge prompts are slow and costly.
+pub const MAX_DIFF_BYTES: usize = 100_000;
+
+/// The built-in prompt for a commit message, rendered by the same engine
+/// and with the same variables as a user's `prompt_template`.
+pub const DEFAULT_TEMPLATE: &str = r#"You are an expert programmer writing a git commit message.
+{% if diff %}
+Your task is to generate a single, git commit message in the '{{ style }}' style for the changes in the following diff.
+{% else %}
+Your task is to generate a single, git commit message in the '{{ style }}' style for the following change description.
+{% endif %}
+
+{% if style_rules %}
+{{ style_rules }}
+
+{% endif %}
+{% if scopes %}
+If the style uses a scope, pick the one that fits best from these, taken from the paths changed: {{ scopes }}.
+
+{% endif %}
+{% if packages %}
+This change spans several packages of the workspace ({{ packages }}). Say in the body what changes in each of them.
+
+{% endif %}
+VERY IMPORTANT: Your entire response must be only the commit message itself. Do not include any surrounding text, explanations, apologies, or markdown formatting like ```.
+
+{% if diff %}
+Diff:
+{{ diff }}{% else %}
+Change Description: "{{ description }}"{% endif %}
+"#;
+
+/// The variables a `prompt_template` can use.
+pub const TEMPLATE_VARIABLES: &[&str] =
+    &["diff", "description", "files", "branch", "recent_commits", "style", "style_rules", "scopes", "packages"];
+
+pub fn from_description(style: &str, description: &str) -> String {
+    render(&default_template(), style, None, Some(description), &[]).expect("the built-in prompt renders")
+}
+
+/// Candidate scopes are worked out from the paths in `diff`, so a style
+/// that uses scopes gets real ones instead of invented ones.
+pub fn from_diff(style: &str, diff: &str) -> String {
+    render(&default_template(), style, Some(diff), None, &[]).expect("the built-in prompt renders")
+}
+
+fn default_template() -> Template {
+    Template::parse(DEFAULT_TEMPLATE, TEMPLATE_VARIABLES).expect("the built-in prompt parses")
+}
+
+/// Reads the user's prompt template at `path`.
+pub fn load_template(path: &std::path::Path) -> anyhow::Result<Template> {
+    Template::load(path, TEMPLATE_VARIABLES)
+}
+
+/// The user's `template` filled in for the change in `diff`, or described
+/// by `description`. The recent `subjects` are only shown where it puts
+/// `{{ recent_commits }}`.
+pub fn from_template(
+    template: &Template,
+    style: &str,
+    diff: Option<&str>,
+    description: Option<&str>,
+    subjects: &[String],
+) -> anyhow::Result<String> {
+    Ok(render(template, style, diff, description, subjects)?.trim_end().to_string())
+}
+
+fn render(
+    template: &Template,
+    style: &str,
+    diff: Option<&str>,
+    description: Option<&str>,
+    subjects: &[String],
+) -> anyhow::Result<String> {
+    let diff = diff.unwrap_or_default();
+    let packages = workspace::discover();
+    let touched = scope::packages_touched(diff, &packages);
+    let values = BTreeMap::from([
+        ("diff", truncate(diff, MAX_DIFF_BYTES)),
+        ("description", description.unwrap_or_default().to_string()),
+        ("files", scope::changed_paths(diff).join("\n")),
+        ("branch", git::current_branch().ok().flatten().unwrap_or_default()),
+        ("recent_commits", subjects.join("\n")),
+        ("style", style.to_string()),
+        ("style_rules", style::instructions(style).trim_end().to_string()),
+        ("scopes", scope::candidates(diff, &packages).join(", ")),
+        // Only worth a mention when there's more than one.
+        ("packages", if touched.len() > 1 { touched.join(", ") } else { String::new() }),
+    ]);
+    template.render(&values)
+}
+
+/// Adds the repository's recent commit subjects to `prompt` as examples,
+/// so the message follows the project's own tense, casing and scopes.
+pub fn with_examples(prompt: String, subjects: &[String]) -> String {
+    if subjects.is_empty() {
+        return prompt;
+    }
+    let subjects: Vec<String> = subjects.iter().map(|subject| format!("- {}", subject)).collect();
+    format!(
+        "{prompt}\n\n\
+        Recent commit subjects in this repository, newest first. Follow their conventions (tense, capitalisation, \
+        scope names) where they don't conflict with the style:\n{subjects}",
+        prompt = prompt,
+        subjects = subjects.join("\n")
+    )
+}
+
+/// Asks again for the message `prompt` asked for, given the messages
+/// suggested so far and what the user asked to change about each.
+pub fn for_refinement(prompt: &str, turns: &[(String, String)]) -> String {
+    let turns: Vec<String> = turns
+        .iter()
+        .enumerate()
+        .map(|(i, (message, instruction))| format!("Message {}:\n{}\n\nRequest: {}", i + 1, message, instruction))
+        .collect();
+    format!(
+        "{prompt}\n\n\
+        You have already suggested messages for this, and the user asked for changes to each:\n\n\
+        {turns}\n\n\
+        Write the message again, following the latest request and keeping to the earlier ones where they still \
+        apply. VERY IMPORTANT: Your entire response must still be only the commit message itself.",
+        prompt = prompt,
+        turns = turns.join("\n\n")
+    )
+}
+
+/// Asks for `subject` again in at most `max` columns, for a model whose
+/// first attempt ran long.
+pub fn for_shorter_subject(subject: &str, max: usize) -> String {
+    format!(
+        "This git commit subject line is {width} columns long, over the limit of {max}:\n\n{subject}\n\n\
+        Rewrite it to fit in {max} columns (CJK characters count as two) without losing what it says the change \
+        does. Keep any prefix, such as a type, scope or emoji, exactly as it is, and keep the language it is written \
+        in. VERY IMPORTANT: Your entire response must be only the new subject line.",
+        width = check::width(subject),
+        max = max,
+        subject = subject
+    )
+}
+
+/// Adds what `shape` allows after the subject, when it limits anything.
+pub fn with_shape(prompt: String, shape: &Shape) -> String {
+    let rules = shape.instructions();
+    if rules.is_empty() {
+        return prompt;
+    }
+    format!("{}\n\n{}", prompt, rules)
+}
+
+/// Asks what the model would need to know, beyond `prompt`'s diff or
+/// description, to explain the change well: at most `max` questions, or
+/// `NONE`.
+pub fn for_questions(prompt: &str, max: u8) -> String {
+    format!(
+        "{prompt}\n\n\
+        Before writing the message: the author of the change is here to answer questions. If there is something the \
+        message should explain but the change itself doesn't tell you, such as why something was removed or what \
+        prompted the change, ask about it. Ask at most {max} short questions, only ones whose answers would improve \
+        the message. Instead of the commit message, reply with just the questions, one per line, or with the single \
+        word NONE if the change needs no explaining.",
+        prompt = prompt,
+        max = max
+    )
+}
+
+/// Adds the author's answers to the model's questions about the change.
+pub fn with_answers(prompt: String, answers: &[(String, String)]) -> String {
+    if answers.is_empty() {
+        return prompt;
+    }
+    let answers: Vec<String> = answers.iter().map(|(question, answer)| format!("Q: {}\nA: {}", question, answer)).collect();
+    format!(
+        "{prompt}\n\n\
+        The author answered these questions about the change. Use the answers to explain why the change was made:\n\n\
+        {answers}",
+        prompt = prompt,
+        answers = answers.join("\n\n")
+    )
+}
+
+/// Has the model fill in the repository's commit message template rather
+/// than writing a message of its own shape.
+pub fn with_template(prompt: String, template: Option<&str>) -> String {
+    let Some(template) = template else {
+        return prompt;
+    };
+    format!(
+        "{prompt}\n\n\
+        This repository has a commit message template. Write the message by filling it in: keep its section headers \
+        and its comment lines (those starting with #) exactly as they are and in the same order, and put the message's \
+        parts where the template asks for them. Leave a section empty rather than inventing something for it.\n\
+        Template:\n{template}",
+        prompt = prompt,
+        template = template.trim_end()
+    )
+}
+
+/// Tells the model whether the change breaks compatibility, and which
+/// public items it breaks when that was detected.
+pub fn with_breaking(prompt: String, verdict: &Verdict) -> String {
+    let note = match verdict {
+        Verdict::Unknown => return prompt,
+        Verdict::NotBreaking => "This is not a breaking change; don't mark it as one.".to_string(),
+        Verdict::Breaking(changes) => {
+            let mut note = "This is a breaking change".to_string();
+            if changes.is_empty() {
+                note.push('.');
+            } else {
+                note.push_str(" to the public API:");
+                for change in changes {
+                    note.push_str(&format!("\n- {}", change.describe()));
+                }
+            }
+            note.push_str(
+                "\nMark it as breaking (in Conventional Commits, a `!` after the type or scope and a `BREAKING CHANGE:` \
+                footer) and explain in the footer how users should migrate.",
+            );
+            note
+        }
+    };
+    format!("{}\n\n{}", prompt, note)
+}
+
+/// Language names for the codes `--lang` is most likely to get; anything
+/// else is passed to the model as given.
+const LANGUAGES: &[(&str, &str)] = &[
+    ("ar", "Arabic"),
+    ("cs", "Czech"),
+    ("da", "Danish"),
+    ("de", "German"),
+    ("el", "Greek"),
+    ("en", "English"),
+    ("es", "Spanish"),
+    ("fi", "Finnish"),
+    ("fr", "French"),
+    ("he", "Hebrew"),
+    ("hi", "Hindi"),
+    ("hu", "Hungarian"),
+    ("id", "Indonesian"),
+    ("it", "Italian"),
+    ("ja", "Japanese"),
+    ("ko", "Korean"),
+    ("nl", "Dutch"),
+    ("no", "Norwegian"),
+    ("pl", "Polish"),
+    ("pt", "Portuguese"),
+    ("pt-br", "Brazilian Portuguese"),
+    ("ro", "Romanian"),
+    ("ru", "Russian"),
+    ("sv", "Swedish"),
+    ("th", "Thai"),
+    ("tr", "Turkish"),
+    ("uk", "Ukrainian"),
+    ("vi", "Vietnamese"),
+    ("zh", "Chinese"),
+    ("zh-cn", "Simplified Chinese"),
+    ("zh-hans", "Simplified Chinese"),
+    ("zh-tw", "Traditional Chinese"),
+    ("zh-hant", "Traditional Chinese"),
+];
+
+/// `lang` as the model should read it: `ja` becomes `Japanese`, and
+/// `ja_JP` or `ja-JP` fall back to their language.
+fn language_name(lang: &str) -> String {
+    let code = lang.trim().to_lowercase().replace('_', "-");
+    let find = |code: &str| LANGUAGES.iter().find(|(c, _)| *c == code).map(|(_, name)| name.to_string());
+    find(&code)
+        .or_else(|| code.split_once('-').and_then(|(language, _)| find(language)))
+        .unwrap_or_else(|| lang.trim().to_string())
+}
+
+/// Chinese, Japanese and Korean text takes two columns a character, which
+/// is how subject limits are counted.
+fn is_wide(language: &str) -> bool {
+    ["Chinese", "Japanese", "Korean"].iter().any(|wide| language.contains(wide))
+}
+
+/// Asks for the message in `lang` (a code such as `ja`, or a language
+/// name) instead of English. The style's keywords and git's trailers stay
+/// as they are, since tools parse them.
+pub fn with_language(prompt: String, lang: Option<&str>, max_subject_length: usize) -> String {
+    let Some(lang) = lang.filter(|lang| !lang.trim().is_empty()) else {
+        return prompt;
+    };
+    let language = language_name(lang);
+    if language.eq_ignore_ascii_case("English") {
+        return prompt;
+    }
+    let mut note = format!(
+        "Write the commit message in {language}. Keep the style's keywords (such as commit types and scopes), \
+        trailer names such as `BREAKING CHANGE` or `Signed-off-by`, and identifiers from the code exactly as they \
+        are, untranslated.",
+    );
+    if is_wide(&language) {
+        note.push_str(&format!(
+            " Each {language} character counts as two columns towards the subject's limit of {max_subject_length}, \
+            so a subject written in it fits in about {} characters.",
+            max_subject_length / 2
+        ));
+    }
+    format!("{}\n\n{}", prompt, note)
+}
+
+/// Asks for a one-line summary of the changes to one file or area, for a
+/// bullet in a detailed commit body.
+pub fn for_area_summary(area: &str, diff: &str) -> String {
+    format!(
+        "You are an expert programmer summarising part of a git commit.\n\
+        Your task is to describe what the following changes to '{area}' do in one short line, under 80 characters, \
+        in the imperative mood and without a trailing period.\n\n\
+        VERY IMPORTANT: Your entire response must be only that line. Do not include any surrounding text, explanations, \
+        or markdown formatting.\n\n\
+        Diff:\n{diff}",
+        area = area,
+        diff = truncate(diff, MAX_AREA_DIFF_BYTES)
+    )
+}
+
+/// Asks for just the subject line of a commit whose body lists
+/// `summaries`, one per file or area.
+pub fn subject_from_summaries(style: &str, summaries: &[(String, String)]) -> String {
+    let summaries: Vec<String> = summaries
+        .iter()
+        .map(|(area, summary)| format!("- {}: {}", area, summary))
+        .collect();
+    format!(
+        "You are an expert programmer writing a git commit message.\n\
+        Your task is to generate only the subject line, in the '{style}' style, for a commit made up of the following changes.\n\n\
+        {rules}\
+        VERY IMPORTANT: Your entire response must be only the subject line itself. Do not include a body or any \
+        surrounding text, explanations, apologies, or markdown formatting like ```.\n\n\
+        Changes:\n{summaries}",
+        style = style,
+        rules = style::instructions(style),
+        summaries = summaries.join("\n")
+    )
+}
+
+/// Asks for a better message for a commit being amended, showing the
+/// message it has now.
+pub fn for_amend(style: &str, diff: &str, current: &str) -> String {
+    format!(
+        "{base}\n\n\
+        The commit currently has this message, which may be inaccurate or incomplete; write an improved one:\n{current}",
+        base = from_diff(style, diff),
+        current = current
+    )
+}
+
+/// Asks for one message covering several commits about to be squashed,
+/// given their combined diff and their individual messages.
+pub fn for_squash(style: &str, diff: &str, messages: &[String]) -> String {
+    let messages: Vec<String> = messages
+        .iter()
+        .enumerate()
+        .map(|(i, message)| format!("{}. {}", i + 1, message.replace('\n', "\n   ")))
+        .collect();
+    format!(
+        "{base}\n\n\
+        These commits are being squashed into one. Their messages, oldest first, were:\n{messages}\n\n\
+        Write a single coherent message describing the combined change, not a list of the individual commits.",
+        base = from_diff(style, diff),
+        messages = messages.join("\n")
+    )
+}
+
+/// Asks for a pull request title and markdown description covering the
+/// commits on a branch and their combined diff.
+pub fn for_pr(diff: &str, messages: &[String]) -> String {
+    let messages: Vec<String> = messages
+        .iter()
+        .map(|message| format!("- {}", message.replace('\n', "\n  ")))
+        .collect();
+    format!(
+        "You are an expert programmer opening a pull request.\n\
+        Your task is to write a title and a description for a pull request containing the commits and diff below.\n\n\
+        Put the title, under 72 characters and without markdown, on the first line. Leave a blank line, then write the \
+        description in markdown with these sections:\n\
+        ## Summary\nOne or two sentences on what the change does and why.\n\
+        ## Changes\nA bulleted list of the notable changes.\n\
+        ## Testing\nHow the change was or should be tested.\n\n\
+        VERY IMPORTANT: Your entire response must be only the title and description. Do not include any surrounding text \
+        or wrap the response in a code block.\n\n\
+        Commits, oldest first:\n{messages}\n\n\
+        Diff:\n{diff}",
+        messages = messages.join("\n"),
+        diff = truncate(diff, MAX_DIFF_BYTES)
+    )
+}
+
+/// Asks for user-facing release notes, rewriting the commits in a release
+/// into feature and bug fix bullets.
+pub fn for_release_notes(release: &str, messages: &[String], stat: &str) -> String {
+    let messages: Vec<String> = messages
+        .iter()
+        .map(|message| format!("- {}", message.replace('\n', "\n  ")))
+        .collect();
+    format!(
+        "You are an expert programmer writing the release notes for {release}, to be published as a GitHub Release.\n\
+        Your task is to turn the commits below into notes for the people who use the project, not the people who work on it: \
+        describe what they can now do or what no longer goes wrong, merge related commits into one bullet, and leave out \
+        refactoring, tests, CI and other internal changes.\n\n\
+        Write markdown with a `## Features` section and a `## Bug fixes` section of bullets, plus `## Breaking changes` \
+        before them when something requires users to act. Omit any section that would be empty.\n\n\
+        VERY IMPORTANT: Your entire response must be only the release notes. Do not include any surrounding text or \
+        wrap the response in a code block.\n\n\
+        Commits, oldest first:\n{messages}\n\n\
+        Files changed:\n{stat}",
+        release = release,
+        messages = messages.join("\n"),
+        stat = truncate(stat, MAX_DIFF_BYTES)
+    )
+}
+
+/// Asks for the message of the annotated tag `name`, summarising the
+/// commits it adds since the previous tag.
+pub fn for_tag(name: &str, messages: &[String], stat: &str) -> String {
+    let messages: Vec<String> = messages
+        .iter()
+        .map(|message| format!("- {}", message.replace('\n', "\n  ")))
+        .collect();
+    format!(
+        "You are an expert programmer writing the message of the annotated git tag {name}.\n\
+        Your task is to summarise what the commits below add since the previous release: a first line of the form \
+        \"{name}: <one-line summary>\", a blank line, then a short plain-text list of the notable changes, one \
+        \"- \" bullet each, merging related commits and leaving out purely internal ones.\n\n\
+        VERY IMPORTANT: Your entire response must be only the tag message itself. Do not use markdown headings \
+        or start any line with #, which git strips from tag messages, and do not wrap the response in a code block.\n\n\
+        Commits, oldest first:\n{messages}\n\n\
+        Files changed:\n{stat}",
+        name = name,
+        messages = messages.join("\n"),
+        stat = truncate(stat, MAX_DIFF_BYTES)
+    )
+}
+
+/// Asks for a merge commit message describing what merging `branch` into
+/// `target` brings in, and which files conflicted.
+pub fn for_merge(style: &str, branch: &str, target: &str, diff: &str, messages: &[String], conflicts: &[String]) -> String {
+    let messages: Vec<String> = messages
+        .iter()
+        .map(|message| format!("- {}", message.replace('\n', "\n  ")))
+        .collect();
+    let conflicts = if conflicts.is_empty() {
+        "The merge has no conflicts.".to_string()
+    } else {
+        format!(
+            "These files conflict and will need resolving; mention them in the body:\n{}",
+            conflicts.iter().map(|path| format!("- {}", path)).collect::<Vec<_>>().join("\n")
+        )
+    };
+    format!(
+        "You are an expert programmer writing the message for a git merge commit.\n\
+        Your task is to write a merge commit message in the '{style}' style for merging the branch '{branch}' into \
+        '{target}'. Instead of a bare \"Merge branch '{branch}'\", the subject should say what the branch brings in, \
+        and the body should summarise its commits.\n\n\
+        {rules}\
+        {conflicts}\n\n\
+        VERY IMPORTANT: Your entire response must be only the commit message itself. Do not include any surrounding text, explanations, apologies, or markdown formatting like ```.\n\n\
+        Commits being merged, oldest first:\n{messages}\n\n\
+        Diff:\n{diff}",
+        style = style,
+        rules = style::instructions(style),
+        branch = branch,
+        target = target,
+        conflicts = conflicts,
+        messages = messages.join("\n"),
+        diff = truncate(diff, MAX_DIFF_BYTES)
+    )
+}
+
+/// Asks for the body of a revert commit: what undoing the commit means,
+/// and why, from the user's `reason` or else inferred from the change.
+pub fn for_revert(original: &str, diff: &str, reason: Option<&str>) -> String {
+    let why = match reason {
+        Some(reason) => format!("The reason for reverting it, in the author's words: \"{}\"", reason),
+        None => "No reason was given; suggest the most likely one from the change itself, worded as a likely reason \
+            rather than a certainty."
+            .to_string(),
+    };
+    format!(
+        "You are an expert programmer writing the body of a git commit that reverts an earlier commit.\n\
+        Your task is to explain in a short paragraph what reverting it undoes and why it is being reverted. \
+        The subject line and the \"This reverts commit\" line are added separately.\n\n\
+        {why}\n\n\
+        VERY IMPORTANT: Your entire response must be only that paragraph. Do not include a subject line or any \
+        surrounding text, explanations, apologies, or markdown formatting like ```.\n\n\
+        Message of the commit being reverted:\n{original}\n\n\
+        Its diff:\n{diff}",
+        why = why,
+        original = original,
+        diff = truncate(diff, MAX_DIFF_BYTES)
+    )
+}
+
+/// Asks for a plain-English explanation of a change, for someone
+/// reviewing it: what it does, and why it was likely made. `message` is the
+/// commit's own message, when it is a commit.
+pub fn for_explanation(diff: &str, message: Option<&str>, lang: Option<&str>) -> String {
+    let message = match message {
+        Some(message) => format!("The commit's message:\n{}\n\n", message.trim_end()),
+        None => String::new(),
+    };
+    let language = match lang.filter(|lang| !lang.trim().is_empty()).map(language_name) {
+        Some(language) if !language.eq_ignore_ascii_case("English") => format!(" Write it in {}.", language),
+        _ => String::new(),
+    };
+    format!(
+        "You are an expert programmer explaining a code change to a colleague who is reviewing it and doesn't know \
+        this part of the code.\n\
+        Your task is to explain in plain English what the following change does and why it might have been made. \
+        Start with a sentence summing it up, then describe what changes in behaviour, then the most likely reasons \
+        for it, worded as likely reasons rather than certainties unless the commit message states them. Point out \
+        anything a reviewer should look at closely. Write a few short paragraphs of plain text, without markdown \
+        headings or formatting like ```.{language}\n\n\
+        {message}\
+        Diff:\n{diff}",
+        language = language,
+        message = message,
+        diff = truncate(diff, MAX_DIFF_BYTES)
+    )
+}
+
+/// Asks for the numbered hunks in `listing` to be partitioned into
+/// logical commits, each with a message, as JSON.
+pub fn for_split(style: &str, listing: &str) -> String {
+    format!(
+        "You are an expert programmer preparing a tidy git history.\n\
+        The staged changes below are split into numbered hunks. Your task is to group them into the smallest number of \
+        commits that each make one logical change, and to write a commit message in the '{style}' style for each commit. \
+        Order the commits so that each one builds on the ones before it. Every hunk belongs to exactly one commit.\n\n\
+        {rules}\
+        VERY IMPORTANT: Your entire response must be only a JSON array, with no surrounding text or markdown, of the form \
+        [{{\"message\": \"feat: ...\", \"hunks\": [1, 2]}}, {{\"message\": \"fix: ...\", \"hunks\": [3]}}].\n\n\
+        Hunks:\n{listing}",
+        style = style,
+        rules = style::instructions(style),
+        listing = truncate(listing, MAX_DIFF_BYTES)
+    )
+}
+
+/// Asks for one changelog bullet summarising a run of related commits.
+pub fn for_changelog_group(section: &str, scope: Option<&str>, items: &[String]) -> String {
+    let area = scope.map(|scope| format!(" in the '{}' area", scope)).unwrap_or_default();
+    let items: Vec<String> = items.iter().map(|item| format!("- {}", item)).collect();
+    format!(
+        "You are an expert programmer writing a changelog entry under the '{section}' heading.\n\
+        Your task is to summarise the following related commits{area} as a single concise changelog bullet aimed at \
+        users of the project, merging overlapping and follow-up changes.\n\n\
+        VERY IMPORTANT: Your entire response must be only the text of that one bullet, without a leading dash. Do not \
+        include any surrounding text, explanations, or markdown formatting like ```.\n\n\
+        Commits, oldest first:\n{items}",
+        section = section,
+        area = area,
+        items = items.join("\n")
+    )
+}
+
+const BRANCH_INSTRUCTIONS: &str = "You are an expert programmer naming a git branch for upcoming work.\n\
+    Summarise the change as a single conventional commit subject of the form `type(scope): description`, \
+    with a short lowercase description of at most five words; omit the scope if none fits.\n\n\
+    VERY IMPORTANT: Your entire response must be only that one line. Do not include any surrounding text, explanations, or markdown formatting like ```.";
+
+/// Asks for a `type(scope): description` line to build a branch name from.
+pub fn branch_from_description(description: &str) -> String {
+    format!("{}\n\nChange Description: \"{}\"", BRANCH_INSTRUCTIONS, description)
+}
+
+pub fn branch_from_diff(diff: &str) -> String {
+    format!("{}\n\nDiff:\n{}", BRANCH_INSTRUCTIONS, truncate(diff, MAX_DIFF_BYTES))
+}
+
+/// Asks for a one-line description of unfinished work about to be stashed,
+/// to replace git's "WIP on <branch>" in `git stash list`.
+pub fn for_stash(diff: &str) -> String {
+    format!(
+        "You are an expert programmer stashing unfinished work with git.\n\
+        Your task is to describe the work in progress in the following diff in one short line (under 60 characters), \
+        so it can be recognised later in `git stash list`: say what was being done, e.g. \"half-done retry logic for uploads\". \
+        No commit type prefix and no trailing period.\n\n\
+        VERY IMPORTANT: Your entire response must be only that line. Do not include any surrounding text, explanations, \
+        or markdown formatting like ```.\n\n\
+        Diff:\n{diff}",
+        diff = truncate(diff, MAX_DIFF_BYTES)
+    )
+}
+
+/// Cuts `diff` to at most `max` bytes at a line boundary, noting how much
+/// was left out.
+pub fn truncate(diff: &str, max: usize) -> String {
+    if diff.len() <= max {
+        return diff.to_string();
+    }
+    let max = diff.floor_char_boundary(max);
+    let cut = diff[..max].rfind('\n').map(|i| i + 1).unwrap_or(max);
+    format!("{}[... {} more bytes of diff omitted]\n", &diff[..cut], diff.len() - cut)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncate_keeps_short_diffs() {
+        assert_eq!(truncate("+a\n+b\n", 100), "+a\n+b\n");
+    }
+
+    #[test]
+    fn test_truncate_at_line_boundary() {
+        assert_eq!(truncate("+aaa\n+bbb\n+ccc\n", 12), "+aaa\n+bbb\n[... 5 more bytes of diff omitted]\n");
+    }
+
+    #[test]
+    fn test_truncate_respects_char_boundaries() {
+        assert_eq!(truncate("ééé", 3), "é[... 4 more bytes of diff omitted]\n");
+    }
+
+    #[test]
+    fn test_from_diff_includes_diff() {
+        let prompt = from_diff("gitmoji", "+fn main() {}\n");
+        assert!(prompt.contains("'gitmoji' style"));
+        assert!(prompt.ends_with("+fn main() {}\n"));
+    }
+
+    #[test]
+    fn test_preset_rules_precede_the_diff() {
+        let prompt = from_diff("kernel", "+x\n");
+        let rules = prompt.find("Rules of the 'kernel' style:\n- The subject is `subsystem: Summary`").unwrap();
+        assert!(rules < prompt.find("Diff:").unwrap());
+        assert!(!from_description("terse", "x").contains("Rules of"));
+    }
+
+    #[test]
+    fn test_for_explanation() {
+        let prompt = for_explanation("+x\n", Some("fix: drop the cache\n"), Some("ja"));
+        assert!(prompt.contains("The commit's message:\nfix: drop the cache\n\nDiff:\n+x\n"));
+        assert!(prompt.contains(" Write it in Japanese."));
+        let staged = for_explanation("+x\n", None, Some("en"));
+        assert!(!staged.contains("commit's message") && !staged.contains("Write it in"));
+    }
+
+    #[test]
+    fn test_with_language() {
+        assert_eq!(with_language("p".to_string(), None, 72), "p");
+        assert_eq!(with_language("p".to_string(), Some("en"), 72), "p");
+        assert_eq!(with_language("p".to_string(), Some("English"), 72), "p");
+        let german = with_language("p".to_string(), Some("de_DE"), 72);
+        assert!(german.contains("in German."));
+        assert!(!german.contains("columns"));
+        let japanese = with_language("p".to_string(), Some("ja"), 72);
+        assert!(japanese.contains("in Japanese."));
+        assert!(japanese.contains("limit of 72, so a subject written in it fits in about 36 characters"));
+        assert!(with_language("p".to_string(), Some("zh-TW"), 50).contains("in Traditional Chinese."));
+        assert!(with_language("p".to_string(), Some("Esperanto"), 72).contains("in Esperanto."));
+    }
+
+    #[test]
+    fn test_from_diff_offers_scopes() {
+        let prompt = from_diff("conventional commit", "diff --git a/src/llm/openai.rs b/src/llm/openai.rs\n+x\n");
+        assert!(prompt.contains("taken from the paths changed: llm.\n\n"));
+        assert!(!from_diff("conventional commit", "+x\n").contains("paths changed"));
+    }
+
+    #[test]
+    fn test_for_tag() {
+        let prompt = for_tag("v1.4.0", &["feat: add tags\n\nWith bodies".into()], " src/main.rs | 2 +-\n");
+        assert!(prompt.contains("annotated git tag v1.4.0"));
+        assert!(prompt.contains("\"v1.4.0: <one-line summary>\""));
+        assert!(prompt.contains("- feat: add tags\n  \n  With bodies"));
+        assert!(prompt.ends_with("Files changed:\n src/main.rs | 2 +-\n"));
+    }
+
+    #[test]
+    fn test_with_examples() {
+        let prompt = with_examples("base".into(), &["Fix parser".into(), "Add lexer".into()]);
+        assert!(prompt.starts_with("base\n\nRecent commit subjects"));
+        assert!(prompt.ends_with(":\n- Fix parser\n- Add lexer"));
+        assert_eq!(with_examples("base".into(), &[]), "base");
+    }
+
+    #[test]
+    fn test_from_template() {
+        let template = Template::parse(
+            "A {{ style }} message for {{ files }}.\n{% if recent_commits %}\nLike:\n{{ recent_commits }}\n{% endif %}\n{{ diff }}\n",
+            TEMPLATE_VARIABLES,
+        )
+        .unwrap();
+        let diff = "diff --git a/src/lib.rs b/src/lib.rs\n+x\n";
+        let subjects = ["fix: a".to_string(), "feat: b".to_string()];
+        assert_eq!(
+            from_template(&template, "kernel", Some(diff), None, &subjects).unwrap(),
+            format!("A kernel message for src/lib.rs.\nLike:\nfix: a\nfeat: b\n{}", diff.trim_end())
+        );
+        assert_eq!(from_template(&template, "kernel", None, Some("typo"), &[]).unwrap(), "A kernel message for .");
+    }
+
+    #[test]
+    fn test_for_refinement() {
+        let turns = vec![("fix: a".to_string(), "shorter".to_string()), ("fix: b".to_string(), "mention x".to_string())];
+        let prompt = for_refinement("base", &turns);
+        assert!(prompt.starts_with("base\n\nYou have already suggested"));
+        assert!(prompt.contains("Message 1:\nfix: a\n\nRequest: shorter\n\nMessage 2:\nfix: b\n\nRequest: mention x\n\n"));
+    }
+
+    #[test]
+    fn test_with_template() {
+        let prompt = with_template("base".into(), Some("# Summary\n\nWhy:\n\n"));
+        assert!(prompt.starts_with("base\n\nThis repository has a commit message template."));
+        assert!(prompt.ends_with("Template:\n# Summary\n\nWhy:"));
+        assert_eq!(with_template("base".into(), None), "base");
+    }
+
+    #[test]
+    fn test_with_breaking() {
+        let changes = vec![crate::breaking::Change { name: "parse".into(), before: "pub fn parse()".into(), signature_changed: false }];
+        let prompt = with_breaking("base".into(), &Verdict::Breaking(changes));
+        assert!(prompt.starts_with("base\n\nThis is a breaking change to the public API:\n- `parse` was removed"));
+        assert_eq!(with_breaking("base".into(), &Verdict::Unknown), "base");
+    }
+
+    #[test]
+    fn test_subject_from_summaries() {
+        let prompt = subject_from_summaries("gitmoji", &[("src/a.rs".into(), "Add parser".into())]);
+        assert!(prompt.contains("in the 'gitmoji' style"));
+        assert!(prompt.ends_with("Changes:\n- src/a.rs: Add parser"));
+    }
+
+    #[test]
+    fn test_for_squash_numbers_messages() {
+        let prompt = for_squash("conventional commit", "+x\n", &["wip".into(), "fix tests\n\nreally".into()]);
+        assert!(prompt.contains("1. wip\n2. fix tests\n   \n   really\n"));
+    }
+
+    #[test]
+    fn test_for_pr_lists_commits() {
+        let prompt = for_pr("+x\n", &["feat: a".into(), "fix: b\n\nbody".into()]);
+        assert!(prompt.contains("Commits, oldest first:\n- feat: a\n- fix: b\n  \n  body\n"));
+        assert!(prompt.ends_with("Diff:\n+x\n"));
+    }
+
+    #[test]
+    fn test_for_release_notes_names_release() {
+        let prompt = for_release_notes("v1.3.0", &["feat: add login".into()], " src/a.rs | 2 +-\n");
+        assert!(prompt.starts_with("You are an expert programmer writing the release notes for v1.3.0,"));
+        assert!(prompt.contains("Commits, oldest first:\n- feat: add login\n"));
+    }
+
+    #[test]
+    fn test_for_merge_lists_conflicts() {
+        let prompt = for_merge("conventional commit", "feature", "main", "+x\n", &["feat: a".into()], &["src/a.rs".into()]);
+        assert!(prompt.contains("merging the branch 'feature' into 'main'"));
+        assert!(prompt.contains("mention them in the body:\n- src/a.rs\n"));
+        assert!(!for_merge("gitmoji", "f", "main", "", &[], &[]).contains("mention them"));
+    }
+
+    #[test]
+    fn test_for_amend_includes_current_message() {
+        let prompt = for_amend("conventional commit", "+x\n", "wip");
+        assert!(prompt.contains("Diff:\n+x\n"));
+        assert!(prompt.ends_with("write an improved one:\nwip"));
+    }
+}
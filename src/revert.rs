@@ -0,0 +1,43 @@
+use crate::check::Style;
+
+/// The subject for reverting a commit with `original` as its subject:
+/// `revert: ...` for Conventional Commits and its relatives, otherwise git's own
+/// `Revert "..."`.
+pub fn subject(original: &str, style: Style) -> String {
+    match style {
+        Style::Conventional | Style::Angular | Style::Karma => format!("revert: {}", original),
+        Style::Gitmoji => format!(":rewind: Revert \"{}\"", original),
+        Style::Plain | Style::Kernel | Style::Other => format!("Revert \"{}\"", original),
+    }
+}
+
+/// The full message: the subject, the model's explanation, and the line
+/// git itself adds naming the reverted commit.
+pub fn message(subject: &str, explanation: &str, sha: &str) -> String {
+    let explanation = explanation.trim();
+    if explanation.is_empty() {
+        format!("{}\n\nThis reverts commit {}.", subject, sha)
+    } else {
+        format!("{}\n\n{}\n\nThis reverts commit {}.", subject, explanation, sha)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_subject() {
+        assert_eq!(subject("feat: add cache", Style::Conventional), "revert: feat: add cache");
+        assert_eq!(subject("Add cache", Style::Other), "Revert \"Add cache\"");
+    }
+
+    #[test]
+    fn test_message() {
+        assert_eq!(
+            message("revert: feat: add cache", "The cache served stale results.\n", "abc123"),
+            "revert: feat: add cache\n\nThe cache served stale results.\n\nThis reverts commit abc123."
+        );
+        assert_eq!(message("Revert \"x\"", "", "abc123"), "Revert \"x\"\n\nThis reverts commit abc123.");
+    }
+}
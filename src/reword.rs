@@ -0,0 +1,113 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::git;
+
+/// A commit in the range, with the message it has now.
+pub struct Commit {
+    pub sha: String,
+    pub message: String,
+}
+
+/// `A..B` is used as given; a single revision means everything after it
+/// up to HEAD.
+pub fn normalize_range(range: &str) -> String {
+    if range.contains("..") {
+        range.to_string()
+    } else {
+        format!("{}..HEAD", range)
+    }
+}
+
+/// The commits in `range`, oldest first. Rewriting happens with a rebase
+/// of HEAD's history, so the range has to be part of it, and merges would
+/// be flattened by the rebase.
+pub fn commits(range: &str) -> anyhow::Result<Vec<Commit>> {
+    let shas = git::rev_list(&["--reverse", range, "--"])?;
+    let Some(last) = shas.last() else {
+        anyhow::bail!("No commits in {}", range);
+    };
+    if !git::rev_list(&["--merges", range, "--"])?.is_empty() {
+        anyhow::bail!("{} contains merge commits, which can't be reworded this way", range);
+    }
+    if !git::is_ancestor(last, "HEAD")? {
+        anyhow::bail!("{} isn't part of the current branch's history", range);
+    }
+
+    shas.into_iter()
+        .map(|sha| {
+            let message = git::commit_message(&sha)?;
+            Ok(Commit { sha, message })
+        })
+        .collect()
+}
+
+/// A rebase todo list that picks every commit in `history` (oldest first)
+/// and amends those with a file in `messages` right after picking them.
+pub fn build_todo(history: &[String], messages: &HashMap<String, PathBuf>) -> String {
+    let mut todo = String::new();
+    for sha in history {
+        todo.push_str(&format!("pick {}\n", sha));
+        if let Some(path) = messages.get(sha) {
+            todo.push_str(&format!(
+                "exec git commit --amend --no-verify --allow-empty --cleanup=whitespace --file {}\n",
+                git::shell_quote(&path.to_string_lossy())
+            ));
+        }
+    }
+    todo
+}
+
+/// Rewrites the messages of the commits in `rewrites` (by full hash). The
+/// rebase starts at the parent of `first`, the oldest commit in the range.
+pub fn apply(first: &str, rewrites: &[(String, String)]) -> anyhow::Result<()> {
+    let dir = std::env::temp_dir().join(format!("commitgen-reword-{}", std::process::id()));
+    std::fs::create_dir_all(&dir)?;
+    let result = apply_in(&dir, first, rewrites);
+    let _ = std::fs::remove_dir_all(&dir);
+    result
+}
+
+fn apply_in(dir: &Path, first: &str, rewrites: &[(String, String)]) -> anyhow::Result<()> {
+    let mut messages = HashMap::new();
+    for (sha, message) in rewrites {
+        let path = dir.join(sha);
+        std::fs::write(&path, format!("{}\n", message))?;
+        messages.insert(sha.clone(), path);
+    }
+
+    let parent = format!("{}^", first);
+    let base = git::rev_list(&["--max-count=1", &parent, "--"]).ok().and_then(|shas| shas.into_iter().next());
+    let history = match &base {
+        Some(base) => git::rev_list(&["--reverse", &format!("{}..HEAD", base), "--"])?,
+        None => git::rev_list(&["--reverse", "HEAD", "--"])?,
+    };
+
+    let todo_file = dir.join("todo");
+    std::fs::write(&todo_file, build_todo(&history, &messages))?;
+    git::scripted_rebase(base.as_deref(), &todo_file)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_range() {
+        assert_eq!(normalize_range("main..feature"), "main..feature");
+        assert_eq!(normalize_range("HEAD~3"), "HEAD~3..HEAD");
+    }
+
+    #[test]
+    fn test_build_todo() {
+        let history = vec!["aaa".to_string(), "bbb".to_string(), "ccc".to_string()];
+        let messages = HashMap::from([("bbb".to_string(), PathBuf::from("/tmp/msg bbb"))]);
+        assert_eq!(
+            build_todo(&history, &messages),
+            "pick aaa\n\
+            pick bbb\n\
+            exec git commit --amend --no-verify --allow-empty --cleanup=whitespace --file '/tmp/msg bbb'\n\
+            pick ccc\n"
+        );
+    }
+}
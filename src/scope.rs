@@ -0,0 +1,128 @@
+use crate::workspace::{self, Package};
+
+/// Directories that hold a project's source rather than naming a part of
+/// it; the scope is the directory (or file) inside them.
+const SOURCE_ROOTS: &[&str] = &["src", "lib", "app", "pkg", "internal", "source"];
+
+/// Directories whose children are separate packages named after their
+/// directory.
+const PACKAGE_ROOTS: &[&str] = &["crates", "packages", "apps", "libs", "modules", "services"];
+
+/// Files that stand for their directory rather than a part of it.
+const ENTRY_POINTS: &[&str] = &["main", "lib", "mod", "index", "__init__"];
+
+/// Only this many candidates are offered; a long list is no better than none.
+const MAX_CANDIDATES: usize = 5;
+
+/// The paths a unified diff touches, from its `diff --git` headers.
+pub fn changed_paths(diff: &str) -> Vec<String> {
+    diff.lines()
+        .filter_map(|line| line.strip_prefix("diff --git "))
+        .filter_map(|header| header.split_once(" b/").map(|(_, path)| path.to_string()))
+        .collect()
+}
+
+/// The scope a single path suggests, e.g. `src/parser/lexer.rs` -> `parser`
+/// and `crates/cli/src/main.rs` -> `cli`. Files at the top of the repo
+/// (READMEs, manifests) suggest nothing.
+pub fn scope_of(path: &str) -> Option<String> {
+    let mut parts: Vec<&str> = path.split('/').collect();
+    let file = parts.pop()?;
+    let name = match parts.first() {
+        None => return None,
+        Some(root) if PACKAGE_ROOTS.contains(root) && parts.len() > 1 => parts[1],
+        Some(root) if SOURCE_ROOTS.contains(root) => match parts.get(1) {
+            Some(dir) => dir,
+            None => {
+                let stem = file.split('.').next().unwrap_or(file);
+                if ENTRY_POINTS.contains(&stem) {
+                    return None;
+                }
+                stem
+            }
+        },
+        Some(dir) => dir,
+    };
+    let name = name.trim_start_matches('.').to_ascii_lowercase();
+    (!name.is_empty()).then_some(name)
+}
+
+/// The scope naming `package`: its name without any npm `@org/` prefix.
+fn package_scope(package: &Package) -> String {
+    let name = package.name.rsplit('/').next().unwrap_or(&package.name);
+    name.to_ascii_lowercase()
+}
+
+/// Scopes suggested by the paths in `diff`, the most touched first. Paths
+/// inside one of the workspace's `packages` suggest the package's name
+/// rather than a directory.
+pub fn candidates(diff: &str, packages: &[Package]) -> Vec<String> {
+    let mut counts: Vec<(String, usize)> = Vec::new();
+    let scopes = changed_paths(diff).into_iter().filter_map(|path| {
+        match workspace::package_of(&path, packages).filter(|package| !package.dir.is_empty()) {
+            Some(package) => Some(package_scope(package)),
+            None => scope_of(&path),
+        }
+    });
+    for scope in scopes {
+        match counts.iter_mut().find(|(s, _)| *s == scope) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((scope, 1)),
+        }
+    }
+    // Stable, so ties keep the order the diff lists them in.
+    counts.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+    counts.into_iter().take(MAX_CANDIDATES).map(|(scope, _)| scope).collect()
+}
+
+/// The names of the workspace packages `diff` touches, in the order it
+/// first touches them.
+pub fn packages_touched(diff: &str, packages: &[Package]) -> Vec<String> {
+    let mut touched: Vec<String> = Vec::new();
+    for path in changed_paths(diff) {
+        if let Some(package) = workspace::package_of(&path, packages)
+            && !touched.contains(&package.name)
+        {
+            touched.push(package.name.clone());
+        }
+    }
+    touched
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scope_of() {
+        assert_eq!(scope_of("src/parser/lexer.rs").as_deref(), Some("parser"));
+        assert_eq!(scope_of("src/git.rs").as_deref(), Some("git"));
+        assert_eq!(scope_of("src/main.rs"), None);
+        assert_eq!(scope_of("crates/cli/src/main.rs").as_deref(), Some("cli"));
+        assert_eq!(scope_of(".github/workflows/ci.yml").as_deref(), Some("github"));
+        assert_eq!(scope_of("docs/usage.md").as_deref(), Some("docs"));
+        assert_eq!(scope_of("Cargo.toml"), None);
+    }
+
+    #[test]
+    fn test_candidates_most_touched_first() {
+        let diff = "diff --git a/src/git.rs b/src/git.rs\n+x\n\
+            diff --git a/src/llm/openai.rs b/src/llm/openai.rs\n+y\n\
+            diff --git a/src/llm/claude.rs b/src/llm/claude.rs\n+z\n\
+            diff --git a/README.md b/README.md\n+w\n";
+        assert_eq!(candidates(diff, &[]), ["llm", "git"]);
+    }
+
+    #[test]
+    fn test_candidates_use_package_names() {
+        let packages = vec![
+            Package { name: "@acme/web-ui".into(), dir: "frontend/ui".into() },
+            Package { name: "billing".into(), dir: "services/pay".into() },
+        ];
+        let diff = "diff --git a/frontend/ui/src/button.tsx b/frontend/ui/src/button.tsx\n+x\n\
+            diff --git a/services/pay/go.mod b/services/pay/go.mod\n+y\n\
+            diff --git a/frontend/ui/index.ts b/frontend/ui/index.ts\n+z\n";
+        assert_eq!(candidates(diff, &packages), ["web-ui", "billing"]);
+        assert_eq!(packages_touched(diff, &packages), ["@acme/web-ui", "billing"]);
+    }
+}
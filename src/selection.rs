@@ -0,0 +1,215 @@
+use std::time::Duration;
+
+use clap::Args;
+
+use crate::config::Config;
+use crate::llm::registry::{self, ProviderOptions};
+use crate::llm::{ClientResult, FallbackChain, GenerationOptions};
+use crate::redact::{Redacting, Redactor};
+
+/// Provider flags shared by every command that generates text.
+#[derive(Args)]
+pub struct ProviderArgs {
+    /// LLM provider to use [default: gemini, or `provider` in the config file]
+    #[arg(short, long, value_parser = parse_provider)]
+    pub provider: Option<String>,
+
+    /// Model to request, e.g. `gemini-2.5-pro` or an OpenRouter slug [default: per provider]
+    #[arg(short, long)]
+    pub model: Option<String>,
+
+    /// Accept a model name that isn't in the provider's known list
+    #[arg(long)]
+    pub allow_unknown_model: bool,
+
+    /// Providers to try in order if the primary one fails or times out, e.g. `openai,ollama`
+    #[arg(long, value_delimiter = ',', value_parser = parse_provider)]
+    pub fallback: Option<Vec<String>>,
+
+    /// Sampling temperature, 0 to 2 [default: 0, or `temperature` in the config file]
+    #[arg(long)]
+    pub temperature: Option<f32>,
+
+    /// Maximum number of tokens to generate [default: 4096, or `max_tokens` in the config file]
+    #[arg(long)]
+    pub max_tokens: Option<u32>,
+
+    /// Nucleus sampling probability mass, above 0 and at most 1 [default: provider's own]
+    #[arg(long)]
+    pub top_p: Option<f32>,
+}
+
+impl ProviderArgs {
+    pub fn selection<'a>(&self, config: &'a Config) -> Selection<'a> {
+        Selection::new(
+            config,
+            self.provider.clone(),
+            self.model.clone(),
+            self.fallback.clone(),
+            self.allow_unknown_model,
+            GenerationOptions {
+                temperature: self.temperature,
+                max_tokens: self.max_tokens,
+                top_p: self.top_p,
+            },
+        )
+    }
+}
+
+/// Canonicalises a provider name or alias for clap.
+pub fn parse_provider(name: &str) -> Result<String, String> {
+    registry::lookup(name)
+        .map(|p| p.name.to_string())
+        .map_err(|e| e.to_string())
+}
+
+/// Which providers to ask and how, resolved from flags and the config
+/// file. Flags win; anything left unset comes from the config, then from
+/// built-in defaults.
+pub struct Selection<'a> {
+    config: &'a Config,
+    pub provider: String,
+    /// Only applies to the primary provider; others use their configured
+    /// or default models.
+    model: Option<String>,
+    fallback: Vec<String>,
+    allow_unknown_model: bool,
+    /// Flags apply to every provider; the config can differ per provider.
+    flag_options: GenerationOptions,
+}
+
+impl<'a> Selection<'a> {
+    pub fn new(
+        config: &'a Config,
+        provider: Option<String>,
+        model: Option<String>,
+        fallback: Option<Vec<String>>,
+        allow_unknown_model: bool,
+        flag_options: GenerationOptions,
+    ) -> Self {
+        // Aliases are resolved so the name matches the `[models]` and
+        // `[endpoints]` keys; an unknown one is kept for the error later.
+        let configured = config
+            .provider
+            .as_deref()
+            .map(|name| registry::lookup(name).map_or_else(|_| name.to_string(), |p| p.name.to_string()))
+            .unwrap_or_else(|| registry::DEFAULT_PROVIDER.to_string());
+        let provider = provider.unwrap_or_else(|| configured.clone());
+        // The top-level `model` goes with the configured provider, and
+        // would be wrong for another one picked with --provider.
+        let config_model = config.model.clone().filter(|_| provider == configured);
+        let model = model.or(config_model).or_else(|| config.models.get(&provider).cloned());
+        Self {
+            config,
+            provider,
+            model,
+            fallback: fallback.unwrap_or_else(|| config.fallback.clone()),
+            allow_unknown_model: allow_unknown_model || config.allow_unknown_model,
+            flag_options,
+        }
+    }
+
+    /// Everything from the config file, as used when there are no flags
+    /// to go on (hooks, for instance).
+    pub fn from_config(config: &'a Config) -> Self {
+        Self::new(config, None, None, None, false, GenerationOptions::default())
+    }
+
+    fn explicit_model(&self, name: &str) -> Option<&str> {
+        if name == self.provider {
+            self.model.as_deref()
+        } else {
+            self.config.models.get(name).map(String::as_str)
+        }
+    }
+
+    /// The client for `name`, redacting every prompt it's given.
+    pub fn client(&self, name: &str) -> ClientResult {
+        let model = self.explicit_model(name);
+        let endpoint = self.config.endpoints.get(name).map(String::as_str);
+        let client =
+            registry::build(name, &ProviderOptions { model, allow_unknown_model: self.allow_unknown_model, endpoint })?;
+        Ok(Box::new(Redacting::new(client, Redactor::from_config(self.config)?)))
+    }
+
+    /// The model `name` is asked for: as configured, or its default.
+    pub fn model(&self, name: &str) -> Option<String> {
+        registry::lookup(name).ok()?.resolve_model(self.explicit_model(name))
+    }
+
+    pub fn options(&self, name: &str) -> anyhow::Result<GenerationOptions> {
+        let options = self.flag_options.or(self.config.generation_options(name));
+        options.validate()?;
+        Ok(options)
+    }
+
+    /// Names of the primary provider and its fallbacks, in the order
+    /// [`Self::chain`] tries them.
+    pub fn names(&self) -> Vec<String> {
+        let mut names = vec![self.provider.clone()];
+        for name in self.fallback.iter().filter(|name| **name != self.provider) {
+            names.push(registry::lookup(name).map_or_else(|_| name.clone(), |p| p.name.to_string()));
+        }
+        names
+    }
+
+    /// The primary provider followed by its fallbacks.
+    pub fn chain(&self) -> anyhow::Result<FallbackChain> {
+        let timeout = self
+            .config
+            .fallback_timeout_secs
+            .map(Duration::from_secs)
+            .unwrap_or(FallbackChain::DEFAULT_TIMEOUT);
+
+        let mut chain = FallbackChain::new(timeout);
+        chain.push(self.provider.clone(), self.client(&self.provider), self.options(&self.provider)?);
+        for name in self.fallback.iter().filter(|name| **name != self.provider) {
+            let name = registry::lookup(name)?.name;
+            chain.push(name, self.client(name), self.options(name)?);
+        }
+        Ok(chain)
+    }
+
+    /// Clients for `--compare`, falling back to the config's `compare` list
+    /// when no names are given.
+    pub fn compare(&self, names: Vec<String>) -> anyhow::Result<Vec<(String, ClientResult, GenerationOptions)>> {
+        let names = if names.is_empty() { self.config.compare.clone() } else { names };
+        if names.is_empty() {
+            anyhow::bail!("No providers to compare; pass --compare gemini,openai or set `compare` in the config file");
+        }
+        names
+            .iter()
+            .map(|name| {
+                let name = registry::lookup(name)?.name;
+                Ok((name.to_string(), self.client(name), self.options(name)?))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn selection<'a>(config: &'a Config, provider: Option<&str>) -> Selection<'a> {
+        Selection::new(config, provider.map(str::to_string), None, None, false, GenerationOptions::default())
+    }
+
+    #[test]
+    fn test_config_model() {
+        let config = Config {
+            provider: Some("anthropic".to_string()),
+            model: Some("claude-sonnet-4-5".to_string()),
+            models: [("openai".to_string(), "gpt-4o".to_string())].into(),
+            ..Config::default()
+        };
+        let configured = selection(&config, None);
+        assert_eq!(configured.provider, "claude");
+        assert_eq!(configured.explicit_model("claude"), Some("claude-sonnet-4-5"));
+        assert_eq!(selection(&config, Some("claude")).explicit_model("claude"), Some("claude-sonnet-4-5"));
+        assert_eq!(selection(&config, Some("openai")).explicit_model("openai"), Some("gpt-4o"));
+
+        let default = Config { model: Some("gemini-2.5-pro".to_string()), ..Config::default() };
+        assert_eq!(selection(&default, Some("ollama")).explicit_model("ollama"), None);
+    }
+}
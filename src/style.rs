@@ -0,0 +1,325 @@
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use serde::Deserialize;
+
+use crate::check::{self, DEFAULT_MAX_SUBJECT_LENGTH, Problem};
+use crate::config::Config;
+use crate::git;
+
+/// Where a repository keeps the styles its team shares.
+pub const REPO_STYLES_DIR: &str = ".commitgen/styles";
+
+/// A named message style: the rules the model is given, and what `check`
+/// enforces afterwards.
+pub struct Preset {
+    pub name: &'static str,
+    pub aliases: &'static [&'static str],
+    pub description: &'static str,
+    /// Rules for the model, one per bullet.
+    pub instructions: &'static [&'static str],
+    pub rules: check::Style,
+    pub max_subject_length: usize,
+    /// The style expects a `Signed-off-by` trailer on every commit.
+    pub signoff: bool,
+}
+
+pub static PRESETS: &[Preset] = &[
+    Preset {
+        name: "conventional",
+        aliases: &["conventional commit", "conventional commits", "conventionalcommits"],
+        description: "Conventional Commits 1.0: `type(scope): description`",
+        instructions: &[
+            "The subject is `type(scope): description`, where type is one of feat, fix, docs, style, refactor, perf, \
+            test, build, ci, chore or revert, and the scope is optional.",
+            "Mark a breaking change with `!` before the colon and a `BREAKING CHANGE:` footer explaining it.",
+            "The description is in the imperative mood, starts lowercase and has no trailing period.",
+            "An optional body, after a blank line, says what changed and why.",
+        ],
+        rules: check::Style::Conventional,
+        max_subject_length: DEFAULT_MAX_SUBJECT_LENGTH,
+        signoff: false,
+    },
+    Preset {
+        name: "gitmoji",
+        aliases: &[],
+        description: "A leading gitmoji, then a short description",
+        instructions: &[
+            "Start the subject with the single gitmoji that fits best, as the emoji itself: ✨ new feature, 🐛 bug \
+            fix, 📝 docs, ♻️ refactor, ⚡️ performance, ✅ tests, 🔧 configuration, ⬆️ dependency upgrade, 🔥 removal, \
+            🚑️ hotfix, 💄 UI and styles, 🎨 structure or formatting.",
+            "Follow it with a short description in the imperative mood.",
+        ],
+        rules: check::Style::Gitmoji,
+        max_subject_length: DEFAULT_MAX_SUBJECT_LENGTH,
+        signoff: false,
+    },
+    Preset {
+        name: "angular",
+        aliases: &[],
+        description: "Angular's commit format: `type(scope): subject`, with a motivated body",
+        instructions: &[
+            "The subject is `type(scope): subject`, where type is one of build, ci, docs, feat, fix, perf, refactor \
+            or test, and the scope names the affected package or area.",
+            "The subject is in the imperative, present tense, not capitalised and without a trailing period.",
+            "The body, after a blank line, explains the motivation for the change and contrasts it with the previous \
+            behaviour.",
+            "Breaking changes go in a footer starting `BREAKING CHANGE:`; referenced issues in `Closes #123` footers.",
+        ],
+        rules: check::Style::Angular,
+        max_subject_length: 100,
+        signoff: false,
+    },
+    Preset {
+        name: "karma",
+        aliases: &[],
+        description: "Karma's format: `type(scope): subject`",
+        instructions: &[
+            "The subject is `type(scope): subject`, where type is one of feat, fix, docs, style, refactor, perf, test \
+            or chore. Leave out the parentheses when no single scope fits.",
+            "The subject is in the imperative, present tense, not capitalised and without a trailing period.",
+            "The body, after a blank line, explains the motivation for the change and contrasts it with the previous \
+            behaviour; a footer references issues as `Closes #123`.",
+        ],
+        rules: check::Style::Karma,
+        max_subject_length: 70,
+        signoff: false,
+    },
+    Preset {
+        name: "semantic-release",
+        aliases: &["semantic release"],
+        description: "Angular-style commits that drive semantic-release's version bumps",
+        instructions: &[
+            "The subject is `type(scope): subject` using the Angular types build, ci, docs, feat, fix, perf, refactor \
+            or test.",
+            "The type decides the release: fix and perf give a patch, feat a minor release, and a \
+            `BREAKING CHANGE:` footer a major one, so choose it by its effect on users.",
+            "The subject is in the imperative, not capitalised and without a trailing period.",
+        ],
+        rules: check::Style::Angular,
+        max_subject_length: 100,
+        signoff: false,
+    },
+    Preset {
+        name: "plain",
+        aliases: &["git"],
+        description: "A capitalised imperative subject of at most 50 characters, as git's own docs suggest",
+        instructions: &[
+            "The subject is a capitalised, imperative summary (\"Add\", \"Fix\", not \"Added\" or \"Fixes\") of at \
+            most 50 characters, with no type prefix and no trailing period.",
+            "The body, after a blank line, is wrapped at 72 columns and explains what changed and why.",
+        ],
+        rules: check::Style::Plain,
+        max_subject_length: 50,
+        signoff: false,
+    },
+    Preset {
+        name: "kernel",
+        aliases: &["linux"],
+        description: "Linux kernel style: `subsystem: Summary`, signed off",
+        instructions: &[
+            "The subject is `subsystem: Summary`, naming the subsystem or driver touched (for example \
+            `net: ipv4: Fix ...`), with the summary in the imperative mood and no trailing period.",
+            "The body, after a blank line, describes the problem first and then how the change solves it, in plain \
+            prose wrapped at 75 columns.",
+            "Leave out the Signed-off-by trailer; it is added afterwards.",
+        ],
+        rules: check::Style::Kernel,
+        max_subject_length: 75,
+        signoff: true,
+    },
+];
+
+/// A style defined in a TOML file named after it, in the repository's
+/// [`REPO_STYLES_DIR`] or in `styles/` next to the config file.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Custom {
+    #[serde(skip)]
+    pub name: String,
+    pub description: String,
+    /// A preset whose rules and checks apply as well.
+    pub extends: Option<String>,
+    pub instructions: Vec<String>,
+    pub max_subject_length: Option<usize>,
+    /// Trailers every message must carry, e.g. `["Refs"]`.
+    pub required_footers: Vec<String>,
+    /// Messages written in the style, shown to the model.
+    pub examples: Vec<String>,
+    pub signoff: bool,
+}
+
+static CUSTOM: OnceLock<Vec<Custom>> = OnceLock::new();
+
+/// Reads the user's and the repository's style files; the repository's
+/// win when both define a name. Until this runs only presets are known.
+pub fn load_custom() -> anyhow::Result<()> {
+    let mut dirs: Vec<PathBuf> = Vec::new();
+    if let Ok(root) = git::run(&["rev-parse", "--show-toplevel"]) {
+        dirs.push(Path::new(root.trim()).join(REPO_STYLES_DIR));
+    }
+    if let Some(dir) = Config::default_path().as_deref().and_then(Path::parent) {
+        dirs.push(dir.join("styles"));
+    }
+
+    let mut styles: Vec<Custom> = Vec::new();
+    for dir in dirs {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        let mut paths: Vec<PathBuf> = entries.flatten().map(|entry| entry.path()).collect();
+        paths.sort();
+        for path in paths.iter().filter(|path| path.extension().is_some_and(|ext| ext == "toml")) {
+            let name = path.file_stem().unwrap_or_default().to_string_lossy().into_owned();
+            if styles.iter().any(|style| style.name == name) {
+                continue;
+            }
+            let contents = std::fs::read_to_string(path)?;
+            let style = parse_custom(&name, &contents)
+                .map_err(|e| anyhow::anyhow!("Invalid style file {}: {}", path.display(), e))?;
+            styles.push(style);
+        }
+    }
+    let _ = CUSTOM.set(styles);
+    Ok(())
+}
+
+pub fn parse_custom(name: &str, contents: &str) -> anyhow::Result<Custom> {
+    let mut style: Custom = toml::from_str(contents)?;
+    if let Some(base) = &style.extends
+        && lookup(base).is_none()
+    {
+        anyhow::bail!("`extends` names '{}', which isn't a built-in preset", base);
+    }
+    style.name = name.to_string();
+    Ok(style)
+}
+
+fn custom(name: &str) -> Option<&'static Custom> {
+    CUSTOM.get()?.iter().find(|style| style.name.eq_ignore_ascii_case(name.trim()))
+}
+
+pub fn is_custom(name: &str) -> bool {
+    custom(name).is_some()
+}
+
+/// Every style name `--style` knows: presets, then style files.
+pub fn names() -> Vec<String> {
+    let presets = PRESETS.iter().map(|preset| preset.name.to_string());
+    presets.chain(CUSTOM.get().into_iter().flatten().map(|style| style.name.clone())).collect()
+}
+
+/// The preset called `name`, or one of its aliases, ignoring case.
+pub fn lookup(name: &str) -> Option<&'static Preset> {
+    let name = name.trim();
+    PRESETS
+        .iter()
+        .find(|preset| preset.name.eq_ignore_ascii_case(name) || preset.aliases.iter().any(|a| a.eq_ignore_ascii_case(name)))
+}
+
+/// The preset whose rules `check` applies to `name`: the preset itself, or
+/// the one a style file extends.
+pub fn base(name: &str) -> Option<&'static Preset> {
+    match custom(name) {
+        Some(style) => style.extends.as_deref().and_then(lookup),
+        None => lookup(name),
+    }
+}
+
+/// The longest subject `style` allows: its own limit, its preset's, or
+/// git's customary 72 for free-form styles.
+pub fn max_subject_length(style: &str) -> usize {
+    custom(style)
+        .and_then(|custom| custom.max_subject_length)
+        .or_else(|| base(style).map(|preset| preset.max_subject_length))
+        .unwrap_or(DEFAULT_MAX_SUBJECT_LENGTH)
+}
+
+pub fn signs_off(style: &str) -> bool {
+    custom(style).is_some_and(|custom| custom.signoff) || base(style).is_some_and(|preset| preset.signoff)
+}
+
+/// Problems beyond `check`'s: trailers a style file requires that
+/// `message` lacks.
+pub fn missing_footers(message: &str, style: &str) -> Vec<Problem> {
+    let Some(custom) = custom(style) else {
+        return Vec::new();
+    };
+    custom
+        .required_footers
+        .iter()
+        .filter(|footer| !message.lines().any(|line| line.strip_prefix(footer.as_str()).is_some_and(|rest| rest.starts_with(':'))))
+        .map(|footer| Problem {
+            message: format!("No `{}:` trailer", footer),
+            hint: format!("The '{}' style requires one, e.g. `{}: ...` at the end of the message", custom.name, footer),
+        })
+        .collect()
+}
+
+/// The paragraph of rules prompts include for `style`, empty for a
+/// free-form style the model is left to interpret.
+pub fn instructions(style: &str) -> String {
+    let (name, mut rules, examples): (String, Vec<String>, &[String]) = match (custom(style), lookup(style)) {
+        (Some(custom), _) => {
+            let mut rules: Vec<String> = base(style).map_or(Vec::new(), |preset| preset.instructions.iter().map(|r| r.to_string()).collect());
+            rules.extend(custom.instructions.iter().cloned());
+            if let Some(length) = custom.max_subject_length {
+                rules.push(format!("The subject is at most {} characters long.", length));
+            }
+            for footer in &custom.required_footers {
+                rules.push(format!("End the message with a `{}:` trailer.", footer));
+            }
+            let name = match custom.description.trim() {
+                "" => format!("'{}' style", custom.name),
+                description => format!("'{}' style ({})", custom.name, description),
+            };
+            (name, rules, &custom.examples)
+        }
+        (None, Some(preset)) => (format!("'{}' style", preset.name), preset.instructions.iter().map(|r| r.to_string()).collect(), &[]),
+        (None, None) => return String::new(),
+    };
+    if rules.is_empty() && examples.is_empty() {
+        return String::new();
+    }
+    rules = rules.into_iter().map(|rule| format!("- {}", rule)).collect();
+    let mut paragraph = format!("Rules of the {}:\n{}\n\n", name, rules.join("\n"));
+    if !examples.is_empty() {
+        let examples: Vec<String> = examples.iter().map(|example| example.trim().to_string()).collect();
+        paragraph.push_str(&format!("Messages written in this style:\n---\n{}\n---\n\n", examples.join("\n---\n")));
+    }
+    paragraph
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lookup() {
+        assert_eq!(lookup("Conventional Commit").map(|p| p.name), Some("conventional"));
+        assert_eq!(lookup("kernel").map(|p| p.rules), Some(check::Style::Kernel));
+        assert!(lookup("like the rest of the repo").is_none());
+        assert_eq!(max_subject_length("plain"), 50);
+        assert_eq!(max_subject_length("whatever"), DEFAULT_MAX_SUBJECT_LENGTH);
+    }
+
+    #[test]
+    fn test_parse_custom() {
+        let style = parse_custom(
+            "team",
+            "extends = \"angular\"\nmax_subject_length = 60\nrequired_footers = [\"Refs\"]\nexamples = [\"feat: a\"]\n",
+        )
+        .unwrap();
+        assert_eq!(style.name, "team");
+        assert_eq!(style.max_subject_length, Some(60));
+        assert!(parse_custom("x", "extends = \"team\"\n").is_err());
+        assert!(parse_custom("x", "max_length = 60\n").is_err());
+    }
+
+    #[test]
+    fn test_instructions() {
+        assert!(instructions("karma").starts_with("Rules of the 'karma' style:\n- The subject is"));
+        assert!(instructions("karma").ends_with("`Closes #123`.\n\n"));
+        assert_eq!(instructions("short and sweet"), "");
+    }
+}
@@ -0,0 +1,109 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use minijinja::Environment;
+use minijinja::syntax::SyntaxConfig;
+use minijinja::value::Value;
+
+/// What a template is called in errors: `(in prompt:3)`.
+const NAME: &str = "prompt";
+
+/// A prompt template in Jinja syntax, rendered by minijinja: `{{ name }}`
+/// variables, filters such as `{{ files | upper }}`, `{% if name %}...
+/// {% else %}...{% endif %}` and `{# comments #}`. Blocks are trimmed as
+/// with Jinja's `trim_blocks` and `lstrip_blocks`, so a line holding
+/// nothing but a block tag is left out altogether.
+#[derive(Debug, Clone)]
+pub struct Template {
+    env: Environment<'static>,
+}
+
+impl Template {
+    /// Parses `source`, whose variables must all be among `names`.
+    pub fn parse(source: &str, names: &[&str]) -> anyhow::Result<Self> {
+        let mut env = Environment::new();
+        let syntax = SyntaxConfig::builder().trim_blocks(true).lstrip_blocks(true).build().map_err(describe)?;
+        env.set_syntax(syntax);
+        env.add_template_owned(NAME, source.to_string()).map_err(describe)?;
+
+        let mut unknown: Vec<String> = env
+            .get_template(NAME)
+            .map_err(describe)?
+            .undeclared_variables(false)
+            .into_iter()
+            .filter(|name| !names.contains(&name.as_str()) && env.globals().all(|(global, _)| global != name))
+            .collect();
+        if !unknown.is_empty() {
+            unknown.sort();
+            anyhow::bail!(
+                "unknown variable{} {}; the variables are {}",
+                if unknown.len() == 1 { "" } else { "s" },
+                unknown.join(", "),
+                names.join(", ")
+            );
+        }
+        Ok(Self { env })
+    }
+
+    /// Reads and parses the template file at `path`.
+    pub fn load(path: &Path, names: &[&str]) -> anyhow::Result<Self> {
+        let source = std::fs::read_to_string(path)
+            .map_err(|e| anyhow::anyhow!("Cannot read prompt template {}: {}", path.display(), e))?;
+        Self::parse(&source, names).map_err(|e| anyhow::anyhow!("Invalid prompt template {}: {}", path.display(), e))
+    }
+
+    /// Fills the template in; variables missing from `values` are empty.
+    pub fn render(&self, values: &BTreeMap<&str, String>) -> anyhow::Result<String> {
+        let context = Value::from_pairs(values.iter().map(|(name, value)| (*name, value.as_str())));
+        self.env.get_template(NAME).and_then(|template| template.render(context)).map_err(describe)
+    }
+}
+
+/// minijinja's message with the template line it's about, without the
+/// source listing its alternate form adds.
+fn describe(e: minijinja::Error) -> anyhow::Error {
+    anyhow::anyhow!("{}", e)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NAMES: &[&str] = &["diff", "branch", "recent_commits"];
+
+    fn render(source: &str, values: &[(&'static str, &str)]) -> String {
+        let values = values.iter().map(|(name, value)| (*name, value.to_string())).collect();
+        Template::parse(source, NAMES).unwrap().render(&values).unwrap()
+    }
+
+    #[test]
+    fn test_variables() {
+        assert_eq!(render("On {{ branch }}:\n{{diff}}", &[("branch", "main"), ("diff", "+x")]), "On main:\n+x");
+        assert_eq!(render("[{{ branch }}]", &[]), "[]");
+        assert_eq!(render("{{ '{{' }}branch}} {# a note #}is {{ branch | upper }}", &[("branch", "main")]), "{{branch}} is MAIN");
+        // Values aren't templates themselves, and nothing is escaped.
+        assert_eq!(render("{{ diff }}", &[("diff", "{{ branch }} <a & b>")]), "{{ branch }} <a & b>");
+    }
+
+    #[test]
+    fn test_if_blocks() {
+        let source = "Write a message.\n{% if recent_commits %}\nRecent:\n{{ recent_commits }}\n{% else %}\nNo history.\n{% endif %}\nDiff:\n{{ diff }}";
+        assert_eq!(
+            render(source, &[("recent_commits", "fix: a\nfeat: b"), ("diff", "+x")]),
+            "Write a message.\nRecent:\nfix: a\nfeat: b\nDiff:\n+x"
+        );
+        assert_eq!(render(source, &[("recent_commits", ""), ("diff", "+x")]), "Write a message.\nNo history.\nDiff:\n+x");
+        assert_eq!(render("a{% if branch %} on {{ branch }}{% endif %}.", &[("branch", "dev")]), "a on dev.");
+    }
+
+    #[test]
+    fn test_errors() {
+        let error = |source: &str| Template::parse(source, NAMES).unwrap_err().to_string();
+        assert_eq!(error("ok\n{{ dif }}"), "unknown variable dif; the variables are diff, branch, recent_commits");
+        assert!(error("{% if diff %}\nno end").contains("(in prompt:2)"));
+        assert!(error("ok\n{% endif %}").contains("(in prompt:2)"));
+        assert!(error("{{ diff").starts_with("syntax error"));
+        // Built-in functions aren't taken for unknown variables.
+        assert!(Template::parse("{% for i in range(2) %}{{ diff }}{% endfor %}", NAMES).is_ok());
+    }
+}
@@ -0,0 +1,88 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use console::Term;
+
+/// Whether escape sequences work on this terminal at all; set by [`init`].
+static ANSI: AtomicBool = AtomicBool::new(true);
+
+/// What the environment says about colour and escape sequences.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Env {
+    /// `NO_COLOR` is set to something.
+    no_color: bool,
+    /// `CLICOLOR_FORCE` is set to something.
+    force: bool,
+    /// `CLICOLOR`, when set: `0` turns colour off.
+    clicolor: Option<bool>,
+    /// The terminal understands ANSI escapes: `TERM` isn't `dumb` and, on
+    /// Windows, the console has virtual terminal processing turned on.
+    ansi: bool,
+}
+
+impl Env {
+    fn read() -> Self {
+        Self {
+            no_color: anstyle_query::no_color(),
+            force: anstyle_query::clicolor_force(),
+            clicolor: anstyle_query::clicolor(),
+            // Turns escapes on in a Windows console, where they're off until
+            // asked for; elsewhere it's `None`. Terminals such as mintty
+            // aren't consoles but set `TERM`.
+            ansi: anstyle_query::windows::enable_ansi_colors() == Some(true) || anstyle_query::term_supports_ansi_color(),
+        }
+    }
+
+    /// Whether output to a stream, a terminal or not, should be coloured.
+    /// `NO_COLOR` wins over everything, as no-color.org asks.
+    fn colors(&self, is_term: bool) -> bool {
+        if self.no_color {
+            false
+        } else if self.force {
+            true
+        } else {
+            is_term && self.ansi && self.clicolor != Some(false)
+        }
+    }
+}
+
+/// Decides colour for stdout and stderr, and whether spinners and redrawn
+/// lines may be used, from the environment and what the streams are. It
+/// runs before anything is printed.
+pub fn init() {
+    let env = Env::read();
+    let (stdout, stderr) = (Term::stdout().is_term(), Term::stderr().is_term());
+    // `colored` has one switch for both streams; the message goes to stdout.
+    colored::control::set_override(env.colors(stdout));
+    console::set_colors_enabled(env.colors(stdout));
+    console::set_colors_enabled_stderr(env.colors(stderr));
+    ANSI.store(env.ansi, Ordering::Relaxed);
+}
+
+/// Whether a spinner can be drawn: stderr is a terminal that understands
+/// escapes, rather than a hook's log or a CI job's output.
+pub fn animates() -> bool {
+    ANSI.load(Ordering::Relaxed) && Term::stderr().is_term()
+}
+
+/// Whether text already printed to stdout can be redrawn, as when the
+/// message is streamed and then tidied up.
+pub fn redraws() -> bool {
+    ANSI.load(Ordering::Relaxed) && Term::stdout().is_term()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TERMINAL: Env = Env { no_color: false, force: false, clicolor: None, ansi: true };
+
+    #[test]
+    fn test_colors() {
+        assert!(TERMINAL.colors(true));
+        assert!(!TERMINAL.colors(false));
+        assert!(!Env { no_color: true, force: true, ..TERMINAL }.colors(true));
+        assert!(Env { force: true, ..TERMINAL }.colors(false));
+        assert!(!Env { clicolor: Some(false), ..TERMINAL }.colors(true));
+        assert!(!Env { ansi: false, ..TERMINAL }.colors(true));
+    }
+}
@@ -0,0 +1,92 @@
+use crate::check::CONVENTIONAL_TYPES;
+use crate::trailers;
+
+/// Where the ticket goes unless `ticket_template` says otherwise.
+pub const DEFAULT_TEMPLATE: &str = "Refs: {ticket}";
+
+/// Words that come before a number in branch names without being a
+/// tracker's project key.
+const NOT_KEYS: &[&str] = &["feature", "bugfix", "hotfix", "release", "wip", "v"];
+
+/// Words that mark a GitHub-style issue number, as in `issue-567`.
+const ISSUE_WORDS: &[&str] = &["issue", "gh"];
+
+/// The ticket a branch name refers to: a tracker key like `JIRA-1234`
+/// (upper-cased, as trackers write them) or an issue number like `#567`.
+pub fn find(branch: &str) -> Option<String> {
+    for segment in branch.split(['/', '_']) {
+        let words: Vec<&str> = segment.split('-').collect();
+        for (i, word) in words.iter().enumerate() {
+            if let Some(number) = word.strip_prefix('#')
+                && is_number(number)
+            {
+                return Some(word.to_string());
+            }
+            let Some(next) = words.get(i + 1).filter(|next| is_number(next)) else {
+                continue;
+            };
+            let lower = word.to_ascii_lowercase();
+            if ISSUE_WORDS.contains(&lower.as_str()) {
+                return Some(format!("#{}", next));
+            }
+            let is_key = word.len() >= 2
+                && word.starts_with(|c: char| c.is_ascii_alphabetic())
+                && word.chars().all(|c| c.is_ascii_alphanumeric());
+            if is_key && !CONVENTIONAL_TYPES.contains(&lower.as_str()) && !NOT_KEYS.contains(&lower.as_str()) {
+                return Some(format!("{}-{}", word.to_ascii_uppercase(), next));
+            }
+        }
+    }
+    None
+}
+
+fn is_number(word: &str) -> bool {
+    !word.is_empty() && word.chars().all(|c| c.is_ascii_digit())
+}
+
+/// Puts `ticket` into `message` following `template`. A template with
+/// `{subject}` rewrites the subject line, e.g. `[{ticket}] {subject}`; any
+/// other template is added as a trailer. Messages that already mention
+/// the ticket are left alone.
+pub fn apply(message: &str, ticket: &str, template: &str) -> String {
+    if message.contains(ticket) {
+        return message.to_string();
+    }
+    let filled = template.replace("{ticket}", ticket);
+    if !filled.contains("{subject}") {
+        return trailers::append(message, &filled);
+    }
+    let (subject, rest) = message.split_once('\n').unwrap_or((message, ""));
+    let subject = filled.replace("{subject}", subject);
+    if rest.is_empty() { subject } else { format!("{}\n{}", subject, rest) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find() {
+        assert_eq!(find("feature/JIRA-1234-add-login").as_deref(), Some("JIRA-1234"));
+        assert_eq!(find("proj-42_fix-crash").as_deref(), Some("PROJ-42"));
+        assert_eq!(find("fix/#567-empty-input").as_deref(), Some("#567"));
+        assert_eq!(find("issue-89").as_deref(), Some("#89"));
+        assert_eq!(find("fix-123-crash"), None);
+        assert_eq!(find("release-2"), None);
+        assert_eq!(find("main"), None);
+    }
+
+    #[test]
+    fn test_apply_as_trailer() {
+        assert_eq!(apply("feat: add login", "JIRA-12", DEFAULT_TEMPLATE), "feat: add login\n\nRefs: JIRA-12");
+        assert_eq!(apply("feat: add login (JIRA-12)", "JIRA-12", DEFAULT_TEMPLATE), "feat: add login (JIRA-12)");
+    }
+
+    #[test]
+    fn test_apply_to_subject() {
+        assert_eq!(
+            apply("feat: add login\n\nBody.", "JIRA-12", "[{ticket}] {subject}"),
+            "[JIRA-12] feat: add login\n\nBody."
+        );
+    }
+}
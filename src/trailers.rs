@@ -0,0 +1,105 @@
+use std::collections::HashMap;
+
+/// Whether `line` is a `Key: value` trailer such as `Refs: #12` or
+/// `Co-authored-by: Name <email>`. Conventional Commits' `BREAKING CHANGE`
+/// is the one key allowed a space.
+pub fn is_trailer(line: &str) -> bool {
+    line.split_once(": ").is_some_and(|(key, _)| {
+        key == "BREAKING CHANGE" || (!key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '-'))
+    })
+}
+
+/// A message taken apart: its subject line, the body paragraphs, and the
+/// trailers of its final block.
+#[derive(Debug, PartialEq)]
+pub struct Parts<'a> {
+    pub subject: &'a str,
+    pub body: &'a str,
+    pub footers: Vec<&'a str>,
+}
+
+pub fn split(message: &str) -> Parts<'_> {
+    let message = message.trim();
+    let (subject, rest) = message.split_once('\n').unwrap_or((message, ""));
+    let rest = rest.trim();
+    let (body, footers) = match rest.rsplit_once("\n\n") {
+        Some((body, last)) if last.lines().all(is_trailer) => (body.trim_end(), last),
+        _ if !rest.is_empty() && rest.lines().all(is_trailer) => ("", rest),
+        _ => (rest, ""),
+    };
+    Parts { subject: subject.trim_end(), body, footers: footers.lines().collect() }
+}
+
+/// Adds `trailer` to the end of `message`: to its trailer block if it has
+/// one, otherwise as a new paragraph. A trailer that is already there
+/// isn't repeated.
+pub fn append(message: &str, trailer: &str) -> String {
+    let message = message.trim_end();
+    if message.lines().any(|line| line.trim() == trailer) {
+        return message.to_string();
+    }
+    let has_block = message
+        .rsplit_once("\n\n")
+        .is_some_and(|(_, last)| last.lines().all(is_trailer));
+    if has_block {
+        format!("{}\n{}", message, trailer)
+    } else {
+        format!("{}\n\n{}", message, trailer)
+    }
+}
+
+/// A `Co-authored-by` identity: `Name <email>` as given, or looked up
+/// by alias in the config's `[co_authors]` table.
+pub fn co_author(value: &str, aliases: &HashMap<String, String>) -> anyhow::Result<String> {
+    let identity = aliases.get(value).map(String::as_str).unwrap_or(value).trim();
+    let well_formed = identity
+        .split_once(" <")
+        .is_some_and(|(name, email)| !name.trim().is_empty() && email.ends_with('>') && email.contains('@'));
+    if !well_formed {
+        anyhow::bail!("Co-author `{}` isn't `Name <email>` or an alias from `[co_authors]` in the config file", value);
+    }
+    Ok(identity.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split() {
+        let parts = split("feat: add x\n\nWhy it matters.\n\nMore.\n\nRefs: #1\nSigned-off-by: A <a@b.c>\n");
+        assert_eq!(parts.subject, "feat: add x");
+        assert_eq!(parts.body, "Why it matters.\n\nMore.");
+        assert_eq!(parts.footers, ["Refs: #1", "Signed-off-by: A <a@b.c>"]);
+
+        assert_eq!(split("fix: y\n\nRefs: #2").footers, ["Refs: #2"]);
+        assert_eq!(split("fix: y\n\nRefs: #2").body, "");
+        assert_eq!(split("fix: y\n\nNote: not a trailer block\nbecause of this line").footers, Vec::<&str>::new());
+    }
+
+    #[test]
+    fn test_is_trailer() {
+        assert!(is_trailer("Refs: JIRA-12"));
+        assert!(is_trailer("Co-authored-by: Ann <ann@example.com>"));
+        assert!(is_trailer("BREAKING CHANGE: gone"));
+        assert!(!is_trailer("Some words: more"));
+        assert!(!is_trailer("no trailer here"));
+    }
+
+    #[test]
+    fn test_append() {
+        assert_eq!(append("feat: add x", "Refs: #1"), "feat: add x\n\nRefs: #1");
+        assert_eq!(append("feat: add x\n\nBody.\n", "Refs: #1"), "feat: add x\n\nBody.\n\nRefs: #1");
+        assert_eq!(append("feat: add x\n\nRefs: #1", "Refs: #2"), "feat: add x\n\nRefs: #1\nRefs: #2");
+        assert_eq!(append("feat: add x\n\nRefs: #1", "Refs: #1"), "feat: add x\n\nRefs: #1");
+    }
+
+    #[test]
+    fn test_co_author() {
+        let aliases = HashMap::from([("ann".to_string(), "Ann Lee <ann@example.com>".to_string())]);
+        assert_eq!(co_author("ann", &aliases).unwrap(), "Ann Lee <ann@example.com>");
+        assert_eq!(co_author("Bo <bo@example.com>", &aliases).unwrap(), "Bo <bo@example.com>");
+        assert!(co_author("bo", &aliases).is_err());
+        assert!(co_author("Bo <bo>", &aliases).is_err());
+    }
+}
@@ -0,0 +1,212 @@
+use colored::*;
+use console::{Key, Term, measure_text_width, truncate_str};
+
+use crate::compare::wrap;
+use crate::llm::{ClientResult, GenerationOptions};
+
+const KEYS: &str = "↑↓ scroll  ←→ pick  r regenerate  p provider  e edit  c commit  q quit";
+
+/// A message generated during the review, and who wrote it.
+pub struct Candidate {
+    pub provider: String,
+    pub message: String,
+}
+
+/// Everything the review screen shows.
+pub struct Review {
+    diff: Vec<String>,
+    /// First diff line on screen.
+    scroll: usize,
+    pub candidates: Vec<Candidate>,
+    pub selected: usize,
+    providers: Vec<(String, ClientResult, GenerationOptions)>,
+    /// The provider `r` asks next.
+    provider: usize,
+    status: String,
+}
+
+impl Review {
+    pub fn new(diff: &str, providers: Vec<(String, ClientResult, GenerationOptions)>) -> Self {
+        Self {
+            diff: diff.lines().map(|line| line.replace('\t', "    ")).collect(),
+            scroll: 0,
+            candidates: Vec::new(),
+            selected: 0,
+            providers,
+            provider: 0,
+            status: String::new(),
+        }
+    }
+
+    fn provider_name(&self) -> &str {
+        &self.providers[self.provider].0
+    }
+
+    /// Asks the current provider for another candidate and selects it.
+    pub async fn generate(&mut self, prompt: &str, polish: &impl Fn(&str) -> String) {
+        let (name, client, options) = &self.providers[self.provider];
+        let result = match client {
+            Ok(client) => client.generate(prompt, options).await,
+            Err(e) => Err(anyhow::anyhow!("{}", e)),
+        };
+        match result {
+            Ok(message) => {
+                self.candidates.push(Candidate { provider: name.clone(), message: polish(message.trim()) });
+                self.selected = self.candidates.len() - 1;
+                self.status.clear();
+            }
+            Err(e) => self.status = format!("{} failed: {}", name, e),
+        }
+    }
+
+    /// The screen as `height` lines of at most `width` columns: the diff
+    /// on the left, the candidates on the right, and a status bar.
+    pub fn render(&self, width: usize, height: usize) -> Vec<String> {
+        let body_height = height.saturating_sub(2).max(1);
+        let left_width = (width / 2).saturating_sub(1).max(10);
+        let right_width = width.saturating_sub(left_width + 3).max(10);
+
+        let diff = self.diff.iter().skip(self.scroll).take(body_height).map(|line| {
+            let line = truncate_str(line, left_width, "…").into_owned();
+            match line.chars().next() {
+                Some('+') if !line.starts_with("+++") => line.green().to_string(),
+                Some('-') if !line.starts_with("---") => line.red().to_string(),
+                Some('@') => line.cyan().to_string(),
+                _ => line,
+            }
+        });
+        let diff: Vec<String> = diff.collect();
+
+        let mut messages: Vec<String> = Vec::new();
+        for (i, candidate) in self.candidates.iter().enumerate() {
+            let header = format!("[{}] {}", i + 1, candidate.provider).bold();
+            let selected = i == self.selected;
+            messages.push(if selected { header.reversed().to_string() } else { header.to_string() });
+            for line in wrap(&candidate.message, right_width) {
+                messages.push(if selected { line.cyan().to_string() } else { line.dimmed().to_string() });
+            }
+            messages.push(String::new());
+        }
+        if self.candidates.is_empty() {
+            messages.push("Generating...".dimmed().to_string());
+        }
+
+        let mut screen = Vec::with_capacity(height);
+        for row in 0..body_height {
+            let left = diff.get(row).map(String::as_str).unwrap_or("");
+            let right = messages.get(row).map(String::as_str).unwrap_or("");
+            let pad = left_width.saturating_sub(measure_text_width(left));
+            screen.push(format!("{}{} │ {}", left, " ".repeat(pad), right).trim_end().to_string());
+        }
+        let status = if self.status.is_empty() {
+            format!("provider: {}", self.provider_name())
+        } else {
+            self.status.clone()
+        };
+        screen.push(truncate_str(&status, width, "…").yellow().to_string());
+        screen.push(truncate_str(KEYS, width, "…").dimmed().to_string());
+        screen
+    }
+}
+
+/// What the review ended with.
+pub enum Outcome {
+    Commit(String),
+    Quit,
+}
+
+/// Runs the review screen on `term` until a message is committed or the
+/// review is abandoned. `polish` finishes every generated message the way
+/// the rest of commitgen does.
+pub async fn run(
+    term: &Term,
+    mut review: Review,
+    prompt: &str,
+    polish: impl Fn(&str) -> String,
+) -> anyhow::Result<Outcome> {
+    term.hide_cursor()?;
+    let outcome = event_loop(term, &mut review, prompt, &polish).await;
+    term.clear_screen()?;
+    term.show_cursor()?;
+    outcome
+}
+
+async fn event_loop(
+    term: &Term,
+    review: &mut Review,
+    prompt: &str,
+    polish: &impl Fn(&str) -> String,
+) -> anyhow::Result<Outcome> {
+    draw(term, review)?;
+    review.generate(prompt, polish).await;
+    loop {
+        draw(term, review)?;
+        let page = (term.size().0 as usize).saturating_sub(3).max(1);
+        let last = review.diff.len().saturating_sub(1);
+        match term.read_key()? {
+            Key::ArrowDown | Key::Char('j') => review.scroll = (review.scroll + 1).min(last),
+            Key::ArrowUp | Key::Char('k') => review.scroll = review.scroll.saturating_sub(1),
+            Key::PageDown | Key::Char(' ') => review.scroll = (review.scroll + page).min(last),
+            Key::PageUp => review.scroll = review.scroll.saturating_sub(page),
+            Key::ArrowRight | Key::Tab | Key::Char('l') if !review.candidates.is_empty() => {
+                review.selected = (review.selected + 1) % review.candidates.len();
+            }
+            Key::ArrowLeft | Key::BackTab | Key::Char('h') if !review.candidates.is_empty() => {
+                review.selected = (review.selected + review.candidates.len() - 1) % review.candidates.len();
+            }
+            Key::Char('r') => {
+                review.status = format!("Asking {}...", review.provider_name());
+                draw(term, review)?;
+                review.generate(prompt, polish).await;
+            }
+            Key::Char('p') => {
+                review.provider = (review.provider + 1) % review.providers.len();
+                review.status.clear();
+            }
+            Key::Char('e') => {
+                if let Some(candidate) = review.candidates.get_mut(review.selected) {
+                    term.clear_screen()?;
+                    term.show_cursor()?;
+                    if let Some(edited) = dialoguer::Editor::new().edit(&candidate.message)? {
+                        candidate.message = edited.trim().to_string();
+                    }
+                    term.hide_cursor()?;
+                }
+            }
+            Key::Char('c') | Key::Enter => {
+                if let Some(candidate) = review.candidates.get(review.selected) {
+                    return Ok(Outcome::Commit(candidate.message.clone()));
+                }
+            }
+            Key::Char('q') | Key::Escape => return Ok(Outcome::Quit),
+            _ => {}
+        }
+    }
+}
+
+fn draw(term: &Term, review: &Review) -> anyhow::Result<()> {
+    let (height, width) = term.size();
+    term.move_cursor_to(0, 0)?;
+    term.clear_screen()?;
+    term.write_str(&review.render(width as usize, height as usize).join("\n"))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_lays_out_panes() {
+        let providers = vec![("gemini".to_string(), Err(anyhow::anyhow!("no key")), GenerationOptions::default())];
+        let mut review = Review::new("+added\n-removed\n context\n", providers);
+        review.candidates.push(Candidate { provider: "gemini".into(), message: "feat: add x".into() });
+        let screen: Vec<String> =
+            review.render(40, 6).iter().map(|line| console::strip_ansi_codes(line).into_owned()).collect();
+        assert_eq!(screen.len(), 6);
+        assert_eq!(screen[0], "+added              │ [1] gemini");
+        assert_eq!(screen[1], "-removed            │ feat: add x");
+        assert_eq!(screen[3], "                    │");
+        assert_eq!(screen[4], "provider: gemini");
+    }
+}
@@ -0,0 +1,112 @@
+use std::path::Path;
+
+use crate::{git, hg, jj};
+
+/// The version control system the current directory is under. A repository
+/// jj colocates with git counts as jj, since that's where its changes are
+/// described.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Vcs {
+    Git,
+    Jujutsu,
+    Mercurial,
+}
+
+impl Vcs {
+    pub fn detect() -> anyhow::Result<Self> {
+        Ok(Self::detect_from(&std::env::current_dir()?))
+    }
+
+    /// The nearest repository wins, so a git checkout inside a jj workspace
+    /// is git. Outside any repository it's git, which says so itself.
+    fn detect_from(dir: &Path) -> Self {
+        for dir in dir.ancestors() {
+            if dir.join(".jj").is_dir() {
+                return Vcs::Jujutsu;
+            }
+            if dir.join(".hg").is_dir() {
+                return Vcs::Mercurial;
+            }
+            if dir.join(".git").exists() {
+                return Vcs::Git;
+            }
+        }
+        Vcs::Git
+    }
+
+    /// The revision `describe` works on when none is given: jj's
+    /// working-copy change, or the last commit in git and hg.
+    pub fn default_rev(self) -> &'static str {
+        match self {
+            Vcs::Git => "HEAD",
+            Vcs::Jujutsu => "@",
+            Vcs::Mercurial => ".",
+        }
+    }
+
+    /// What `rev` changed, relative to its (first) parent.
+    pub fn diff(self, rev: &str) -> anyhow::Result<String> {
+        match self {
+            Vcs::Git => git::commit_diff(rev),
+            Vcs::Jujutsu => jj::diff(rev),
+            Vcs::Mercurial => hg::diff(rev),
+        }
+    }
+
+    /// The message `rev` currently has.
+    pub fn description(self, rev: &str) -> anyhow::Result<String> {
+        match self {
+            Vcs::Git => git::commit_message(rev),
+            Vcs::Jujutsu => jj::description(rev),
+            Vcs::Mercurial => hg::description(rev),
+        }
+    }
+
+    pub fn recent_subjects(self, count: usize) -> anyhow::Result<Vec<String>> {
+        match self {
+            Vcs::Git => git::recent_subjects(count),
+            Vcs::Jujutsu => jj::recent_subjects(count),
+            Vcs::Mercurial => hg::recent_subjects(count),
+        }
+    }
+
+    /// Gives `rev` the description `message`. git and hg can only do this
+    /// in place for the last commit; older git ones need `commitgen reword`.
+    pub fn describe(self, rev: &str, message: &str) -> anyhow::Result<()> {
+        match self {
+            Vcs::Jujutsu => jj::describe(rev, message),
+            Vcs::Mercurial => hg::describe(rev, message),
+            Vcs::Git => {
+                let head = git::run(&["rev-parse", "HEAD"])?;
+                let target = git::run(&["rev-parse", "--verify", &format!("{}^{{commit}}", rev)])?;
+                if head != target {
+                    anyhow::bail!("git can only redescribe the last commit here; use `commitgen reword {}`", rev);
+                }
+                git::reword_head(message)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_nearest_repository() {
+        let root = std::env::temp_dir().join(format!("commitgen-vcs-{}", std::process::id()));
+        std::fs::create_dir_all(root.join(".jj")).unwrap();
+        std::fs::create_dir_all(root.join(".git")).unwrap();
+        std::fs::create_dir_all(root.join("vendor/lib/.git")).unwrap();
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::create_dir_all(root.join("vendor/hg/.hg")).unwrap();
+
+        let colocated = Vcs::detect_from(&root.join("src"));
+        let nested = Vcs::detect_from(&root.join("vendor/lib"));
+        let mercurial = Vcs::detect_from(&root.join("vendor/hg"));
+        std::fs::remove_dir_all(&root).unwrap();
+        assert_eq!(colocated, Vcs::Jujutsu);
+        assert_eq!(nested, Vcs::Git);
+        assert_eq!(mercurial, Vcs::Mercurial);
+    }
+}
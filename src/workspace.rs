@@ -0,0 +1,215 @@
+use std::path::Path;
+
+use crate::{git, glob};
+
+/// A package in a monorepo, at `dir` relative to the repository root.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Package {
+    pub name: String,
+    pub dir: String,
+}
+
+/// The packages of the repository's Cargo workspace, pnpm/npm/yarn
+/// workspaces and Go workspace, whichever it has. Outside a repository,
+/// or in one without workspaces, there are none.
+pub fn discover() -> Vec<Package> {
+    let Ok(root) = git::run(&["rev-parse", "--show-toplevel"]) else {
+        return Vec::new();
+    };
+    let root = Path::new(root.trim());
+    let mut packages = cargo(root);
+    packages.extend(node(root));
+    packages.extend(go(root));
+    packages
+}
+
+/// The package `path` belongs to: the one with the deepest directory
+/// containing it.
+pub fn package_of<'a>(path: &str, packages: &'a [Package]) -> Option<&'a Package> {
+    packages
+        .iter()
+        .filter(|package| package.dir.is_empty() || path.starts_with(&format!("{}/", package.dir)))
+        .max_by_key(|package| package.dir.len())
+}
+
+/// Directories under `root` matching workspace member patterns such as
+/// `crates/*` or `packages/**`, plus plain paths as given.
+fn expand_members(root: &Path, patterns: &[String]) -> Vec<String> {
+    let mut dirs = Vec::new();
+    for pattern in patterns {
+        let pattern = pattern.trim_start_matches("./").trim_end_matches('/');
+        if !pattern.contains(['*', '?']) {
+            dirs.push(pattern.to_string());
+            continue;
+        }
+        // Walk down to the first wildcard, then match what's below it.
+        let fixed: Vec<&str> = pattern.split('/').take_while(|s| !s.contains(['*', '?'])).collect();
+        let base = fixed.join("/");
+        let depth = if pattern.contains("**") { 3 } else { pattern.split('/').count() - fixed.len() };
+        collect_dirs(root, &base, depth, &mut |dir| {
+            if glob::matches(&format!("/{}", pattern), dir) {
+                dirs.push(dir.to_string());
+            }
+        });
+    }
+    dirs
+}
+
+fn collect_dirs(root: &Path, dir: &str, depth: usize, found: &mut impl FnMut(&str)) {
+    if depth == 0 {
+        return;
+    }
+    let Ok(entries) = std::fs::read_dir(root.join(dir)) else { return };
+    let mut names: Vec<String> = entries
+        .flatten()
+        .filter(|entry| entry.file_type().is_ok_and(|t| t.is_dir()))
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .filter(|name| !name.starts_with('.') && name != "node_modules" && name != "target")
+        .collect();
+    names.sort();
+    for name in names {
+        let path = if dir.is_empty() { name } else { format!("{}/{}", dir, name) };
+        found(&path);
+        collect_dirs(root, &path, depth - 1, found);
+    }
+}
+
+fn cargo(root: &Path) -> Vec<Package> {
+    let Some(manifest) = read_toml(&root.join("Cargo.toml")) else { return Vec::new() };
+    let Some(members) = manifest.get("workspace").and_then(|w| w.get("members")).and_then(|m| m.as_array()) else {
+        return Vec::new();
+    };
+    let members: Vec<String> = members.iter().filter_map(|m| m.as_str()).map(str::to_string).collect();
+    expand_members(root, &members)
+        .into_iter()
+        .filter_map(|dir| {
+            let manifest = read_toml(&root.join(&dir).join("Cargo.toml"))?;
+            let name = manifest.get("package")?.get("name")?.as_str()?.to_string();
+            Some(Package { name, dir })
+        })
+        .collect()
+}
+
+fn read_toml(path: &Path) -> Option<toml::Table> {
+    std::fs::read_to_string(path).ok()?.parse().ok()
+}
+
+/// Members from `pnpm-workspace.yaml`, or `workspaces` in `package.json`
+/// for npm and yarn, named by each member's `package.json`.
+fn node(root: &Path) -> Vec<Package> {
+    let mut patterns = std::fs::read_to_string(root.join("pnpm-workspace.yaml"))
+        .map(|yaml| pnpm_patterns(&yaml))
+        .unwrap_or_default();
+    if patterns.is_empty()
+        && let Some(manifest) = read_json(&root.join("package.json"))
+    {
+        let workspaces = manifest.get("workspaces");
+        // Yarn also allows `{ "packages": [...] }`.
+        let list = workspaces.and_then(|w| w.as_array().or_else(|| w.get("packages")?.as_array()));
+        patterns = list.into_iter().flatten().filter_map(|p| p.as_str()).map(str::to_string).collect();
+    }
+    expand_members(root, &patterns)
+        .into_iter()
+        .filter_map(|dir| {
+            let name = read_json(&root.join(&dir).join("package.json"))?.get("name")?.as_str()?.to_string();
+            Some(Package { name, dir })
+        })
+        .collect()
+}
+
+fn read_json(path: &Path) -> Option<serde_json::Value> {
+    serde_json::from_str(&std::fs::read_to_string(path).ok()?).ok()
+}
+
+/// The entries of the `packages:` list in a pnpm-workspace.yaml, leaving
+/// out `!` exclusions.
+pub fn pnpm_patterns(yaml: &str) -> Vec<String> {
+    yaml.lines()
+        .skip_while(|line| !line.starts_with("packages:"))
+        .skip(1)
+        .take_while(|line| line.starts_with(' ') || line.starts_with('-') || line.trim().is_empty())
+        .filter_map(|line| line.trim().strip_prefix('-'))
+        .map(|item| item.trim().trim_matches(['\'', '"']).to_string())
+        .filter(|item| !item.is_empty() && !item.starts_with('!'))
+        .collect()
+}
+
+/// Modules listed in `go.work`, named by the last part of their module
+/// path (`example.com/shop/billing` is `billing`).
+fn go(root: &Path) -> Vec<Package> {
+    let Ok(work) = std::fs::read_to_string(root.join("go.work")) else { return Vec::new() };
+    go_work_dirs(&work)
+        .into_iter()
+        .filter_map(|dir| {
+            let module = std::fs::read_to_string(root.join(&dir).join("go.mod")).ok()?;
+            let path = module.lines().find_map(|line| line.trim().strip_prefix("module "))?.trim();
+            let name = path.trim_matches('"').rsplit('/').next()?.to_string();
+            Some(Package { name, dir })
+        })
+        .collect()
+}
+
+/// Directories from a go.work's `use` directives, single or in a block.
+pub fn go_work_dirs(work: &str) -> Vec<String> {
+    let mut dirs = Vec::new();
+    let mut in_block = false;
+    for line in work.lines().map(|line| line.split("//").next().unwrap_or("").trim()) {
+        let dir = if in_block {
+            if line == ")" {
+                in_block = false;
+                continue;
+            }
+            line
+        } else if line == "use (" {
+            in_block = true;
+            continue;
+        } else if let Some(dir) = line.strip_prefix("use ") {
+            dir
+        } else {
+            continue;
+        };
+        let dir = dir.trim().trim_start_matches("./").trim_end_matches('/');
+        if !dir.is_empty() {
+            dirs.push(if dir == "." { String::new() } else { dir.to_string() });
+        }
+    }
+    dirs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_package_of_prefers_deepest() {
+        let packages = vec![
+            Package { name: "root".into(), dir: String::new() },
+            Package { name: "cli".into(), dir: "crates/cli".into() },
+        ];
+        assert_eq!(package_of("crates/cli/src/main.rs", &packages).unwrap().name, "cli");
+        assert_eq!(package_of("crates/cli-extra/a.rs", &packages).unwrap().name, "root");
+    }
+
+    #[test]
+    fn test_pnpm_patterns() {
+        let yaml = "packages:\n  - 'packages/*'\n  - \"apps/**\"\n  - '!**/test/**'\ncatalog:\n  - nope\n";
+        assert_eq!(pnpm_patterns(yaml), ["packages/*", "apps/**"]);
+    }
+
+    #[test]
+    fn test_go_work_dirs() {
+        let work = "go 1.22\n\nuse (\n\t./billing // payments\n\t./shop/\n)\nuse ./tools\n";
+        assert_eq!(go_work_dirs(work), ["billing", "shop", "tools"]);
+    }
+
+    #[test]
+    fn test_expand_members() {
+        let root = std::env::temp_dir().join(format!("commitgen-workspace-{}", std::process::id()));
+        for dir in ["crates/a", "crates/b/nested", "tools/x"] {
+            std::fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        let members = expand_members(&root, &["crates/*".to_string(), "tools/x".to_string()]);
+        std::fs::remove_dir_all(&root).unwrap();
+        assert_eq!(members, ["crates/a", "crates/b", "tools/x"]);
+    }
+}
@@ -0,0 +1,145 @@
+use clap::ValueEnum;
+use serde::Deserialize;
+
+use crate::check::width;
+use crate::trailers;
+
+/// Body lines are wrapped here unless configured, as git's docs suggest.
+pub const DEFAULT_WRAP_COLUMN: usize = 72;
+
+/// What happens to a subject over the style's length limit.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum LongSubject {
+    /// Cut it at the last word that fits
+    #[default]
+    Truncate,
+    /// Ask the model for a shorter one, truncating if that is still too long
+    Rewrite,
+    /// Leave it as it is, with a warning
+    Keep,
+}
+
+/// Cuts `subject` down to `max` columns at a word boundary, dropping any
+/// punctuation left dangling at the end. When that would lose more than
+/// half the room, as with a long word or CJK text without spaces, it's cut
+/// wherever the limit falls instead.
+pub fn truncate_subject(subject: &str, max: usize) -> String {
+    if width(subject) <= max {
+        return subject.to_string();
+    }
+    let mut cut = String::new();
+    for word in subject.split(' ') {
+        let next = if cut.is_empty() { word.to_string() } else { format!("{} {}", cut, word) };
+        if width(&next) > max {
+            break;
+        }
+        cut = next;
+    }
+    if width(&cut) < max / 2 {
+        cut.clear();
+        for c in subject.chars() {
+            cut.push(c);
+            if width(&cut) > max {
+                cut.pop();
+                break;
+            }
+        }
+    }
+    cut.trim_end_matches([' ', ',', ';', ':', '-', '(']).to_string()
+}
+
+/// `message` with its first line replaced by `subject`.
+pub fn with_subject(message: &str, subject: &str) -> String {
+    match message.split_once('\n') {
+        Some((_, rest)) => format!("{}\n{}", subject, rest),
+        None => subject.to_string(),
+    }
+}
+
+/// Hard-wraps the body of `message` at `column`. The subject, trailers,
+/// code (fenced or indented) and lines with no room to break, such as
+/// long URLs, are left as they are; list items keep their indent on the
+/// lines they wrap onto.
+pub fn wrap_body(message: &str, column: usize) -> String {
+    let Some((subject, body)) = message.split_once('\n') else {
+        return message.to_string();
+    };
+    let mut lines = vec![subject.to_string()];
+    let mut fenced = false;
+    for line in body.lines() {
+        if line.trim_start().starts_with("```") {
+            fenced = !fenced;
+        }
+        if fenced || width(line) <= column || line.starts_with("    ") || line.starts_with('\t') || trailers::is_trailer(line) {
+            lines.push(line.to_string());
+        } else {
+            lines.extend(wrap_line(line, column));
+        }
+    }
+    lines.join("\n")
+}
+
+/// Splits one line into lines of at most `column` columns, where its
+/// spaces allow.
+fn wrap_line(line: &str, column: usize) -> Vec<String> {
+    let indent = hanging_indent(line);
+    let (first, rest) = line.split_at(indent.len().min(line.len()));
+    let mut lines = Vec::new();
+    let mut current = first.to_string();
+    let mut has_word = false;
+    for word in rest.split(' ').filter(|word| !word.is_empty()) {
+        let next = if has_word { format!("{} {}", current, word) } else { format!("{}{}", current, word) };
+        if has_word && width(&next) > column {
+            lines.push(current);
+            current = format!("{}{}", " ".repeat(width(&indent)), word);
+        } else {
+            current = next;
+        }
+        has_word = true;
+    }
+    lines.push(current);
+    lines
+}
+
+/// The prefix continuation lines are indented to match: the leading
+/// spaces, plus a list marker such as `- `, `* ` or `1. ` when there is one.
+fn hanging_indent(line: &str) -> String {
+    let spaces = line.len() - line.trim_start_matches(' ').len();
+    let rest = &line[spaces..];
+    let marker = if rest.starts_with("- ") || rest.starts_with("* ") {
+        2
+    } else {
+        let digits = rest.chars().take_while(char::is_ascii_digit).count();
+        if digits > 0 && (rest[digits..].starts_with(". ") || rest[digits..].starts_with(") ")) { digits + 2 } else { 0 }
+    };
+    line[..spaces + marker].to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncate_subject() {
+        assert_eq!(truncate_subject("fix: short", 50), "fix: short");
+        assert_eq!(truncate_subject("fix(parser): handle empty input, and more besides", 35), "fix(parser): handle empty input");
+        assert_eq!(truncate_subject("a".repeat(10).as_str(), 4), "aaaa");
+        assert_eq!(truncate_subject("修正: ログイン画面の表示を直す", 12), "修正: ログイ");
+    }
+
+    #[test]
+    fn test_wrap_body() {
+        let long = "This paragraph is long enough that it needs to be wrapped onto a second line to fit.";
+        assert_eq!(
+            wrap_body(&format!("fix: x\n\n{}", long), 40),
+            "fix: x\n\nThis paragraph is long enough that it\nneeds to be wrapped onto a second line\nto fit."
+        );
+        assert_eq!(
+            wrap_body("fix: x\n\n- a list item that goes on for rather too long", 30),
+            "fix: x\n\n- a list item that goes on for\n  rather too long"
+        );
+        let kept = "fix: x\n\nhttps://example.com/a/very/long/url/that/cannot/be/broken\n\n    let code = \"stays on one line however long it is\";\n\nSigned-off-by: Somebody With A Long Name <somebody@example.com>";
+        assert_eq!(wrap_body(kept, 30), kept);
+    }
+}